@@ -0,0 +1,16 @@
+-- Portfolio Snapshots Table
+-- Periodic cross-exchange equity snapshots, valued in a single quote currency
+
+CREATE TABLE IF NOT EXISTS portfolio_snapshots (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    quote_currency TEXT NOT NULL,              -- Currency all values are expressed in (e.g. "USDT")
+    total_equity REAL NOT NULL,
+    assets_json TEXT NOT NULL,                 -- JSON array of per-asset exposure
+    exchanges_json TEXT NOT NULL,              -- JSON array of per-exchange allocation
+    created_at INTEGER NOT NULL,               -- Snapshot time (ms)
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_portfolio_snapshots_user_time
+ON portfolio_snapshots(user_id, created_at DESC);
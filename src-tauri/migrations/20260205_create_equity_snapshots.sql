@@ -0,0 +1,18 @@
+-- Equity Snapshots Table
+-- Periodic equity and PnL per user, per exchange account and per strategy instance
+
+CREATE TABLE IF NOT EXISTS equity_snapshots (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    scope TEXT NOT NULL,                       -- "user", "account" or "instance"
+    exchange_id TEXT,                          -- Set for account snapshots
+    strategy_instance_id TEXT,                 -- Set for instance snapshots
+    equity REAL NOT NULL,                      -- Instances: realized + unrealized PnL
+    realized_pnl REAL NOT NULL,                -- Cumulative realized PnL from fills
+    unrealized_pnl REAL NOT NULL,
+    created_at INTEGER NOT NULL,               -- Snapshot time (ms)
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_equity_snapshots_scope_time
+ON equity_snapshots(user_id, scope, created_at DESC);
@@ -0,0 +1,32 @@
+-- Conditional Orders Table
+-- Client-side stop, take-profit, trailing-stop and OCO orders watched against the ticker stream
+
+CREATE TABLE IF NOT EXISTS conditional_orders (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    exchange_id TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    side TEXT NOT NULL,                        -- "buy" or "sell"
+    quantity REAL NOT NULL,
+    limit_price REAL,                          -- NULL: market order on trigger
+    kind TEXT NOT NULL,                        -- "stop", "take_profit" or "trailing_stop"
+    trigger_price REAL,                        -- Stop and take-profit trigger
+    trail_unit TEXT,                           -- Trailing stop: "percent" or "absolute"
+    trail_value REAL,
+    extreme_price REAL,                        -- Trailing stop: best price seen so far
+    oco_group_id TEXT,                         -- Legs of one OCO pair share a group
+    status TEXT NOT NULL DEFAULT 'pending',    -- pending, triggered, canceled, failed
+    order_id TEXT,                             -- Order submitted on trigger
+    error TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    triggered_at INTEGER,
+    FOREIGN KEY (user_id) REFERENCES users(id),
+    FOREIGN KEY (exchange_id) REFERENCES exchanges(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_conditional_orders_exchange_status
+ON conditional_orders(exchange_id, status);
+
+CREATE INDEX IF NOT EXISTS idx_conditional_orders_user_time
+ON conditional_orders(user_id, created_at DESC);
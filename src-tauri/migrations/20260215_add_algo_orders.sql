@@ -0,0 +1,8 @@
+-- Execution Algorithm Orders
+-- Parent orders (TWAP, VWAP, iceberg) and their child orders share the orders table
+
+ALTER TABLE orders ADD COLUMN parent_order_id TEXT REFERENCES orders(id);
+ALTER TABLE orders ADD COLUMN algo TEXT;             -- Set on parent orders: "twap", "vwap" or "iceberg"
+ALTER TABLE orders ADD COLUMN arrival_price REAL;    -- Market price when the parent order was submitted
+
+CREATE INDEX IF NOT EXISTS idx_orders_parent_order_id ON orders(parent_order_id);
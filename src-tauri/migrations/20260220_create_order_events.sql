@@ -0,0 +1,20 @@
+-- Order Event History
+-- Every recorded change to an order, including updates refused by the order state machine
+
+ALTER TABLE orders ADD COLUMN exchange_updated_at INTEGER;  -- Exchange time of the last update applied (ms)
+
+CREATE TABLE IF NOT EXISTS order_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    order_id TEXT NOT NULL,
+    kind TEXT NOT NULL,                -- created, status_changed, fill, amended, resubmitted, ignored
+    source TEXT NOT NULL,              -- local, exchange, stream, reconcile
+    from_status TEXT,
+    to_status TEXT,
+    filled_quantity REAL,              -- Cumulative filled quantity after the event
+    detail TEXT,
+    exchange_time INTEGER,             -- Exchange time of the update (ms), when known
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (order_id) REFERENCES orders(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_events_order_id ON order_events(order_id, id);
@@ -0,0 +1,5 @@
+-- Position Lots
+-- Open lots behind each position and fees paid over its life
+
+ALTER TABLE positions ADD COLUMN lots TEXT;             -- JSON array of open lots: [{"quantity", "price", "openedAt"}]
+ALTER TABLE positions ADD COLUMN fees REAL DEFAULT 0;   -- Fees paid in the quote asset, already deducted from realized_pnl
@@ -0,0 +1,19 @@
+-- Pre-trade risk limits
+-- Checked by TradeService before any order is sent. Keys are scoped by
+-- user, strategy instance or symbol; a missing key means no limit. The
+-- instance limits match what strategy instances used to hardcode. The
+-- balance ratios are new and apply to manual orders too; they are measured
+-- against equity (quote balance plus open position value), so a spot
+-- position doesn't eat into its own allowance.
+
+INSERT OR IGNORE INTO risk_rules (name, display_name, description, rule_type, enabled, action, notify_methods, params_json) VALUES
+    (
+        'pre_trade_limits',
+        '下单前风控',
+        '下单前检查单笔金额、日内下单次数和持仓规模，超限的订单直接拒绝',
+        'pre_trade',
+        1,
+        'reject',
+        '["log"]',
+        '{"instance.max_order_value": 1000.0, "instance.max_daily_orders": 100.0, "symbol.max_balance_ratio": 0.5, "user.max_balance_ratio": 0.9}'
+    );
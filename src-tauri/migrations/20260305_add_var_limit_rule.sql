@@ -0,0 +1,18 @@
+-- Value-at-Risk limit
+-- Estimated from the klines stored in the klines table (1h bars, scaled to
+-- a 24-bar horizon). Disabled until enough history has been collected for
+-- the symbols traded. parametric = 1 switches from historical to
+-- variance-covariance VaR; max_cvar_pct = 0 leaves expected shortfall
+-- unchecked.
+
+INSERT OR IGNORE INTO risk_rules (name, display_name, description, rule_type, enabled, action, notify_methods, params_json) VALUES
+    (
+        'var_limit',
+        'VaR 限制规则',
+        '根据历史K线估算持仓的风险价值（VaR）和预期亏损（CVaR），超过权益比例时触发',
+        'var_limit',
+        0,
+        'warning',
+        '["log"]',
+        '{"max_var_pct": 5.0, "max_cvar_pct": 0.0, "confidence": 0.99, "lookback": 500.0, "horizon_bars": 24.0, "parametric": 0.0}'
+    );
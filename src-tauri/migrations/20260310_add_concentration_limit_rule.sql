@@ -0,0 +1,17 @@
+-- Concentration limit
+-- Caps are fractions of total equity. Asset groups are added through the
+-- settings page as group.<name>.max_ratio plus group.<name>.<ASSET> = 1 per
+-- member; per-asset and per-account overrides use asset.<ASSET>.max_ratio
+-- and account.<exchange_id>.max_ratio.
+
+INSERT OR IGNORE INTO risk_rules (name, display_name, description, rule_type, enabled, action, notify_methods, params_json) VALUES
+    (
+        'concentration_limit',
+        '集中度限制规则',
+        '限制单一币种、自定义币种分组、单一交易所账户的持仓占权益比例，以及总杠杆和净杠杆',
+        'concentration_limit',
+        0,
+        'warning',
+        '["log"]',
+        '{"asset.max_ratio": 0.5, "account.max_ratio": 0.8, "max_gross_leverage": 3.0, "max_net_leverage": 2.0}'
+    );
@@ -0,0 +1,18 @@
+-- Liquidity and slippage guard
+-- Market orders are checked against an order book snapshot before they are
+-- sent. The estimate is kept on the order (arrival_price holds the best
+-- price at submission) so each fill can be compared with it.
+
+ALTER TABLE orders ADD COLUMN expected_slippage_bps REAL;  -- Estimated slippage of a market order at submission
+
+INSERT OR IGNORE INTO risk_rules (name, display_name, description, rule_type, enabled, action, notify_methods, params_json) VALUES
+    (
+        'slippage_guard',
+        '滑点与流动性保护',
+        '市价单下单前根据盘口深度估算滑点，超过容忍度或占盘口挂单量比例过高时拒绝或缩减数量',
+        'slippage_guard',
+        0,
+        'reject',
+        '["log"]',
+        '{"max_slippage_bps": 30.0, "max_book_share": 0.5, "top_levels": 5.0, "depth_levels": 50.0, "downsize": 1.0}'
+    );
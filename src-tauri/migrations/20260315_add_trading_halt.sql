@@ -0,0 +1,13 @@
+-- App-wide trading halt (kill switch)
+-- At most one row. While it exists, orders that do not reduce a position are
+-- refused on every account. Re-arming deletes the row; engaging, escalating
+-- and re-arming are recorded in audit_logs.
+
+CREATE TABLE IF NOT EXISTS trading_halt (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    level TEXT NOT NULL,              -- pause_entries / flatten / full_halt
+    reason TEXT NOT NULL,
+    engaged_by TEXT,                  -- User who engaged or escalated it; NULL for the risk monitor
+    engaged_at INTEGER NOT NULL,      -- First engaged (ms)
+    updated_at INTEGER NOT NULL       -- Last escalation (ms)
+);
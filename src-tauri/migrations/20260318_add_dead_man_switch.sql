@@ -0,0 +1,16 @@
+-- Dead-man's switch
+-- While enabled, the cancel-all-after timer of each connected account is
+-- renewed on every heartbeat where the venue supports one, and a local
+-- watchdog cancels all open orders when strategies or market data stall.
+
+INSERT OR IGNORE INTO risk_rules (name, display_name, description, rule_type, enabled, action, notify_methods, params_json) VALUES
+    (
+        'dead_man_switch',
+        '失联保护',
+        '定期续期交易所的超时全撤单计时器；策略卡死或行情中断超过超时时间时自动撤销所有挂单',
+        'dead_man_switch',
+        0,
+        'warning',
+        '["log"]',
+        '{"timeout_secs": 60.0, "heartbeat_secs": 15.0}'
+    );
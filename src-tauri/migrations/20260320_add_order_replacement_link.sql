@@ -0,0 +1,7 @@
+-- Cancel-replace amendments
+-- Venues without native amend cancel the order and place a new one for its
+-- unfilled quantity; the new row points back at the order it replaced.
+
+ALTER TABLE orders ADD COLUMN replaces_order_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_orders_replaces_order_id ON orders(replaces_order_id);
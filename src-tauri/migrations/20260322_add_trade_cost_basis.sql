@@ -0,0 +1,10 @@
+-- Running cost basis on trades
+-- Each trade stores the account's average-cost basis in its symbol after
+-- the fill, so realised PnL of the next fill starts from the last trade
+-- instead of replaying the account's whole history.
+
+ALTER TABLE trades ADD COLUMN basis_quantity REAL;
+ALTER TABLE trades ADD COLUMN basis_avg_price REAL;
+
+CREATE INDEX IF NOT EXISTS idx_trades_exchange_symbol_timestamp
+ON trades(exchange_id, symbol, timestamp DESC);
@@ -0,0 +1,7 @@
+-- Client order ids per user
+-- Order submission is deduplicated by (user_id, client_order_id). The key
+-- is enforced here so two submissions racing past the lookup can't both be
+-- recorded; the loser finds the winner's order instead.
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_orders_user_client_order_id
+ON orders(user_id, client_order_id);
@@ -0,0 +1,77 @@
+-- Client order ids unique per user only
+-- The initial schema declared `client_order_id TEXT UNIQUE`, which made ids
+-- unique across all users and left idx_orders_user_client_order_id with
+-- nothing to add. SQLite can't drop a column constraint, so the table is
+-- rebuilt without it.
+--
+-- Migrations run in a transaction with foreign keys on, so dropping the old
+-- table cascades to trades and order events; their rows are kept aside and
+-- put back once the new table has taken its place.
+
+CREATE TEMP TABLE trades_kept AS SELECT * FROM trades;
+CREATE TEMP TABLE order_events_kept AS SELECT * FROM order_events;
+
+CREATE TABLE orders_new (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    exchange_id TEXT NOT NULL,
+    strategy_instance_id TEXT,
+    exchange_order_id TEXT UNIQUE,
+    client_order_id TEXT,
+    symbol TEXT NOT NULL,
+    side TEXT NOT NULL,
+    order_type TEXT NOT NULL,
+    price REAL,
+    quantity REAL NOT NULL,
+    filled_quantity REAL DEFAULT 0,
+    avg_price REAL,
+    status TEXT NOT NULL,
+    commission REAL DEFAULT 0,
+    commission_asset TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    filled_at INTEGER,
+    parent_order_id TEXT REFERENCES orders_new(id),
+    algo TEXT,
+    arrival_price REAL,
+    exchange_updated_at INTEGER,
+    expected_slippage_bps REAL,
+    replaces_order_id TEXT,
+    FOREIGN KEY (user_id) REFERENCES users(id),
+    FOREIGN KEY (exchange_id) REFERENCES exchanges(id),
+    FOREIGN KEY (strategy_instance_id) REFERENCES strategy_instances(id)
+);
+
+INSERT INTO orders_new (id, user_id, exchange_id, strategy_instance_id, exchange_order_id, client_order_id,
+                        symbol, side, order_type, price, quantity, filled_quantity, avg_price, status,
+                        commission, commission_asset, created_at, updated_at, filled_at, parent_order_id,
+                        algo, arrival_price, exchange_updated_at, expected_slippage_bps, replaces_order_id)
+SELECT id, user_id, exchange_id, strategy_instance_id, exchange_order_id, client_order_id,
+       symbol, side, order_type, price, quantity, filled_quantity, avg_price, status,
+       commission, commission_asset, created_at, updated_at, filled_at, parent_order_id,
+       algo, arrival_price, exchange_updated_at, expected_slippage_bps, replaces_order_id
+FROM orders;
+
+-- Legacy renaming leaves the views over orders to resolve the name again;
+-- the self reference still follows the rename
+PRAGMA legacy_alter_table = ON;
+DROP TABLE orders;
+ALTER TABLE orders_new RENAME TO orders;
+PRAGMA legacy_alter_table = OFF;
+
+INSERT INTO trades SELECT * FROM trades_kept;
+INSERT INTO order_events SELECT * FROM order_events_kept;
+DROP TABLE trades_kept;
+DROP TABLE order_events_kept;
+
+CREATE INDEX idx_orders_user_id ON orders(user_id);
+CREATE INDEX idx_orders_exchange_id ON orders(exchange_id);
+CREATE INDEX idx_orders_strategy_instance_id ON orders(strategy_instance_id);
+CREATE INDEX idx_orders_status ON orders(status);
+CREATE INDEX idx_orders_created_at ON orders(created_at DESC);
+CREATE INDEX idx_orders_user_status ON orders(user_id, status, created_at DESC);
+CREATE INDEX idx_orders_user_symbol_status ON orders(user_id, symbol, status, created_at DESC);
+CREATE INDEX idx_orders_exchange_symbol ON orders(exchange_id, symbol, status, created_at DESC);
+CREATE INDEX idx_orders_parent_order_id ON orders(parent_order_id);
+CREATE INDEX idx_orders_replaces_order_id ON orders(replaces_order_id);
+CREATE UNIQUE INDEX idx_orders_user_client_order_id ON orders(user_id, client_order_id);
@@ -1,10 +1,13 @@
 //! Emergency commands for Tauri
 //!
-//! This module provides Tauri command handlers for emergency operations.
+//! This module provides Tauri command handlers for emergency operations,
+//! the app-wide kill switch and the dead-man's switch.
 
 use crate::core::response::{ApiResponse, ApiError};
+use crate::core::risk::{HaltLevel, TradingHalt};
+use crate::core::AppError;
 use crate::infrastructure::Database;
-use crate::services::{EmergencyService, EmergencyReport};
+use crate::services::{DeadManStatus, EmergencyReport, KillSwitchOutcome};
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
@@ -33,44 +36,144 @@ impl From<EmergencyReport> for EmergencyStopReport {
     }
 }
 
+/// Kill switch engage result for frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KillSwitchEngageResult {
+    /// Halt in force after the request
+    pub halt: TradingHalt,
+    /// Whether the request engaged or raised the halt
+    pub escalated: bool,
+    pub report: EmergencyStopReport,
+}
+
+impl From<KillSwitchOutcome> for KillSwitchEngageResult {
+    fn from(outcome: KillSwitchOutcome) -> Self {
+        Self {
+            halt: outcome.halt,
+            escalated: outcome.escalated,
+            report: outcome.report.into(),
+        }
+    }
+}
+
 /// Execute emergency stop - halts all trading activities
 ///
-/// This command will:
+/// Engages the kill switch at full halt, which will:
 /// 1. Stop all running strategy instances
 /// 2. Cancel all active orders
 /// 3. Close all positions with market orders
-/// 4. Send emergency alert notification
 ///
 /// Each step continues even if previous steps fail, ensuring maximum cleanup.
+/// Trading stays halted until an authorised user re-arms it.
 #[tauri::command]
 pub async fn emergency_stop(
     db: State<'_, Database>,
     user_id: String,
+    reason: Option<String>,
 ) -> Result<ApiResponse<EmergencyStopReport>, String> {
     let request_id = uuid::Uuid::new_v4().to_string();
     log::error!("[{}] EMERGENCY STOP triggered by user: {}", request_id, user_id);
 
-    // Get services from database state
-    let strategy_engine = db.get_strategy_engine();
-    let exchange = db.get_exchange();
+    let reason = reason.unwrap_or_else(|| "紧急停止".to_string());
+    match db.get_kill_switch().engage(HaltLevel::FullHalt, &reason, Some(&user_id)).await {
+        Ok(outcome) => {
+            log::error!("[{}] EMERGENCY STOP completed successfully: {:?}", request_id, outcome.report);
+            Ok(ApiResponse::success(outcome.report.into()).with_request_id(request_id))
+        }
+        Err(e) => {
+            log::error!("[{}] EMERGENCY STOP failed: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed(format!("紧急停止失败: {}", e))).with_request_id(request_id))
+        }
+    }
+}
+
+/// Get the engaged trading halt; `None` when trading is armed
+#[tauri::command]
+pub async fn kill_switch_status(
+    db: State<'_, Database>,
+) -> Result<ApiResponse<Option<TradingHalt>>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+
+    match db.get_kill_switch().status().await {
+        Ok(halt) => Ok(ApiResponse::success(halt).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to get trading halt: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("查询交易熔断状态失败")).with_request_id(request_id))
+        }
+    }
+}
+
+/// Get the dead-man's switch settings, venue timers and last trip
+#[tauri::command]
+pub async fn dead_man_switch_status(
+    db: State<'_, Database>,
+) -> Result<ApiResponse<DeadManStatus>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+
+    match db.get_dead_man_switch().status().await {
+        Ok(status) => Ok(ApiResponse::success(status).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to get dead-man's switch status: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("查询失联保护状态失败")).with_request_id(request_id))
+        }
+    }
+}
 
-    let trade_service = std::sync::Arc::new(crate::services::TradeService::new(
-        exchange,
-        db.pool.clone(),
-    ));
+/// Halt trading, or escalate the halt in force
+///
+/// # Arguments
+/// * `level` - "pause_entries", "flatten" or "full_halt"
+/// * `reason` - Why trading is halted
+#[tauri::command]
+pub async fn kill_switch_engage(
+    db: State<'_, Database>,
+    user_id: String,
+    level: String,
+    reason: String,
+) -> Result<ApiResponse<KillSwitchEngageResult>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::warn!("[{}] kill_switch_engage called: user_id={}, level={}, reason={}", request_id, user_id, level, reason);
 
-    // Create emergency service
-    let emergency_service = EmergencyService::new(trade_service, strategy_engine);
+    let level: HaltLevel = match level.parse() {
+        Ok(level) => level,
+        Err(_) => return Ok(ApiResponse::error(ApiError::invalid_parameter("level")).with_request_id(request_id)),
+    };
 
-    // Execute emergency stop
-    match emergency_service.emergency_stop_all(&user_id).await {
-        Ok(report) => {
-            log::error!("[{}] EMERGENCY STOP completed successfully: {:?}", request_id, report);
-            Ok(ApiResponse::success(report.into()).with_request_id(request_id))
+    match db.get_kill_switch().engage(level, &reason, Some(&user_id)).await {
+        Ok(outcome) => Ok(ApiResponse::success(outcome.into()).with_request_id(request_id)),
+        Err(AppError::Validation(msg)) => {
+            Ok(ApiResponse::error(ApiError::validation_failed("reason", msg)).with_request_id(request_id))
         }
         Err(e) => {
-            log::error!("[{}] EMERGENCY STOP failed: {}", request_id, e);
-            Ok(ApiResponse::error(ApiError::operation_failed(format!("紧急停止失败: {}", e))).with_request_id(request_id))
+            log::error!("[{}] Failed to engage kill switch: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("启用交易熔断失败")).with_request_id(request_id))
+        }
+    }
+}
+
+/// Re-arm trading after a halt
+///
+/// Needs a user with the `risk:write` permission and a reason; returns the
+/// halt that was cleared.
+#[tauri::command]
+pub async fn kill_switch_rearm(
+    db: State<'_, Database>,
+    user_id: String,
+    reason: String,
+) -> Result<ApiResponse<TradingHalt>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::warn!("[{}] kill_switch_rearm called: user_id={}, reason={}", request_id, user_id, reason);
+
+    match db.get_kill_switch().rearm(&user_id, &reason).await {
+        Ok(halt) => Ok(ApiResponse::success(halt).with_request_id(request_id)),
+        Err(AppError::Auth(_)) | Err(AppError::Permission(_)) => {
+            Ok(ApiResponse::error(ApiError::forbidden("无权解除交易熔断")).with_request_id(request_id))
+        }
+        Err(AppError::Validation(msg)) => Ok(ApiResponse::error(ApiError::business_error(msg)).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to re-arm trading: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("解除交易熔断失败")).with_request_id(request_id))
         }
     }
 }
@@ -98,6 +201,27 @@ mod tests {
         assert!(frontend_report.timestamp > 0);
     }
 
+    #[test]
+    fn test_kill_switch_engage_result_conversion() {
+        let outcome = KillSwitchOutcome {
+            halt: TradingHalt::new(HaltLevel::Flatten, "manual", Some("u1".to_string()), 1_000),
+            escalated: true,
+            report: EmergencyReport {
+                positions_closed: 2,
+                ..Default::default()
+            },
+        };
+
+        let result: KillSwitchEngageResult = outcome.into();
+        assert_eq!(result.halt.level, HaltLevel::Flatten);
+        assert!(result.escalated);
+        assert_eq!(result.report.positions_closed, 2);
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["halt"]["level"], "flatten");
+        assert_eq!(json["halt"]["engagedBy"], "u1");
+    }
+
     #[test]
     fn test_emergency_stop_report_empty() {
         let report = EmergencyReport::default();
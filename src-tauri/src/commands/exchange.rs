@@ -184,6 +184,9 @@ pub async fn exchange_update(
         return Ok(ApiResponse::error(ApiError::database_error(format!("更新失败: {}", e))).with_request_id(request_id));
     }
 
+    // 凭据变更后重建该账户的会话
+    db.get_exchange_sessions().invalidate(&config.id).await;
+
     log::info!("[{}] Exchange config updated successfully", request_id);
     Ok(ApiResponse::success(config.into()).with_request_id(request_id))
 }
@@ -296,6 +299,7 @@ pub async fn exchange_delete(
     let repo = ExchangeRepository::new(db.pool.clone());
     match repo.delete(&config_id).await {
         Ok(()) => {
+            db.get_exchange_sessions().invalidate(&config_id).await;
             log::info!("[{}] Exchange config deleted successfully", request_id);
             Ok(ApiResponse::success_empty().with_request_id(request_id))
         }
@@ -327,6 +331,7 @@ pub async fn exchange_update_status(
     let repo = ExchangeRepository::new(db.pool.clone());
     match repo.update_status(&config_id, &status).await {
         Ok(()) => {
+            db.get_exchange_sessions().invalidate(&config_id).await;
             log::info!("[{}] Exchange status updated successfully", request_id);
             Ok(ApiResponse::success_empty().with_request_id(request_id))
         }
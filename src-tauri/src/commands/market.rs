@@ -4,6 +4,7 @@
 
 use crate::core::response::{ApiResponse, ApiError};
 use crate::core::validation::{validate_symbol, validate_interval, validate_limit};
+use crate::core::trade::exchange::{ExchangeName, RateLimitUsage};
 use crate::core::trade::types::*;
 use crate::services::MarketService;
 use std::sync::Arc;
@@ -111,38 +112,79 @@ pub async fn market_get_klines(
     Ok(ApiResponse::success(klines).with_request_id(request_id))
 }
 
+/// 解析交易所参数，默认 Binance
+fn parse_exchange_name(exchange: Option<String>) -> Result<ExchangeName, ApiError> {
+    match exchange {
+        Some(name) => ExchangeName::parse(&name.to_lowercase())
+            .ok_or_else(|| ApiError::invalid_parameter("exchange")),
+        None => Ok(ExchangeName::Binance),
+    }
+}
+
 /// 获取交易对列表
 ///
+/// # 参数
+/// - `exchange`: 交易所名称 (可选，默认 binance)
+///
 /// # 返回
-/// 返回支持的交易对列表
+/// 返回交易所当前可交易的交易对列表（来自交易所规则缓存）
 #[tauri::command]
 pub async fn market_get_symbols(
-    _market_service: State<'_, Arc<MarketService>>,
+    market_service: State<'_, Arc<MarketService>>,
+    exchange: Option<String>,
 ) -> Result<ApiResponse<Vec<String>>, String> {
     let request_id = Uuid::new_v4().to_string();
-    log::info!("[{}] market_get_symbols called", request_id);
-
-    // TODO: 从配置或数据库获取
-    // 当前返回硬编码的常用交易对
-    let symbols = vec![
-        "BTCUSDT".to_string(),
-        "ETHUSDT".to_string(),
-        "BNBUSDT".to_string(),
-        "SOLUSDT".to_string(),
-        "XRPUSDT".to_string(),
-        "ADAUSDT".to_string(),
-        "DOGEUSDT".to_string(),
-        "DOTUSDT".to_string(),
-        "MATICUSDT".to_string(),
-        "LINKUSDT".to_string(),
-        "AVAXUSDT".to_string(),
-        "UNIUSDT".to_string(),
-    ];
+    log::info!("[{}] market_get_symbols called: exchange={:?}", request_id, exchange);
+
+    let exchange_name = match parse_exchange_name(exchange) {
+        Ok(name) => name,
+        Err(e) => return Ok(ApiResponse::error(e).with_request_id(request_id)),
+    };
+
+    let symbols = match market_service.get_trading_symbols(exchange_name).await {
+        Ok(symbols) => symbols,
+        Err(e) => {
+            log::error!("[{}] Failed to get symbols: {}", request_id, e);
+            return Ok(ApiResponse::error(ApiError::operation_failed("获取交易对列表失败")).with_request_id(request_id));
+        }
+    };
 
     log::debug!("[{}] Returning {} symbols", request_id, symbols.len());
     Ok(ApiResponse::success(symbols).with_request_id(request_id))
 }
 
+/// 获取交易对规则（价格精度、数量精度、最小下单金额）
+///
+/// # 参数
+/// - `exchange`: 交易所名称 (可选，默认 binance)
+///
+/// # 返回
+/// 返回交易所全部交易对的交易规则
+#[tauri::command]
+pub async fn market_get_instruments(
+    market_service: State<'_, Arc<MarketService>>,
+    exchange: Option<String>,
+) -> Result<ApiResponse<Vec<Instrument>>, String> {
+    let request_id = Uuid::new_v4().to_string();
+    log::info!("[{}] market_get_instruments called: exchange={:?}", request_id, exchange);
+
+    let exchange_name = match parse_exchange_name(exchange) {
+        Ok(name) => name,
+        Err(e) => return Ok(ApiResponse::error(e).with_request_id(request_id)),
+    };
+
+    let instruments = match market_service.get_instruments(exchange_name).await {
+        Ok(instruments) => instruments,
+        Err(e) => {
+            log::error!("[{}] Failed to get instruments: {}", request_id, e);
+            return Ok(ApiResponse::error(ApiError::operation_failed("获取交易对规则失败")).with_request_id(request_id));
+        }
+    };
+
+    log::debug!("[{}] Returning {} instruments", request_id, instruments.len());
+    Ok(ApiResponse::success(instruments).with_request_id(request_id))
+}
+
 /// 获取当前市场状态
 ///
 /// # 返回
@@ -156,6 +198,7 @@ pub async fn market_get_status(
 
     let exchanges = market_service.list_exchanges().await;
     let exchanges_str: Vec<String> = exchanges.iter().map(|e| format!("{:?}", e)).collect();
+    let rate_limits = market_service.rate_limit_usage().await;
 
     let status = MarketStatus {
         connected: !exchanges.is_empty(),
@@ -163,6 +206,7 @@ pub async fn market_get_status(
         exchanges: exchanges_str,
         subscriptions_count: 0, // TODO: Track actual subscription count
         last_update: Some(chrono::Utc::now().timestamp_millis()),
+        rate_limits,
     };
 
     log::debug!(
@@ -224,4 +268,7 @@ pub struct MarketStatus {
     pub subscriptions_count: usize,
     /// 最后更新时间
     pub last_update: Option<i64>,
+    /// 各交易所 REST 限频使用情况
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rate_limits: Vec<RateLimitUsage>,
 }
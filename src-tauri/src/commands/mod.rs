@@ -7,6 +7,8 @@ pub mod strategy_engine;
 pub mod strategy_instance;
 pub mod strategy_debug;
 pub mod trade;
+pub mod portfolio;
+pub mod pnl;
 pub mod risk;
 pub mod emergency;
 pub mod config;
@@ -23,6 +25,7 @@ pub use market::{
     market_subscribe_ticker,
     market_get_klines,
     market_get_symbols,
+    market_get_instruments,
     market_get_status,
     market_unsubscribe_ticker,
 };
@@ -59,14 +62,44 @@ pub use strategy_instance::{
 pub use trade::{
     trade_place_order,
     trade_cancel_order,
+    trade_amend_order,
     trade_get_order,
     trade_get_orders,
     trade_get_open_orders,
+    trade_get_trades,
     trade_sync_order_status,
+    trade_reconcile_orders,
+    trade_start_reconciliation,
+    trade_stop_reconciliation,
     trade_get_positions,
     trade_get_balance,
     trade_cancel_all_orders,
     trade_close_position,
+    trade_place_conditional_order,
+    trade_place_oco_order,
+    trade_cancel_conditional_order,
+    trade_get_conditional_orders,
+    trade_place_algo_order,
+    trade_cancel_algo_order,
+    trade_get_execution_report,
+    trade_get_child_orders,
+    trade_get_order_timeline,
+    trade_check_order,
+};
+pub use portfolio::{
+    portfolio_get,
+    portfolio_snapshot,
+    portfolio_get_history,
+    portfolio_start_snapshots,
+    portfolio_stop_snapshots,
+};
+pub use pnl::{
+    pnl_get_today,
+    pnl_get_statements,
+    pnl_get_equity_history,
+    pnl_record_snapshot,
+    pnl_start_snapshots,
+    pnl_stop_snapshots,
 };
 pub use risk::{
     get_risk_overview,
@@ -0,0 +1,150 @@
+//! PnL commands for Tauri
+//!
+//! Equity history and daily/weekly/monthly PnL statements per user,
+//! exchange account or strategy instance.
+
+use crate::core::response::{ApiResponse, ApiError};
+use crate::infrastructure::Database;
+use crate::services::pnl_service::DEFAULT_EQUITY_SNAPSHOT_INTERVAL;
+use crate::services::{EquitySnapshot, PnlScope, PnlStatement, StatementPeriod};
+use tauri::State;
+
+/// Get today's PnL (UTC day)
+#[tauri::command]
+pub async fn pnl_get_today(
+    db: State<'_, Database>,
+    user_id: String,
+    exchange_id: Option<String>,
+    instance_id: Option<String>,
+) -> Result<ApiResponse<f64>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!(
+        "[{}] pnl_get_today called: user_id={}, exchange_id={:?}, instance_id={:?}",
+        request_id, user_id, exchange_id, instance_id
+    );
+
+    let scope = PnlScope::from_ids(exchange_id, instance_id);
+    match db.get_pnl_service().today_pnl(&user_id, &scope).await {
+        Ok(pnl) => Ok(ApiResponse::success(pnl).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to get today's PnL: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("查询今日盈亏失败")).with_request_id(request_id))
+        }
+    }
+}
+
+/// Get PnL statements for a period granularity ("daily", "weekly", "monthly")
+#[tauri::command]
+pub async fn pnl_get_statements(
+    db: State<'_, Database>,
+    user_id: String,
+    period: String,
+    from: i64,
+    to: Option<i64>,
+    exchange_id: Option<String>,
+    instance_id: Option<String>,
+) -> Result<ApiResponse<Vec<PnlStatement>>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!(
+        "[{}] pnl_get_statements called: user_id={}, period={}, from={}, to={:?}, exchange_id={:?}, instance_id={:?}",
+        request_id, user_id, period, from, to, exchange_id, instance_id
+    );
+
+    let period: StatementPeriod = match period.parse() {
+        Ok(p) => p,
+        Err(_) => return Ok(ApiResponse::error(ApiError::invalid_parameter("period")).with_request_id(request_id)),
+    };
+    let to = to.unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    let scope = PnlScope::from_ids(exchange_id, instance_id);
+
+    match db.get_pnl_service().get_statements(&user_id, &scope, period, from, to).await {
+        Ok(statements) => Ok(ApiResponse::success(statements).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to get PnL statements: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("查询盈亏报表失败")).with_request_id(request_id))
+        }
+    }
+}
+
+/// Get stored equity snapshots, newest first
+#[tauri::command]
+pub async fn pnl_get_equity_history(
+    db: State<'_, Database>,
+    user_id: String,
+    exchange_id: Option<String>,
+    instance_id: Option<String>,
+    since: Option<i64>,
+    limit: Option<usize>,
+) -> Result<ApiResponse<Vec<EquitySnapshot>>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let limit = limit.unwrap_or(1000);
+    log::info!(
+        "[{}] pnl_get_equity_history called: user_id={}, exchange_id={:?}, instance_id={:?}, since={:?}, limit={}",
+        request_id, user_id, exchange_id, instance_id, since, limit
+    );
+
+    let scope = PnlScope::from_ids(exchange_id, instance_id);
+    match db.get_pnl_service().get_equity_history(&user_id, &scope, since, limit).await {
+        Ok(history) => Ok(ApiResponse::success(history).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to get equity history: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("查询权益历史失败")).with_request_id(request_id))
+        }
+    }
+}
+
+/// Record equity snapshots now
+#[tauri::command]
+pub async fn pnl_record_snapshot(
+    db: State<'_, Database>,
+    user_id: String,
+) -> Result<ApiResponse<Vec<EquitySnapshot>>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!("[{}] pnl_record_snapshot called: user_id={}", request_id, user_id);
+
+    match db.get_pnl_service().record_snapshots(&user_id).await {
+        Ok(snapshots) => Ok(ApiResponse::success(snapshots).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to record equity snapshot: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("保存权益快照失败")).with_request_id(request_id))
+        }
+    }
+}
+
+/// Start periodic equity snapshots
+#[tauri::command]
+pub async fn pnl_start_snapshots(
+    db: State<'_, Database>,
+    user_id: String,
+    interval_secs: Option<u64>,
+) -> Result<ApiResponse<()>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!(
+        "[{}] pnl_start_snapshots called: user_id={}, interval_secs={:?}",
+        request_id, user_id, interval_secs
+    );
+
+    let interval = match interval_secs {
+        Some(0) => {
+            return Ok(ApiResponse::error(ApiError::invalid_parameter("interval_secs")).with_request_id(request_id));
+        }
+        Some(secs) => std::time::Duration::from_secs(secs),
+        None => DEFAULT_EQUITY_SNAPSHOT_INTERVAL,
+    };
+
+    db.get_pnl_service().start(&user_id, interval).await;
+    Ok(ApiResponse::success_empty().with_request_id(request_id))
+}
+
+/// Stop periodic equity snapshots
+#[tauri::command]
+pub async fn pnl_stop_snapshots(
+    db: State<'_, Database>,
+    user_id: String,
+) -> Result<ApiResponse<bool>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!("[{}] pnl_stop_snapshots called: user_id={}", request_id, user_id);
+
+    let stopped = db.get_pnl_service().stop(&user_id).await;
+    Ok(ApiResponse::success(stopped).with_request_id(request_id))
+}
@@ -0,0 +1,107 @@
+//! Portfolio commands for Tauri
+//!
+//! Cross-exchange equity, exposure and allocation, plus snapshot history.
+
+use crate::core::response::{ApiResponse, ApiError};
+use crate::infrastructure::Database;
+use crate::services::portfolio_service::DEFAULT_SNAPSHOT_INTERVAL;
+use crate::services::PortfolioSnapshot;
+use tauri::State;
+
+/// Get the current portfolio across all active exchange accounts
+#[tauri::command]
+pub async fn portfolio_get(
+    db: State<'_, Database>,
+    user_id: String,
+) -> Result<ApiResponse<PortfolioSnapshot>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!("[{}] portfolio_get called: user_id={}", request_id, user_id);
+
+    match db.get_portfolio_service().get_portfolio(&user_id).await {
+        Ok(snapshot) => Ok(ApiResponse::success(snapshot).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to get portfolio: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("查询资产组合失败")).with_request_id(request_id))
+        }
+    }
+}
+
+/// Take a portfolio snapshot now and store it in the history
+#[tauri::command]
+pub async fn portfolio_snapshot(
+    db: State<'_, Database>,
+    user_id: String,
+) -> Result<ApiResponse<PortfolioSnapshot>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!("[{}] portfolio_snapshot called: user_id={}", request_id, user_id);
+
+    match db.get_portfolio_service().snapshot(&user_id).await {
+        Ok(snapshot) => Ok(ApiResponse::success(snapshot).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to snapshot portfolio: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("保存资产快照失败")).with_request_id(request_id))
+        }
+    }
+}
+
+/// Get stored portfolio snapshots, newest first
+#[tauri::command]
+pub async fn portfolio_get_history(
+    db: State<'_, Database>,
+    user_id: String,
+    since: Option<i64>,
+    limit: Option<usize>,
+) -> Result<ApiResponse<Vec<PortfolioSnapshot>>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let limit = limit.unwrap_or(500);
+    log::info!(
+        "[{}] portfolio_get_history called: user_id={}, since={:?}, limit={}",
+        request_id, user_id, since, limit
+    );
+
+    match db.get_portfolio_service().get_history(&user_id, since, limit).await {
+        Ok(history) => Ok(ApiResponse::success(history).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to get portfolio history: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("查询资产历史失败")).with_request_id(request_id))
+        }
+    }
+}
+
+/// Start periodic portfolio snapshots
+#[tauri::command]
+pub async fn portfolio_start_snapshots(
+    db: State<'_, Database>,
+    user_id: String,
+    interval_secs: Option<u64>,
+) -> Result<ApiResponse<()>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!(
+        "[{}] portfolio_start_snapshots called: user_id={}, interval_secs={:?}",
+        request_id, user_id, interval_secs
+    );
+
+    let interval = match interval_secs {
+        Some(0) => {
+            return Ok(ApiResponse::error(ApiError::invalid_parameter("interval_secs")).with_request_id(request_id));
+        }
+        Some(secs) => std::time::Duration::from_secs(secs),
+        None => DEFAULT_SNAPSHOT_INTERVAL,
+    };
+
+    db.get_portfolio_service().start(&user_id, interval).await;
+    Ok(ApiResponse::success_empty().with_request_id(request_id))
+}
+
+/// Stop periodic portfolio snapshots
+#[tauri::command]
+pub async fn portfolio_stop_snapshots(
+    db: State<'_, Database>,
+    user_id: String,
+) -> Result<ApiResponse<bool>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!("[{}] portfolio_stop_snapshots called: user_id={}", request_id, user_id);
+
+    let stopped = db.get_portfolio_service().stop(&user_id).await;
+    Ok(ApiResponse::success(stopped).with_request_id(request_id))
+}
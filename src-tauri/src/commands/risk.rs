@@ -3,13 +3,21 @@
 //! This module provides Tauri command handlers for risk management operations.
 
 use crate::core::response::{ApiResponse, ApiError};
+use crate::core::risk::{
+    build_rule, parse_action, replay_rules, ConcentrationLimits, DeadManConfig, PreTradeLimits, ReplayPoint,
+    ReplayReport, RiskRule, SlippageLimits, DEAD_MAN_SWITCH_RULE, HALT_TRADING_PARAM, PRE_TRADE_RULE,
+    SLIPPAGE_GUARD_RULE,
+};
 use crate::infrastructure::Database;
 use crate::repository::risk_alert_repo::RiskAlertRepository;
+use crate::repository::risk_rule_repo::{RiskRule as RiskRuleRow, RiskRuleRepository};
+use crate::services::{BacktestService, PnlScope};
 use anyhow::Result;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Row};
 use std::collections::HashMap;
+use std::sync::Arc;
 use tauri::State;
 
 /// Risk overview data for dashboard
@@ -82,37 +90,15 @@ pub async fn get_risk_overview(
         .sum();
 
     // ========== 2. 计算今日 P&L ==========
-    // 从已成交订单计算今日盈亏
-    let today_start = Utc::now()
-        .date_naive()
-        .and_hms_opt(0, 0, 0)
-        .unwrap()
-        .and_utc()
-        .timestamp();
-
-    let pnl_query = r#"
-        SELECT
-            SUM(
-                CASE
-                    WHEN side = 'BUY' THEN -1 * filled_quantity * avg_price
-                    ELSE filled_quantity * avg_price
-                END
-            ) AS today_pnl
-        FROM orders
-        WHERE user_id = ?
-            AND status IN ('filled', 'partially_filled')
-            AND filled_at >= ?
-    "#;
-
-    let today_pnl: f64 = match sqlx::query(pnl_query)
-        .bind(&uid)
-        .bind(today_start)
-        .fetch_one(&pool)
+    // 今日成交的已实现盈亏 + 未实现盈亏的变化
+    let today_pnl = db
+        .get_pnl_service()
+        .today_pnl(&uid, &PnlScope::User)
         .await
-        {
-            Ok(row) => row.get::<Option<f64>, _>("today_pnl").unwrap_or(0.0),
-            Err(_) => 0.0,
-        };
+        .unwrap_or_else(|e| {
+            log::warn!("[{}] Failed to compute today's PnL: {}", request_id, e);
+            0.0
+        });
 
     // ========== 3. 获取账户余额 ==========
     // 从 positions 表的 realized_pnl 计算可用余额
@@ -359,7 +345,7 @@ pub async fn update_risk_rule(
     let request_id = uuid::Uuid::new_v4().to_string();
 
     // Validate action
-    if !["warning", "stop_strategy", "emergency_close"].contains(&config.action.as_str()) {
+    if !["warning", "stop_strategy", "emergency_close", "reject"].contains(&config.action.as_str()) {
         return Ok(ApiResponse::error(ApiError::invalid_parameter("action")).with_request_id(request_id));
     }
 
@@ -368,7 +354,12 @@ pub async fn update_risk_rule(
         return Ok(ApiResponse::error(ApiError::validation_failed(&rule_name, e)).with_request_id(request_id));
     }
 
+    // Build the rule as it would be saved, so a row the monitor can't load is refused
     let repo = db.risk_rule_repo();
+    if let Err(e) = build_edited_rule(&repo, &rule_name, &config).await {
+        return Ok(ApiResponse::error(ApiError::validation_failed(&rule_name, e)).with_request_id(request_id));
+    }
+
     match repo.update(
         &rule_name,
         config.enabled,
@@ -381,6 +372,11 @@ pub async fn update_risk_rule(
                 "[{}] Updated risk rule: {} (enabled: {}, action: {})",
                 request_id, rule_name, config.enabled, config.action
             );
+            // 立即生效，无需重启
+            if let Err(e) = db.reload_risk_rule(&rule_name).await {
+                log::error!("[{}] Failed to reload risk rules: {}", request_id, e);
+                return Ok(ApiResponse::error(ApiError::operation_failed("风控规则已保存，但重新加载失败")).with_request_id(request_id));
+            }
             Ok(ApiResponse::success_empty().with_request_id(request_id))
         }
         Err(e) => {
@@ -390,8 +386,38 @@ pub async fn update_risk_rule(
     }
 }
 
+/// Build the rule `rule_name` would become with `config`
+async fn build_edited_rule(
+    repo: &RiskRuleRepository,
+    rule_name: &str,
+    config: &FrontendRiskRuleConfig,
+) -> Result<(), String> {
+    let row = repo
+        .find_by_name(rule_name)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Unknown risk rule: {}", rule_name))?;
+    let edited = RiskRuleRow {
+        enabled: config.enabled,
+        action: config.action.clone(),
+        notify_methods: serde_json::to_string(&config.notify_methods).map_err(|e| e.to_string())?,
+        params_json: serde_json::to_string(&config.params).map_err(|e| e.to_string())?,
+        ..row
+    };
+    build_rule(&edited, None).map(|_| ()).map_err(|e| e.to_string())
+}
+
 /// Validate rule parameters based on rule type
 fn validate_rule_params(rule_name: &str, params: &HashMap<String, f64>) -> Result<(), String> {
+    // Any monitored rule may opt in to halting all trading
+    let mut params = params.clone();
+    if let Some(halt_trading) = params.remove(HALT_TRADING_PARAM) {
+        if halt_trading != 0.0 && halt_trading != 1.0 {
+            return Err(format!("{} must be 0 or 1", HALT_TRADING_PARAM));
+        }
+    }
+    let params = &params;
+
     match rule_name {
         "position_limit" => {
             let max_position = params
@@ -421,6 +447,35 @@ fn validate_rule_params(rule_name: &str, params: &HashMap<String, f64>) -> Resul
                 return Err("max_drawdown_pct must be between 0 and 100".to_string());
             }
         }
+        "var_limit" => {
+            let max_var = params.get("max_var_pct").ok_or("Missing max_var_pct")?;
+            if *max_var <= 0.0 || *max_var > 100.0 {
+                return Err("max_var_pct must be between 0 and 100".to_string());
+            }
+            if let Some(confidence) = params.get("confidence") {
+                if *confidence <= 0.5 || *confidence >= 1.0 {
+                    return Err("confidence must be between 0.5 and 1".to_string());
+                }
+            }
+            if params.get("max_cvar_pct").is_some_and(|v| *v < 0.0) {
+                return Err("max_cvar_pct must not be negative".to_string());
+            }
+            if params.get("lookback").is_some_and(|v| *v < 2.0) {
+                return Err("lookback must be at least 2".to_string());
+            }
+        }
+        "concentration_limit" => {
+            ConcentrationLimits::from_params(params)?;
+        }
+        PRE_TRADE_RULE => {
+            PreTradeLimits::from_params(params)?;
+        }
+        SLIPPAGE_GUARD_RULE => {
+            SlippageLimits::from_params(params)?;
+        }
+        DEAD_MAN_SWITCH_RULE => {
+            DeadManConfig::from_params(params)?;
+        }
         _ => {
             return Err(format!("Unknown rule type: {}", rule_name));
         }
@@ -745,6 +800,165 @@ pub async fn delete_alert(
     }
 }
 
+/// History a rule replay runs over
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ReplaySource {
+    /// A finished backtest job
+    #[serde(rename_all = "camelCase")]
+    Backtest { job_id: String },
+    /// Stored equity snapshots
+    #[serde(rename_all = "camelCase")]
+    EquityHistory {
+        exchange_id: Option<String>,
+        instance_id: Option<String>,
+        from: i64,
+        to: Option<i64>,
+    },
+    /// Realised PnL of recorded fills, starting from `initial_equity`
+    #[serde(rename_all = "camelCase")]
+    TradeHistory {
+        exchange_id: Option<String>,
+        instance_id: Option<String>,
+        from: i64,
+        to: Option<i64>,
+        initial_equity: f64,
+    },
+}
+
+/// Candidate settings to replay a rule with instead of its stored ones
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleReplayOverride {
+    pub action: Option<String>,
+    /// Replaces the stored values of the given keys
+    #[serde(default)]
+    pub params: HashMap<String, f64>,
+}
+
+/// Replay risk rules over historical data
+///
+/// Reports when each rule would have fired and with which action. Rules
+/// are replayed whether or not they are enabled.
+///
+/// # Arguments
+/// * `source` - Backtest, equity history or trade history to replay
+/// * `rules` - Rule names to replay; all rules when omitted
+/// * `overrides` - Candidate action/params per rule name
+#[tauri::command]
+pub async fn risk_replay_rules(
+    db: State<'_, Database>,
+    backtest_service: State<'_, Arc<BacktestService>>,
+    user_id: String,
+    source: ReplaySource,
+    rules: Option<Vec<String>>,
+    overrides: Option<HashMap<String, RuleReplayOverride>>,
+) -> Result<ApiResponse<ReplayReport>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!(
+        "[{}] risk_replay_rules called: user_id={}, source={:?}, rules={:?}",
+        request_id, user_id, source, rules
+    );
+
+    let rows = match db.risk_rule_repo().find_all().await {
+        Ok(rows) => rows,
+        Err(e) => {
+            log::error!("[{}] Failed to fetch risk rules: {}", request_id, e);
+            return Ok(ApiResponse::error(ApiError::operation_failed("查询风控规则失败")).with_request_id(request_id));
+        }
+    };
+    let replayed = match replay_rule_set(rows, rules.as_deref(), &overrides.unwrap_or_default(), Some(&db.pool)) {
+        Ok(rules) => rules,
+        Err(e) => {
+            return Ok(ApiResponse::error(ApiError::validation_failed("overrides", e)).with_request_id(request_id));
+        }
+    };
+
+    let points = match source {
+        ReplaySource::Backtest { job_id } => {
+            match backtest_service.get_job(&job_id).await.and_then(|job| job.result) {
+                Some(result) => ReplayPoint::from_backtest(&result),
+                None => return Ok(ApiResponse::error(ApiError::not_found("回测结果")).with_request_id(request_id)),
+            }
+        }
+        ReplaySource::EquityHistory { exchange_id, instance_id, from, to } => {
+            let to = to.unwrap_or_else(|| Utc::now().timestamp_millis());
+            let scope = PnlScope::from_ids(exchange_id, instance_id);
+            match db.get_pnl_service().get_snapshots(&user_id, &scope, from, to).await {
+                Ok(snapshots) => ReplayPoint::from_equity(snapshots.iter().map(|s| (s.timestamp, s.equity))),
+                Err(e) => {
+                    log::error!("[{}] Failed to load equity history: {}", request_id, e);
+                    return Ok(ApiResponse::error(ApiError::operation_failed("查询权益历史失败")).with_request_id(request_id));
+                }
+            }
+        }
+        ReplaySource::TradeHistory { exchange_id, instance_id, from, to, initial_equity } => {
+            let to = to.unwrap_or_else(|| Utc::now().timestamp_millis());
+            let scope = PnlScope::from_ids(exchange_id, instance_id);
+            match db.get_pnl_service().get_realized_fills(&user_id, &scope, from, to).await {
+                Ok(fills) => ReplayPoint::from_fills(initial_equity, &fills),
+                Err(e) => {
+                    log::error!("[{}] Failed to load trade history: {}", request_id, e);
+                    return Ok(ApiResponse::error(ApiError::operation_failed("查询成交历史失败")).with_request_id(request_id));
+                }
+            }
+        }
+    };
+
+    match replay_rules(&replayed, &points).await {
+        Ok(report) => {
+            log::info!(
+                "[{}] Replayed {} rules over {} points: {} triggers",
+                request_id, report.rules.len(), report.points, report.triggers.len()
+            );
+            Ok(ApiResponse::success(report).with_request_id(request_id))
+        }
+        Err(e) => {
+            log::error!("[{}] Failed to replay risk rules: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("风控规则回放失败")).with_request_id(request_id))
+        }
+    }
+}
+
+/// Build the rules to replay from their stored rows
+///
+/// Selected rows are enabled and overridden before building; rows that are
+/// not monitored rules (pre-trade limits, slippage guard) are left out.
+fn replay_rule_set(
+    rows: Vec<RiskRuleRow>,
+    names: Option<&[String]>,
+    overrides: &HashMap<String, RuleReplayOverride>,
+    pool: Option<&sqlx::SqlitePool>,
+) -> Result<Vec<Box<dyn RiskRule>>, String> {
+    if let Some(unknown) = overrides.keys().find(|name| !rows.iter().any(|row| &row.name == *name)) {
+        return Err(format!("unknown rule '{}'", unknown));
+    }
+
+    let mut rules = Vec::new();
+    for mut row in rows {
+        if names.is_some_and(|names| !names.contains(&row.name)) {
+            continue;
+        }
+        row.enabled = true;
+        if let Some(candidate) = overrides.get(&row.name) {
+            if let Some(action) = &candidate.action {
+                if parse_action(action).is_err() {
+                    return Err(format!("invalid action '{}' for rule '{}'", action, row.name));
+                }
+                row.action = action.clone();
+            }
+            let mut params = row.get_params().map_err(|e| e.to_string())?;
+            params.extend(candidate.params.iter().map(|(key, value)| (key.clone(), *value)));
+            validate_rule_params(&row.name, &params)?;
+            row.params_json = serde_json::to_string(&params).map_err(|e| e.to_string())?;
+        }
+        if let Some(rule) = build_rule(&row, pool).map_err(|e| format!("rule '{}': {}", row.name, e))? {
+            rules.push(rule);
+        }
+    }
+    Ok(rules)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -791,6 +1005,116 @@ mod tests {
         assert!(validate_rule_params("drawdown_limit", &params).is_err());
     }
 
+    #[test]
+    fn test_validate_var_limit_params() {
+        let mut params = HashMap::new();
+        params.insert("max_var_pct".to_string(), 5.0);
+        params.insert("confidence".to_string(), 0.99);
+
+        assert!(validate_rule_params("var_limit", &params).is_ok());
+
+        params.insert("confidence".to_string(), 1.0);
+        assert!(validate_rule_params("var_limit", &params).is_err());
+    }
+
+    #[test]
+    fn test_validate_concentration_limit_params() {
+        let mut params = HashMap::new();
+        params.insert("asset.max_ratio".to_string(), 0.5);
+        params.insert("group.memecoins.max_ratio".to_string(), 0.1);
+        params.insert("group.memecoins.DOGE".to_string(), 1.0);
+
+        assert!(validate_rule_params("concentration_limit", &params).is_ok());
+
+        params.remove("group.memecoins.DOGE");
+        assert!(validate_rule_params("concentration_limit", &params).is_err());
+    }
+
+    #[test]
+    fn test_validate_slippage_guard_params() {
+        let mut params = HashMap::new();
+        params.insert("max_slippage_bps".to_string(), 30.0);
+        params.insert("downsize".to_string(), 1.0);
+
+        assert!(validate_rule_params(SLIPPAGE_GUARD_RULE, &params).is_ok());
+
+        params.insert("max_book_share".to_string(), 2.0);
+        assert!(validate_rule_params(SLIPPAGE_GUARD_RULE, &params).is_err());
+    }
+
+    #[test]
+    fn test_validate_halt_trading_param() {
+        let mut params = HashMap::new();
+        params.insert("max_drawdown_pct".to_string(), 15.0);
+        params.insert(HALT_TRADING_PARAM.to_string(), 1.0);
+        assert!(validate_rule_params("drawdown_limit", &params).is_ok());
+
+        params.insert(HALT_TRADING_PARAM.to_string(), 2.0);
+        assert!(validate_rule_params("drawdown_limit", &params).is_err());
+    }
+
+    #[test]
+    fn test_validate_dead_man_switch_params() {
+        let mut params = HashMap::new();
+        params.insert("timeout_secs".to_string(), 60.0);
+        params.insert("heartbeat_secs".to_string(), 15.0);
+
+        assert!(validate_rule_params(DEAD_MAN_SWITCH_RULE, &params).is_ok());
+
+        params.insert("heartbeat_secs".to_string(), 90.0);
+        assert!(validate_rule_params(DEAD_MAN_SWITCH_RULE, &params).is_err());
+    }
+
+    fn rule_row(name: &str, action: &str, params_json: &str) -> RiskRuleRow {
+        RiskRuleRow {
+            id: 1,
+            name: name.to_string(),
+            display_name: name.to_string(),
+            description: String::new(),
+            rule_type: name.to_string(),
+            enabled: false,
+            action: action.to_string(),
+            notify_methods: r#"["log"]"#.to_string(),
+            params_json: params_json.to_string(),
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_replay_rule_set() {
+        let rows = || {
+            vec![
+                rule_row("drawdown_limit", "warning", r#"{"max_drawdown_pct": 10.0}"#),
+                rule_row("daily_loss_limit", "warning", r#"{"max_daily_loss": 500.0}"#),
+                rule_row(PRE_TRADE_RULE, "reject", "{}"),
+            ]
+        };
+
+        let mut overrides = HashMap::new();
+        overrides.insert(
+            "drawdown_limit".to_string(),
+            RuleReplayOverride {
+                action: Some("stop_strategy".to_string()),
+                params: HashMap::from([("max_drawdown_pct".to_string(), 5.0)]),
+            },
+        );
+        let rules = replay_rule_set(rows(), None, &overrides, None).unwrap();
+        assert_eq!(rules.len(), 2);
+        assert!(rules.iter().all(|rule| rule.config().enabled));
+        assert_eq!(rules[0].config().action, crate::core::risk::RiskAction::PauseStrategy);
+
+        let names = vec!["daily_loss_limit".to_string()];
+        let rules = replay_rule_set(rows(), Some(&names), &HashMap::new(), None).unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].name(), "daily_loss_limit");
+
+        overrides.get_mut("drawdown_limit").unwrap().params.insert("max_drawdown_pct".to_string(), 150.0);
+        assert!(replay_rule_set(rows(), None, &overrides, None).is_err());
+        let unknown = HashMap::from([("no_such_rule".to_string(), RuleReplayOverride::default())]);
+        assert!(replay_rule_set(rows(), None, &unknown, None).is_err());
+    }
+
     #[test]
     fn test_alert_history_filter() {
         let filter = AlertHistoryFilter {
@@ -3,8 +3,17 @@
 //! This module provides Tauri command handlers for trading operations.
 
 use crate::core::response::{ApiResponse, ApiError};
+use crate::core::risk::{OrderOrigin, PreTradeRejection};
+use crate::core::trade::execution::{AlgoOrderRequest, ExecutionReport};
+use crate::core::trade::order::{
+    ConditionalOrder, ConditionalOrderEngine, ConditionalOrderRequest, ConditionalStatus, OrderTimeline,
+    ReconcileReport,
+};
 use crate::core::trade::types::*;
 use crate::infrastructure::Database;
+use crate::services::order_reconciler::DEFAULT_RECONCILE_INTERVAL;
+use crate::services::TradeService;
+use std::sync::Arc;
 use tauri::State;
 use serde::{Deserialize, Serialize};
 
@@ -47,22 +56,56 @@ impl TryFrom<PlaceOrderRequest> for OrderRequest {
     }
 }
 
+/// Resolve the TradeService for a user's exchange account
+///
+/// Without `exchange_id` the user's default account is used.
+async fn resolve_trade_service<T>(
+    db: &Database,
+    user_id: &str,
+    exchange_id: Option<&str>,
+    request_id: &str,
+) -> Result<Arc<TradeService>, ApiResponse<T>> {
+    db.get_trade_service_for_user(user_id, exchange_id).await.map_err(|e| {
+        log::error!("[{}] Failed to resolve exchange account {:?}: {}", request_id, exchange_id, e);
+        ApiResponse::error(ApiError::operation_failed(format!("交易所账户不可用: {}", e)))
+            .with_request_id(request_id.to_string())
+    })
+}
+
+/// Resolve the TradeService for the exchange account an order was placed on
+async fn resolve_order_trade_service<T>(
+    db: &Database,
+    user_id: &str,
+    order_id: &str,
+    request_id: &str,
+) -> Result<Arc<TradeService>, ApiResponse<T>> {
+    db.get_trade_service_for_order(user_id, order_id).await.map_err(|e| {
+        log::error!("[{}] Failed to resolve exchange account for order {}: {}", request_id, order_id, e);
+        ApiResponse::error(ApiError::operation_failed(format!("交易所账户不可用: {}", e)))
+            .with_request_id(request_id.to_string())
+    })
+}
+
 /// Place a new order
 #[tauri::command]
 pub async fn trade_place_order(
     db: State<'_, Database>,
     user_id: String,
+    exchange_id: Option<String>,
     request: PlaceOrderRequest,
 ) -> Result<ApiResponse<Order>, String> {
     let request_id = uuid::Uuid::new_v4().to_string();
-    log::info!("[{}] trade_place_order called: user_id={}", request_id, user_id);
+    log::info!("[{}] trade_place_order called: user_id={}, exchange_id={:?}", request_id, user_id, exchange_id);
 
     // Convert request to internal format
     let order_request: OrderRequest = request.try_into()
         .map_err(|e| format!("Invalid request: {}", e))?;
 
     // Get TradeService and place the order
-    let trade_service = db.get_trade_service().await;
+    let trade_service = match resolve_trade_service(&db, &user_id, exchange_id.as_deref(), &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
     match trade_service.place_order(order_request, &user_id).await {
         Ok(order) => {
             log::info!("[{}] Order placed successfully: {}", request_id, order.id);
@@ -85,7 +128,10 @@ pub async fn trade_cancel_order(
     let request_id = uuid::Uuid::new_v4().to_string();
     log::info!("[{}] trade_cancel_order called: user_id={}, order_id={}", request_id, user_id, order_id);
 
-    let trade_service = db.get_trade_service().await;
+    let trade_service = match resolve_order_trade_service(&db, &user_id, &order_id, &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
     match trade_service.cancel_order(&order_id, &user_id).await {
         Ok(()) => {
             log::info!("[{}] Order canceled successfully: {}", request_id, order_id);
@@ -98,6 +144,37 @@ pub async fn trade_cancel_order(
     }
 }
 
+/// Amend the price and/or quantity of a resting order
+#[tauri::command]
+pub async fn trade_amend_order(
+    db: State<'_, Database>,
+    user_id: String,
+    order_id: String,
+    price: Option<f64>,
+    quantity: Option<f64>,
+) -> Result<ApiResponse<Order>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!(
+        "[{}] trade_amend_order called: user_id={}, order_id={}, price={:?}, quantity={:?}",
+        request_id, user_id, order_id, price, quantity
+    );
+
+    let trade_service = match resolve_order_trade_service(&db, &user_id, &order_id, &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
+    match trade_service.amend_order(&order_id, &user_id, AmendRequest { price, quantity }).await {
+        Ok(order) => {
+            log::info!("[{}] Order amended successfully: {}", request_id, order_id);
+            Ok(ApiResponse::success(order).with_request_id(request_id))
+        }
+        Err(e) => {
+            log::error!("[{}] Failed to amend order: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed(format!("改单失败: {}", e))).with_request_id(request_id))
+        }
+    }
+}
+
 /// Get order by ID
 #[tauri::command]
 pub async fn trade_get_order(
@@ -108,7 +185,10 @@ pub async fn trade_get_order(
     let request_id = uuid::Uuid::new_v4().to_string();
     log::info!("[{}] trade_get_order called: user_id={}, order_id={}", request_id, user_id, order_id);
 
-    let trade_service = db.get_trade_service().await;
+    let trade_service = match resolve_order_trade_service(&db, &user_id, &order_id, &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
     match trade_service.get_order(&order_id, &user_id).await {
         Ok(order) => Ok(ApiResponse::success(order).with_request_id(request_id)),
         Err(e) => {
@@ -123,6 +203,7 @@ pub async fn trade_get_order(
 pub async fn trade_get_orders(
     db: State<'_, Database>,
     user_id: String,
+    exchange_id: Option<String>,
     symbol: Option<String>,
     status: Option<String>,
     limit: Option<usize>,
@@ -130,11 +211,14 @@ pub async fn trade_get_orders(
     let request_id = uuid::Uuid::new_v4().to_string();
     let limit = limit.unwrap_or(100);
     log::info!(
-        "[{}] trade_get_orders called: user_id={}, symbol={:?}, status={:?}, limit={}",
-        request_id, user_id, symbol, status, limit
+        "[{}] trade_get_orders called: user_id={}, exchange_id={:?}, symbol={:?}, status={:?}, limit={}",
+        request_id, user_id, exchange_id, symbol, status, limit
     );
 
-    let trade_service = db.get_trade_service().await;
+    let trade_service = match resolve_trade_service(&db, &user_id, exchange_id.as_deref(), &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
     let order_status = status.as_ref().and_then(|s| s.parse().ok());
     match trade_service.get_orders(&user_id, symbol.as_deref(), order_status, limit).await {
         Ok(orders) => Ok(ApiResponse::success(orders).with_request_id(request_id)),
@@ -150,11 +234,15 @@ pub async fn trade_get_orders(
 pub async fn trade_get_open_orders(
     db: State<'_, Database>,
     user_id: String,
+    exchange_id: Option<String>,
 ) -> Result<ApiResponse<Vec<Order>>, String> {
     let request_id = uuid::Uuid::new_v4().to_string();
-    log::info!("[{}] trade_get_open_orders called: user_id={}", request_id, user_id);
+    log::info!("[{}] trade_get_open_orders called: user_id={}, exchange_id={:?}", request_id, user_id, exchange_id);
 
-    let trade_service = db.get_trade_service().await;
+    let trade_service = match resolve_trade_service(&db, &user_id, exchange_id.as_deref(), &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
     match trade_service.get_open_orders(&user_id).await {
         Ok(orders) => Ok(ApiResponse::success(orders).with_request_id(request_id)),
         Err(e) => {
@@ -164,6 +252,35 @@ pub async fn trade_get_open_orders(
     }
 }
 
+/// Get recorded fills with realised PnL and commission
+#[tauri::command]
+pub async fn trade_get_trades(
+    db: State<'_, Database>,
+    user_id: String,
+    exchange_id: Option<String>,
+    symbol: Option<String>,
+    limit: Option<usize>,
+) -> Result<ApiResponse<Vec<TradeRecord>>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let limit = limit.unwrap_or(100);
+    log::info!(
+        "[{}] trade_get_trades called: user_id={}, exchange_id={:?}, symbol={:?}, limit={}",
+        request_id, user_id, exchange_id, symbol, limit
+    );
+
+    let trade_service = match resolve_trade_service(&db, &user_id, exchange_id.as_deref(), &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
+    match trade_service.get_trades(&user_id, symbol.as_deref(), limit).await {
+        Ok(trades) => Ok(ApiResponse::success(trades).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to get trades: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("查询成交记录失败")).with_request_id(request_id))
+        }
+    }
+}
+
 /// Sync order status from exchange
 #[tauri::command]
 pub async fn trade_sync_order_status(
@@ -177,7 +294,10 @@ pub async fn trade_sync_order_status(
         request_id, user_id, order_id
     );
 
-    let trade_service = db.get_trade_service().await;
+    let trade_service = match resolve_order_trade_service(&db, &user_id, &order_id, &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
     match trade_service.sync_order_status(&order_id, &user_id).await {
         Ok(order) => Ok(ApiResponse::success(order).with_request_id(request_id)),
         Err(e) => {
@@ -187,16 +307,81 @@ pub async fn trade_sync_order_status(
     }
 }
 
+/// Reconcile all active orders with the exchange once
+#[tauri::command]
+pub async fn trade_reconcile_orders(
+    db: State<'_, Database>,
+    user_id: String,
+) -> Result<ApiResponse<ReconcileReport>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!("[{}] trade_reconcile_orders called: user_id={}", request_id, user_id);
+
+    let reconciler = db.get_order_reconciler().await;
+    match reconciler.reconcile_now(&user_id).await {
+        Ok(report) => Ok(ApiResponse::success(report).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to reconcile orders: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed(format!("订单对账失败: {}", e))).with_request_id(request_id))
+        }
+    }
+}
+
+/// Start periodic order reconciliation
+#[tauri::command]
+pub async fn trade_start_reconciliation(
+    db: State<'_, Database>,
+    user_id: String,
+    interval_secs: Option<u64>,
+) -> Result<ApiResponse<()>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!(
+        "[{}] trade_start_reconciliation called: user_id={}, interval_secs={:?}",
+        request_id, user_id, interval_secs
+    );
+
+    let interval = match interval_secs {
+        Some(0) => {
+            return Ok(ApiResponse::error(ApiError::invalid_parameter("interval_secs")).with_request_id(request_id));
+        }
+        Some(secs) => std::time::Duration::from_secs(secs),
+        None => DEFAULT_RECONCILE_INTERVAL,
+    };
+
+    let reconciler = db.get_order_reconciler().await;
+    reconciler.start(&user_id, interval).await;
+
+    Ok(ApiResponse::success_empty().with_request_id(request_id))
+}
+
+/// Stop periodic order reconciliation
+#[tauri::command]
+pub async fn trade_stop_reconciliation(
+    db: State<'_, Database>,
+    user_id: String,
+) -> Result<ApiResponse<bool>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!("[{}] trade_stop_reconciliation called: user_id={}", request_id, user_id);
+
+    let reconciler = db.get_order_reconciler().await;
+    let stopped = reconciler.stop(&user_id).await;
+
+    Ok(ApiResponse::success(stopped).with_request_id(request_id))
+}
+
 /// Get current positions
 #[tauri::command]
 pub async fn trade_get_positions(
     db: State<'_, Database>,
     user_id: String,
+    exchange_id: Option<String>,
 ) -> Result<ApiResponse<Vec<Position>>, String> {
     let request_id = uuid::Uuid::new_v4().to_string();
-    log::info!("[{}] trade_get_positions called: user_id={}", request_id, user_id);
+    log::info!("[{}] trade_get_positions called: user_id={}, exchange_id={:?}", request_id, user_id, exchange_id);
 
-    let trade_service = db.get_trade_service().await;
+    let trade_service = match resolve_trade_service(&db, &user_id, exchange_id.as_deref(), &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
     match trade_service.get_positions(&user_id).await {
         Ok(positions) => Ok(ApiResponse::success(positions).with_request_id(request_id)),
         Err(e) => {
@@ -210,11 +395,22 @@ pub async fn trade_get_positions(
 #[tauri::command]
 pub async fn trade_get_balance(
     db: State<'_, Database>,
+    user_id: Option<String>,
+    exchange_id: Option<String>,
 ) -> Result<ApiResponse<Vec<Balance>>, String> {
     let request_id = uuid::Uuid::new_v4().to_string();
-    log::info!("[{}] trade_get_balance called", request_id);
+    log::info!(
+        "[{}] trade_get_balance called: user_id={:?}, exchange_id={:?}",
+        request_id, user_id, exchange_id
+    );
 
-    let trade_service = db.get_trade_service().await;
+    let Some(user_id) = user_id else {
+        return Ok(ApiResponse::error(ApiError::invalid_parameter("user_id")).with_request_id(request_id));
+    };
+    let trade_service = match resolve_trade_service(&db, &user_id, exchange_id.as_deref(), &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
     match trade_service.get_balance().await {
         Ok(balance) => Ok(ApiResponse::success(balance).with_request_id(request_id)),
         Err(e) => {
@@ -229,34 +425,29 @@ pub async fn trade_get_balance(
 pub async fn trade_cancel_all_orders(
     db: State<'_, Database>,
     user_id: String,
+    exchange_id: Option<String>,
     symbol: Option<String>,
 ) -> Result<ApiResponse<usize>, String> {
     let request_id = uuid::Uuid::new_v4().to_string();
     log::info!(
-        "[{}] trade_cancel_all_orders called: user_id={}, symbol={:?}",
-        request_id, user_id, symbol
+        "[{}] trade_cancel_all_orders called: user_id={}, exchange_id={:?}, symbol={:?}",
+        request_id, user_id, exchange_id, symbol
     );
 
-    let trade_service = db.get_trade_service().await;
+    let trade_service = match resolve_trade_service(&db, &user_id, exchange_id.as_deref(), &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
 
-    // Get all open orders and cancel them one by one
-    let open_orders = match trade_service.get_open_orders(&user_id).await {
-        Ok(orders) => orders,
+    // Cancel-all per symbol on the exchange, batch cancel as fallback
+    let canceled_count = match trade_service.cancel_all_orders(&user_id, symbol.as_deref()).await {
+        Ok(count) => count,
         Err(e) => {
-            log::error!("[{}] Failed to get open orders: {}", request_id, e);
-            return Ok(ApiResponse::error(ApiError::operation_failed("查询挂单失败")).with_request_id(request_id));
+            log::error!("[{}] Failed to cancel orders: {}", request_id, e);
+            return Ok(ApiResponse::error(ApiError::operation_failed("撤销挂单失败")).with_request_id(request_id));
         }
     };
 
-    let mut canceled_count = 0;
-    for order in open_orders {
-        if symbol.as_ref().is_none_or(|s| order.symbol == *s)
-            && trade_service.cancel_order(&order.id, &user_id).await.is_ok()
-        {
-            canceled_count += 1;
-        }
-    }
-
     log::info!("[{}] Canceled {} orders", request_id, canceled_count);
     Ok(ApiResponse::success(canceled_count).with_request_id(request_id))
 }
@@ -266,17 +457,21 @@ pub async fn trade_cancel_all_orders(
 pub async fn trade_close_position(
     db: State<'_, Database>,
     user_id: String,
+    exchange_id: Option<String>,
     symbol: String,
     side: String,
     quantity: Option<f64>,
 ) -> Result<ApiResponse<f64>, String> {
     let request_id = uuid::Uuid::new_v4().to_string();
     log::info!(
-        "[{}] trade_close_position called: user_id={}, symbol={}, side={}, quantity={:?}",
-        request_id, user_id, symbol, side, quantity
+        "[{}] trade_close_position called: user_id={}, exchange_id={:?}, symbol={}, side={}, quantity={:?}",
+        request_id, user_id, exchange_id, symbol, side, quantity
     );
 
-    let trade_service = db.get_trade_service().await;
+    let trade_service = match resolve_trade_service(&db, &user_id, exchange_id.as_deref(), &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
 
     // Get current positions
     let positions = match trade_service.get_positions(&user_id).await {
@@ -324,7 +519,7 @@ pub async fn trade_close_position(
         price: None,
         stop_price: None,
         quantity: close_qty,
-        client_order_id: Some(format!("close_{}", uuid::Uuid::new_v4())),
+        client_order_id: Some(OrderRequest::new_client_order_id("close")),
         time_in_force: None,
     };
 
@@ -341,6 +536,299 @@ pub async fn trade_close_position(
     }
 }
 
+/// Resolve the conditional order engine for a user's exchange account
+async fn resolve_conditional_engine<T>(
+    db: &Database,
+    user_id: &str,
+    exchange_id: Option<&str>,
+    request_id: &str,
+) -> Result<Arc<ConditionalOrderEngine>, ApiResponse<T>> {
+    db.get_conditional_order_engine(user_id, exchange_id).await.map_err(|e| {
+        log::error!("[{}] Failed to resolve exchange account {:?}: {}", request_id, exchange_id, e);
+        ApiResponse::error(ApiError::operation_failed(format!("交易所账户不可用: {}", e)))
+            .with_request_id(request_id.to_string())
+    })
+}
+
+/// Place a client-side conditional order (stop, take-profit or trailing stop)
+#[tauri::command]
+pub async fn trade_place_conditional_order(
+    db: State<'_, Database>,
+    user_id: String,
+    exchange_id: Option<String>,
+    request: ConditionalOrderRequest,
+) -> Result<ApiResponse<ConditionalOrder>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!(
+        "[{}] trade_place_conditional_order called: user_id={}, exchange_id={:?}, symbol={}, kind={}",
+        request_id, user_id, exchange_id, request.symbol, request.trigger.kind()
+    );
+
+    let engine = match resolve_conditional_engine(&db, &user_id, exchange_id.as_deref(), &request_id).await {
+        Ok(engine) => engine,
+        Err(response) => return Ok(response),
+    };
+    match engine.place(&user_id, request).await {
+        Ok(order) => {
+            log::info!("[{}] Conditional order placed: {}", request_id, order.id);
+            Ok(ApiResponse::success(order).with_request_id(request_id))
+        }
+        Err(e) => {
+            log::error!("[{}] Failed to place conditional order: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed(format!("条件单下单失败: {}", e))).with_request_id(request_id))
+        }
+    }
+}
+
+/// Place an OCO pair; when one leg triggers the other is canceled
+#[tauri::command]
+pub async fn trade_place_oco_order(
+    db: State<'_, Database>,
+    user_id: String,
+    exchange_id: Option<String>,
+    first: ConditionalOrderRequest,
+    second: ConditionalOrderRequest,
+) -> Result<ApiResponse<Vec<ConditionalOrder>>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!(
+        "[{}] trade_place_oco_order called: user_id={}, exchange_id={:?}, symbol={}",
+        request_id, user_id, exchange_id, first.symbol
+    );
+
+    let engine = match resolve_conditional_engine(&db, &user_id, exchange_id.as_deref(), &request_id).await {
+        Ok(engine) => engine,
+        Err(response) => return Ok(response),
+    };
+    match engine.place_oco(&user_id, first, second).await {
+        Ok(legs) => Ok(ApiResponse::success(legs).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to place OCO order: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed(format!("OCO下单失败: {}", e))).with_request_id(request_id))
+        }
+    }
+}
+
+/// Cancel a pending conditional order (both legs of an OCO pair)
+#[tauri::command]
+pub async fn trade_cancel_conditional_order(
+    db: State<'_, Database>,
+    user_id: String,
+    id: String,
+) -> Result<ApiResponse<Vec<ConditionalOrder>>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!("[{}] trade_cancel_conditional_order called: user_id={}, id={}", request_id, user_id, id);
+
+    let engine = match db.get_conditional_order_engine_for(&user_id, &id).await {
+        Ok(engine) => engine,
+        Err(e) => {
+            log::error!("[{}] Failed to resolve conditional order {}: {}", request_id, id, e);
+            return Ok(ApiResponse::error(ApiError::operation_failed(format!("条件单不可用: {}", e))).with_request_id(request_id));
+        }
+    };
+    match engine.cancel(&user_id, &id).await {
+        Ok(canceled) => Ok(ApiResponse::success(canceled).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to cancel conditional order: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed(format!("撤销条件单失败: {}", e))).with_request_id(request_id))
+        }
+    }
+}
+
+/// Get conditional orders of an exchange account
+#[tauri::command]
+pub async fn trade_get_conditional_orders(
+    db: State<'_, Database>,
+    user_id: String,
+    exchange_id: Option<String>,
+    status: Option<String>,
+    limit: Option<usize>,
+) -> Result<ApiResponse<Vec<ConditionalOrder>>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!(
+        "[{}] trade_get_conditional_orders called: user_id={}, exchange_id={:?}, status={:?}",
+        request_id, user_id, exchange_id, status
+    );
+
+    let status: Option<ConditionalStatus> = match status.map(|s| s.parse()).transpose() {
+        Ok(status) => status,
+        Err(_) => return Ok(ApiResponse::error(ApiError::invalid_parameter("status")).with_request_id(request_id)),
+    };
+    let engine = match resolve_conditional_engine(&db, &user_id, exchange_id.as_deref(), &request_id).await {
+        Ok(engine) => engine,
+        Err(response) => return Ok(response),
+    };
+    match engine.list(&user_id, status, limit.unwrap_or(100)).await {
+        Ok(orders) => Ok(ApiResponse::success(orders).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to get conditional orders: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("查询条件单失败")).with_request_id(request_id))
+        }
+    }
+}
+
+/// Submit a parent order worked by an execution algorithm (TWAP, VWAP or iceberg)
+#[tauri::command]
+pub async fn trade_place_algo_order(
+    db: State<'_, Database>,
+    user_id: String,
+    exchange_id: Option<String>,
+    request: AlgoOrderRequest,
+) -> Result<ApiResponse<Order>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!(
+        "[{}] trade_place_algo_order called: user_id={}, exchange_id={:?}, symbol={}, algo={}",
+        request_id, user_id, exchange_id, request.symbol, request.algo.name()
+    );
+
+    let engine = match db.get_execution_engine(&user_id, exchange_id.as_deref()).await {
+        Ok(engine) => engine,
+        Err(e) => {
+            log::error!("[{}] Failed to resolve exchange account {:?}: {}", request_id, exchange_id, e);
+            return Ok(ApiResponse::error(ApiError::operation_failed(format!("交易所账户不可用: {}", e))).with_request_id(request_id));
+        }
+    };
+    match engine.submit(&user_id, request, None).await {
+        Ok(parent) => {
+            log::info!("[{}] Algo order started: {}", request_id, parent.id);
+            Ok(ApiResponse::success(parent).with_request_id(request_id))
+        }
+        Err(e) => {
+            log::error!("[{}] Failed to start algo order: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed(format!("算法下单失败: {}", e))).with_request_id(request_id))
+        }
+    }
+}
+
+/// Stop an algo order and cancel its resting child orders
+#[tauri::command]
+pub async fn trade_cancel_algo_order(
+    db: State<'_, Database>,
+    user_id: String,
+    order_id: String,
+) -> Result<ApiResponse<ExecutionReport>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!("[{}] trade_cancel_algo_order called: user_id={}, order_id={}", request_id, user_id, order_id);
+
+    let engine = match db.get_execution_engine_for_order(&user_id, &order_id).await {
+        Ok(engine) => engine,
+        Err(e) => {
+            log::error!("[{}] Failed to resolve algo order {}: {}", request_id, order_id, e);
+            return Ok(ApiResponse::error(ApiError::operation_failed(format!("算法单不可用: {}", e))).with_request_id(request_id));
+        }
+    };
+    match engine.cancel(&user_id, &order_id).await {
+        Ok(report) => Ok(ApiResponse::success(report).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to cancel algo order: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed(format!("撤销算法单失败: {}", e))).with_request_id(request_id))
+        }
+    }
+}
+
+/// Get average fill and slippage versus arrival price of an algo order
+#[tauri::command]
+pub async fn trade_get_execution_report(
+    db: State<'_, Database>,
+    user_id: String,
+    order_id: String,
+) -> Result<ApiResponse<ExecutionReport>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!("[{}] trade_get_execution_report called: user_id={}, order_id={}", request_id, user_id, order_id);
+
+    let trade_service = match resolve_order_trade_service(&db, &user_id, &order_id, &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
+    match trade_service.get_execution_report(&order_id, &user_id).await {
+        Ok(report) => Ok(ApiResponse::success(report).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to get execution report: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("查询执行报告失败")).with_request_id(request_id))
+        }
+    }
+}
+
+/// Get the child orders of an algo order
+#[tauri::command]
+pub async fn trade_get_child_orders(
+    db: State<'_, Database>,
+    user_id: String,
+    order_id: String,
+) -> Result<ApiResponse<Vec<Order>>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!("[{}] trade_get_child_orders called: user_id={}, order_id={}", request_id, user_id, order_id);
+
+    let trade_service = match resolve_order_trade_service(&db, &user_id, &order_id, &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
+    // Ownership check before listing children
+    if let Err(e) = trade_service.get_order(&order_id, &user_id).await {
+        log::error!("[{}] Order not found: {}", request_id, e);
+        return Ok(ApiResponse::error(ApiError::not_found("订单")).with_request_id(request_id));
+    }
+    match trade_service.get_child_orders(&order_id).await {
+        Ok(children) => Ok(ApiResponse::success(children).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to get child orders: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("查询子订单失败")).with_request_id(request_id))
+        }
+    }
+}
+
+/// Get an order's event history and fills, for debugging fills
+#[tauri::command]
+pub async fn trade_get_order_timeline(
+    db: State<'_, Database>,
+    user_id: String,
+    order_id: String,
+) -> Result<ApiResponse<OrderTimeline>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!("[{}] trade_get_order_timeline called: user_id={}, order_id={}", request_id, user_id, order_id);
+
+    let trade_service = match resolve_order_trade_service(&db, &user_id, &order_id, &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
+    match trade_service.get_order_timeline(&order_id, &user_id).await {
+        Ok(timeline) => Ok(ApiResponse::success(timeline).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to get order timeline: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("查询订单时间线失败")).with_request_id(request_id))
+        }
+    }
+}
+
+/// Run the pre-trade risk checks for an order without placing it
+///
+/// Returns every limit the order would break; an empty list means it would
+/// be accepted.
+#[tauri::command]
+pub async fn trade_check_order(
+    db: State<'_, Database>,
+    user_id: String,
+    exchange_id: Option<String>,
+    request: PlaceOrderRequest,
+) -> Result<ApiResponse<Vec<PreTradeRejection>>, String> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    log::info!("[{}] trade_check_order called: user_id={}, exchange_id={:?}", request_id, user_id, exchange_id);
+
+    let order_request: OrderRequest = request.try_into()
+        .map_err(|e| format!("Invalid request: {}", e))?;
+
+    let trade_service = match resolve_trade_service(&db, &user_id, exchange_id.as_deref(), &request_id).await {
+        Ok(service) => service,
+        Err(response) => return Ok(response),
+    };
+    match trade_service.check_order(&order_request, &user_id, &OrderOrigin::Manual).await {
+        Ok(rejections) => Ok(ApiResponse::success(rejections).with_request_id(request_id)),
+        Err(e) => {
+            log::error!("[{}] Failed to run pre-trade checks: {}", request_id, e);
+            Ok(ApiResponse::error(ApiError::operation_failed("下单前风控检查失败")).with_request_id(request_id))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -362,7 +362,9 @@ mod tests {
             status: crate::core::trade::types::OrderState::Open,
             commission: 0.001,
             created_at: 1234567890,
+            commission_asset: None,
             filled_at: None,
+            updated_at: None,
         };
 
         bus.publish_order_placed(order.clone());
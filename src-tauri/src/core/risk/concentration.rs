@@ -0,0 +1,477 @@
+//! Concentration Limit Rule
+//!
+//! Caps how much of equity sits in one base asset, in a user-defined group
+//! of assets (e.g. "memecoins", "l1"), or on one exchange account, and caps
+//! gross and net leverage. Caps are fractions of equity.
+//!
+//! Parameters use scoped keys, like the pre-trade limits:
+//! - `asset.max_ratio`, `asset.<ASSET>.max_ratio`
+//! - `group.<name>.max_ratio`, plus `group.<name>.<ASSET> = 1` per member
+//! - `account.max_ratio`, `account.<exchange_id>.max_ratio`
+//! - `max_gross_leverage`, `max_net_leverage`
+//!
+//! Asset and group exposure is net per asset, so a hedged asset does not
+//! count; account exposure and gross leverage add up position values
+//! regardless of side.
+
+use crate::core::risk::rule::{RiskAction, RiskContext, RiskMeasurement, RiskRule, RiskRuleConfig};
+use crate::core::trade::types::Position;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Quote currencies stripped from symbols to find the base asset
+const QUOTE_ASSETS: [&str; 6] = ["USDT", "USDC", "FDUSD", "BUSD", "TUSD", "USD"];
+
+/// A user-defined group of base assets with a shared cap
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AssetGroup {
+    pub assets: Vec<String>,
+    pub max_ratio: Option<f64>,
+}
+
+/// Concentration caps; a missing cap is not checked
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConcentrationLimits {
+    /// Cap for any single base asset
+    pub max_asset_ratio: Option<f64>,
+    /// Per-asset caps overriding `max_asset_ratio`
+    pub asset_ratios: HashMap<String, f64>,
+    pub groups: BTreeMap<String, AssetGroup>,
+    /// Cap for any single exchange account
+    pub max_account_ratio: Option<f64>,
+    /// Per-account caps overriding `max_account_ratio`
+    pub account_ratios: HashMap<String, f64>,
+    /// Cap on the sum of position values over equity
+    pub max_gross_leverage: Option<f64>,
+    /// Cap on the net position value over equity
+    pub max_net_leverage: Option<f64>,
+}
+
+impl ConcentrationLimits {
+    /// Parse limits from rule parameters
+    pub fn from_params(params: &HashMap<String, f64>) -> Result<Self, String> {
+        let mut limits = Self::default();
+        for (key, &value) in params {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(format!("{} must be positive", key));
+            }
+            let ratio = || {
+                if value > 1.0 {
+                    Err(format!("{} must be between 0 and 1", key))
+                } else {
+                    Ok(value)
+                }
+            };
+
+            match key.as_str() {
+                "asset.max_ratio" => limits.max_asset_ratio = Some(ratio()?),
+                "account.max_ratio" => limits.max_account_ratio = Some(ratio()?),
+                "max_gross_leverage" => limits.max_gross_leverage = Some(value),
+                "max_net_leverage" => limits.max_net_leverage = Some(value),
+                _ => {
+                    let parts: Vec<&str> = key.splitn(3, '.').collect();
+                    match parts.as_slice() {
+                        ["asset", asset, "max_ratio"] if !asset.is_empty() => {
+                            limits.asset_ratios.insert(asset.to_uppercase(), ratio()?);
+                        }
+                        ["account", account, "max_ratio"] if !account.is_empty() => {
+                            limits.account_ratios.insert(account.to_string(), ratio()?);
+                        }
+                        ["group", name, "max_ratio"] if !name.is_empty() => {
+                            limits.groups.entry(name.to_string()).or_default().max_ratio = Some(ratio()?);
+                        }
+                        ["group", name, asset] if !name.is_empty() && !asset.is_empty() => {
+                            limits.groups.entry(name.to_string()).or_default().assets.push(asset.to_uppercase());
+                        }
+                        _ => return Err(format!("Unknown concentration limit: {}", key)),
+                    }
+                }
+            }
+        }
+
+        for (name, group) in &mut limits.groups {
+            if group.max_ratio.is_none() {
+                return Err(format!("group.{}.max_ratio is missing", name));
+            }
+            if group.assets.is_empty() {
+                return Err(format!("group {} has no assets", name));
+            }
+            group.assets.sort();
+        }
+        Ok(limits)
+    }
+
+    /// Exposure of the context's positions against every cap
+    pub fn breakdown(&self, context: &RiskContext) -> ExposureBreakdown {
+        let equity = context.total_equity();
+        let mut lines = Vec::new();
+        let mut line = |kind: ExposureKind, name: &str, value: f64, limit: Option<f64>| {
+            let ratio = if equity > 0.0 { value / equity } else { 0.0 };
+            lines.push(ExposureLine {
+                kind,
+                name: name.to_string(),
+                value,
+                ratio,
+                limit,
+            });
+        };
+
+        let mut assets: BTreeMap<String, f64> = BTreeMap::new();
+        let mut accounts: BTreeMap<String, f64> = BTreeMap::new();
+        let mut gross = 0.0;
+        for position in &context.positions {
+            let value = position_value(position);
+            *assets.entry(base_asset(&position.symbol)).or_default() += value;
+            if let Some(account) = context.account_of(position) {
+                *accounts.entry(account.to_string()).or_default() += value.abs();
+            }
+            gross += value.abs();
+        }
+        let net: f64 = assets.values().sum();
+
+        for (asset, value) in &assets {
+            let limit = self.asset_ratios.get(asset).copied().or(self.max_asset_ratio);
+            line(ExposureKind::Asset, asset, value.abs(), limit);
+        }
+        for (name, group) in &self.groups {
+            let value = group
+                .assets
+                .iter()
+                .filter_map(|asset| assets.get(asset))
+                .map(|value| value.abs())
+                .sum();
+            line(ExposureKind::Group, name, value, group.max_ratio);
+        }
+        for (account, value) in &accounts {
+            let limit = self.account_ratios.get(account).copied().or(self.max_account_ratio);
+            line(ExposureKind::Account, account, *value, limit);
+        }
+        line(ExposureKind::GrossLeverage, "gross", gross, self.max_gross_leverage);
+        line(ExposureKind::NetLeverage, "net", net.abs(), self.max_net_leverage);
+
+        ExposureBreakdown { equity, lines }
+    }
+}
+
+/// What an exposure line covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExposureKind {
+    Asset,
+    Group,
+    Account,
+    GrossLeverage,
+    NetLeverage,
+}
+
+/// Exposure of one asset, group or account, or the account's leverage
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExposureLine {
+    pub kind: ExposureKind,
+    pub name: String,
+    /// Position value (quote currency)
+    pub value: f64,
+    /// Value over equity
+    pub ratio: f64,
+    pub limit: Option<f64>,
+}
+
+impl ExposureLine {
+    pub fn is_breached(&self) -> bool {
+        self.limit.is_some_and(|limit| self.ratio > limit)
+    }
+
+    /// Metric name reported in alerts, e.g. `asset_ratio.BTC`
+    pub fn metric(&self) -> String {
+        match self.kind {
+            ExposureKind::Asset => format!("asset_ratio.{}", self.name),
+            ExposureKind::Group => format!("group_ratio.{}", self.name),
+            ExposureKind::Account => format!("account_ratio.{}", self.name),
+            ExposureKind::GrossLeverage => "gross_leverage".to_string(),
+            ExposureKind::NetLeverage => "net_leverage".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ExposureLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (label, ratio, limit) = match self.kind {
+            ExposureKind::GrossLeverage | ExposureKind::NetLeverage => (
+                format!("{} leverage", self.name),
+                format!("{:.2}x", self.ratio),
+                self.limit.map(|l| format!("{:.2}x", l)),
+            ),
+            kind => (
+                format!("{} {}", format!("{:?}", kind).to_lowercase(), self.name),
+                format!("{:.1}%", self.ratio * 100.0),
+                self.limit.map(|l| format!("{:.1}%", l * 100.0)),
+            ),
+        };
+        write!(f, "{} {:.2} ({}", label, self.value, ratio)?;
+        match limit {
+            Some(limit) if self.is_breached() => write!(f, " > cap {})", limit),
+            Some(limit) => write!(f, ", cap {})", limit),
+            None => write!(f, ")"),
+        }
+    }
+}
+
+/// Exposures of a context, in the order assets, groups, accounts, leverage
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExposureBreakdown {
+    pub equity: f64,
+    pub lines: Vec<ExposureLine>,
+}
+
+impl ExposureBreakdown {
+    /// Lines over their cap
+    pub fn breaches(&self) -> impl Iterator<Item = &ExposureLine> {
+        self.lines.iter().filter(|line| line.is_breached())
+    }
+
+    /// Capped line closest to (or furthest over) its cap
+    pub fn worst(&self) -> Option<&ExposureLine> {
+        self.lines
+            .iter()
+            .filter_map(|line| line.limit.map(|limit| (line, line.ratio / limit)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(line, _)| line)
+    }
+}
+
+impl std::fmt::Display for ExposureBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "equity {:.2}", self.equity)?;
+        for line in &self.lines {
+            write!(f, "; {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Concentration limit rule
+pub struct ConcentrationLimitRule {
+    config: RiskRuleConfig,
+    limits: ConcentrationLimits,
+}
+
+impl ConcentrationLimitRule {
+    /// Create a new concentration limit rule
+    pub fn new(limits: ConcentrationLimits, action: RiskAction) -> Self {
+        Self {
+            config: RiskRuleConfig {
+                enabled: true,
+                action,
+                notify_methods: vec!["log".to_string()],
+                halt_trading: false,
+            },
+            limits,
+        }
+    }
+
+    pub fn limits(&self) -> &ConcentrationLimits {
+        &self.limits
+    }
+}
+
+#[async_trait]
+impl RiskRule for ConcentrationLimitRule {
+    fn name(&self) -> &str {
+        "concentration_limit"
+    }
+
+    fn description(&self) -> &str {
+        "Caps exposure per base asset, asset group and exchange account, and gross/net leverage"
+    }
+
+    async fn check(&self, context: &RiskContext) -> Result<bool> {
+        if !self.config.enabled || context.total_equity() <= 0.0 {
+            return Ok(false);
+        }
+
+        let breakdown = self.limits.breakdown(context);
+        let breaches: Vec<String> = breakdown.breaches().map(|line| line.to_string()).collect();
+        if breaches.is_empty() {
+            return Ok(false);
+        }
+
+        log::warn!("Concentration limit exceeded: {}", breaches.join("; "));
+        Ok(true)
+    }
+
+    fn measure(&self, context: &RiskContext) -> Option<RiskMeasurement> {
+        let breakdown = self.limits.breakdown(context);
+        let worst = breakdown.worst()?;
+        Some(
+            RiskMeasurement::new(&worst.metric(), worst.ratio, worst.limit.unwrap_or_default())
+                .with_detail(breakdown.to_string()),
+        )
+    }
+
+    fn config(&self) -> &RiskRuleConfig {
+        &self.config
+    }
+
+    fn update_config(&mut self, config: RiskRuleConfig) -> Result<()> {
+        self.config = config;
+        Ok(())
+    }
+}
+
+/// Signed position value at the current price, short positions negative
+fn position_value(position: &Position) -> f64 {
+    let price = position.current_price.unwrap_or(position.entry_price);
+    let sign = if position.side == "short" { -1.0 } else { 1.0 };
+    sign * position.quantity * price
+}
+
+/// Base asset of a symbol, e.g. `BTC` for `BTCUSDT` or `btc-usdt`
+fn base_asset(symbol: &str) -> String {
+    let symbol = symbol.to_uppercase();
+    QUOTE_ASSETS
+        .iter()
+        .find_map(|quote| symbol.strip_suffix(quote))
+        .map(|base| base.trim_end_matches(['-', '/', '_']))
+        .filter(|base| !base.is_empty())
+        .unwrap_or(&symbol)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(id: &str, symbol: &str, side: &str, value: f64) -> Position {
+        Position {
+            id: id.to_string(),
+            symbol: symbol.to_string(),
+            side: side.to_string(),
+            quantity: 1.0,
+            entry_price: value,
+            current_price: Some(value),
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+            opened_at: 0,
+        }
+    }
+
+    fn params(pairs: &[(&str, f64)]) -> HashMap<String, f64> {
+        pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    /// 10_000 equity: 5_000 balance plus 5_000 of positions
+    fn context() -> RiskContext {
+        let positions = vec![
+            position("p1", "BTCUSDT", "long", 3_000.0),
+            position("p2", "DOGEUSDT", "long", 800.0),
+            position("p3", "PEPE-USDT", "long", 700.0),
+            position("p4", "ETHUSDT", "long", 300.0),
+            position("p5", "ETHUSDT", "short", 200.0),
+        ];
+        let accounts = [("p1", "binance"), ("p2", "okx"), ("p3", "okx"), ("p4", "binance"), ("p5", "binance")]
+            .iter()
+            .map(|(p, a)| (p.to_string(), a.to_string()))
+            .collect();
+        RiskContext::for_user(positions, vec![], 5_000.0, 0.0, "u1".to_string()).with_position_accounts(accounts)
+    }
+
+    #[test]
+    fn test_base_asset() {
+        assert_eq!(base_asset("BTCUSDT"), "BTC");
+        assert_eq!(base_asset("eth-usdc"), "ETH");
+        assert_eq!(base_asset("SOL/FDUSD"), "SOL");
+        assert_eq!(base_asset("USDT"), "USDT");
+    }
+
+    #[test]
+    fn test_from_params() {
+        let limits = ConcentrationLimits::from_params(&params(&[
+            ("asset.max_ratio", 0.3),
+            ("asset.btc.max_ratio", 0.5),
+            ("group.memecoins.max_ratio", 0.1),
+            ("group.memecoins.DOGE", 1.0),
+            ("group.memecoins.pepe", 1.0),
+            ("account.okx.max_ratio", 0.2),
+            ("max_gross_leverage", 3.0),
+        ]))
+        .unwrap();
+        assert_eq!(limits.max_asset_ratio, Some(0.3));
+        assert_eq!(limits.asset_ratios["BTC"], 0.5);
+        assert_eq!(
+            limits.groups["memecoins"],
+            AssetGroup {
+                assets: vec!["DOGE".to_string(), "PEPE".to_string()],
+                max_ratio: Some(0.1),
+            }
+        );
+        assert_eq!(limits.account_ratios["okx"], 0.2);
+        assert_eq!(limits.max_gross_leverage, Some(3.0));
+
+        assert!(ConcentrationLimits::from_params(&params(&[("asset.max_ratio", 1.5)])).is_err());
+        assert!(ConcentrationLimits::from_params(&params(&[("group.l1.ETH", 1.0)])).is_err());
+        assert!(ConcentrationLimits::from_params(&params(&[("sector.max_ratio", 0.5)])).is_err());
+    }
+
+    #[test]
+    fn test_breakdown() {
+        let limits = ConcentrationLimits::from_params(&params(&[
+            ("asset.max_ratio", 0.25),
+            ("asset.BTC.max_ratio", 0.5),
+            ("group.memecoins.max_ratio", 0.1),
+            ("group.memecoins.DOGE", 1.0),
+            ("group.memecoins.PEPE", 1.0),
+            ("account.max_ratio", 0.4),
+            ("max_net_leverage", 1.0),
+        ]))
+        .unwrap();
+        let breakdown = limits.breakdown(&context());
+        assert_eq!(breakdown.equity, 10_000.0);
+
+        let ratio = |metric: &str| {
+            breakdown
+                .lines
+                .iter()
+                .find(|l| l.metric() == metric)
+                .map(|l| (l.ratio, l.is_breached()))
+                .unwrap()
+        };
+        // BTC is over the default cap but within its own
+        assert_eq!(ratio("asset_ratio.BTC"), (0.3, false));
+        // ETH is net 100
+        assert_eq!(ratio("asset_ratio.ETH"), (0.01, false));
+        assert_eq!(ratio("group_ratio.memecoins"), (0.15, true));
+        // Account exposure is gross: 3_000 + 300 + 200
+        assert_eq!(ratio("account_ratio.binance"), (0.35, false));
+        assert_eq!(ratio("account_ratio.okx"), (0.15, false));
+        assert_eq!(ratio("gross_leverage"), (0.5, false));
+        assert_eq!(ratio("net_leverage"), (0.46, false));
+
+        let breaches: Vec<String> = breakdown.breaches().map(|l| l.metric()).collect();
+        assert_eq!(breaches, vec!["group_ratio.memecoins"]);
+        assert_eq!(breakdown.worst().unwrap().metric(), "group_ratio.memecoins");
+    }
+
+    #[tokio::test]
+    async fn test_rule_reports_breakdown() {
+        let limits = ConcentrationLimits::from_params(&params(&[("asset.max_ratio", 0.2)])).unwrap();
+        let rule = ConcentrationLimitRule::new(limits, RiskAction::Notify);
+        let context = context();
+
+        assert!(rule.check(&context).await.unwrap());
+        let measurement = rule.measure(&context).unwrap();
+        assert_eq!(measurement.metric, "asset_ratio.BTC");
+        assert_eq!(measurement.current_value, 0.3);
+        assert_eq!(measurement.threshold_value, 0.2);
+        let detail = measurement.detail.unwrap();
+        assert!(detail.starts_with("equity 10000.00; asset BTC 3000.00 (30.0% > cap 20.0%)"));
+        assert!(detail.contains("account okx 1500.00 (15.0%)"));
+        assert!(detail.contains("gross leverage 5000.00 (0.50x)"));
+
+        // Within every cap
+        let limits = ConcentrationLimits::from_params(&params(&[("asset.max_ratio", 0.5)])).unwrap();
+        let rule = ConcentrationLimitRule::new(limits, RiskAction::Notify);
+        assert!(!rule.check(&context).await.unwrap());
+    }
+}
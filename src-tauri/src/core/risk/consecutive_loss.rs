@@ -2,7 +2,7 @@
 //!
 //! Stops trading after a configured number of consecutive losing trades.
 
-use crate::core::risk::rule::{RiskRule, RiskContext, RiskRuleConfig, RiskAction};
+use crate::core::risk::rule::{RiskRule, RiskContext, RiskMeasurement, RiskRuleConfig, RiskAction};
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -59,6 +59,7 @@ impl ConsecutiveLossLimitRule {
                 enabled: true,
                 action,
                 notify_methods: vec!["log".to_string()],
+                halt_trading: false,
             },
             params,
             consecutive_data: Arc::new(RwLock::new(HashMap::new())),
@@ -77,8 +78,12 @@ impl ConsecutiveLossLimitRule {
 
     /// Record a trade outcome and update consecutive loss count
     pub fn record_trade(&self, instance_id: &str, pnl: f64) {
+        self.record_trade_at(instance_id, pnl, Utc::now().timestamp());
+    }
+
+    /// Record a trade outcome as of `now` (seconds)
+    fn record_trade_at(&self, instance_id: &str, pnl: f64, now: i64) {
         let mut data = self.consecutive_data.write().unwrap();
-        let now = Utc::now().timestamp();
 
         if let Some((count, last_time, triggered_time)) = data.get_mut(instance_id) {
             // Check if we're still in cooling period
@@ -184,19 +189,19 @@ impl RiskRule for ConsecutiveLossLimitRule {
 
         // Update count based on today's P&L
         // In a real implementation, this would track each trade individually
-        let _count = self.get_consecutive_count(&context.instance_id);
+        let _count = self.get_consecutive_count(context.scope_key());
 
         // Update with today's result
+        let now = context.now().timestamp();
         if context.today_pnl < -self.params.min_loss_threshold {
-            self.record_trade(&context.instance_id, context.today_pnl);
-            let updated_count = self.get_consecutive_count(&context.instance_id);
+            self.record_trade_at(context.scope_key(), context.today_pnl, now);
+            let updated_count = self.get_consecutive_count(context.scope_key());
 
             // Check if we've hit the limit
             if updated_count >= self.params.max_consecutive_losses {
                 // Mark as triggered
                 let mut data = self.consecutive_data.write().unwrap();
-                let now = Utc::now().timestamp();
-                if let Some((_count, _last_time, triggered)) = data.get_mut(&context.instance_id) {
+                if let Some((_count, _last_time, triggered)) = data.get_mut(context.scope_key()) {
                     if triggered.is_none() {
                         *triggered = Some(now);
                     }
@@ -205,12 +210,20 @@ impl RiskRule for ConsecutiveLossLimitRule {
             }
         } else if context.today_pnl > 0.0 {
             // Win, reset count
-            self.reset_count(&context.instance_id);
+            self.reset_count(context.scope_key());
         }
 
         Ok(false)
     }
 
+    fn measure(&self, context: &RiskContext) -> Option<RiskMeasurement> {
+        Some(RiskMeasurement::new(
+            "consecutive_losses",
+            self.get_consecutive_count(context.scope_key()) as f64,
+            self.params.max_consecutive_losses as f64,
+        ))
+    }
+
     fn config(&self) -> &RiskRuleConfig {
         &self.config
     }
@@ -2,13 +2,13 @@
 //!
 //! Stops trading when daily losses exceed a configured threshold.
 
-use crate::core::risk::rule::{RiskRule, RiskContext, RiskRuleConfig, RiskAction};
+use crate::core::risk::rule::{RiskRule, RiskContext, RiskMeasurement, RiskRuleConfig, RiskAction};
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
-use chrono::{Utc, Timelike, Datelike};
+use chrono::{DateTime, Datelike, Duration, Utc};
 
 /// Daily loss limit rule parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,6 +50,7 @@ impl DailyLossLimitRule {
                 enabled: true,
                 action,
                 notify_methods: vec!["log".to_string()],
+                halt_trading: false,
             },
             params,
             daily_losses: Arc::new(RwLock::new(HashMap::new())),
@@ -66,60 +67,28 @@ impl DailyLossLimitRule {
         Self::new(DailyLossLimitParams::default(), RiskAction::PauseStrategy)
     }
 
-    /// Get the current date key for tracking
-    fn get_date_key() -> String {
-        let now = Utc::now();
-        format!("{}-{}-{}", now.year(), now.month(), now.day())
+    /// Trading day `now` falls in, with days starting at the reset time
+    fn date_key(now: DateTime<Utc>, reset_hour: u8, reset_minute: u8) -> String {
+        let day = now - Duration::minutes(reset_hour as i64 * 60 + reset_minute as i64);
+        format!("{}-{}-{}", day.year(), day.month(), day.day())
     }
 
-    /// Check if we need to reset the daily counter
-    fn should_reset(&self) -> bool {
-        let now = Utc::now();
-        now.hour() as u8 == self.params.reset_hour && now.minute() as u8 == self.params.reset_minute && now.second() < 10
+    /// Trading day of a context
+    fn day_of(&self, context: &RiskContext) -> String {
+        Self::date_key(context.now(), self.params.reset_hour, self.params.reset_minute)
     }
 
-    /// Get or initialize daily loss for an instance
-    fn get_daily_loss(&self, instance_id: &str) -> f64 {
-        let mut losses = self.daily_losses.write().unwrap();
-        let current_date = Self::get_date_key();
-
-        if let Some((date, loss)) = losses.get(instance_id) {
-            if *date == current_date {
-                *loss
-            } else {
-                // New day, reset
-                losses.insert(instance_id.to_string(), (current_date, 0.0));
-                0.0
-            }
-        } else {
-            losses.insert(instance_id.to_string(), (current_date, 0.0));
-            0.0
-        }
-    }
-
-    /// Update daily loss for an instance
-    fn update_daily_loss(&self, instance_id: &str, loss: f64) {
-        let mut losses = self.daily_losses.write().unwrap();
-        let current_date = Self::get_date_key();
-
-        let current_loss = if let Some((date, existing_loss)) = losses.get(instance_id) {
-            if *date == current_date {
-                *existing_loss
-            } else {
-                0.0
-            }
-        } else {
-            0.0
-        };
-
-        losses.insert(instance_id.to_string(), (current_date, current_loss + loss));
+    /// Day's loss so far; `today_pnl` is negative on a losing day
+    fn daily_loss(context: &RiskContext) -> f64 {
+        (-context.today_pnl).max(0.0)
     }
 
-    /// Reset daily loss for an instance
-    pub fn reset_daily_loss(&self, instance_id: &str) {
-        let mut losses = self.daily_losses.write().unwrap();
-        let current_date = Self::get_date_key();
-        losses.insert(instance_id.to_string(), (current_date, 0.0));
+    /// Record the day's loss so far
+    ///
+    /// `today_pnl` is already the day's running total, so each check
+    /// replaces the loss instead of adding to it.
+    fn record_daily_loss(&self, instance_id: &str, day: String, loss: f64) {
+        self.daily_losses.write().unwrap().insert(instance_id.to_string(), (day, loss));
     }
 
     /// Get all daily losses
@@ -143,26 +112,15 @@ impl RiskRule for DailyLossLimitRule {
             return Ok(false);
         }
 
-        // Reset if needed
-        if self.should_reset() {
-            self.reset_daily_loss(&context.instance_id);
-        }
-
-        // Get current daily loss
-        let _daily_loss = self.get_daily_loss(&context.instance_id);
+        let daily_loss = Self::daily_loss(context);
+        self.record_daily_loss(context.scope_key(), self.day_of(context), daily_loss);
 
-        // Add current day's P&L (negative value means loss)
-        let current_day_pnl = context.today_pnl;
-        if current_day_pnl < 0.0 {
-            self.update_daily_loss(&context.instance_id, current_day_pnl.abs());
-            let updated_loss = self.get_daily_loss(&context.instance_id);
+        // Check if threshold exceeded
+        Ok(daily_loss > 0.0 && daily_loss >= self.params.max_daily_loss)
+    }
 
-            // Check if threshold exceeded
-            Ok(updated_loss >= self.params.max_daily_loss)
-        } else {
-            // No losses today
-            Ok(false)
-        }
+    fn measure(&self, context: &RiskContext) -> Option<RiskMeasurement> {
+        Some(RiskMeasurement::new("daily_loss", Self::daily_loss(context), self.params.max_daily_loss))
     }
 
     fn config(&self) -> &RiskRuleConfig {
@@ -179,8 +137,8 @@ impl RiskRule for DailyLossLimitRule {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_daily_loss_tracking() {
+    #[tokio::test]
+    async fn test_daily_loss_tracking() {
         let rule = DailyLossLimitRule::new(
             DailyLossLimitParams {
                 max_daily_loss: 500.0,
@@ -189,30 +147,66 @@ mod tests {
             RiskAction::LogOnly,
         );
 
-        let instance_id = "test-instance";
+        let context = |today_pnl: f64| {
+            RiskContext::new(vec![], vec![], 1_000.0, today_pnl, "test-instance".to_string(), "u1".to_string())
+        };
 
         // Initial state
-        assert_eq!(rule.get_daily_loss(instance_id), 0.0);
+        assert_eq!(rule.measure(&context(0.0)).unwrap().current_value, 0.0);
 
-        // Add some loss
-        rule.update_daily_loss(instance_id, 200.0);
-        assert_eq!(rule.get_daily_loss(instance_id), 200.0);
+        // Some loss
+        assert!(!rule.check(&context(-200.0)).await.unwrap());
+        assert_eq!(rule.measure(&context(-200.0)).unwrap().current_value, 200.0);
 
-        // Add more loss
-        rule.update_daily_loss(instance_id, 150.0);
-        assert_eq!(rule.get_daily_loss(instance_id), 350.0);
+        // More loss
+        assert!(!rule.check(&context(-350.0)).await.unwrap());
+        assert_eq!(rule.measure(&context(-350.0)).unwrap().current_value, 350.0);
 
-        // Reset
-        rule.reset_daily_loss(instance_id);
-        assert_eq!(rule.get_daily_loss(instance_id), 0.0);
+        // Measured from the context alone, without a check first
+        assert_eq!(rule.measure(&context(-50.0)).unwrap().current_value, 50.0);
+        assert_eq!(rule.measure(&context(120.0)).unwrap().current_value, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_check_follows_the_days_running_loss() {
+        let rule = DailyLossLimitRule::new(
+            DailyLossLimitParams {
+                max_daily_loss: 500.0,
+                reset_hour: 8,
+                reset_minute: 0,
+            },
+            RiskAction::LogOnly,
+        );
+        // 2024-03-01 10:00 UTC
+        let day = 1_709_287_200_000;
+        let context = |hours: i64, today_pnl: f64| {
+            RiskContext::new(vec![], vec![], 1_000.0, today_pnl, "i1".to_string(), "u1".to_string())
+                .at(day + hours * 3_600_000)
+        };
+
+        // Repeated checks at -300 don't add up
+        assert!(!rule.check(&context(0, -300.0)).await.unwrap());
+        assert!(!rule.check(&context(1, -300.0)).await.unwrap());
+        assert!(rule.check(&context(2, -600.0)).await.unwrap());
+        assert!(!rule.check(&context(3, -450.0)).await.unwrap());
+        assert_eq!(rule.get_all_losses()["i1"].1, 450.0);
+
+        // The next trading day starts at 08:00
+        assert!(!rule.check(&context(22, -100.0)).await.unwrap());
+        assert_eq!(rule.measure(&context(22, -100.0)).unwrap().current_value, 100.0);
     }
 
     #[test]
     fn test_date_key_format() {
-        let key = DailyLossLimitRule::get_date_key();
+        let key = DailyLossLimitRule::date_key(Utc::now(), 0, 0);
         assert!(key.contains('-'));
         let parts: Vec<&str> = key.split('-').collect();
         assert_eq!(parts.len(), 3);
+
+        // 2024-03-01 07:30 UTC still belongs to Feb 29 with an 08:00 reset
+        let early = DateTime::from_timestamp_millis(1_709_278_200_000).unwrap();
+        assert_eq!(DailyLossLimitRule::date_key(early, 8, 0), "2024-2-29");
+        assert_eq!(DailyLossLimitRule::date_key(early, 0, 0), "2024-3-1");
     }
 
     #[test]
@@ -0,0 +1,115 @@
+//! Dead-man's switch
+//!
+//! Cancels resting orders when the app stops looking after them. Where the
+//! venue supports it, a cancel-all-after timer is armed and renewed on every
+//! heartbeat, so the venue cancels the orders itself if the app crashes or
+//! loses its connection. A local watchdog covers the venues without one and
+//! the failures that leave the process running: a strategy instance stuck
+//! on a kline, or a market data stream that has gone quiet.
+//!
+//! Settings come from the `dead_man_switch` row of `risk_rules`:
+//!
+//! * `timeout_secs`: how long the app may be unresponsive before its orders
+//!   are canceled (default 60)
+//! * `heartbeat_secs`: how often venue timers are renewed and the watchdog
+//!   checks (default 15); must be shorter than `timeout_secs`
+
+use std::collections::HashMap;
+
+/// Name of the `risk_rules` row holding the dead-man's switch settings
+pub const DEAD_MAN_SWITCH_RULE: &str = "dead_man_switch";
+
+/// Dead-man's switch settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadManConfig {
+    pub timeout_secs: u64,
+    pub heartbeat_secs: u64,
+}
+
+impl Default for DeadManConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 60,
+            heartbeat_secs: 15,
+        }
+    }
+}
+
+impl DeadManConfig {
+    pub fn from_params(params: &HashMap<String, f64>) -> Result<Self, String> {
+        let mut config = Self::default();
+        for (key, &value) in params {
+            if !value.is_finite() || value < 1.0 {
+                return Err(format!("{} must be at least 1 second", key));
+            }
+            match key.as_str() {
+                "timeout_secs" => config.timeout_secs = value as u64,
+                "heartbeat_secs" => config.heartbeat_secs = value as u64,
+                _ => return Err(format!("Unknown dead-man's switch setting: {}", key)),
+            }
+        }
+        if config.heartbeat_secs >= config.timeout_secs {
+            return Err("heartbeat_secs must be shorter than timeout_secs".to_string());
+        }
+        Ok(config)
+    }
+
+    pub fn timeout_ms(&self) -> i64 {
+        self.timeout_secs as i64 * 1000
+    }
+}
+
+/// Why the app counts as unresponsive; `None` while it is healthy
+///
+/// `stalled` lists the strategy instances busy on one kline for longer than
+/// the timeout. `market_age_ms` is the time since the last market event,
+/// `None` when no strategy is running and quiet market data harms nothing.
+pub fn unresponsive(stalled: &[String], market_age_ms: Option<i64>, timeout_ms: i64) -> Option<String> {
+    if !stalled.is_empty() {
+        return Some(format!("Strategy instances not responding: {}", stalled.join(", ")));
+    }
+    match market_age_ms {
+        Some(age) if age > timeout_ms => Some(format!("No market data for {} s", age / 1000)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_from_params() {
+        assert_eq!(DeadManConfig::from_params(&HashMap::new()).unwrap(), DeadManConfig::default());
+
+        let mut params = HashMap::new();
+        params.insert("timeout_secs".to_string(), 30.0);
+        params.insert("heartbeat_secs".to_string(), 10.0);
+        let config = DeadManConfig::from_params(&params).unwrap();
+        assert_eq!((config.timeout_secs, config.heartbeat_secs), (30, 10));
+        assert_eq!(config.timeout_ms(), 30_000);
+
+        params.insert("heartbeat_secs".to_string(), 30.0);
+        assert!(DeadManConfig::from_params(&params).is_err());
+        params.insert("heartbeat_secs".to_string(), 0.0);
+        assert!(DeadManConfig::from_params(&params).is_err());
+        params.remove("heartbeat_secs");
+        params.insert("grace_secs".to_string(), 5.0);
+        assert!(DeadManConfig::from_params(&params).is_err());
+    }
+
+    #[test]
+    fn test_unresponsive() {
+        assert_eq!(unresponsive(&[], Some(5_000), 60_000), None);
+        // Quiet market data only matters while strategies are running
+        assert_eq!(unresponsive(&[], None, 60_000), None);
+        assert_eq!(
+            unresponsive(&[], Some(90_000), 60_000).as_deref(),
+            Some("No market data for 90 s")
+        );
+        assert_eq!(
+            unresponsive(&["s1".to_string(), "s2".to_string()], Some(0), 60_000).as_deref(),
+            Some("Strategy instances not responding: s1, s2")
+        );
+    }
+}
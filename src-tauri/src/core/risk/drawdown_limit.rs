@@ -3,10 +3,11 @@
 //! This rule monitors equity drawdown and triggers protective actions
 //! when the drawdown exceeds a specified threshold.
 
-use super::rule::{RiskContext, RiskRule, RiskRuleConfig};
+use super::rule::{RiskContext, RiskMeasurement, RiskRule, RiskRuleConfig};
 use anyhow::Result;
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::sync::RwLock;
 
 /// Drawdown limit risk rule
 ///
@@ -16,8 +17,8 @@ pub struct DrawdownLimitRule {
     config: RiskRuleConfig,
     /// Maximum drawdown percentage allowed
     max_drawdown_pct: f64,
-    /// Peak equity tracking per strategy instance
-    peak_equity: HashMap<String, f64>,
+    /// Peak equity tracking per strategy instance, raised by every check
+    peak_equity: RwLock<HashMap<String, f64>>,
 }
 
 impl DrawdownLimitRule {
@@ -31,9 +32,10 @@ impl DrawdownLimitRule {
                 enabled: true,
                 action: super::rule::RiskAction::ClosePositions,
                 notify_methods: vec!["dingtalk".to_string(), "email".to_string()],
+                halt_trading: false,
             },
             max_drawdown_pct,
-            peak_equity: HashMap::new(),
+            peak_equity: RwLock::new(HashMap::new()),
         }
     }
 
@@ -46,7 +48,7 @@ impl DrawdownLimitRule {
         Self {
             config,
             max_drawdown_pct,
-            peak_equity: HashMap::new(),
+            peak_equity: RwLock::new(HashMap::new()),
         }
     }
 
@@ -76,11 +78,13 @@ impl DrawdownLimitRule {
     /// * `instance_id` - Strategy instance identifier
     /// * `current_equity` - Current total equity
     pub fn update_peak(&mut self, instance_id: &str, current_equity: f64) {
-        let peak = self
-            .peak_equity
-            .entry(instance_id.to_string())
-            .or_insert(current_equity);
+        Self::raise_peak(self.peak_equity.get_mut().unwrap(), instance_id, current_equity);
+    }
+
+    fn raise_peak(peaks: &mut HashMap<String, f64>, instance_id: &str, current_equity: f64) -> f64 {
+        let peak = peaks.entry(instance_id.to_string()).or_insert(current_equity);
         *peak = (*peak).max(current_equity);
+        *peak
     }
 
     /// Gets current peak equity for an instance
@@ -91,7 +95,7 @@ impl DrawdownLimitRule {
     /// # Returns
     /// Peak equity value, or None if not tracked
     pub fn get_peak(&self, instance_id: &str) -> Option<f64> {
-        self.peak_equity.get(instance_id).copied()
+        self.peak_equity.read().unwrap().get(instance_id).copied()
     }
 
     /// Gets the current max drawdown percentage
@@ -135,12 +139,8 @@ impl RiskRule for DrawdownLimitRule {
         // Calculate current total equity
         let current_equity = self.calculate_total_equity(context);
 
-        // Get or initialize peak equity
-        let peak = self
-            .peak_equity
-            .get(&context.instance_id)
-            .copied()
-            .unwrap_or(current_equity);
+        // Raise the peak to the current equity if it's a new high
+        let peak = Self::raise_peak(&mut self.peak_equity.write().unwrap(), context.scope_key(), current_equity);
 
         // Calculate drawdown percentage
         let drawdown = self.calculate_drawdown(current_equity, peak);
@@ -160,6 +160,17 @@ impl RiskRule for DrawdownLimitRule {
         Ok(false)
     }
 
+    fn measure(&self, context: &RiskContext) -> Option<RiskMeasurement> {
+        let current_equity = self.calculate_total_equity(context);
+        let peak = self.get_peak(context.scope_key()).unwrap_or(current_equity).max(current_equity);
+
+        Some(RiskMeasurement::new(
+            "drawdown_pct",
+            self.calculate_drawdown(current_equity, peak),
+            self.max_drawdown_pct,
+        ))
+    }
+
     fn config(&self) -> &RiskRuleConfig {
         &self.config
     }
@@ -183,6 +194,9 @@ mod tests {
             balance,
             today_pnl: 0.0,
             instance_id: instance_id.to_string(),
+            user_id: "test_user".to_string(),
+            position_accounts: Default::default(),
+            timestamp: None,
         }
     }
 
@@ -349,6 +363,7 @@ mod tests {
             enabled: false,
             action: RiskAction::Notify,
             notify_methods: vec!["email".to_string()],
+            halt_trading: false,
         };
 
         let rule = DrawdownLimitRule::with_config(5.0, config.clone());
@@ -370,6 +385,7 @@ mod tests {
             enabled: false,
             action: RiskAction::PauseStrategy,
             notify_methods: vec![],
+            halt_trading: false,
         };
 
         rule.update_config(new_config.clone()).unwrap();
@@ -401,4 +417,29 @@ mod tests {
         let context2 = create_test_context("instance2", 19000.0, vec![]);
         assert!(!rule.check(&context2).await.unwrap());
     }
+
+    #[tokio::test]
+    async fn test_check_tracks_peak() {
+        let rule = DrawdownLimitRule::new(10.0);
+
+        assert!(!rule.check(&create_test_context("instance1", 10000.0, vec![])).await.unwrap());
+        assert!(!rule.check(&create_test_context("instance1", 12000.0, vec![])).await.unwrap());
+        assert_eq!(rule.get_peak("instance1"), Some(12000.0));
+
+        // 12.5% below the new high
+        assert!(rule.check(&create_test_context("instance1", 10500.0, vec![])).await.unwrap());
+        assert_eq!(rule.get_peak("instance1"), Some(12000.0));
+    }
+
+    #[test]
+    fn test_measure_drawdown() {
+        let mut rule = DrawdownLimitRule::new(10.0);
+        rule.update_peak("instance1", 10000.0);
+
+        let context = create_test_context("instance1", 8500.0, vec![]);
+        assert_eq!(
+            rule.measure(&context),
+            Some(RiskMeasurement::new("drawdown_pct", 15.0, 10.0))
+        );
+    }
 }
@@ -0,0 +1,305 @@
+//! Risk rule factory
+//!
+//! Builds `RiskRule` trait objects from rows of the `risk_rules` table, so
+//! the thresholds edited through `update_risk_rule` are the ones checked.
+//! Rules keep per-instance state (peaks, loss counters), so every consumer
+//! builds its own set. Editing a rule rebuilds only that rule, so the state
+//! of the others carries over.
+
+use super::concentration::{ConcentrationLimitRule, ConcentrationLimits};
+use super::consecutive_loss::{ConsecutiveLossLimitParams, ConsecutiveLossLimitRule};
+use super::daily_loss::{DailyLossLimitParams, DailyLossLimitRule};
+use super::drawdown_limit::DrawdownLimitRule;
+use super::position_limit::PositionLimitRule;
+use super::pretrade::PRE_TRADE_RULE;
+use super::dead_man::DEAD_MAN_SWITCH_RULE;
+use super::kill_switch::HALT_TRADING_PARAM;
+use super::slippage::SLIPPAGE_GUARD_RULE;
+use super::rule::{RiskAction, RiskRule, RiskRuleConfig};
+use super::var_limit::{VarLimitParams, VarLimitRule, VarMethod};
+use super::volatility_limit::{VolatilityLimitParams, VolatilityLimitRule};
+use crate::repository::risk_rule_repo::{RiskRule as RiskRuleRow, RiskRuleRepository};
+use crate::repository::KlineRepository;
+use anyhow::{anyhow, Result};
+use sqlx::SqlitePool;
+
+/// Parses the action stored in `risk_rules.action`
+///
+/// Accepts the names the settings page writes ("warning", "stop_strategy",
+/// "emergency_close") as well as `RiskAction`'s own names.
+pub fn parse_action(action: &str) -> Result<RiskAction> {
+    match action {
+        "warning" => Ok(RiskAction::Notify),
+        "stop_strategy" => Ok(RiskAction::PauseStrategy),
+        "emergency_close" => Ok(RiskAction::EmergencyStop),
+        other => other.parse(),
+    }
+}
+
+/// Builds the rule a row describes
+///
+/// Returns `None` for rows that are not monitored rules, such as the
+/// pre-trade limits and slippage guard checked by `TradeService` and the
+/// dead-man's switch settings. Missing parameters fall back to the rule's
+/// defaults. Rules that read stored market data get it from `pool`.
+pub fn build_rule(row: &RiskRuleRow, pool: Option<&SqlitePool>) -> Result<Option<Box<dyn RiskRule>>> {
+    if row.name == PRE_TRADE_RULE
+        || row.rule_type == "pre_trade"
+        || row.name == SLIPPAGE_GUARD_RULE
+        || row.name == DEAD_MAN_SWITCH_RULE
+    {
+        return Ok(None);
+    }
+
+    let mut params = row.get_params()?;
+    let halt_trading = params.remove(HALT_TRADING_PARAM).is_some_and(|v| v != 0.0);
+    let config = RiskRuleConfig::new(row.enabled, parse_action(&row.action)?, row.get_notify_methods()?)
+        .with_halt_trading(halt_trading);
+    let param = |key: &str, default: f64| params.get(key).copied().unwrap_or(default);
+
+    let mut rule: Box<dyn RiskRule> = match row.rule_type.as_str() {
+        "position_limit" => Box::new(PositionLimitRule::with_config(
+            param("max_position_value", 10_000.0),
+            param("max_total_value", 50_000.0),
+            param("max_direction_ratio", 0.7),
+            config.clone(),
+        )),
+        "drawdown_limit" => Box::new(DrawdownLimitRule::with_config(
+            param("max_drawdown_pct", 15.0),
+            config.clone(),
+        )),
+        "daily_loss_limit" => {
+            let defaults = DailyLossLimitParams::default();
+            Box::new(DailyLossLimitRule::new(
+                DailyLossLimitParams {
+                    max_daily_loss: param("max_daily_loss", defaults.max_daily_loss),
+                    reset_hour: param("reset_hour", defaults.reset_hour as f64) as u8,
+                    reset_minute: param("reset_minute", defaults.reset_minute as f64) as u8,
+                },
+                config.action.clone(),
+            ))
+        }
+        "consecutive_loss_limit" => {
+            let defaults = ConsecutiveLossLimitParams::default();
+            Box::new(ConsecutiveLossLimitRule::new(
+                ConsecutiveLossLimitParams {
+                    max_consecutive_losses: param("max_consecutive_losses", defaults.max_consecutive_losses as f64)
+                        as usize,
+                    min_loss_threshold: param("min_loss_threshold", defaults.min_loss_threshold),
+                    cooling_period_seconds: param("cooling_period_seconds", defaults.cooling_period_seconds as f64)
+                        as u64,
+                },
+                config.action.clone(),
+            ))
+        }
+        "volatility_limit" => {
+            let defaults = VolatilityLimitParams::default();
+            Box::new(VolatilityLimitRule::new(
+                VolatilityLimitParams {
+                    max_atr_ratio: param("max_atr_ratio", defaults.max_atr_ratio),
+                    atr_period: param("atr_period", defaults.atr_period as f64) as usize,
+                    history_size: param("history_size", defaults.history_size as f64) as usize,
+                },
+                config.action.clone(),
+            ))
+        }
+        "var_limit" => {
+            let defaults = VarLimitParams::default();
+            let method = if param("parametric", 0.0) != 0.0 {
+                VarMethod::Parametric
+            } else {
+                VarMethod::Historical
+            };
+            let rule = VarLimitRule::new(
+                VarLimitParams {
+                    method,
+                    confidence: param("confidence", defaults.confidence),
+                    max_var_pct: param("max_var_pct", defaults.max_var_pct),
+                    max_cvar_pct: param("max_cvar_pct", defaults.max_cvar_pct),
+                    lookback: param("lookback", defaults.lookback as f64) as usize,
+                    horizon_bars: param("horizon_bars", defaults.horizon_bars as f64) as usize,
+                    ..defaults
+                },
+                config.action.clone(),
+            );
+            match pool {
+                Some(pool) => Box::new(rule.with_klines(KlineRepository::new(pool.clone()))),
+                None => Box::new(rule),
+            }
+        }
+        "concentration_limit" => Box::new(ConcentrationLimitRule::new(
+            ConcentrationLimits::from_params(&params).map_err(|e| anyhow!(e))?,
+            config.action.clone(),
+        )),
+        other => return Err(anyhow!("Unknown risk rule type: {}", other)),
+    };
+
+    rule.update_config(config)?;
+    Ok(Some(rule))
+}
+
+/// Builds every monitored rule stored in the database
+///
+/// Rows that fail to build are logged and skipped, so one bad row does not
+/// disable the other rules.
+pub async fn load_rules(pool: &SqlitePool) -> Result<Vec<Box<dyn RiskRule>>> {
+    let rows = RiskRuleRepository::new(pool.clone()).find_all().await?;
+    Ok(build_rules(&rows, Some(pool)))
+}
+
+/// Builds the monitored rule stored under `name`
+///
+/// Unlike `load_rules`, a row that fails to build is an error. Returns
+/// `None` when the row is missing or not a monitored rule.
+pub async fn load_rule(pool: &SqlitePool, name: &str) -> Result<Option<Box<dyn RiskRule>>> {
+    match RiskRuleRepository::new(pool.clone()).find_by_name(name).await? {
+        Some(row) => build_rule(&row, Some(pool)),
+        None => Ok(None),
+    }
+}
+
+/// Puts `rule` in place of the rule of the same name, or adds it
+pub fn replace_rule(rules: &mut Vec<Box<dyn RiskRule>>, rule: Box<dyn RiskRule>) {
+    match rules.iter().position(|r| r.name() == rule.name()) {
+        Some(index) => rules[index] = rule,
+        None => rules.push(rule),
+    }
+}
+
+/// Builds the monitored rules among `rows`, skipping rows that fail to build
+pub fn build_rules(rows: &[RiskRuleRow], pool: Option<&SqlitePool>) -> Vec<Box<dyn RiskRule>> {
+    rows.iter()
+        .filter_map(|row| match build_rule(row, pool) {
+            Ok(rule) => rule,
+            Err(e) => {
+                log::warn!("Skipping risk rule '{}': {}", row.name, e);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::risk::rule::RiskContext;
+    use crate::core::trade::types::Position;
+
+    fn row(name: &str, rule_type: &str, action: &str, params_json: &str) -> RiskRuleRow {
+        RiskRuleRow {
+            id: 1,
+            name: name.to_string(),
+            display_name: name.to_string(),
+            description: String::new(),
+            rule_type: rule_type.to_string(),
+            enabled: true,
+            action: action.to_string(),
+            notify_methods: r#"["log"]"#.to_string(),
+            params_json: params_json.to_string(),
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_parse_action() {
+        assert_eq!(parse_action("warning").unwrap(), RiskAction::Notify);
+        assert_eq!(parse_action("stop_strategy").unwrap(), RiskAction::PauseStrategy);
+        assert_eq!(parse_action("emergency_close").unwrap(), RiskAction::EmergencyStop);
+        assert_eq!(parse_action("close_positions").unwrap(), RiskAction::ClosePositions);
+        assert!(parse_action("reject").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_build_rule_uses_stored_params() {
+        let rule = build_rule(&row(
+            "position_limit",
+            "position_limit",
+            "stop_strategy",
+            r#"{"max_position_value": 100.0, "max_total_value": 500.0, "max_direction_ratio": 1.0}"#,
+        ), None)
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(rule.name(), "position_limit");
+        assert_eq!(rule.config().action, RiskAction::PauseStrategy);
+        assert_eq!(rule.config().notify_methods, vec!["log".to_string()]);
+
+        // 150 > the stored 100, far below the 10_000 default
+        let position = Position {
+            id: "p1".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side: "long".to_string(),
+            quantity: 1.5,
+            entry_price: 100.0,
+            current_price: None,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+            opened_at: 0,
+        };
+        let context = RiskContext::new(vec![position], vec![], 1_000.0, 0.0, "i1".to_string(), "u1".to_string());
+        assert!(rule.check(&context).await.unwrap());
+    }
+
+    #[test]
+    fn test_build_rule_halt_trading_opt_in() {
+        let scoped = row("drawdown_limit", "drawdown_limit", "emergency_close", r#"{"max_drawdown_pct": 15.0}"#);
+        assert!(!build_rule(&scoped, None).unwrap().unwrap().config().halt_trading);
+
+        let halting = row(
+            "drawdown_limit",
+            "drawdown_limit",
+            "emergency_close",
+            r#"{"max_drawdown_pct": 15.0, "halt_trading": 1.0}"#,
+        );
+        let rule = build_rule(&halting, None).unwrap().unwrap();
+        assert!(rule.config().halt_trading);
+        assert_eq!(rule.config().action, RiskAction::EmergencyStop);
+    }
+
+    #[test]
+    fn test_build_rule_defaults_and_skips() {
+        let mut disabled = row("daily_loss_limit", "daily_loss_limit", "warning", "{}");
+        disabled.enabled = false;
+        let rule = build_rule(&disabled, None).unwrap().unwrap();
+        assert!(!rule.config().enabled);
+        assert_eq!(rule.config().action, RiskAction::Notify);
+
+        let pre_trade = row(PRE_TRADE_RULE, "pre_trade", "reject", "{}");
+        assert!(build_rule(&pre_trade, None).unwrap().is_none());
+        let slippage = row(SLIPPAGE_GUARD_RULE, "slippage_guard", "reject", "{}");
+        assert!(build_rule(&slippage, None).unwrap().is_none());
+        let dead_man = row(DEAD_MAN_SWITCH_RULE, "dead_man_switch", "warning", "{}");
+        assert!(build_rule(&dead_man, None).unwrap().is_none());
+
+        let unknown = row("custom", "custom", "warning", "{}");
+        assert!(build_rule(&unknown, None).is_err());
+
+        let rules = build_rules(&[
+            row("drawdown_limit", "drawdown_limit", "emergency_close", r#"{"max_drawdown_pct": 10.0}"#),
+            unknown,
+            pre_trade,
+        ], None);
+        let names: Vec<&str> = rules.iter().map(|r| r.name()).collect();
+        assert_eq!(names, vec!["drawdown_limit"]);
+    }
+
+    #[test]
+    fn test_replace_rule_keeps_other_rules() {
+        let mut rules = build_rules(&[
+            row("drawdown_limit", "drawdown_limit", "warning", r#"{"max_drawdown_pct": 10.0}"#),
+            row("position_limit", "position_limit", "warning", "{}"),
+        ], None);
+
+        let edited = row("drawdown_limit", "drawdown_limit", "emergency_close", r#"{"max_drawdown_pct": 5.0}"#);
+        replace_rule(&mut rules, build_rule(&edited, None).unwrap().unwrap());
+        let names: Vec<&str> = rules.iter().map(|r| r.name()).collect();
+        assert_eq!(names, vec!["drawdown_limit", "position_limit"]);
+        assert_eq!(rules[0].config().action, RiskAction::EmergencyStop);
+        assert_eq!(rules[1].config().action, RiskAction::Notify);
+
+        let added = row("daily_loss_limit", "daily_loss_limit", "warning", "{}");
+        replace_rule(&mut rules, build_rule(&added, None).unwrap().unwrap());
+        assert_eq!(rules.len(), 3);
+    }
+}
@@ -0,0 +1,214 @@
+//! Kill switch
+//!
+//! An app-wide trading halt. While a halt is engaged, every order that
+//! would open or add to a position is refused, whichever account, user or
+//! strategy it comes from; orders that only reduce a position still go out,
+//! so positions can be closed.
+//!
+//! A halt has one of three levels, each including the ones below it:
+//! pause new entries, flatten (cancel open orders and close positions) and
+//! full halt (also stop every strategy instance). An engaged halt can only
+//! escalate; it is cleared by re-arming, which `KillSwitchService` allows
+//! only for authorised users with a reason. The halt is stored in the
+//! `trading_halt` table, so it survives restarts.
+
+use super::pretrade::{is_reduce_only, LimitScope, PreTradeRejection, RejectCode};
+use super::rule::RiskAction;
+use crate::core::trade::types::{OrderSide, Position};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Rule parameter that lets the rule's emergency stop halt all trading
+pub const HALT_TRADING_PARAM: &str = "halt_trading";
+
+/// How far a halt goes, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HaltLevel {
+    /// Refuse orders that open or add to positions
+    PauseEntries,
+    /// Also cancel open orders and close all positions
+    Flatten,
+    /// Also stop every strategy instance
+    FullHalt,
+}
+
+impl HaltLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PauseEntries => "pause_entries",
+            Self::Flatten => "flatten",
+            Self::FullHalt => "full_halt",
+        }
+    }
+
+    /// Whether engaging the level closes positions
+    pub fn flattens(&self) -> bool {
+        *self >= Self::Flatten
+    }
+
+    /// Whether engaging the level stops strategy instances
+    pub fn stops_strategies(&self) -> bool {
+        *self == Self::FullHalt
+    }
+
+    /// Level a triggered risk rule engages, if any
+    ///
+    /// Only an emergency stop of a rule with `halt_trading` set halts the
+    /// whole app; other actions stay with the instance or user the rule
+    /// fired for.
+    pub fn for_action(action: &RiskAction) -> Option<Self> {
+        match action {
+            RiskAction::EmergencyStop => Some(Self::FullHalt),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for HaltLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for HaltLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pause_entries" => Ok(Self::PauseEntries),
+            "flatten" => Ok(Self::Flatten),
+            "full_halt" => Ok(Self::FullHalt),
+            other => Err(format!("Unknown halt level: {}", other)),
+        }
+    }
+}
+
+/// An engaged trading halt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TradingHalt {
+    pub level: HaltLevel,
+    /// Reason given when the halt was engaged or last escalated
+    pub reason: String,
+    /// User who engaged or last escalated it; `None` for the risk monitor
+    pub engaged_by: Option<String>,
+    /// Time (ms) the halt was first engaged
+    pub engaged_at: i64,
+    /// Time (ms) of the last escalation
+    pub updated_at: i64,
+}
+
+impl TradingHalt {
+    pub fn new(level: HaltLevel, reason: impl Into<String>, engaged_by: Option<String>, now: i64) -> Self {
+        Self {
+            level,
+            reason: reason.into(),
+            engaged_by,
+            engaged_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Raise the halt to `level`
+    ///
+    /// Returns false, leaving the halt unchanged, when it is already at or
+    /// above that level.
+    pub fn escalate(&mut self, level: HaltLevel, reason: impl Into<String>, engaged_by: Option<String>, now: i64) -> bool {
+        if level <= self.level {
+            return false;
+        }
+        self.level = level;
+        self.reason = reason.into();
+        self.engaged_by = engaged_by;
+        self.updated_at = now;
+        true
+    }
+
+    /// Why an order is refused while the halt is engaged; `None` if it only
+    /// reduces a position
+    pub fn rejection(
+        &self,
+        side: OrderSide,
+        quantity: f64,
+        symbol: &str,
+        positions: &[Position],
+    ) -> Option<PreTradeRejection> {
+        if is_reduce_only(side, quantity, symbol, positions) {
+            return None;
+        }
+        Some(PreTradeRejection {
+            code: RejectCode::TradingHalted,
+            scope: LimitScope::User,
+            limit: 0.0,
+            actual: quantity,
+            message: format!("Trading halted ({}): {}", self.level, self.reason),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(side: &str, quantity: f64) -> Position {
+        Position {
+            id: "p1".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side: side.to_string(),
+            quantity,
+            entry_price: 50_000.0,
+            current_price: None,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+            opened_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_halt_level_order_and_parsing() {
+        assert!(HaltLevel::PauseEntries < HaltLevel::Flatten);
+        assert!(HaltLevel::Flatten < HaltLevel::FullHalt);
+        assert!(!HaltLevel::PauseEntries.flattens());
+        assert!(HaltLevel::Flatten.flattens() && !HaltLevel::Flatten.stops_strategies());
+        assert!(HaltLevel::FullHalt.flattens() && HaltLevel::FullHalt.stops_strategies());
+
+        for level in [HaltLevel::PauseEntries, HaltLevel::Flatten, HaltLevel::FullHalt] {
+            assert_eq!(level.as_str().parse::<HaltLevel>().unwrap(), level);
+        }
+        assert!("halt".parse::<HaltLevel>().is_err());
+
+        assert_eq!(HaltLevel::for_action(&RiskAction::EmergencyStop), Some(HaltLevel::FullHalt));
+        assert_eq!(HaltLevel::for_action(&RiskAction::ClosePositions), None);
+    }
+
+    #[test]
+    fn test_halt_only_escalates() {
+        let mut halt = TradingHalt::new(HaltLevel::Flatten, "manual", Some("u1".to_string()), 1_000);
+
+        assert!(!halt.escalate(HaltLevel::PauseEntries, "lower", None, 2_000));
+        assert!(!halt.escalate(HaltLevel::Flatten, "same", None, 2_000));
+        assert_eq!((halt.level, halt.reason.as_str(), halt.updated_at), (HaltLevel::Flatten, "manual", 1_000));
+
+        assert!(halt.escalate(HaltLevel::FullHalt, "drawdown", None, 3_000));
+        assert_eq!(halt.level, HaltLevel::FullHalt);
+        assert_eq!(halt.engaged_by, None);
+        assert_eq!((halt.engaged_at, halt.updated_at), (1_000, 3_000));
+    }
+
+    #[test]
+    fn test_halt_lets_reducing_orders_through() {
+        let halt = TradingHalt::new(HaltLevel::PauseEntries, "manual", None, 0);
+        let positions = vec![position("long", 0.5)];
+
+        assert!(halt.rejection(OrderSide::Sell, 0.5, "BTCUSDT", &positions).is_none());
+
+        let rejection = halt.rejection(OrderSide::Buy, 0.1, "BTCUSDT", &positions).unwrap();
+        assert_eq!(rejection.code, RejectCode::TradingHalted);
+        assert_eq!(rejection.message, "Trading halted (pause_entries): manual");
+        // Selling more than the position would flip it short
+        assert!(halt.rejection(OrderSide::Sell, 0.8, "BTCUSDT", &positions).is_some());
+        assert!(halt.rejection(OrderSide::Sell, 0.1, "ETHUSDT", &positions).is_some());
+    }
+}
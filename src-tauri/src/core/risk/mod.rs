@@ -10,11 +10,32 @@ pub mod position_limit;
 pub mod daily_loss;
 pub mod consecutive_loss;
 pub mod volatility_limit;
+pub mod var_limit;
+pub mod concentration;
+pub mod pretrade;
+pub mod slippage;
+pub mod replay;
+pub mod kill_switch;
+pub mod dead_man;
+pub mod factory;
 
-pub use rule::{RiskRule, RiskContext, RiskRuleConfig, RiskAction};
+pub use rule::{RiskRule, RiskContext, RiskMeasurement, RiskRuleConfig, RiskAction};
 pub use drawdown_limit::DrawdownLimitRule;
 pub use monitor::RiskMonitor;
 pub use position_limit::PositionLimitRule;
 pub use daily_loss::{DailyLossLimitRule, DailyLossLimitParams};
 pub use consecutive_loss::{ConsecutiveLossLimitRule, ConsecutiveLossLimitParams};
 pub use volatility_limit::{VolatilityLimitRule, VolatilityLimitParams};
+pub use var_limit::{VarEstimate, VarLimitParams, VarLimitRule, VarMethod};
+pub use concentration::{
+    AssetGroup, ConcentrationLimitRule, ConcentrationLimits, ExposureBreakdown, ExposureKind, ExposureLine,
+};
+pub use factory::{build_rule, build_rules, load_rule, load_rules, parse_action, replace_rule};
+pub use kill_switch::{HaltLevel, TradingHalt, HALT_TRADING_PARAM};
+pub use dead_man::{unresponsive, DeadManConfig, DEAD_MAN_SWITCH_RULE};
+pub use replay::{replay_rules, ReplayPoint, ReplayReport, ReplayTrigger, RuleReplaySummary};
+pub use slippage::{estimate_slippage, SlippageDecision, SlippageEstimate, SlippageLimits, SLIPPAGE_GUARD_RULE};
+pub use pretrade::{
+    is_reduce_only, LimitScope, OrderOrigin, PreTradeLimits, PreTradeOrder, PreTradeRejection, PreTradeSnapshot,
+    RejectCode, PRE_TRADE_RULE,
+};
@@ -2,54 +2,156 @@
 //!
 //! This module provides the main risk monitoring service that coordinates
 //! all risk rules and executes appropriate actions when rules are triggered.
+//!
+//! Rules are checked once for every running strategy instance, against the
+//! instance's own positions, orders and PnL, and once for every user across
+//! all of the user's connected exchange accounts. Actions hit the instance
+//! or user the context was built for, except an emergency stop, which
+//! engages the app-wide kill switch when one is configured.
+//!
+//! A rule that triggers records one alert in `risk_alerts`, publishes it on
+//! the event bus and runs its action once. While the rule stays in breach
+//! for the same instance or user nothing is repeated; when it clears, a
+//! risk-normalized event is published.
+//!
+//! Rules are built from the `risk_rules` table by
+//! [`RiskMonitor::reload_rules`]; [`RiskMonitor::reload_rule`] rebuilds
+//! the one rule that was edited.
 
 use super::rule::*;
-use crate::core::strategy::StrategyEngine;
-use crate::core::trade::types::*;
+use crate::core::event::{EventBus, RiskAlertData, RiskNormalizedData, RiskThresholdData};
+use crate::core::strategy::{InstanceRiskTarget, StrategyEngine};
 use crate::infrastructure::NotificationService;
-use crate::services::{EmergencyService, TradeService};
+use crate::models::{AlertSeverity, CreateAlertRequest, RiskAlert};
+use crate::repository::RiskAlertRepository;
+use super::kill_switch::HaltLevel;
+use crate::services::{
+    EmergencyService, ExchangeSession, ExchangeSessionRegistry, KillSwitchService, PnlScope, PnlService, TradeService,
+};
 use anyhow::{Context, Result};
 use chrono::Utc;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::time::{interval, Duration};
-use uuid::Uuid;
+
+/// What a risk context was built for
+enum RiskTarget {
+    /// A running strategy instance
+    Instance(InstanceRiskTarget),
+    /// All connected exchange accounts of a user
+    User {
+        user_id: String,
+        sessions: Vec<Arc<ExchangeSession>>,
+    },
+}
+
+/// A rule in breach for one instance or user
+struct Breach {
+    /// Alert raised when the breach started
+    alert_id: String,
+    /// Measurement at the time of the alert
+    measurement: Option<RiskMeasurement>,
+}
+
+/// Open breaches, keyed by rule name and context scope
+#[derive(Default)]
+struct BreachTracker {
+    open: HashMap<(String, String), Breach>,
+}
+
+impl BreachTracker {
+    fn is_open(&self, rule: &str, scope: &str) -> bool {
+        self.open.contains_key(&(rule.to_string(), scope.to_string()))
+    }
+
+    fn open(&mut self, rule: &str, scope: &str, breach: Breach) {
+        self.open.insert((rule.to_string(), scope.to_string()), breach);
+    }
+
+    fn close(&mut self, rule: &str, scope: &str) -> Option<Breach> {
+        self.open.remove(&(rule.to_string(), scope.to_string()))
+    }
+
+    /// Forget breaches of rules that are no longer checked
+    fn retain_rules(&mut self, rules: &[&str]) {
+        self.open.retain(|(rule, _), _| rules.contains(&rule.as_str()));
+    }
+
+    /// Forget breaches of a rule that has been disabled
+    fn forget_rule(&mut self, rule: &str) {
+        self.open.retain(|(breached, _), _| breached != rule);
+    }
+}
 
 /// Risk monitoring service that continuously checks risk rules
 pub struct RiskMonitor {
     /// Collection of risk rules to check
     rules: Arc<RwLock<Vec<Box<dyn RiskRule>>>>,
-    /// Trade service for accessing trading data
-    trade_service: Arc<TradeService>,
+    /// Connected exchange accounts, for user-wide checks and actions
+    sessions: Arc<ExchangeSessionRegistry>,
     /// Strategy engine for stopping/pausing strategies
     strategy_engine: Arc<StrategyEngine>,
-    /// Emergency service for emergency operations
-    emergency_service: Arc<EmergencyService>,
     /// Notification service for sending alerts
     notification_service: Arc<dyn NotificationService>,
+    /// PnL service for today's PnL
+    pnl_service: Option<Arc<PnlService>>,
+    /// Storage for triggered alerts
+    alert_repo: Option<RiskAlertRepository>,
+    /// Bus risk events are published on
+    event_bus: Option<Arc<EventBus>>,
+    /// App-wide trading halt engaged by emergency stops of rules that opt in
+    kill_switch: Option<Arc<KillSwitchService>>,
+    /// Rules currently in breach
+    breaches: RwLock<BreachTracker>,
 }
 
 impl RiskMonitor {
     /// Create a new risk monitor
     pub fn new(
-        trade_service: Arc<TradeService>,
+        sessions: Arc<ExchangeSessionRegistry>,
         strategy_engine: Arc<StrategyEngine>,
         notification_service: Arc<dyn NotificationService>,
     ) -> Self {
-        let emergency_service = Arc::new(EmergencyService::new(
-            trade_service.clone(),
-            strategy_engine.clone(),
-        ));
-
         Self {
             rules: Arc::new(RwLock::new(Vec::new())),
-            trade_service,
+            sessions,
             strategy_engine,
-            emergency_service,
             notification_service,
+            pnl_service: None,
+            alert_repo: None,
+            event_bus: None,
+            kill_switch: None,
+            breaches: RwLock::new(BreachTracker::default()),
         }
     }
 
+    /// Compute `today_pnl` for risk checks from recorded fills and snapshots
+    pub fn with_pnl_service(mut self, pnl_service: Arc<PnlService>) -> Self {
+        self.pnl_service = Some(pnl_service);
+        self
+    }
+
+    /// Store triggered alerts in `risk_alerts`
+    pub fn with_alert_repository(mut self, alert_repo: RiskAlertRepository) -> Self {
+        self.alert_repo = Some(alert_repo);
+        self
+    }
+
+    /// Publish alert and recovery events on the event bus
+    pub fn with_event_bus(mut self, event_bus: Arc<EventBus>) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// Halt trading app-wide through the kill switch when a rule's action
+    /// calls for it, instead of stopping only the rule's instance or user
+    pub fn with_kill_switch(mut self, kill_switch: Arc<KillSwitchService>) -> Self {
+        self.kill_switch = Some(kill_switch);
+        self
+    }
+
     /// Add a risk rule to the monitor
     pub async fn add_rule(&self, rule: Box<dyn RiskRule>) {
         let mut rules = self.rules.write().await;
@@ -57,6 +159,43 @@ impl RiskMonitor {
         log::info!("Risk rule added to monitor");
     }
 
+    /// Replace the checked rules with the ones stored in `risk_rules`
+    ///
+    /// Open breaches of rules that are still enabled are kept, so a reload
+    /// does not raise their alerts again.
+    pub async fn reload_rules(&self, pool: &SqlitePool) -> Result<usize> {
+        let rules = super::factory::load_rules(pool).await?;
+        let count = rules.len();
+
+        let enabled: Vec<&str> = rules
+            .iter()
+            .filter(|r| r.config().enabled)
+            .map(|r| r.name())
+            .collect();
+        self.breaches.write().await.retain_rules(&enabled);
+
+        *self.rules.write().await = rules;
+        log::info!("Risk monitor loaded {} rules", count);
+        Ok(count)
+    }
+
+    /// Rebuild the rule stored under `name` after it was edited
+    ///
+    /// The other rules keep their state. A row that no longer builds fails
+    /// the reload and leaves the rule as it was.
+    pub async fn reload_rule(&self, pool: &SqlitePool, name: &str) -> Result<()> {
+        let Some(rule) = super::factory::load_rule(pool, name).await? else {
+            return Ok(());
+        };
+        if !rule.config().enabled {
+            self.breaches.write().await.forget_rule(rule.name());
+        }
+
+        super::factory::replace_rule(&mut *self.rules.write().await, rule);
+        log::info!("Risk monitor reloaded rule {}", name);
+        Ok(())
+    }
+
     /// Start the risk monitoring loop
     ///
     /// This runs continuously in the background, checking all enabled
@@ -77,93 +216,166 @@ impl RiskMonitor {
         log::info!("Risk monitor started");
     }
 
-    /// Check all enabled risk rules
+    /// Check all enabled risk rules for every instance and every user
     async fn check_all_rules(&self) -> Result<()> {
-        // Get current trading state
-        // Note: Using a placeholder user_id for now
-        let user_id = "system";
-
-        // Get current positions
-        let positions = self
-            .trade_service
-            .get_positions(user_id)
-            .await
-            .unwrap_or_default();
+        for target in self.strategy_engine.risk_targets().await {
+            let Some(trade_service) = target.trade_service.clone() else {
+                continue;
+            };
+            let context = self.instance_context(&target, &trade_service).await;
+            self.check_rules(&RiskTarget::Instance(target), &context).await;
+        }
+
+        let mut users: HashMap<String, Vec<Arc<ExchangeSession>>> = HashMap::new();
+        for session in self.sessions.open_sessions().await {
+            users.entry(session.user_id.clone()).or_default().push(session);
+        }
+        for (user_id, sessions) in users {
+            let context = self.user_context(&user_id, &sessions).await;
+            self.check_rules(&RiskTarget::User { user_id, sessions }, &context).await;
+        }
 
-        // Get active orders
-        let orders = self
-            .trade_service
-            .get_open_orders(user_id)
+        Ok(())
+    }
+
+    /// Positions, orders and PnL of one strategy instance
+    ///
+    /// Positions are netted per account, so the instance sees only the
+    /// positions built by its own orders; positions shared with other
+    /// instances or manual orders are left to the user-wide checks.
+    async fn instance_context(&self, target: &InstanceRiskTarget, trade_service: &TradeService) -> RiskContext {
+        let positions = trade_service
+            .get_instance_positions(&target.user_id, &target.instance_id)
             .await
             .unwrap_or_default();
-
-        // Get account balance
-        let balances = self
-            .trade_service
-            .get_balance()
+        let orders = trade_service
+            .get_instance_open_orders(&target.user_id, &target.instance_id)
             .await
             .unwrap_or_default();
-        let balance = balances.iter().map(|b| b.total).sum();
+        let balance = total_balance(trade_service).await;
+        let today_pnl = self
+            .today_pnl(&target.user_id, &PnlScope::Instance(target.instance_id.clone()))
+            .await;
+
+        let accounts = match trade_service.exchange_id() {
+            Some(exchange_id) => positions.iter().map(|p| (p.id.clone(), exchange_id.to_string())).collect(),
+            None => HashMap::new(),
+        };
 
-        // Build risk context
-        let context = RiskContext {
+        RiskContext::new(
             positions,
             orders,
             balance,
-            today_pnl: 0.0, // TODO: Calculate from database
-            instance_id: "default".to_string(),
-        };
+            today_pnl,
+            target.instance_id.clone(),
+            target.user_id.clone(),
+        )
+        .with_position_accounts(accounts)
+    }
+
+    /// Positions, orders and PnL across a user's connected accounts
+    async fn user_context(&self, user_id: &str, sessions: &[Arc<ExchangeSession>]) -> RiskContext {
+        let mut positions = Vec::new();
+        let mut accounts = HashMap::new();
+        let mut orders = Vec::new();
+        let mut balance = 0.0;
+        for session in sessions {
+            let trade_service = &session.trade_service;
+            let account_positions = trade_service.get_positions(user_id).await.unwrap_or_default();
+            accounts.extend(account_positions.iter().map(|p| (p.id.clone(), session.config_id.clone())));
+            positions.extend(account_positions);
+            orders.extend(trade_service.get_open_orders(user_id).await.unwrap_or_default());
+            balance += total_balance(trade_service).await;
+        }
+        let today_pnl = self.today_pnl(user_id, &PnlScope::User).await;
+
+        RiskContext::for_user(positions, orders, balance, today_pnl, user_id.to_string())
+            .with_position_accounts(accounts)
+    }
+
+    async fn today_pnl(&self, user_id: &str, scope: &PnlScope) -> f64 {
+        match &self.pnl_service {
+            Some(pnl_service) => pnl_service
+                .today_pnl(user_id, scope)
+                .await
+                .unwrap_or_else(|e| {
+                    log::warn!("Failed to compute today's PnL: {}", e);
+                    0.0
+                }),
+            None => 0.0,
+        }
+    }
 
-        // Check all rules
+    /// Check every enabled rule against one context
+    async fn check_rules(&self, target: &RiskTarget, context: &RiskContext) {
         let rules = self.rules.read().await;
         for rule in rules.iter() {
             if !rule.config().enabled {
                 continue;
             }
 
-            match rule.check(&context).await {
-                Ok(triggered) => {
-                    if triggered {
-                        if let Err(e) = self.handle_rule_trigger(&**rule, &context).await {
-                            log::error!("Failed to handle rule trigger for {}: {}", rule.name(), e);
-                        }
+            match rule.check(context).await {
+                Ok(true) => {
+                    if self.breaches.read().await.is_open(rule.name(), context.scope_key()) {
+                        log::debug!("Risk rule '{}' still in breach for {}", rule.name(), describe(context));
+                        continue;
+                    }
+                    if let Err(e) = self.handle_rule_trigger(&**rule, target, context).await {
+                        log::error!("Failed to handle rule trigger for {}: {}", rule.name(), e);
                     }
                 }
+                Ok(false) => self.clear_breach(&**rule, context).await,
                 Err(e) => {
-                    log::error!("Rule {} check failed: {}", rule.name(), e);
+                    log::error!("Rule {} check failed for {}: {}", rule.name(), describe(context), e);
                 }
             }
         }
-
-        Ok(())
     }
 
     /// Handle a triggered risk rule
     async fn handle_rule_trigger(
         &self,
         rule: &dyn RiskRule,
+        target: &RiskTarget,
         context: &RiskContext,
     ) -> Result<()> {
         let config = rule.config();
 
         log::warn!(
-            "Risk rule '{}' triggered for strategy '{}'",
+            "Risk rule '{}' triggered for {}",
             rule.name(),
-            context.instance_id
+            describe(context)
         );
 
-        // Record alert to database
-        if let Err(e) = self.record_alert(rule, context).await {
-            log::error!("Failed to record alert: {}", e);
-        }
+        // Record the alert and mark the breach open, so the action runs once
+        let measurement = rule.measure(context);
+        let alert = self.record_alert(rule, context, measurement.as_ref()).await;
+        self.breaches.write().await.open(
+            rule.name(),
+            context.scope_key(),
+            Breach {
+                alert_id: alert.id.clone(),
+                measurement: measurement.clone(),
+            },
+        );
+        self.publish_triggered(rule, &alert, measurement.as_ref());
 
         // Send notifications
         for method in &config.notify_methods {
-            if let Err(e) = self.send_notification(method, rule, context).await {
+            if let Err(e) = self.send_notification(method, rule, context, &alert.message).await {
                 log::error!("Failed to send {} notification: {}", method, e);
             }
         }
 
+        // Rules that opted in halt all trading through the kill switch
+        let halt_level = HaltLevel::for_action(&config.action).filter(|_| config.halt_trading);
+        if let (Some(kill_switch), Some(level)) = (&self.kill_switch, halt_level) {
+            let reason = format!("Risk rule '{}' triggered for {}", rule.name(), describe(context));
+            let outcome = kill_switch.engage(level, &reason, None).await?;
+            log::error!("Kill switch at {} after risk rule '{}': {:?}", outcome.halt.level, rule.name(), outcome.report);
+            return Ok(());
+        }
+
         // Execute the configured action
         match &config.action {
             RiskAction::LogOnly => {
@@ -173,53 +385,101 @@ impl RiskMonitor {
                 // Notifications already sent above
             }
             RiskAction::PauseStrategy => {
-                self.pause_strategy(&context.instance_id).await?;
+                self.pause_strategies(target).await?;
             }
             RiskAction::ClosePositions => {
-                self.close_all_positions(&context.instance_id).await?;
+                // Stop new entries first so closed positions aren't reopened
+                self.pause_strategies(target).await?;
+                self.close_positions(target).await?;
             }
             RiskAction::EmergencyStop => {
-                self.emergency_stop().await?;
+                self.emergency_stop(target).await?;
             }
         }
 
         Ok(())
     }
 
-    /// Record alert to database
+    /// Record the alert in `risk_alerts`
+    ///
+    /// A failed insert is logged; the alert is still raised.
     async fn record_alert(
         &self,
         rule: &dyn RiskRule,
-        _context: &RiskContext,
-    ) -> Result<()> {
-        let alert_id = Uuid::new_v4().to_string();
-        let timestamp = Utc::now().timestamp();
+        context: &RiskContext,
+        measurement: Option<&RiskMeasurement>,
+    ) -> RiskAlert {
+        let request = alert_request(rule, context, measurement);
+
+        if let Some(alert_repo) = &self.alert_repo {
+            match alert_repo.create(request.clone()).await {
+                Ok(alert) => return alert,
+                Err(e) => log::error!("Failed to record alert: {}", e),
+            }
+        }
+        RiskAlert::new(request)
+    }
+
+    /// Publish a new alert and the threshold it crossed
+    fn publish_triggered(&self, rule: &dyn RiskRule, alert: &RiskAlert, measurement: Option<&RiskMeasurement>) {
+        let Some(event_bus) = &self.event_bus else {
+            return;
+        };
+
+        event_bus.publish_alert_triggered(RiskAlertData {
+            id: alert.id.clone(),
+            rule_id: alert.rule_id.clone(),
+            user_id: alert.user_id.clone(),
+            severity: alert.severity.clone(),
+            title: alert.title.clone(),
+            message: alert.message.clone(),
+            strategy_instance_id: alert.strategy_instance_id.clone(),
+            symbol: alert.symbol.clone(),
+            current_value: alert.current_value,
+            threshold_value: alert.threshold_value,
+        });
+        if let Some(measurement) = measurement {
+            event_bus.publish_threshold_exceeded(RiskThresholdData {
+                rule_id: alert.rule_id.clone(),
+                user_id: alert.user_id.clone(),
+                rule_name: rule.name().to_string(),
+                metric_name: measurement.metric.clone(),
+                current_value: measurement.current_value,
+                threshold_value: measurement.threshold_value,
+                severity: alert.severity.clone(),
+            });
+        }
+    }
+
+    /// Close the rule's breach for the context, if open, and publish the recovery
+    async fn clear_breach(&self, rule: &dyn RiskRule, context: &RiskContext) {
+        let Some(breach) = self.breaches.write().await.close(rule.name(), context.scope_key()) else {
+            return;
+        };
 
-        // TODO: Implement database insertion when risk_alerts table is created
         log::info!(
-            "Recording alert: {} - {} at {}",
-            alert_id,
+            "Risk rule '{}' back within limits for {} (alert {})",
             rule.name(),
-            timestamp
+            describe(context),
+            breach.alert_id
         );
 
-        // Placeholder for database insertion
-        // sqlx::query(
-        //     r#"
-        //     INSERT INTO risk_alerts (id, rule_name, instance_id, message, action, created_at)
-        //     VALUES (?, ?, ?, ?, ?, ?)
-        //     "#
-        // )
-        // .bind(&alert_id)
-        // .bind(rule.name())
-        // .bind(&context.instance_id)
-        // .bind(format!("Rule '{}' triggered", rule.name()))
-        // .bind(format!("{:?}", rule.config().action))
-        // .bind(timestamp)
-        // .execute(&self.db.pool)
-        // .await?;
-
-        Ok(())
+        let Some(event_bus) = &self.event_bus else {
+            return;
+        };
+        let (metric_name, current_value, threshold_value) = rule
+            .measure(context)
+            .or(breach.measurement)
+            .map(|m| (m.metric, m.current_value, m.threshold_value))
+            .unwrap_or_default();
+        event_bus.publish_risk_normalized(RiskNormalizedData {
+            rule_id: rule.name().to_string(),
+            user_id: context.user_id.clone(),
+            rule_name: rule.name().to_string(),
+            metric_name,
+            current_value,
+            threshold_value,
+        });
     }
 
     /// Send notification via specified method
@@ -228,17 +488,20 @@ impl RiskMonitor {
         method: &str,
         rule: &dyn RiskRule,
         context: &RiskContext,
+        detail: &str,
     ) -> Result<()> {
         let message = format!(
             "风控触发预警\n\
              规则: {}\n\
-             策略: {}\n\
+             对象: {}\n\
+             详情: {}\n\
              动作: {:?}\n\
              时间: {}\n\
              持仓数: {}\n\
              账户余额: {:.2}",
             rule.name(),
-            context.instance_id,
+            describe(context),
+            detail,
             rule.config().action,
             Utc::now().format("%Y-%m-%d %H:%M:%S"),
             context.positions.len(),
@@ -269,103 +532,173 @@ impl RiskMonitor {
         Ok(())
     }
 
-    /// Pause a strategy instance
-    async fn pause_strategy(&self, instance_id: &str) -> Result<()> {
-        log::warn!("Pausing strategy due to risk rule: {}", instance_id);
-
-        self.strategy_engine
-            .pause_instance(instance_id)
-            .await
-            .context(format!("Failed to pause strategy instance {}", instance_id))?;
+    /// Pause the instance, or every running instance of the user
+    async fn pause_strategies(&self, target: &RiskTarget) -> Result<()> {
+        let instance_ids: Vec<String> = match target {
+            RiskTarget::Instance(instance) if !instance.paused => vec![instance.instance_id.clone()],
+            RiskTarget::Instance(_) => Vec::new(),
+            RiskTarget::User { user_id, .. } => self
+                .strategy_engine
+                .risk_targets()
+                .await
+                .into_iter()
+                .filter(|t| t.user_id == *user_id && !t.paused)
+                .map(|t| t.instance_id)
+                .collect(),
+        };
 
-        log::warn!("Strategy {} paused due to risk rule", instance_id);
+        for instance_id in instance_ids {
+            log::warn!("Pausing strategy due to risk rule: {}", instance_id);
+            self.strategy_engine
+                .pause_instance(&instance_id)
+                .await
+                .context(format!("Failed to pause strategy instance {}", instance_id))?;
+            log::warn!("Strategy {} paused due to risk rule", instance_id);
+        }
         Ok(())
     }
 
-    /// Close all positions for a strategy (closes all positions for now)
-    async fn close_all_positions(&self, instance_id: &str) -> Result<()> {
-        log::warn!("Closing all positions for strategy: {}", instance_id);
-
-        let user_id = "system"; // TODO: Get actual user_id from context
-        let mut closed_count = 0;
-
-        // Get all positions
-        let positions = self
-            .trade_service
-            .get_positions(user_id)
-            .await
-            .unwrap_or_default();
-
-        for position in positions {
-            // Determine closing side: opposite of position side
-            let close_side = match position.side.as_str() {
-                "long" | "buy" => OrderSide::Sell,
-                "short" | "sell" => OrderSide::Buy,
-                _ => {
-                    log::error!("Unknown position side: {}", position.side);
-                    continue;
-                }
-            };
-
-            // Create market order to close position
-            let close_request = OrderRequest {
-                symbol: position.symbol.clone(),
-                side: close_side,
-                order_type: OrderType::Market,
-                price: None,
-                stop_price: None,
-                quantity: position.quantity.abs(), // Ensure positive quantity
-                client_order_id: Some(format!("RISK-CLOSE-{}", Uuid::new_v4())),
-                time_in_force: Some(TimeInForce::IOC),
-            };
-
-            match self
-                .trade_service
-                .place_order(close_request, user_id)
-                .await
-            {
-                Ok(_) => {
-                    closed_count += 1;
-                    log::warn!(
-                        "Closed position {} ({}) due to risk rule",
-                        position.symbol,
-                        position.id
-                    );
-                }
-                Err(e) => {
-                    log::error!(
-                        "Failed to close position {} ({}): {}",
-                        position.symbol,
-                        position.id,
-                        e
-                    );
+    /// Close the instance's positions, or all of the user's positions
+    async fn close_positions(&self, target: &RiskTarget) -> Result<()> {
+        // Closing orders go out as one batch per account
+        let closed_count = match target {
+            RiskTarget::Instance(instance) => {
+                let Some(trade_service) = instance.trade_service.clone() else {
+                    return Ok(());
+                };
+                log::warn!("Closing positions of strategy: {}", instance.instance_id);
+                self.emergency_service(trade_service)
+                    .close_instance_positions(&instance.user_id, &instance.instance_id, &instance.symbols, "RISK")
+                    .await
+                    .unwrap_or_else(|e| {
+                        log::error!("Failed to close positions for strategy {}: {}", instance.instance_id, e);
+                        0
+                    })
+            }
+            RiskTarget::User { user_id, sessions } => {
+                log::warn!("Closing all positions of user: {}", user_id);
+                let mut closed_count = 0;
+                for session in sessions {
+                    closed_count += self
+                        .emergency_service(session.trade_service.clone())
+                        .close_all_positions(user_id, "RISK")
+                        .await
+                        .unwrap_or_else(|e| {
+                            log::error!("Failed to close positions on account {}: {}", session.config_id, e);
+                            0
+                        });
                 }
+                closed_count
             }
-        }
+        };
 
-        log::warn!("Closed {} positions for strategy {}", closed_count, instance_id);
+        log::warn!("Closed {} positions due to risk rule", closed_count);
         Ok(())
     }
 
     /// Emergency stop - stop all strategies and close all positions
-    async fn emergency_stop(&self) -> Result<()> {
+    async fn emergency_stop(&self, target: &RiskTarget) -> Result<()> {
         log::error!("!!! EMERGENCY STOP TRIGGERED !!!");
 
-        let user_id = "system"; // TODO: Get actual user_id from context
+        let (user_id, trade_services): (&str, Vec<Arc<TradeService>>) = match target {
+            RiskTarget::Instance(instance) => (
+                &instance.user_id,
+                instance.trade_service.iter().cloned().collect(),
+            ),
+            RiskTarget::User { user_id, sessions } => (
+                user_id,
+                sessions.iter().map(|s| s.trade_service.clone()).collect(),
+            ),
+        };
 
         // Use the emergency service to execute full emergency stop
-        let report = self
-            .emergency_service
-            .emergency_stop_all(user_id)
-            .await
-            .context("Failed to execute emergency stop")?;
+        for trade_service in trade_services {
+            let report = self
+                .emergency_service(trade_service)
+                .emergency_stop_all(user_id)
+                .await
+                .context("Failed to execute emergency stop")?;
 
-        log::error!(
-            "!!! EMERGENCY STOP COMPLETED: {:?} !!!",
-            report
-        );
+            log::error!(
+                "!!! EMERGENCY STOP COMPLETED: {:?} !!!",
+                report
+            );
+        }
         Ok(())
     }
+
+    fn emergency_service(&self, trade_service: Arc<TradeService>) -> EmergencyService {
+        EmergencyService::new(trade_service, self.strategy_engine.clone())
+    }
+}
+
+/// Sum of balance totals of an account
+async fn total_balance(trade_service: &TradeService) -> f64 {
+    trade_service
+        .get_balance()
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|b| b.total)
+        .sum()
+}
+
+/// Alert severity for the action a rule takes
+fn alert_severity(action: &RiskAction) -> AlertSeverity {
+    match action {
+        RiskAction::LogOnly => AlertSeverity::Low,
+        RiskAction::Notify => AlertSeverity::Medium,
+        RiskAction::PauseStrategy => AlertSeverity::High,
+        RiskAction::ClosePositions | RiskAction::EmergencyStop => AlertSeverity::Critical,
+    }
+}
+
+/// Alert row for a triggered rule
+fn alert_request(
+    rule: &dyn RiskRule,
+    context: &RiskContext,
+    measurement: Option<&RiskMeasurement>,
+) -> CreateAlertRequest {
+    let mut message = format!("Rule '{}' triggered for {}", rule.name(), describe(context));
+    if let Some(m) = measurement {
+        message.push_str(&format!(
+            ": {} {} exceeds {}",
+            m.metric,
+            round4(m.current_value),
+            round4(m.threshold_value)
+        ));
+        if let Some(symbol) = &m.symbol {
+            message.push_str(&format!(" ({})", symbol));
+        }
+        if let Some(detail) = &m.detail {
+            message.push_str(&format!(". {}", detail));
+        }
+    }
+
+    CreateAlertRequest {
+        rule_id: rule.name().to_string(),
+        user_id: context.user_id.clone(),
+        severity: alert_severity(&rule.config().action).as_str().to_string(),
+        title: rule.name().to_string(),
+        message,
+        strategy_instance_id: (!context.instance_id.is_empty()).then(|| context.instance_id.clone()),
+        symbol: measurement.and_then(|m| m.symbol.clone()),
+        current_value: measurement.map_or(0.0, |m| m.current_value),
+        threshold_value: measurement.map_or(0.0, |m| m.threshold_value),
+    }
+}
+
+fn round4(value: f64) -> f64 {
+    (value * 10_000.0).round() / 10_000.0
+}
+
+/// Who a context describes, for logs and notifications
+fn describe(context: &RiskContext) -> String {
+    if context.instance_id.is_empty() {
+        format!("user '{}'", context.user_id)
+    } else {
+        format!("strategy '{}'", context.instance_id)
+    }
 }
 
 #[cfg(test)]
@@ -378,4 +711,101 @@ mod tests {
         // For now, just verify compilation
         assert!(true);
     }
+
+    #[test]
+    fn test_describe_context() {
+        let instance = RiskContext::new(vec![], vec![], 0.0, 0.0, "i1".to_string(), "u1".to_string());
+        assert_eq!(describe(&instance), "strategy 'i1'");
+        let user = RiskContext::for_user(vec![], vec![], 0.0, 0.0, "u1".to_string());
+        assert_eq!(describe(&user), "user 'u1'");
+    }
+
+    struct FixedRule {
+        config: RiskRuleConfig,
+    }
+
+    #[async_trait::async_trait]
+    impl RiskRule for FixedRule {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        fn description(&self) -> &str {
+            "Always triggers"
+        }
+
+        async fn check(&self, _context: &RiskContext) -> Result<bool> {
+            Ok(true)
+        }
+
+        fn config(&self) -> &RiskRuleConfig {
+            &self.config
+        }
+
+        fn update_config(&mut self, config: RiskRuleConfig) -> Result<()> {
+            self.config = config;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_alert_severity_follows_action() {
+        assert_eq!(alert_severity(&RiskAction::LogOnly), AlertSeverity::Low);
+        assert_eq!(alert_severity(&RiskAction::Notify), AlertSeverity::Medium);
+        assert_eq!(alert_severity(&RiskAction::PauseStrategy), AlertSeverity::High);
+        assert_eq!(alert_severity(&RiskAction::ClosePositions), AlertSeverity::Critical);
+        assert_eq!(alert_severity(&RiskAction::EmergencyStop), AlertSeverity::Critical);
+    }
+
+    #[test]
+    fn test_alert_request_carries_measurement() {
+        let rule = FixedRule {
+            config: RiskRuleConfig::new(true, RiskAction::PauseStrategy, vec![]),
+        };
+        let instance = RiskContext::new(vec![], vec![], 0.0, 0.0, "i1".to_string(), "u1".to_string());
+        let measurement = RiskMeasurement::new("position_value", 1500.0, 1000.0).with_symbol("BTCUSDT");
+
+        let request = alert_request(&rule, &instance, Some(&measurement));
+        assert_eq!(request.rule_id, "fixed");
+        assert_eq!(request.user_id, "u1");
+        assert_eq!(request.severity, "high");
+        assert_eq!(request.strategy_instance_id.as_deref(), Some("i1"));
+        assert_eq!(request.symbol.as_deref(), Some("BTCUSDT"));
+        assert_eq!(request.current_value, 1500.0);
+        assert_eq!(request.threshold_value, 1000.0);
+        assert_eq!(
+            request.message,
+            "Rule 'fixed' triggered for strategy 'i1': position_value 1500 exceeds 1000 (BTCUSDT)"
+        );
+
+        let user = RiskContext::for_user(vec![], vec![], 0.0, 0.0, "u1".to_string());
+        let request = alert_request(&rule, &user, None);
+        assert_eq!(request.strategy_instance_id, None);
+        assert_eq!(request.current_value, 0.0);
+        assert_eq!(request.message, "Rule 'fixed' triggered for user 'u1'");
+    }
+
+    #[test]
+    fn test_breach_tracker_per_rule_and_scope() {
+        let mut breaches = BreachTracker::default();
+        let breach = |id: &str| Breach {
+            alert_id: id.to_string(),
+            measurement: None,
+        };
+
+        breaches.open("drawdown_limit", "i1", breach("a1"));
+        assert!(breaches.is_open("drawdown_limit", "i1"));
+        assert!(!breaches.is_open("drawdown_limit", "i2"));
+        assert!(!breaches.is_open("position_limit", "i1"));
+
+        assert_eq!(breaches.close("drawdown_limit", "i1").map(|b| b.alert_id), Some("a1".to_string()));
+        assert!(!breaches.is_open("drawdown_limit", "i1"));
+        assert!(breaches.close("drawdown_limit", "i1").is_none());
+
+        breaches.open("drawdown_limit", "i1", breach("a2"));
+        breaches.open("position_limit", "u1", breach("a3"));
+        breaches.retain_rules(&["position_limit"]);
+        assert!(!breaches.is_open("drawdown_limit", "i1"));
+        assert!(breaches.is_open("position_limit", "u1"));
+    }
 }
@@ -36,6 +36,7 @@ impl PositionLimitRule {
                 enabled: true,
                 action: RiskAction::Notify,
                 notify_methods: vec!["dingtalk".to_string()],
+                halt_trading: false,
             },
             max_position_value,
             max_total_value,
@@ -166,6 +167,40 @@ impl RiskRule for PositionLimitRule {
         Ok(false)
     }
 
+    fn measure(&self, context: &RiskContext) -> Option<RiskMeasurement> {
+        let positions = &context.positions;
+
+        // Report the limit check() stops at, in the same order
+        if let Some(position) = positions
+            .iter()
+            .find(|p| p.quantity * p.entry_price > self.max_position_value)
+        {
+            return Some(
+                RiskMeasurement::new(
+                    "position_value",
+                    position.quantity * position.entry_price,
+                    self.max_position_value,
+                )
+                .with_symbol(&position.symbol),
+            );
+        }
+
+        let total_value = self.calculate_position_value(positions);
+        if total_value > self.max_total_value {
+            return Some(RiskMeasurement::new("total_position_value", total_value, self.max_total_value));
+        }
+
+        let long_ratio = self.calculate_long_ratio(positions);
+        if long_ratio > self.max_direction_ratio {
+            return Some(RiskMeasurement::new("long_ratio", long_ratio, self.max_direction_ratio));
+        }
+        if 1.0 - long_ratio > self.max_direction_ratio {
+            return Some(RiskMeasurement::new("short_ratio", 1.0 - long_ratio, self.max_direction_ratio));
+        }
+
+        Some(RiskMeasurement::new("total_position_value", total_value, self.max_total_value))
+    }
+
     fn config(&self) -> &RiskRuleConfig {
         &self.config
     }
@@ -201,6 +236,9 @@ mod tests {
             balance: 10000.0,
             today_pnl: 0.0,
             instance_id: "test_instance".to_string(),
+            user_id: "test_user".to_string(),
+            position_accounts: Default::default(),
+            timestamp: None,
         }
     }
 
@@ -345,6 +383,7 @@ mod tests {
             enabled: false,
             action: RiskAction::PauseStrategy,
             notify_methods: vec!["email".to_string(), "dingtalk".to_string()],
+            halt_trading: false,
         };
 
         let rule = PositionLimitRule::with_config(1000.0, 5000.0, 0.7, config.clone());
@@ -362,6 +401,7 @@ mod tests {
             enabled: false,
             action: RiskAction::EmergencyStop,
             notify_methods: vec!["email".to_string()],
+            halt_trading: false,
         };
 
         rule.update_config(new_config).unwrap();
@@ -380,6 +420,7 @@ mod tests {
             enabled: false,
             action: RiskAction::Notify,
             notify_methods: vec![],
+            halt_trading: false,
         };
         rule.update_config(disabled_config).unwrap();
 
@@ -439,4 +480,33 @@ mod tests {
         let context = create_test_context(imbalanced);
         assert!(rule.check(&context).await.unwrap());
     }
+
+    #[test]
+    fn test_measure_reports_breached_limit() {
+        let rule = PositionLimitRule::new(1000.0, 5000.0, 0.7);
+
+        let context = create_test_context(vec![create_test_position("BTCUSDT", "long", 1.5, 1000.0)]);
+        let measurement = rule.measure(&context).unwrap();
+        assert_eq!(measurement.metric, "position_value");
+        assert_eq!(measurement.current_value, 1500.0);
+        assert_eq!(measurement.threshold_value, 1000.0);
+        assert_eq!(measurement.symbol.as_deref(), Some("BTCUSDT"));
+
+        let context = create_test_context(vec![
+            create_test_position("BTCUSDT", "long", 1.0, 800.0),
+            create_test_position("ETHUSDT", "short", 1.0, 200.0),
+        ]);
+        let measurement = rule.measure(&context).unwrap();
+        assert_eq!(measurement.metric, "long_ratio");
+        assert!((measurement.current_value - 0.8).abs() < 1e-9);
+        assert_eq!(measurement.symbol, None);
+
+        // Nothing breached: total exposure against its limit
+        let context = create_test_context(vec![create_test_position("BTCUSDT", "long", 1.0, 500.0)]);
+        let rule = PositionLimitRule::new(1000.0, 5000.0, 1.0);
+        assert_eq!(
+            rule.measure(&context).unwrap(),
+            RiskMeasurement::new("total_position_value", 500.0, 5000.0)
+        );
+    }
 }
@@ -0,0 +1,584 @@
+//! Pre-trade risk checks
+//!
+//! Every order passes these checks before it is sent, whether it comes from
+//! the UI, a strategy instance, a conditional order or an execution algo.
+//! Limits come from the `pre_trade_limits` row of `risk_rules`; its
+//! `params_json` holds dotted keys:
+//!
+//! * `user.max_order_value`, `user.max_daily_orders`,
+//!   `user.max_position_value`, `user.max_balance_ratio`
+//! * `instance.max_order_value`, `instance.max_daily_orders`
+//! * `symbol.max_position_value`, `symbol.max_balance_ratio`
+//! * `symbol.<SYMBOL>.max_position_value` to override one symbol
+//!
+//! A missing key means no limit. Balance ratios are measured against the
+//! account equity: the quote balance plus the value of open positions, so
+//! buying into a position doesn't shrink what the ratio allows.
+
+use crate::core::trade::types::{OrderSide, Position};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Name of the `risk_rules` row holding the pre-trade limits
+pub const PRE_TRADE_RULE: &str = "pre_trade_limits";
+
+/// Who is placing an order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderOrigin {
+    /// Placed by the user from the UI
+    Manual,
+    /// Placed by a strategy instance
+    Instance(String),
+    /// Emergency close; reduce-only orders skip the limits
+    Emergency,
+    /// Fired by a stop, take-profit or trailing stop; reduce-only orders
+    /// skip the limits so an exit isn't refused
+    Conditional,
+}
+
+impl OrderOrigin {
+    /// Origin for an order that may belong to a strategy instance
+    pub fn for_instance(instance_id: Option<&str>) -> Self {
+        match instance_id {
+            Some(id) => Self::Instance(id.to_string()),
+            None => Self::Manual,
+        }
+    }
+
+    /// Whether an order of this origin that only reduces a position skips
+    /// the limits
+    pub fn reduce_only_exempt(&self) -> bool {
+        matches!(self, Self::Emergency | Self::Conditional)
+    }
+
+    fn instance_id(&self) -> Option<&str> {
+        match self {
+            Self::Instance(id) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+/// Which limit refused an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectCode {
+    /// Value limits are set but the order has no price to value it at
+    NoReferencePrice,
+    OrderValue,
+    DailyOrders,
+    PositionValue,
+    BalanceRatio,
+    /// No depth snapshot to estimate a market order's slippage
+    NoOrderBook,
+    Slippage,
+    /// Visible depth too thin for the order
+    Liquidity,
+    /// The kill switch is engaged and the order does not reduce a position
+    TradingHalted,
+}
+
+/// Scope a limit applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitScope {
+    User,
+    Instance,
+    Symbol,
+}
+
+/// One reason an order was refused
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreTradeRejection {
+    pub code: RejectCode,
+    pub scope: LimitScope,
+    /// Configured limit
+    pub limit: f64,
+    /// Value the order would reach
+    pub actual: f64,
+    pub message: String,
+}
+
+impl std::fmt::Display for PreTradeRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Limits for orders of a user or of a strategy instance
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScopeLimits {
+    /// Maximum value of a single order (quote asset)
+    pub max_order_value: Option<f64>,
+    /// Maximum orders placed per UTC day
+    pub max_daily_orders: Option<u32>,
+}
+
+/// Pre-trade limits parsed from the `pre_trade_limits` rule
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PreTradeLimits {
+    pub user: ScopeLimits,
+    pub instance: ScopeLimits,
+    /// Maximum value of all open positions after the order
+    pub max_total_value: Option<f64>,
+    /// Maximum value of all open positions as a share of the equity
+    pub max_total_balance_ratio: Option<f64>,
+    /// Maximum position value per symbol
+    pub max_symbol_value: Option<f64>,
+    /// Maximum position value per symbol as a share of the equity
+    pub max_symbol_balance_ratio: Option<f64>,
+    /// Per-symbol overrides of `max_symbol_value`
+    pub symbol_values: HashMap<String, f64>,
+}
+
+/// Order being checked
+#[derive(Debug, Clone)]
+pub struct PreTradeOrder {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: f64,
+    /// Limit price, or the latest price for market orders
+    pub price: Option<f64>,
+    pub origin: OrderOrigin,
+}
+
+/// Account state the order is checked against
+#[derive(Debug, Clone, Default)]
+pub struct PreTradeSnapshot {
+    /// Orders the user placed today
+    pub user_orders_today: u32,
+    /// Orders the instance placed today
+    pub instance_orders_today: u32,
+    /// Open positions of the account
+    pub positions: Vec<Position>,
+    /// Balance of the symbol's quote asset, when known
+    pub balance: Option<f64>,
+}
+
+impl PreTradeSnapshot {
+    /// Quote balance plus the value of open positions; a short's proceeds
+    /// already sit in the balance, so its value counts against it
+    pub fn equity(&self) -> Option<f64> {
+        let positions: f64 = self
+            .positions
+            .iter()
+            .map(|p| match p.side.as_str() {
+                "short" | "sell" => -position_value(p),
+                _ => position_value(p),
+            })
+            .sum();
+        self.balance.map(|balance| balance + positions)
+    }
+}
+
+impl PreTradeLimits {
+    /// Parse limits from rule parameters
+    pub fn from_params(params: &HashMap<String, f64>) -> Result<Self, String> {
+        let mut limits = Self::default();
+        for (key, &value) in params {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(format!("{} must be positive", key));
+            }
+            let ratio = || {
+                if value > 1.0 {
+                    Err(format!("{} must be between 0 and 1", key))
+                } else {
+                    Ok(Some(value))
+                }
+            };
+            match key.as_str() {
+                "user.max_order_value" => limits.user.max_order_value = Some(value),
+                "user.max_daily_orders" => limits.user.max_daily_orders = Some(value as u32),
+                "user.max_position_value" => limits.max_total_value = Some(value),
+                "user.max_balance_ratio" => limits.max_total_balance_ratio = ratio()?,
+                "instance.max_order_value" => limits.instance.max_order_value = Some(value),
+                "instance.max_daily_orders" => limits.instance.max_daily_orders = Some(value as u32),
+                "symbol.max_position_value" => limits.max_symbol_value = Some(value),
+                "symbol.max_balance_ratio" => limits.max_symbol_balance_ratio = ratio()?,
+                _ => match key
+                    .strip_prefix("symbol.")
+                    .and_then(|rest| rest.strip_suffix(".max_position_value"))
+                {
+                    Some(symbol) if !symbol.is_empty() => {
+                        limits.symbol_values.insert(symbol.to_string(), value);
+                    }
+                    _ => return Err(format!("Unknown pre-trade limit: {}", key)),
+                },
+            }
+        }
+        Ok(limits)
+    }
+
+    /// Whether any limit needs the order's value
+    pub fn has_value_limits(&self) -> bool {
+        self.user.max_order_value.is_some()
+            || self.instance.max_order_value.is_some()
+            || self.max_total_value.is_some()
+            || self.max_total_balance_ratio.is_some()
+            || self.max_symbol_value.is_some()
+            || self.max_symbol_balance_ratio.is_some()
+            || !self.symbol_values.is_empty()
+    }
+
+    /// Check an order; an empty result means it may be sent
+    ///
+    /// Exposure limits only apply to orders that grow the symbol's position,
+    /// so an order that reduces or closes a position is never refused for
+    /// being over them.
+    pub fn evaluate(&self, order: &PreTradeOrder, snapshot: &PreTradeSnapshot) -> Vec<PreTradeRejection> {
+        let mut rejections = Vec::new();
+        let instance = order.origin.instance_id();
+
+        // Order counts
+        if let Some(max) = self.user.max_daily_orders {
+            if snapshot.user_orders_today >= max {
+                rejections.push(reject(
+                    RejectCode::DailyOrders,
+                    LimitScope::User,
+                    max as f64,
+                    snapshot.user_orders_today as f64 + 1.0,
+                    format!("Daily order limit reached: {}/{}", snapshot.user_orders_today, max),
+                ));
+            }
+        }
+        if let (Some(id), Some(max)) = (instance, self.instance.max_daily_orders) {
+            if snapshot.instance_orders_today >= max {
+                rejections.push(reject(
+                    RejectCode::DailyOrders,
+                    LimitScope::Instance,
+                    max as f64,
+                    snapshot.instance_orders_today as f64 + 1.0,
+                    format!("Daily order limit of instance {} reached: {}/{}", id, snapshot.instance_orders_today, max),
+                ));
+            }
+        }
+
+        let Some(price) = order.price.filter(|p| *p > 0.0) else {
+            if self.has_value_limits() {
+                rejections.push(reject(
+                    RejectCode::NoReferencePrice,
+                    LimitScope::Symbol,
+                    0.0,
+                    0.0,
+                    format!("No price to value the {} order against the limits", order.symbol),
+                ));
+            }
+            return rejections;
+        };
+
+        // Order value
+        let order_value = order.quantity * price;
+        if let Some(max) = self.user.max_order_value.filter(|max| order_value > *max) {
+            rejections.push(reject(
+                RejectCode::OrderValue,
+                LimitScope::User,
+                max,
+                order_value,
+                format!("Order value {:.2} exceeds the limit of {:.2}", order_value, max),
+            ));
+        }
+        if let Some(id) = instance {
+            if let Some(max) = self.instance.max_order_value.filter(|max| order_value > *max) {
+                rejections.push(reject(
+                    RejectCode::OrderValue,
+                    LimitScope::Instance,
+                    max,
+                    order_value,
+                    format!("Order value {:.2} exceeds the limit of {:.2} for instance {}", order_value, max, id),
+                ));
+            }
+        }
+
+        // Exposure
+        let current = net_quantity(&snapshot.positions, &order.symbol);
+        let projected = match order.side {
+            OrderSide::Buy => current + order.quantity,
+            OrderSide::Sell => current - order.quantity,
+        };
+        if projected.abs() <= current.abs() {
+            return rejections;
+        }
+
+        let symbol_value = projected.abs() * price;
+        let max_symbol_value = self.symbol_values.get(&order.symbol).copied().or(self.max_symbol_value);
+        if let Some(max) = max_symbol_value.filter(|max| symbol_value > *max) {
+            rejections.push(reject(
+                RejectCode::PositionValue,
+                LimitScope::Symbol,
+                max,
+                symbol_value,
+                format!("{} position value {:.2} would exceed the limit of {:.2}", order.symbol, symbol_value, max),
+            ));
+        }
+
+        let total_value = snapshot
+            .positions
+            .iter()
+            .filter(|p| p.symbol != order.symbol)
+            .map(position_value)
+            .sum::<f64>()
+            + symbol_value;
+        if let Some(max) = self.max_total_value.filter(|max| total_value > *max) {
+            rejections.push(reject(
+                RejectCode::PositionValue,
+                LimitScope::User,
+                max,
+                total_value,
+                format!("Total position value {:.2} would exceed the limit of {:.2}", total_value, max),
+            ));
+        }
+
+        if let Some(equity) = snapshot.equity() {
+            if let Some(ratio) = self.max_symbol_balance_ratio.filter(|r| symbol_value > equity * r) {
+                rejections.push(reject(
+                    RejectCode::BalanceRatio,
+                    LimitScope::Symbol,
+                    equity * ratio,
+                    symbol_value,
+                    format!(
+                        "{} position value {:.2} would exceed {:.0}% of the equity {:.2}",
+                        order.symbol,
+                        symbol_value,
+                        ratio * 100.0,
+                        equity
+                    ),
+                ));
+            }
+            if let Some(ratio) = self.max_total_balance_ratio.filter(|r| total_value > equity * r) {
+                rejections.push(reject(
+                    RejectCode::BalanceRatio,
+                    LimitScope::User,
+                    equity * ratio,
+                    total_value,
+                    format!(
+                        "Total position value {:.2} would exceed {:.0}% of the equity {:.2}",
+                        total_value,
+                        ratio * 100.0,
+                        equity
+                    ),
+                ));
+            }
+        }
+
+        rejections
+    }
+
+    /// Whether the checks need the account balance
+    pub fn needs_balance(&self) -> bool {
+        self.max_symbol_balance_ratio.is_some() || self.max_total_balance_ratio.is_some()
+    }
+}
+
+/// Whether an order only reduces the symbol's open position
+pub fn is_reduce_only(side: OrderSide, quantity: f64, symbol: &str, positions: &[Position]) -> bool {
+    let current = net_quantity(positions, symbol);
+    match side {
+        OrderSide::Buy => current < 0.0 && quantity <= -current,
+        OrderSide::Sell => current > 0.0 && quantity <= current,
+    }
+}
+
+/// Net position of a symbol: long positive, short negative
+fn net_quantity(positions: &[Position], symbol: &str) -> f64 {
+    positions
+        .iter()
+        .filter(|p| p.symbol == symbol)
+        .map(|p| match p.side.as_str() {
+            "short" | "sell" => -p.quantity.abs(),
+            _ => p.quantity.abs(),
+        })
+        .sum()
+}
+
+fn position_value(position: &Position) -> f64 {
+    position.quantity.abs() * position.current_price.unwrap_or(position.entry_price)
+}
+
+fn reject(code: RejectCode, scope: LimitScope, limit: f64, actual: f64, message: String) -> PreTradeRejection {
+    PreTradeRejection {
+        code,
+        scope,
+        limit,
+        actual,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(symbol: &str, side: &str, quantity: f64, price: f64) -> Position {
+        Position {
+            id: format!("{}-{}", symbol, side),
+            symbol: symbol.to_string(),
+            side: side.to_string(),
+            quantity,
+            entry_price: price,
+            current_price: Some(price),
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+            opened_at: 0,
+        }
+    }
+
+    fn order(side: OrderSide, quantity: f64, price: Option<f64>, origin: OrderOrigin) -> PreTradeOrder {
+        PreTradeOrder {
+            symbol: "BTCUSDT".to_string(),
+            side,
+            quantity,
+            price,
+            origin,
+        }
+    }
+
+    fn limits(params: &[(&str, f64)]) -> PreTradeLimits {
+        let params: HashMap<String, f64> = params.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        PreTradeLimits::from_params(&params).unwrap()
+    }
+
+    #[test]
+    fn test_from_params() {
+        let parsed = limits(&[
+            ("instance.max_order_value", 1000.0),
+            ("instance.max_daily_orders", 100.0),
+            ("symbol.max_balance_ratio", 0.5),
+            ("symbol.ETHUSDT.max_position_value", 2500.0),
+        ]);
+        assert_eq!(parsed.instance.max_order_value, Some(1000.0));
+        assert_eq!(parsed.instance.max_daily_orders, Some(100));
+        assert_eq!(parsed.max_symbol_balance_ratio, Some(0.5));
+        assert_eq!(parsed.symbol_values.get("ETHUSDT"), Some(&2500.0));
+        assert!(parsed.user.max_order_value.is_none());
+
+        let bad = |key: &str, value: f64| {
+            PreTradeLimits::from_params(&HashMap::from([(key.to_string(), value)])).is_err()
+        };
+        assert!(bad("user.max_order_value", -1.0));
+        assert!(bad("user.max_balance_ratio", 1.5));
+        assert!(bad("user.max_leverage", 3.0));
+        assert!(bad("symbol..max_position_value", 10.0));
+    }
+
+    #[test]
+    fn test_instance_limits_only_apply_to_instances() {
+        let limits = limits(&[("instance.max_order_value", 1000.0), ("instance.max_daily_orders", 2.0)]);
+        let snapshot = PreTradeSnapshot {
+            instance_orders_today: 2,
+            ..Default::default()
+        };
+
+        let rejections = limits.evaluate(
+            &order(OrderSide::Buy, 1.0, Some(5000.0), OrderOrigin::Instance("i1".to_string())),
+            &snapshot,
+        );
+        let codes: Vec<_> = rejections.iter().map(|r| (r.code, r.scope)).collect();
+        assert_eq!(
+            codes,
+            vec![
+                (RejectCode::DailyOrders, LimitScope::Instance),
+                (RejectCode::OrderValue, LimitScope::Instance)
+            ]
+        );
+        assert_eq!(rejections[1].limit, 1000.0);
+        assert_eq!(rejections[1].actual, 5000.0);
+
+        assert!(limits
+            .evaluate(&order(OrderSide::Buy, 1.0, Some(5000.0), OrderOrigin::Manual), &snapshot)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_symbol_and_total_exposure() {
+        let limits = limits(&[
+            ("symbol.max_position_value", 10_000.0),
+            ("symbol.BTCUSDT.max_position_value", 60_000.0),
+            ("user.max_position_value", 65_000.0),
+        ]);
+        let snapshot = PreTradeSnapshot {
+            positions: vec![position("BTCUSDT", "long", 1.0, 50_000.0), position("ETHUSDT", "long", 5.0, 3_000.0)],
+            ..Default::default()
+        };
+
+        // 1.1 BTC is within the BTC override but not the total
+        let rejections = limits.evaluate(&order(OrderSide::Buy, 0.1, Some(50_000.0), OrderOrigin::Manual), &snapshot);
+        assert_eq!(rejections.len(), 1);
+        assert_eq!(rejections[0].scope, LimitScope::User);
+        assert!((rejections[0].actual - 70_000.0).abs() < 1e-6);
+
+        // 1.3 BTC exceeds the override too
+        let rejections = limits.evaluate(&order(OrderSide::Buy, 0.3, Some(50_000.0), OrderOrigin::Manual), &snapshot);
+        assert_eq!(rejections[0].code, RejectCode::PositionValue);
+        assert_eq!(rejections[0].scope, LimitScope::Symbol);
+        assert_eq!(rejections[0].limit, 60_000.0);
+    }
+
+    #[test]
+    fn test_reducing_orders_skip_exposure_limits() {
+        let limits = limits(&[("symbol.max_position_value", 1_000.0), ("user.max_balance_ratio", 0.5)]);
+        let snapshot = PreTradeSnapshot {
+            positions: vec![position("BTCUSDT", "long", 1.0, 50_000.0)],
+            balance: Some(10_000.0),
+            ..Default::default()
+        };
+
+        assert!(limits
+            .evaluate(&order(OrderSide::Sell, 0.5, Some(50_000.0), OrderOrigin::Manual), &snapshot)
+            .is_empty());
+        // Flipping to a larger short grows the exposure
+        let rejections = limits.evaluate(&order(OrderSide::Sell, 2.5, Some(50_000.0), OrderOrigin::Manual), &snapshot);
+        let codes: Vec<_> = rejections.iter().map(|r| r.code).collect();
+        assert_eq!(codes, vec![RejectCode::PositionValue, RejectCode::BalanceRatio]);
+    }
+
+    #[test]
+    fn test_balance_ratio_measured_against_equity() {
+        let limits = limits(&[("symbol.max_balance_ratio", 0.5)]);
+        // Most of the quote balance already went into ETH
+        let snapshot = PreTradeSnapshot {
+            positions: vec![position("ETHUSDT", "long", 4.0, 2_000.0)],
+            balance: Some(2_000.0),
+            ..Default::default()
+        };
+        assert_eq!(snapshot.equity(), Some(10_000.0));
+
+        assert!(limits
+            .evaluate(&order(OrderSide::Buy, 0.06, Some(50_000.0), OrderOrigin::Manual), &snapshot)
+            .is_empty());
+        let rejections = limits.evaluate(&order(OrderSide::Buy, 0.12, Some(50_000.0), OrderOrigin::Manual), &snapshot);
+        assert_eq!(rejections[0].code, RejectCode::BalanceRatio);
+        assert_eq!(rejections[0].limit, 5_000.0);
+    }
+
+    #[test]
+    fn test_missing_price_with_value_limits() {
+        let snapshot = PreTradeSnapshot::default();
+        let rejections = limits(&[("user.max_order_value", 100.0)])
+            .evaluate(&order(OrderSide::Buy, 1.0, None, OrderOrigin::Manual), &snapshot);
+        assert_eq!(rejections[0].code, RejectCode::NoReferencePrice);
+
+        // Count-only limits don't need a price
+        assert!(limits(&[("user.max_daily_orders", 10.0)])
+            .evaluate(&order(OrderSide::Buy, 1.0, None, OrderOrigin::Manual), &snapshot)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_is_reduce_only() {
+        let positions = vec![position("BTCUSDT", "long", 1.0, 50_000.0), position("ETHUSDT", "short", 2.0, 3_000.0)];
+        assert!(is_reduce_only(OrderSide::Sell, 1.0, "BTCUSDT", &positions));
+        assert!(!is_reduce_only(OrderSide::Sell, 1.5, "BTCUSDT", &positions));
+        assert!(!is_reduce_only(OrderSide::Buy, 0.1, "BTCUSDT", &positions));
+        assert!(is_reduce_only(OrderSide::Buy, 2.0, "ETHUSDT", &positions));
+        assert!(!is_reduce_only(OrderSide::Sell, 0.1, "SOLUSDT", &positions));
+    }
+
+    #[test]
+    fn test_reduce_only_exempt_origins() {
+        assert!(OrderOrigin::Emergency.reduce_only_exempt());
+        assert!(OrderOrigin::Conditional.reduce_only_exempt());
+        assert!(!OrderOrigin::Manual.reduce_only_exempt());
+        assert!(!OrderOrigin::Instance("i1".to_string()).reduce_only_exempt());
+    }
+}
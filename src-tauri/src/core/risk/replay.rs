@@ -0,0 +1,309 @@
+//! Risk rule replay
+//!
+//! Runs a rule set over recorded history to show how often each rule would
+//! have fired, and with which action, before it is switched on. History is
+//! a series of equity points: a backtest's equity curve, stored equity
+//! snapshots, or the realised PnL of fills. Each point becomes a
+//! `RiskContext` at the point's time, with the equity as balance and the
+//! change since the start of the point's UTC day as `today_pnl`.
+//!
+//! The contexts hold no positions, so rules that need positions or market
+//! data (position, VaR, volatility, concentration limits) never fire here.
+//! Replayed history is not altered by a trigger: after a rule that would
+//! have paused the strategy, the trades that followed are still replayed.
+
+use super::rule::{RiskAction, RiskContext, RiskMeasurement, RiskRule};
+use crate::types::backtest::BacktestResult;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const DAY_MS: i64 = 86_400_000;
+
+/// Scope key the replayed contexts are checked under
+const REPLAY_SCOPE: &str = "replay";
+
+/// Equity at one point of history
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayPoint {
+    /// Time (ms)
+    pub timestamp: i64,
+    pub equity: f64,
+    /// Change in equity since the start of the UTC day
+    pub today_pnl: f64,
+}
+
+impl ReplayPoint {
+    /// Points from `(timestamp, equity)` pairs
+    ///
+    /// A day opens at the last equity before it, or at its first point when
+    /// the series starts that day.
+    pub fn from_equity(series: impl IntoIterator<Item = (i64, f64)>) -> Vec<Self> {
+        let mut series: Vec<(i64, f64)> = series.into_iter().collect();
+        series.sort_by_key(|(timestamp, _)| *timestamp);
+
+        let mut points = Vec::with_capacity(series.len());
+        let mut day = None;
+        let mut opening = 0.0;
+        let mut previous = None;
+        for (timestamp, equity) in series {
+            let point_day = timestamp.div_euclid(DAY_MS);
+            if day != Some(point_day) {
+                day = Some(point_day);
+                opening = previous.unwrap_or(equity);
+            }
+            points.push(Self {
+                timestamp,
+                equity,
+                today_pnl: equity - opening,
+            });
+            previous = Some(equity);
+        }
+        points
+    }
+
+    /// Points from the realised PnL of fills, `(timestamp, pnl)`
+    ///
+    /// Equity starts at `initial_equity` just before the first fill.
+    pub fn from_fills(initial_equity: f64, fills: &[(i64, f64)]) -> Vec<Self> {
+        let mut fills = fills.to_vec();
+        fills.sort_by_key(|(timestamp, _)| *timestamp);
+        let Some((first, _)) = fills.first().copied() else {
+            return Vec::new();
+        };
+
+        let mut equity = initial_equity;
+        let series = fills.into_iter().map(|(timestamp, pnl)| {
+            equity += pnl;
+            (timestamp, equity)
+        });
+        let mut points = Self::from_equity(std::iter::once((first, initial_equity)).chain(series));
+        points.remove(0);
+        points
+    }
+
+    /// Points from a backtest: its equity curve, or its closed trades when
+    /// the curve is empty
+    pub fn from_backtest(result: &BacktestResult) -> Vec<Self> {
+        if !result.equity_curve.is_empty() {
+            return Self::from_equity(result.equity_curve.iter().map(|p| (p.time, p.equity)));
+        }
+        let fills: Vec<(i64, f64)> = result
+            .trades
+            .iter()
+            .filter_map(|t| Some((t.exit_time?, t.pnl?)))
+            .collect();
+        Self::from_fills(result.initial_capital, &fills)
+    }
+
+    fn context(&self) -> RiskContext {
+        RiskContext::new(
+            Vec::new(),
+            Vec::new(),
+            self.equity,
+            self.today_pnl,
+            REPLAY_SCOPE.to_string(),
+            String::new(),
+        )
+        .at(self.timestamp)
+    }
+}
+
+/// A time a rule would have fired
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayTrigger {
+    pub rule: String,
+    pub timestamp: i64,
+    pub action: RiskAction,
+    /// Whether the action would have stopped trading
+    pub stops_trading: bool,
+    pub equity: f64,
+    pub today_pnl: f64,
+    pub measurement: Option<RiskMeasurement>,
+    /// First point the rule no longer fired at; `None` if it fired to the end
+    pub cleared_at: Option<i64>,
+}
+
+/// How one rule fared over the replay
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleReplaySummary {
+    pub rule: String,
+    pub action: RiskAction,
+    /// Times the rule went from clear to firing
+    pub triggers: usize,
+    /// Points at which the rule fired
+    pub breached_points: usize,
+    pub first_triggered_at: Option<i64>,
+}
+
+/// Result of replaying a rule set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayReport {
+    pub points: usize,
+    pub start: Option<i64>,
+    pub end: Option<i64>,
+    pub rules: Vec<RuleReplaySummary>,
+    /// Every trigger, in time order
+    pub triggers: Vec<ReplayTrigger>,
+}
+
+/// Check every rule at every point
+///
+/// A rule that keeps firing counts as one trigger until it clears, as in
+/// `RiskMonitor`. Rules keep their state between points, so pass freshly
+/// built rules; disabled rules never fire.
+pub async fn replay_rules(rules: &[Box<dyn RiskRule>], points: &[ReplayPoint]) -> Result<ReplayReport> {
+    let mut summaries: Vec<RuleReplaySummary> = rules
+        .iter()
+        .map(|rule| RuleReplaySummary {
+            rule: rule.name().to_string(),
+            action: rule.config().action.clone(),
+            triggers: 0,
+            breached_points: 0,
+            first_triggered_at: None,
+        })
+        .collect();
+    let mut triggers: Vec<ReplayTrigger> = Vec::new();
+    // Rule index -> index of its open trigger
+    let mut open: HashMap<usize, usize> = HashMap::new();
+
+    for point in points {
+        let context = point.context();
+        for (index, rule) in rules.iter().enumerate() {
+            if !rule.config().enabled {
+                continue;
+            }
+
+            if !rule.check(&context).await? {
+                if let Some(trigger) = open.remove(&index) {
+                    triggers[trigger].cleared_at = Some(point.timestamp);
+                }
+                continue;
+            }
+
+            let summary = &mut summaries[index];
+            summary.breached_points += 1;
+            if open.contains_key(&index) {
+                continue;
+            }
+            summary.triggers += 1;
+            summary.first_triggered_at.get_or_insert(point.timestamp);
+
+            let action = rule.config().action.clone();
+            open.insert(index, triggers.len());
+            triggers.push(ReplayTrigger {
+                rule: rule.name().to_string(),
+                timestamp: point.timestamp,
+                stops_trading: action.stops_trading(),
+                action,
+                equity: point.equity,
+                today_pnl: point.today_pnl,
+                measurement: rule.measure(&context),
+                cleared_at: None,
+            });
+        }
+    }
+
+    Ok(ReplayReport {
+        points: points.len(),
+        start: points.first().map(|p| p.timestamp),
+        end: points.last().map(|p| p.timestamp),
+        rules: summaries,
+        triggers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::risk::daily_loss::{DailyLossLimitParams, DailyLossLimitRule};
+    use crate::core::risk::drawdown_limit::DrawdownLimitRule;
+    use crate::core::risk::rule::RiskRuleConfig;
+
+    const HOUR: i64 = 3_600_000;
+    /// 2024-03-01 00:00 UTC
+    const DAY: i64 = 1_709_251_200_000;
+
+    #[test]
+    fn test_points_from_equity() {
+        let points = ReplayPoint::from_equity([
+            (DAY + HOUR, 1_000.0),
+            (DAY + 2 * HOUR, 900.0),
+            (DAY + DAY_MS + HOUR, 950.0),
+            (DAY + DAY_MS + 2 * HOUR, 800.0),
+        ]);
+        let today: Vec<f64> = points.iter().map(|p| p.today_pnl).collect();
+        // The second day opens at the first day's last equity
+        assert_eq!(today, vec![0.0, -100.0, 50.0, -100.0]);
+    }
+
+    #[test]
+    fn test_points_from_fills() {
+        let points = ReplayPoint::from_fills(1_000.0, &[(DAY + 2 * HOUR, 50.0), (DAY + HOUR, -200.0)]);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].equity, 800.0);
+        assert_eq!(points[0].today_pnl, -200.0);
+        assert_eq!(points[1].equity, 850.0);
+        assert_eq!(points[1].today_pnl, -150.0);
+        assert!(ReplayPoint::from_fills(1_000.0, &[]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_replay_reports_triggers() {
+        let mut drawdown = DrawdownLimitRule::new(10.0);
+        drawdown
+            .update_config(RiskRuleConfig::new(true, RiskAction::PauseStrategy, vec!["log".to_string()]))
+            .unwrap();
+        let rules: Vec<Box<dyn RiskRule>> = vec![
+            Box::new(drawdown),
+            Box::new(DailyLossLimitRule::new(
+                DailyLossLimitParams {
+                    max_daily_loss: 150.0,
+                    ..Default::default()
+                },
+                RiskAction::Notify,
+            )),
+        ];
+
+        let points = ReplayPoint::from_equity([
+            (DAY + HOUR, 1_000.0),
+            (DAY + 2 * HOUR, 880.0),
+            (DAY + 3 * HOUR, 840.0),
+            (DAY + 4 * HOUR, 990.0),
+            (DAY + DAY_MS + HOUR, 870.0),
+        ]);
+        let report = replay_rules(&rules, &points).await.unwrap();
+
+        assert_eq!(report.points, 5);
+        assert_eq!(report.start, Some(DAY + HOUR));
+
+        let drawdown = &report.rules[0];
+        assert_eq!((drawdown.triggers, drawdown.breached_points), (2, 3));
+        let daily = &report.rules[1];
+        assert_eq!((daily.triggers, daily.breached_points), (1, 1));
+        assert_eq!(daily.first_triggered_at, Some(DAY + 3 * HOUR));
+
+        let fired: Vec<(&str, i64, Option<i64>)> = report
+            .triggers
+            .iter()
+            .map(|t| (t.rule.as_str(), t.timestamp - DAY, t.cleared_at.map(|c| c - DAY)))
+            .collect();
+        assert_eq!(
+            fired,
+            vec![
+                ("drawdown_limit", 2 * HOUR, Some(4 * HOUR)),
+                ("daily_loss_limit", 3 * HOUR, Some(4 * HOUR)),
+                ("drawdown_limit", DAY_MS + HOUR, None),
+            ]
+        );
+
+        let first = &report.triggers[0];
+        assert_eq!(first.action, RiskAction::PauseStrategy);
+        assert!(first.stops_trading);
+        assert_eq!(first.measurement.as_ref().unwrap().current_value, 12.0);
+    }
+}
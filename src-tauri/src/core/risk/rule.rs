@@ -4,8 +4,10 @@
 
 use crate::core::trade::types::{Position, Order};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Risk rule trait
 ///
@@ -34,6 +36,14 @@ pub trait RiskRule: Send + Sync {
     /// * `Err(_)` - Error occurred during checking
     async fn check(&self, context: &RiskContext) -> Result<bool>;
 
+    /// Returns the value the rule compares against its limit
+    ///
+    /// Used to fill in alerts. Rules without a single meaningful metric
+    /// return `None`.
+    fn measure(&self, _context: &RiskContext) -> Option<RiskMeasurement> {
+        None
+    }
+
     /// Returns the rule configuration
     fn config(&self) -> &RiskRuleConfig;
 
@@ -61,8 +71,14 @@ pub struct RiskContext {
     pub balance: f64,
     /// Today's profit and loss
     pub today_pnl: f64,
-    /// Strategy instance ID
+    /// Strategy instance ID; empty when the context covers a whole user
     pub instance_id: String,
+    /// User who owns the positions and orders
+    pub user_id: String,
+    /// Exchange account of each position, by position id; empty when unknown
+    pub position_accounts: HashMap<String, String>,
+    /// Time the context describes (ms); `None` means now. Set when replaying history.
+    pub timestamp: Option<i64>,
 }
 
 impl RiskContext {
@@ -74,13 +90,15 @@ impl RiskContext {
     /// * `balance` - Account balance
     /// * `today_pnl` - Today's P&L
     /// * `instance_id` - Strategy instance ID
+    /// * `user_id` - Owner of the instance or account
     #[must_use]
-    pub const fn new(
+    pub fn new(
         positions: Vec<Position>,
         orders: Vec<Order>,
         balance: f64,
         today_pnl: f64,
         instance_id: String,
+        user_id: String,
     ) -> Self {
         Self {
             positions,
@@ -88,6 +106,60 @@ impl RiskContext {
             balance,
             today_pnl,
             instance_id,
+            user_id,
+            position_accounts: HashMap::new(),
+            timestamp: None,
+        }
+    }
+
+    /// Creates a context covering all of a user's accounts
+    #[must_use]
+    pub fn for_user(
+        positions: Vec<Position>,
+        orders: Vec<Order>,
+        balance: f64,
+        today_pnl: f64,
+        user_id: String,
+    ) -> Self {
+        Self::new(positions, orders, balance, today_pnl, String::new(), user_id)
+    }
+
+    /// Records which exchange account each position is held on
+    #[must_use]
+    pub fn with_position_accounts(mut self, position_accounts: HashMap<String, String>) -> Self {
+        self.position_accounts = position_accounts;
+        self
+    }
+
+    /// Sets the time the context describes (ms)
+    #[must_use]
+    pub fn at(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Time rules should treat as now: the context's timestamp, or the clock
+    #[must_use]
+    pub fn now(&self) -> DateTime<Utc> {
+        self.timestamp
+            .and_then(DateTime::from_timestamp_millis)
+            .unwrap_or_else(Utc::now)
+    }
+
+    /// Exchange account a position is held on, if known
+    #[must_use]
+    pub fn account_of(&self, position: &Position) -> Option<&str> {
+        self.position_accounts.get(&position.id).map(String::as_str)
+    }
+
+    /// Key rules track state under: the instance, or the user for a
+    /// user-wide context
+    #[must_use]
+    pub fn scope_key(&self) -> &str {
+        if self.instance_id.is_empty() {
+            &self.user_id
+        } else {
+            &self.instance_id
         }
     }
 
@@ -102,6 +174,9 @@ impl RiskContext {
             balance: 0.0,
             today_pnl: 0.0,
             instance_id: String::new(),
+            user_id: String::new(),
+            position_accounts: HashMap::new(),
+            timestamp: None,
         }
     }
 
@@ -116,6 +191,17 @@ impl RiskContext {
             .sum()
     }
 
+    /// Calculates equity as balance plus position value and unrealized PnL
+    #[must_use]
+    pub fn total_equity(&self) -> f64 {
+        self.balance
+            + self
+                .positions
+                .iter()
+                .map(|p| p.quantity * p.entry_price + p.unrealized_pnl)
+                .sum::<f64>()
+    }
+
     /// Calculates total unrealized PnL
     ///
     /// Sums the unrealized PnL from all positions.
@@ -137,6 +223,49 @@ impl RiskContext {
     }
 }
 
+/// A rule's metric against its limit
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RiskMeasurement {
+    /// Metric name, e.g. "drawdown_pct"
+    pub metric: String,
+    /// Current value of the metric
+    pub current_value: f64,
+    /// Limit the rule triggers at
+    pub threshold_value: f64,
+    /// Symbol the measurement refers to, if any
+    pub symbol: Option<String>,
+    /// Extra context for the alert message, e.g. an exposure breakdown
+    pub detail: Option<String>,
+}
+
+impl RiskMeasurement {
+    /// Creates a measurement not tied to a symbol
+    #[must_use]
+    pub fn new(metric: &str, current_value: f64, threshold_value: f64) -> Self {
+        Self {
+            metric: metric.to_string(),
+            current_value,
+            threshold_value,
+            symbol: None,
+            detail: None,
+        }
+    }
+
+    /// Ties the measurement to a symbol
+    #[must_use]
+    pub fn with_symbol(mut self, symbol: &str) -> Self {
+        self.symbol = Some(symbol.to_string());
+        self
+    }
+
+    /// Adds context shown in the alert message
+    #[must_use]
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
 /// Risk rule configuration
 ///
 /// Defines how a risk rule behaves when triggered.
@@ -152,6 +281,12 @@ pub struct RiskRuleConfig {
     ///
     /// Supported values: "dingtalk", "email", "log"
     pub notify_methods: Vec<String>,
+    /// Whether an emergency stop halts all trading through the kill switch
+    ///
+    /// Off by default: the emergency stop then applies only to the instance
+    /// or user the rule fired for.
+    #[serde(default)]
+    pub halt_trading: bool,
 }
 
 impl RiskRuleConfig {
@@ -167,9 +302,17 @@ impl RiskRuleConfig {
             enabled,
             action,
             notify_methods,
+            halt_trading: false,
         }
     }
 
+    /// Lets an emergency stop halt all trading
+    #[must_use]
+    pub fn with_halt_trading(mut self, halt_trading: bool) -> Self {
+        self.halt_trading = halt_trading;
+        self
+    }
+
     /// Creates a default configuration with logging only
     #[must_use]
     pub fn log_only() -> Self {
@@ -177,6 +320,7 @@ impl RiskRuleConfig {
             enabled: true,
             action: RiskAction::LogOnly,
             notify_methods: vec!["log".to_string()],
+            halt_trading: false,
         }
     }
 
@@ -187,6 +331,7 @@ impl RiskRuleConfig {
             enabled: true,
             action: RiskAction::Notify,
             notify_methods,
+            halt_trading: false,
         }
     }
 }
@@ -330,6 +475,7 @@ mod tests {
             10000.0,
             500.0,
             "test".to_string(),
+            "user".to_string(),
         );
 
         assert_eq!(ctx.position_count(), 2);
@@ -337,6 +483,16 @@ mod tests {
         assert_eq!(ctx.total_unrealized_pnl(), 2000.0); // 1000 + 1000
     }
 
+    #[test]
+    fn test_risk_context_scope_key() {
+        let ctx = RiskContext::new(vec![], vec![], 0.0, 0.0, "instance".to_string(), "user".to_string());
+        assert_eq!(ctx.scope_key(), "instance");
+
+        let ctx = RiskContext::for_user(vec![], vec![], 0.0, 0.0, "user".to_string());
+        assert!(ctx.instance_id.is_empty());
+        assert_eq!(ctx.scope_key(), "user");
+    }
+
     #[test]
     fn test_risk_rule_config_default() {
         let config = RiskRuleConfig::default();
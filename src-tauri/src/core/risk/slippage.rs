@@ -0,0 +1,434 @@
+//! Liquidity and slippage guard
+//!
+//! Walks the order book to estimate what a market order would pay before it
+//! is sent. Limits come from the `slippage_guard` row of `risk_rules`:
+//!
+//! * `max_slippage_bps`: cap on the expected average fill price's distance
+//!   from the best price on the side the order takes from
+//! * `max_book_share`: cap on the order's share of the volume resting in
+//!   the first `top_levels` levels (default 1, the top of book)
+//! * `depth_levels`: levels fetched per side (default 50)
+//! * `downsize`: non-zero to shrink a refused order to the largest size
+//!   within the limits instead of rejecting it
+//!
+//! A missing cap means no limit.
+
+use super::pretrade::{LimitScope, PreTradeRejection, RejectCode};
+use crate::core::trade::types::{OrderBook, OrderBookLevel, OrderSide};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Name of the `risk_rules` row holding the slippage limits
+pub const SLIPPAGE_GUARD_RULE: &str = "slippage_guard";
+
+/// Quantities below this are treated as zero
+const QTY_EPSILON: f64 = 1e-12;
+
+/// Slippage and liquidity limits for market orders
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlippageLimits {
+    pub max_slippage_bps: Option<f64>,
+    pub max_book_share: Option<f64>,
+    /// Levels counted as the top of book for `max_book_share`
+    pub top_levels: usize,
+    /// Levels fetched per side
+    pub depth_levels: usize,
+    pub downsize: bool,
+}
+
+impl Default for SlippageLimits {
+    fn default() -> Self {
+        Self {
+            max_slippage_bps: None,
+            max_book_share: None,
+            top_levels: 1,
+            depth_levels: 50,
+            downsize: false,
+        }
+    }
+}
+
+/// Expected execution of a market order against a depth snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlippageEstimate {
+    /// Best price on the side the order takes from
+    pub reference_price: f64,
+    /// Expected average fill price of the fillable quantity
+    pub avg_price: f64,
+    /// Price of the last level the order reaches
+    pub worst_price: f64,
+    /// Distance of `avg_price` from `reference_price`, adverse positive
+    pub slippage_bps: f64,
+    /// Order quantity over the top-of-book volume
+    pub book_share: f64,
+    pub quantity: f64,
+    /// Quantity the visible depth can fill
+    pub fillable_quantity: f64,
+}
+
+impl SlippageEstimate {
+    /// Whether the visible depth covers the whole order
+    pub fn is_fillable(&self) -> bool {
+        self.fillable_quantity + QTY_EPSILON >= self.quantity
+    }
+}
+
+impl std::fmt::Display for SlippageEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expected avg {} vs {} ({:.1} bps, worst {}), {:.0}% of top of book",
+            self.avg_price,
+            self.reference_price,
+            self.slippage_bps,
+            self.worst_price,
+            self.book_share * 100.0
+        )
+    }
+}
+
+/// Outcome of the slippage guard for one order
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlippageDecision {
+    Accept(SlippageEstimate),
+    /// Send `quantity` instead of the requested size
+    Downsize {
+        quantity: f64,
+        estimate: SlippageEstimate,
+        rejections: Vec<PreTradeRejection>,
+    },
+    Reject {
+        estimate: Option<SlippageEstimate>,
+        rejections: Vec<PreTradeRejection>,
+    },
+}
+
+impl SlippageLimits {
+    /// Parse limits from rule parameters
+    pub fn from_params(params: &HashMap<String, f64>) -> Result<Self, String> {
+        let mut limits = Self::default();
+        for (key, &value) in params {
+            if !value.is_finite() || value < 0.0 {
+                return Err(format!("{} must not be negative", key));
+            }
+            match key.as_str() {
+                "downsize" => limits.downsize = value != 0.0,
+                _ if value == 0.0 => return Err(format!("{} must be positive", key)),
+                "max_slippage_bps" => limits.max_slippage_bps = Some(value),
+                "max_book_share" if value > 1.0 => {
+                    return Err(format!("{} must be between 0 and 1", key));
+                }
+                "max_book_share" => limits.max_book_share = Some(value),
+                "top_levels" => limits.top_levels = value as usize,
+                "depth_levels" => limits.depth_levels = value as usize,
+                _ => return Err(format!("Unknown slippage limit: {}", key)),
+            }
+        }
+        if limits.top_levels == 0 || limits.depth_levels == 0 {
+            return Err("top_levels and depth_levels must be at least 1".to_string());
+        }
+        Ok(limits)
+    }
+
+    /// Whether any limit is set
+    pub fn is_active(&self) -> bool {
+        self.max_slippage_bps.is_some() || self.max_book_share.is_some()
+    }
+
+    /// Check a market order against a depth snapshot
+    pub fn evaluate(&self, book: &OrderBook, side: OrderSide, quantity: f64) -> SlippageDecision {
+        let Some(estimate) = estimate_slippage(book, side, quantity, self.top_levels) else {
+            return SlippageDecision::Reject {
+                estimate: None,
+                rejections: vec![rejection(
+                    RejectCode::NoOrderBook,
+                    0.0,
+                    0.0,
+                    format!("No {} depth for {} to estimate slippage", side_name(side), book.symbol),
+                )],
+            };
+        };
+
+        let rejections = self.breaches(&estimate, &book.symbol);
+        if rejections.is_empty() {
+            return SlippageDecision::Accept(estimate);
+        }
+
+        if self.downsize {
+            let allowed = self.max_quantity(book, side).min(quantity);
+            if allowed > QTY_EPSILON {
+                if let Some(estimate) = estimate_slippage(book, side, allowed, self.top_levels) {
+                    return SlippageDecision::Downsize {
+                        quantity: allowed,
+                        estimate,
+                        rejections,
+                    };
+                }
+            }
+        }
+
+        SlippageDecision::Reject {
+            estimate: Some(estimate),
+            rejections,
+        }
+    }
+
+    /// Largest quantity the visible depth fills within every limit
+    pub fn max_quantity(&self, book: &OrderBook, side: OrderSide) -> f64 {
+        let levels = book.levels_for(side);
+        let mut allowed: f64 = levels.iter().map(|l| l.quantity).sum();
+
+        if let Some(share) = self.max_book_share {
+            allowed = allowed.min(share * top_volume(levels, self.top_levels));
+        }
+
+        if let (Some(bps), Some(best)) = (self.max_slippage_bps, levels.first()) {
+            let offset = best.price * bps / 10_000.0;
+            let limit_price = match side {
+                OrderSide::Buy => best.price + offset,
+                OrderSide::Sell => best.price - offset,
+            };
+            allowed = allowed.min(quantity_within(levels, side, limit_price));
+        }
+
+        allowed.max(0.0)
+    }
+
+    fn breaches(&self, estimate: &SlippageEstimate, symbol: &str) -> Vec<PreTradeRejection> {
+        let mut rejections = Vec::new();
+        if !estimate.is_fillable() {
+            rejections.push(rejection(
+                RejectCode::Liquidity,
+                estimate.fillable_quantity,
+                estimate.quantity,
+                format!(
+                    "{} depth fills only {} of {}",
+                    symbol, estimate.fillable_quantity, estimate.quantity
+                ),
+            ));
+        }
+        if let Some(max) = self.max_slippage_bps.filter(|max| estimate.slippage_bps > *max) {
+            rejections.push(rejection(
+                RejectCode::Slippage,
+                max,
+                estimate.slippage_bps,
+                format!(
+                    "Expected slippage on {} of {:.1} bps exceeds the limit of {:.1} bps",
+                    symbol, estimate.slippage_bps, max
+                ),
+            ));
+        }
+        if let Some(max) = self.max_book_share.filter(|max| estimate.book_share > *max) {
+            rejections.push(rejection(
+                RejectCode::Liquidity,
+                max,
+                estimate.book_share,
+                format!(
+                    "Order takes {:.0}% of the {} top-of-book volume, over the limit of {:.0}%",
+                    estimate.book_share * 100.0,
+                    symbol,
+                    max * 100.0
+                ),
+            ));
+        }
+        rejections
+    }
+}
+
+/// Walk the book for a market order of `quantity`
+///
+/// Returns `None` when the side the order takes from is empty.
+pub fn estimate_slippage(
+    book: &OrderBook,
+    side: OrderSide,
+    quantity: f64,
+    top_levels: usize,
+) -> Option<SlippageEstimate> {
+    let levels = book.levels_for(side);
+    let reference_price = levels.first()?.price;
+
+    let mut remaining = quantity;
+    let mut filled = 0.0;
+    let mut notional = 0.0;
+    let mut worst_price = reference_price;
+    for level in levels {
+        if remaining <= QTY_EPSILON {
+            break;
+        }
+        let take = remaining.min(level.quantity);
+        filled += take;
+        notional += take * level.price;
+        remaining -= take;
+        worst_price = level.price;
+    }
+
+    let avg_price = if filled > 0.0 { notional / filled } else { reference_price };
+    let adverse = match side {
+        OrderSide::Buy => avg_price - reference_price,
+        OrderSide::Sell => reference_price - avg_price,
+    };
+    let top = top_volume(levels, top_levels);
+
+    Some(SlippageEstimate {
+        reference_price,
+        avg_price,
+        worst_price,
+        slippage_bps: adverse / reference_price * 10_000.0,
+        book_share: if top > 0.0 { quantity / top } else { f64::INFINITY },
+        quantity,
+        fillable_quantity: filled,
+    })
+}
+
+fn top_volume(levels: &[OrderBookLevel], top_levels: usize) -> f64 {
+    levels.iter().take(top_levels).map(|l| l.quantity).sum()
+}
+
+/// Quantity whose average fill price stays at or better than `limit_price`
+fn quantity_within(levels: &[OrderBookLevel], side: OrderSide, limit_price: f64) -> f64 {
+    let within = |price: f64| match side {
+        OrderSide::Buy => price <= limit_price,
+        OrderSide::Sell => price >= limit_price,
+    };
+    let mut filled = 0.0;
+    let mut notional = 0.0;
+    for level in levels {
+        let avg = (notional + level.quantity * level.price) / (filled + level.quantity);
+        if !within(avg) {
+            // Largest x with (notional + x * price) / (filled + x) == limit_price
+            return filled + (limit_price * filled - notional) / (level.price - limit_price);
+        }
+        filled += level.quantity;
+        notional += level.quantity * level.price;
+    }
+    filled
+}
+
+fn side_name(side: OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "ask",
+        OrderSide::Sell => "bid",
+    }
+}
+
+fn rejection(code: RejectCode, limit: f64, actual: f64, message: String) -> PreTradeRejection {
+    PreTradeRejection {
+        code,
+        scope: LimitScope::Symbol,
+        limit,
+        actual,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(price: f64, quantity: f64) -> OrderBookLevel {
+        OrderBookLevel { price, quantity }
+    }
+
+    /// Asks 100 x1, 101 x2, 102 x3; bids 99 x1, 98 x1
+    fn book() -> OrderBook {
+        OrderBook {
+            symbol: "BTCUSDT".to_string(),
+            bids: vec![level(99.0, 1.0), level(98.0, 1.0)],
+            asks: vec![level(100.0, 1.0), level(101.0, 2.0), level(102.0, 3.0)],
+            timestamp: 0,
+        }
+    }
+
+    fn limits(params: &[(&str, f64)]) -> SlippageLimits {
+        let params: HashMap<String, f64> = params.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        SlippageLimits::from_params(&params).unwrap()
+    }
+
+    #[test]
+    fn test_from_params() {
+        let parsed = limits(&[("max_slippage_bps", 25.0), ("max_book_share", 0.5), ("top_levels", 5.0), ("downsize", 1.0)]);
+        assert_eq!(parsed.max_slippage_bps, Some(25.0));
+        assert_eq!(parsed.max_book_share, Some(0.5));
+        assert_eq!(parsed.top_levels, 5);
+        assert_eq!(parsed.depth_levels, 50);
+        assert!(parsed.downsize);
+        assert!(!SlippageLimits::default().is_active());
+
+        let bad = |key: &str, value: f64| {
+            SlippageLimits::from_params(&HashMap::from([(key.to_string(), value)])).is_err()
+        };
+        assert!(bad("max_book_share", 1.5));
+        assert!(bad("max_slippage_bps", 0.0));
+        assert!(bad("max_slippage_bps", -5.0));
+        assert!(bad("max_spread_bps", 10.0));
+    }
+
+    #[test]
+    fn test_estimate_walks_the_book() {
+        // 1 @ 100 + 2 @ 101 = 302 for 3
+        let estimate = estimate_slippage(&book(), OrderSide::Buy, 3.0, 1).unwrap();
+        assert_eq!(estimate.reference_price, 100.0);
+        assert!((estimate.avg_price - 302.0 / 3.0).abs() < 1e-9);
+        assert_eq!(estimate.worst_price, 101.0);
+        assert!((estimate.slippage_bps - 200.0 / 3.0).abs() < 1e-9);
+        assert_eq!(estimate.book_share, 3.0);
+        assert!(estimate.is_fillable());
+
+        // Sells take the bids; only 2 are resting
+        let estimate = estimate_slippage(&book(), OrderSide::Sell, 3.0, 2).unwrap();
+        assert_eq!(estimate.avg_price, 98.5);
+        assert_eq!(estimate.fillable_quantity, 2.0);
+        assert!(!estimate.is_fillable());
+
+        let empty = OrderBook { asks: vec![], ..book() };
+        assert!(estimate_slippage(&empty, OrderSide::Buy, 1.0, 1).is_none());
+    }
+
+    #[test]
+    fn test_reject_or_downsize() {
+        let strict = limits(&[("max_slippage_bps", 50.0)]);
+        assert!(matches!(strict.evaluate(&book(), OrderSide::Buy, 1.5), SlippageDecision::Accept(_)));
+
+        match strict.evaluate(&book(), OrderSide::Buy, 3.0) {
+            SlippageDecision::Reject { rejections, .. } => {
+                assert_eq!(rejections.len(), 1);
+                assert_eq!(rejections[0].code, RejectCode::Slippage);
+                assert_eq!(rejections[0].limit, 50.0);
+            }
+            other => panic!("expected a rejection, got {:?}", other),
+        }
+
+        // 50 bps over 100 allows an average of 100.5: 1 @ 100 + 1 @ 101
+        let downsizing = limits(&[("max_slippage_bps", 50.0), ("downsize", 1.0)]);
+        match downsizing.evaluate(&book(), OrderSide::Buy, 3.0) {
+            SlippageDecision::Downsize { quantity, estimate, .. } => {
+                assert!((quantity - 2.0).abs() < 1e-9);
+                assert!((estimate.slippage_bps - 50.0).abs() < 1e-6);
+            }
+            other => panic!("expected a downsize, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_book_share_and_missing_depth() {
+        let share = limits(&[("max_book_share", 0.5), ("top_levels", 2.0), ("downsize", 1.0)]);
+        // Top two ask levels hold 3
+        match share.evaluate(&book(), OrderSide::Buy, 2.0) {
+            SlippageDecision::Downsize { quantity, rejections, .. } => {
+                assert_eq!(quantity, 1.5);
+                assert_eq!(rejections[0].code, RejectCode::Liquidity);
+            }
+            other => panic!("expected a downsize, got {:?}", other),
+        }
+
+        let empty = OrderBook { bids: vec![], ..book() };
+        match share.evaluate(&empty, OrderSide::Sell, 1.0) {
+            SlippageDecision::Reject { estimate, rejections } => {
+                assert!(estimate.is_none());
+                assert_eq!(rejections[0].code, RejectCode::NoOrderBook);
+            }
+            other => panic!("expected a rejection, got {:?}", other),
+        }
+    }
+}
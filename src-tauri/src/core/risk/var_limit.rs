@@ -0,0 +1,529 @@
+//! Value-at-Risk Limit Rule
+//!
+//! Estimates how much the current positions could lose at a given
+//! confidence, from the stored kline history of the symbols held, and
+//! triggers when Value-at-Risk or expected shortfall (CVaR) exceeds a share
+//! of equity.
+//!
+//! Returns of all symbols are taken over the same bars, so correlation
+//! between symbols is part of the estimate: the historical method replays
+//! the joint returns against today's exposures, the parametric method uses
+//! their covariance matrix.
+
+use crate::core::risk::rule::{RiskAction, RiskContext, RiskMeasurement, RiskRule, RiskRuleConfig};
+use crate::core::trade::types::{Kline, Position};
+use crate::repository::KlineRepository;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+/// How VaR is estimated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VarMethod {
+    /// Quantile of losses the positions would have made over the lookback
+    Historical,
+    /// Normal approximation from the mean and covariance of returns
+    Parametric,
+}
+
+/// VaR limit rule parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VarLimitParams {
+    pub method: VarMethod,
+    /// Confidence level, e.g. 0.99
+    pub confidence: f64,
+    /// Maximum VaR as a percentage of equity
+    pub max_var_pct: f64,
+    /// Maximum expected shortfall as a percentage of equity; 0 disables it
+    pub max_cvar_pct: f64,
+    /// Kline timeframe the returns are taken from
+    pub timeframe: String,
+    /// Number of returns used
+    pub lookback: usize,
+    /// Horizon in bars; one-bar estimates are scaled by its square root
+    pub horizon_bars: usize,
+    /// Fewer common returns than this and the rule does not trigger
+    pub min_observations: usize,
+}
+
+impl Default for VarLimitParams {
+    fn default() -> Self {
+        Self {
+            method: VarMethod::Historical,
+            confidence: 0.99,
+            max_var_pct: 5.0,
+            max_cvar_pct: 0.0,
+            timeframe: "1h".to_string(),
+            lookback: 500,
+            horizon_bars: 24, // one day of hourly bars
+            min_observations: 30,
+        }
+    }
+}
+
+/// VaR and expected shortfall of a set of positions, in quote currency
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VarEstimate {
+    pub var: f64,
+    pub cvar: f64,
+    pub equity: f64,
+}
+
+impl VarEstimate {
+    pub fn var_pct(&self) -> f64 {
+        self.var / self.equity * 100.0
+    }
+
+    pub fn cvar_pct(&self) -> f64 {
+        self.cvar / self.equity * 100.0
+    }
+}
+
+/// Value-at-Risk limit rule
+pub struct VarLimitRule {
+    config: RiskRuleConfig,
+    params: VarLimitParams,
+    /// Source of kline history; without one the rule never triggers
+    klines: Option<KlineRepository>,
+    // Last estimate per instance or user, reported by measure()
+    estimates: Arc<RwLock<HashMap<String, VarEstimate>>>,
+}
+
+impl VarLimitRule {
+    /// Create a new VaR limit rule
+    pub fn new(params: VarLimitParams, action: RiskAction) -> Self {
+        Self {
+            config: RiskRuleConfig {
+                enabled: true,
+                action,
+                notify_methods: vec!["log".to_string()],
+                halt_trading: false,
+            },
+            params,
+            klines: None,
+            estimates: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Read kline history from the database
+    pub fn with_klines(mut self, klines: KlineRepository) -> Self {
+        self.klines = Some(klines);
+        self
+    }
+
+    /// Last estimate for an instance or user
+    pub fn get_estimate(&self, scope: &str) -> Option<VarEstimate> {
+        self.estimates.read().unwrap().get(scope).copied()
+    }
+
+    /// Estimate VaR of the exposures from aligned returns
+    ///
+    /// Returns `None` when there are too few observations.
+    pub fn estimate(&self, exposures: &[f64], returns: &[Vec<f64>], equity: f64) -> Option<VarEstimate> {
+        let observations = returns.first().map_or(0, |r| r.len());
+        if observations < self.params.min_observations.max(2) {
+            return None;
+        }
+
+        let (var, cvar) = match self.params.method {
+            VarMethod::Historical => historical_var(exposures, returns, self.params.confidence),
+            VarMethod::Parametric => parametric_var(exposures, returns, self.params.confidence),
+        };
+        let scale = (self.params.horizon_bars.max(1) as f64).sqrt();
+        Some(VarEstimate {
+            var: var * scale,
+            cvar: cvar * scale,
+            equity,
+        })
+    }
+
+    fn breached(&self, estimate: &VarEstimate) -> (bool, bool) {
+        let var = estimate.var_pct() > self.params.max_var_pct;
+        let cvar = self.params.max_cvar_pct > 0.0 && estimate.cvar_pct() > self.params.max_cvar_pct;
+        (var, cvar)
+    }
+}
+
+#[async_trait]
+impl RiskRule for VarLimitRule {
+    fn name(&self) -> &str {
+        "var_limit"
+    }
+
+    fn description(&self) -> &str {
+        "Limits Value-at-Risk and expected shortfall of open positions as a share of equity"
+    }
+
+    async fn check(&self, context: &RiskContext) -> Result<bool> {
+        if !self.config.enabled {
+            return Ok(false);
+        }
+
+        let scope = context.scope_key().to_string();
+        let exposures = net_exposures(&context.positions);
+        let equity = context.total_equity();
+        let Some(klines) = &self.klines else {
+            return Ok(false);
+        };
+        if exposures.is_empty() || equity <= 0.0 {
+            self.estimates.write().unwrap().remove(&scope);
+            return Ok(false);
+        }
+
+        let mut history = Vec::with_capacity(exposures.len());
+        for (symbol, _) in &exposures {
+            history.push(
+                klines
+                    .find_recent(symbol, &self.params.timeframe, self.params.lookback + 1)
+                    .await?,
+            );
+        }
+        let returns = aligned_returns(&history);
+        let values: Vec<f64> = exposures.iter().map(|(_, value)| *value).collect();
+
+        let Some(estimate) = self.estimate(&values, &returns, equity) else {
+            log::debug!(
+                "Not enough {} kline history for VaR of {}",
+                self.params.timeframe,
+                scope
+            );
+            self.estimates.write().unwrap().remove(&scope);
+            return Ok(false);
+        };
+        self.estimates.write().unwrap().insert(scope, estimate);
+
+        let (var, cvar) = self.breached(&estimate);
+        if var || cvar {
+            log::warn!(
+                "VaR limit exceeded: VaR {:.2}% (max {:.2}%), CVaR {:.2}%, equity {:.2}",
+                estimate.var_pct(),
+                self.params.max_var_pct,
+                estimate.cvar_pct(),
+                equity
+            );
+        }
+        Ok(var || cvar)
+    }
+
+    fn measure(&self, context: &RiskContext) -> Option<RiskMeasurement> {
+        let estimate = self.get_estimate(context.scope_key())?;
+
+        // Report expected shortfall only when it alone is over its limit
+        match self.breached(&estimate) {
+            (false, true) => Some(RiskMeasurement::new(
+                "cvar_pct",
+                estimate.cvar_pct(),
+                self.params.max_cvar_pct,
+            )),
+            _ => Some(RiskMeasurement::new(
+                "var_pct",
+                estimate.var_pct(),
+                self.params.max_var_pct,
+            )),
+        }
+    }
+
+    fn config(&self) -> &RiskRuleConfig {
+        &self.config
+    }
+
+    fn update_config(&mut self, config: RiskRuleConfig) -> Result<()> {
+        self.config = config;
+        Ok(())
+    }
+}
+
+/// Signed position value per symbol, short positions negative
+fn net_exposures(positions: &[Position]) -> Vec<(String, f64)> {
+    let mut exposures: BTreeMap<String, f64> = BTreeMap::new();
+    for position in positions {
+        let price = position.current_price.unwrap_or(position.entry_price);
+        let sign = if position.side == "short" { -1.0 } else { 1.0 };
+        *exposures.entry(position.symbol.clone()).or_default() += sign * position.quantity * price;
+    }
+    exposures.into_iter().filter(|(_, value)| *value != 0.0).collect()
+}
+
+/// Close-to-close returns of each series over the bars all series share
+fn aligned_returns(history: &[Vec<Kline>]) -> Vec<Vec<f64>> {
+    let closes: Vec<HashMap<i64, f64>> = history
+        .iter()
+        .map(|klines| klines.iter().map(|k| (k.timestamp, k.close)).collect())
+        .collect();
+
+    let mut timestamps: Vec<i64> = match history.first() {
+        Some(first) => first
+            .iter()
+            .map(|k| k.timestamp)
+            .filter(|t| closes.iter().all(|c| c.contains_key(t)))
+            .collect(),
+        None => return Vec::new(),
+    };
+    timestamps.sort_unstable();
+    timestamps.dedup();
+
+    closes
+        .iter()
+        .map(|c| {
+            timestamps
+                .windows(2)
+                .map(|w| c[&w[1]] / c[&w[0]] - 1.0)
+                .collect()
+        })
+        .collect()
+}
+
+/// One-bar historical VaR and expected shortfall
+fn historical_var(exposures: &[f64], returns: &[Vec<f64>], confidence: f64) -> (f64, f64) {
+    let observations = returns.first().map_or(0, |r| r.len());
+    let mut losses: Vec<f64> = (0..observations)
+        .map(|t| {
+            -exposures
+                .iter()
+                .zip(returns)
+                .map(|(value, r)| value * r[t])
+                .sum::<f64>()
+        })
+        .collect();
+    if losses.is_empty() {
+        return (0.0, 0.0);
+    }
+    losses.sort_by(|a, b| b.total_cmp(a));
+
+    // Worst (1 - confidence) share of outcomes; the epsilon keeps 0.05 * 100 at 5
+    let tail = (((1.0 - confidence) * losses.len() as f64 - 1e-9).ceil() as usize).clamp(1, losses.len());
+    let var = losses[tail - 1];
+    let cvar = losses[..tail].iter().sum::<f64>() / tail as f64;
+    (var.max(0.0), cvar.max(0.0))
+}
+
+/// One-bar parametric (variance-covariance) VaR and expected shortfall
+fn parametric_var(exposures: &[f64], returns: &[Vec<f64>], confidence: f64) -> (f64, f64) {
+    let means: Vec<f64> = returns
+        .iter()
+        .map(|r| r.iter().sum::<f64>() / r.len() as f64)
+        .collect();
+    let covariance = |i: usize, j: usize| {
+        let n = returns[i].len();
+        returns[i]
+            .iter()
+            .zip(&returns[j])
+            .map(|(a, b)| (a - means[i]) * (b - means[j]))
+            .sum::<f64>()
+            / (n - 1) as f64
+    };
+
+    let mean: f64 = exposures.iter().zip(&means).map(|(w, m)| w * m).sum();
+    let mut variance = 0.0;
+    for i in 0..exposures.len() {
+        for j in 0..exposures.len() {
+            variance += exposures[i] * exposures[j] * covariance(i, j);
+        }
+    }
+    let sigma = variance.max(0.0).sqrt();
+
+    let z = normal_quantile(confidence);
+    let var = z * sigma - mean;
+    let cvar = sigma * normal_pdf(z) / (1.0 - confidence) - mean;
+    (var.max(0.0), cvar.max(0.0))
+}
+
+fn normal_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Inverse of the standard normal CDF (Acklam's approximation)
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e+01,
+        2.209460984245205e+02,
+        -2.759285104469687e+02,
+        1.38357751867269e+02,
+        -3.066479806614716e+01,
+        2.506628277459239e+00,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e+01,
+        1.615858368580409e+02,
+        -1.556989798598866e+02,
+        6.680131188771972e+01,
+        -1.328068155288572e+01,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-03,
+        -3.223964580411365e-01,
+        -2.400758277161838e+00,
+        -2.549732539343734e+00,
+        4.374664141464968e+00,
+        2.938163982698783e+00,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-03,
+        3.224671290700398e-01,
+        2.445134137142996e+00,
+        3.754408661907416e+00,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+
+    if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p <= 1.0 - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kline(symbol: &str, timestamp: i64, close: f64) -> Kline {
+        Kline {
+            symbol: symbol.to_string(),
+            timeframe: "1h".to_string(),
+            timestamp,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 1.0,
+            quote_volume: None,
+        }
+    }
+
+    fn position(symbol: &str, side: &str, quantity: f64, price: f64) -> Position {
+        Position {
+            id: symbol.to_string(),
+            symbol: symbol.to_string(),
+            side: side.to_string(),
+            quantity,
+            entry_price: price,
+            current_price: Some(price),
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+            opened_at: 0,
+        }
+    }
+
+    /// Alternating +/- returns of the given size
+    fn zigzag(size: f64, n: usize) -> Vec<f64> {
+        (0..n).map(|i| if i % 2 == 0 { size } else { -size }).collect()
+    }
+
+    #[test]
+    fn test_normal_quantile() {
+        assert!((normal_quantile(0.99) - 2.326348).abs() < 1e-5);
+        assert!((normal_quantile(0.95) - 1.644854).abs() < 1e-5);
+        assert!(normal_quantile(0.5).abs() < 1e-9);
+        assert!((normal_quantile(0.01) + 2.326348).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_net_exposures_and_alignment() {
+        let exposures = net_exposures(&[
+            position("ETHUSDT", "short", 2.0, 100.0),
+            position("BTCUSDT", "long", 1.0, 1000.0),
+            position("BTCUSDT", "long", 1.0, 1000.0),
+        ]);
+        assert_eq!(
+            exposures,
+            vec![("BTCUSDT".to_string(), 2000.0), ("ETHUSDT".to_string(), -200.0)]
+        );
+
+        // ETH misses the bar at 2: returns run over 1 -> 3 -> 4 for both
+        let btc = vec![kline("BTCUSDT", 1, 100.0), kline("BTCUSDT", 2, 105.0), kline("BTCUSDT", 3, 110.0), kline("BTCUSDT", 4, 99.0)];
+        let eth = vec![kline("ETHUSDT", 1, 10.0), kline("ETHUSDT", 3, 12.0), kline("ETHUSDT", 4, 12.0)];
+        let returns = aligned_returns(&[btc, eth]);
+        assert_eq!(returns.len(), 2);
+        assert_eq!(returns[0].len(), 2);
+        assert!((returns[0][0] - 0.10).abs() < 1e-12);
+        assert!((returns[0][1] + 0.10).abs() < 1e-12);
+        assert!((returns[1][0] - 0.20).abs() < 1e-12);
+        assert_eq!(returns[1][1], 0.0);
+    }
+
+    #[test]
+    fn test_historical_var_tail() {
+        // 100 outcomes: losses of 1..=100 on a 1000 position (returns -0.001..-0.1)
+        let returns = vec![(1..=100).map(|i| -(i as f64) / 1000.0).collect::<Vec<_>>()];
+        let (var, cvar) = historical_var(&[1000.0], &returns, 0.95);
+        // Worst 5: 100, 99, 98, 97, 96
+        assert!((var - 96.0).abs() < 1e-9);
+        assert!((cvar - 98.0).abs() < 1e-9);
+
+        // All gains: no loss at risk
+        let returns = vec![vec![0.01; 50]];
+        assert_eq!(historical_var(&[1000.0], &returns, 0.99), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_parametric_var_uses_correlation() {
+        let a = zigzag(0.01, 100);
+        let anti: Vec<f64> = a.iter().map(|r| -r).collect();
+        let z = normal_quantile(0.99);
+
+        // Long both of two perfectly correlated symbols: risks add up
+        let (var, _) = parametric_var(&[1000.0, 1000.0], &[a.clone(), a.clone()], 0.99);
+        let sigma_one = 1000.0 * (0.01f64.powi(2) * 100.0 / 99.0).sqrt();
+        assert!((var - 2.0 * z * sigma_one).abs() < 1e-6);
+
+        // Long both of two perfectly anti-correlated symbols: fully hedged
+        let (var, cvar) = parametric_var(&[1000.0, 1000.0], &[a, anti], 0.99);
+        assert!(var.abs() < 1e-9);
+        assert!(cvar.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_scales_and_checks_limits() {
+        let params = VarLimitParams {
+            method: VarMethod::Historical,
+            confidence: 0.95,
+            max_var_pct: 5.0,
+            max_cvar_pct: 0.0,
+            horizon_bars: 4,
+            min_observations: 10,
+            ..VarLimitParams::default()
+        };
+        let rule = VarLimitRule::new(params, RiskAction::Notify);
+
+        let returns = vec![(1..=100).map(|i| -(i as f64) / 1000.0).collect::<Vec<_>>()];
+        // One-bar VaR 96, doubled over 4 bars
+        let estimate = rule.estimate(&[1000.0], &returns, 10_000.0).unwrap();
+        assert!((estimate.var - 192.0).abs() < 1e-9);
+        assert!((estimate.var_pct() - 1.92).abs() < 1e-9);
+        assert_eq!(rule.breached(&estimate), (false, false));
+
+        let estimate = rule.estimate(&[1000.0], &returns, 3_000.0).unwrap();
+        assert_eq!(rule.breached(&estimate), (true, false));
+
+        // Too little history
+        assert!(rule.estimate(&[1000.0], &[vec![-0.01; 5]], 10_000.0).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_check_without_history_does_not_trigger() {
+        let rule = VarLimitRule::new(VarLimitParams::default(), RiskAction::Notify);
+        let context = RiskContext::new(
+            vec![position("BTCUSDT", "long", 1.0, 1000.0)],
+            vec![],
+            1_000.0,
+            0.0,
+            "i1".to_string(),
+            "u1".to_string(),
+        );
+        assert!(!rule.check(&context).await.unwrap());
+        assert!(rule.measure(&context).is_none());
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! Stops trading when market volatility exceeds a configured threshold.
 
-use crate::core::risk::rule::{RiskRule, RiskContext, RiskRuleConfig, RiskAction};
+use crate::core::risk::rule::{RiskRule, RiskContext, RiskMeasurement, RiskRuleConfig, RiskAction};
 use crate::core::trade::types::Kline;
 use anyhow::Result;
 use async_trait::async_trait;
@@ -49,6 +49,7 @@ impl VolatilityLimitRule {
                 enabled: true,
                 action,
                 notify_methods: vec!["log".to_string()],
+                halt_trading: false,
             },
             params,
             kline_history: Arc::new(RwLock::new(HashMap::new())),
@@ -175,6 +176,18 @@ impl RiskRule for VolatilityLimitRule {
         Ok(false)
     }
 
+    fn measure(&self, context: &RiskContext) -> Option<RiskMeasurement> {
+        // The most volatile symbol held
+        context
+            .positions
+            .iter()
+            .filter_map(|p| self.get_volatility(&p.symbol).map(|v| (p.symbol.as_str(), v)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(symbol, volatility)| {
+                RiskMeasurement::new("atr_ratio", volatility, self.params.max_atr_ratio).with_symbol(symbol)
+            })
+    }
+
     fn config(&self) -> &RiskRuleConfig {
         &self.config
     }
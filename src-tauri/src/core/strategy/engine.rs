@@ -1,10 +1,13 @@
 use crate::core::event::{EventBus, MarketEvent};
 use crate::core::strategy::ScriptExecutor;
 use crate::core::trade::exchange::Exchange;
+use crate::core::trade::execution::{AlgoOrderRequest, ExecutionAlgo, ExecutionAlgoEngine, ExecutionPolicy};
 use crate::core::trade::types::*;
 use crate::core::risk::rule::{RiskContext, RiskRule};
 use crate::models::CreateInstanceRequest;
-use crate::repository::StrategyInstanceRepository;
+use crate::repository::{RiskRuleRepository, StrategyInstanceRepository};
+use sqlx::SqlitePool;
+use crate::services::{ExchangeSession, ExchangeSessionRegistry, PnlScope, PnlService, TradeService};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -69,6 +72,30 @@ pub struct InstanceInfo {
     pub stats: Option<InstanceStats>,
 }
 
+/// 风控监控所需的运行实例信息
+#[derive(Clone)]
+pub struct InstanceRiskTarget {
+    pub instance_id: String,
+    pub user_id: String,
+    /// 实例交易的交易对，用于筛选实例的持仓
+    pub symbols: Vec<String>,
+    /// 实例所用账户的 TradeService
+    pub trade_service: Option<Arc<TradeService>>,
+    pub paused: bool,
+}
+
+/// 无需锁住运行实例即可访问的实例句柄
+///
+/// 运行循环持有实例的写锁，暂停、风控查询和风控规则重载经句柄完成。
+struct InstanceHandle {
+    user_id: String,
+    symbols: Vec<String>,
+    trade_service: Option<Arc<TradeService>>,
+    paused: Arc<std::sync::atomic::AtomicBool>,
+    busy_since: Arc<std::sync::atomic::AtomicI64>,
+    risk_rules: Arc<RwLock<Vec<Box<dyn RiskRule>>>>,
+}
+
 /// 运行中的策略实例
 struct RunningInstance {
     id: String,
@@ -76,19 +103,22 @@ struct RunningInstance {
     executor: ScriptExecutor,
     event_bus: Arc<EventBus>,
     exchange: Arc<dyn Exchange>,
+    /// 账户的 TradeService，存在时订单经其下单并归属到本实例
+    trade_service: Option<Arc<TradeService>>,
+    /// 账户的执行算法引擎，大额信号按 `execution` 参数拆单
+    execution: Option<Arc<ExecutionAlgoEngine>>,
+    /// 计算今日盈亏
+    pnl_service: Option<Arc<PnlService>>,
     /// User ID who owns this strategy instance
-    #[allow(dead_code)]
     user_id: String,
     instance_repo: Arc<StrategyInstanceRepository>,
     shutdown_tx: Option<broadcast::Sender<()>>,
     paused: Arc<std::sync::atomic::AtomicBool>,
+    /// 开始处理当前 K 线的时间（毫秒），空闲时为 0
+    busy_since: Arc<std::sync::atomic::AtomicI64>,
     history: HashMap<String, Vec<Kline>>, // symbol -> klines
-    /// 风控规则列表
-    risk_rules: Vec<Box<dyn RiskRule>>,
-    /// 最大单笔订单金额限制
-    max_order_amount: f64,
-    /// 最大日内交易次数
-    max_daily_trades: usize,
+    /// 风控规则列表，规则修改后由引擎整体替换
+    risk_rules: Arc<RwLock<Vec<Box<dyn RiskRule>>>>,
     /// 今日已交易次数
     daily_trade_count: Arc<std::sync::atomic::AtomicUsize>,
     /// 账户余额缓存
@@ -118,15 +148,16 @@ impl RunningInstance {
             executor,
             event_bus,
             exchange,
+            trade_service: None,
+            execution: None,
+            pnl_service: None,
             user_id,
             instance_repo,
             shutdown_tx: None,
             paused: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            busy_since: Arc::new(std::sync::atomic::AtomicI64::new(0)),
             history: HashMap::new(),
-            // 默认风控配置
-            risk_rules,
-            max_order_amount: 1000.0,  // 默认最大单笔订单金额
-            max_daily_trades: 100,      // 默认最大日内交易次数
+            risk_rules: Arc::new(RwLock::new(risk_rules)),
             daily_trade_count: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
             cached_balance: Arc::new(RwLock::new(None)),
             cached_positions: Arc::new(RwLock::new(Vec::new())),
@@ -179,7 +210,10 @@ impl RunningInstance {
                             if let MarketEvent::Kline(kline) = event {
                                 // 再次检查暂停状态，防止在等待期间收到信号后暂停
                                 if !self.paused.load(std::sync::atomic::Ordering::SeqCst) {
-                                    if let Err(e) = self.on_kline(kline).await {
+                                    self.busy_since.store(chrono::Utc::now().timestamp_millis(), std::sync::atomic::Ordering::SeqCst);
+                                    let result = self.on_kline(kline).await;
+                                    self.busy_since.store(0, std::sync::atomic::Ordering::SeqCst);
+                                    if let Err(e) = result {
                                         log::error!("Error processing kline: {}", e);
                                         // 更新数据库状态为 error
                                         let error_msg = format!("Kline processing error: {}", e);
@@ -327,12 +361,47 @@ impl RunningInstance {
             price: signal.price,
             stop_price: None,
             quantity: signal.quantity,
-            client_order_id: Some(format!("{}-{}", self.id, uuid::Uuid::new_v4())),
+            client_order_id: Some(OrderRequest::new_client_order_id("strat")),
             time_in_force: Some(TimeInForce::IOC),
         };
 
-        // 执行订单
-        match self.exchange.place_order(&order_request).await {
+        // 大额信号交给执行算法拆单
+        if let (Some(execution), Some(policy)) = (&self.execution, ExecutionPolicy::from_parameters(&self.config.parameters)) {
+            if policy.applies_to(signal.quantity) {
+                let request = AlgoOrderRequest {
+                    symbol: signal.symbol.clone(),
+                    side,
+                    quantity: signal.quantity,
+                    limit_price: match policy.algo {
+                        ExecutionAlgo::Iceberg { .. } => signal.price,
+                        _ => None,
+                    },
+                    algo: policy.algo,
+                };
+                match execution.submit(&self.user_id, request, Some(&self.id)).await {
+                    Ok(parent) => {
+                        log::info!("Signal routed to {} parent order {}", policy.algo.name(), parent.id);
+                        self.event_bus.publish_order_placed(parent);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to start execution algo: {}", e);
+                        self.event_bus
+                            .publish_strategy_error(format!("Execution algo failed: {}", e));
+                    }
+                }
+                return Ok(());
+            }
+        }
+
+        // 执行订单：单笔金额、日内次数和持仓限制由 TradeService 的下单前风控检查
+        let result = match &self.trade_service {
+            Some(trade_service) => trade_service
+                .place_instance_order(order_request, &self.user_id, &self.id)
+                .await
+                .map_err(anyhow::Error::from),
+            None => Err(anyhow::anyhow!("No trade service for instance {}, order not sent", self.id)),
+        };
+        match result {
             Ok(order) => {
                 log::info!("Order placed successfully: {}", order.id);
                 self.event_bus.publish_order_placed(order);
@@ -348,6 +417,8 @@ impl RunningInstance {
     }
 
     /// 风控检查
+    ///
+    /// 只检查信号本身和实例的风控规则；金额、次数和持仓限制在下单前风控中检查。
     async fn check_risk(&self, signal: &crate::core::event::Signal) -> bool {
         // ========== 基础参数检查 ==========
 
@@ -365,7 +436,7 @@ impl RunningInstance {
             }
         }
 
-        // ========== 账户余额检查 ==========
+        // ========== 刷新风控上下文 ==========
 
         // 刷新账户余额缓存
         if let Ok(balances) = self.exchange.get_balance().await {
@@ -373,112 +444,63 @@ impl RunningInstance {
             *self.cached_balance.write().await = Some(total_balance);
         }
 
-        // 检查余额是否足够
-        let order_value = signal.quantity * signal.price.unwrap_or(0.0);
-        if let Some(balance) = *self.cached_balance.read().await {
-            if order_value > balance {
-                log::warn!(
-                    "[{}] Insufficient balance: order_value={}, balance={}",
-                    self.id, order_value, balance
-                );
-                return false;
-            }
-        }
-
-        // ========== 单笔订单金额限制 ==========
-
-        if order_value > self.max_order_amount {
-            log::warn!(
-                "[{}] Order amount exceeds limit: order_value={}, max={}",
-                self.id, order_value, self.max_order_amount
-            );
-            return false;
-        }
-
-        // ========== 日内交易次数限制 ==========
-
-        let current_count = self.daily_trade_count.load(std::sync::atomic::Ordering::Relaxed);
-        if current_count >= self.max_daily_trades {
-            log::warn!(
-                "[{}] Daily trade limit reached: {}/{}",
-                self.id, current_count, self.max_daily_trades
-            );
-            return false;
-        }
-
-        // ========== 持仓限制检查 ==========
-
         // 刷新持仓缓存
         if let Ok(positions) = self.exchange.get_positions().await {
             *self.cached_positions.write().await = positions;
         }
-
-        // 检查单个持仓限制
         let positions = self.cached_positions.read().await;
-        let symbol_position = positions.iter()
-            .find(|p| p.symbol == signal.symbol)
-            .map(|p| p.quantity * p.entry_price)
-            .unwrap_or(0.0);
-
-        // 计算新订单后的持仓价值
-        let new_position_value = symbol_position + order_value;
-
-        // 单个交易对最大持仓限制（默认为账户余额的50%）
-        let max_single_position = self.cached_balance.read().await
-            .map(|b| b * 0.5)
-            .unwrap_or(5000.0);
-
-        if new_position_value > max_single_position {
-            log::warn!(
-                "[{}] Single position limit exceeded: symbol={}, new_value={}, max={}",
-                self.id, signal.symbol, new_position_value, max_single_position
-            );
-            return false;
-        }
-
-        // 总持仓限制（默认为账户余额的90%）
-        let total_position_value: f64 = positions.iter()
-            .map(|p| p.quantity * p.entry_price)
-            .sum::<f64>() + order_value;
-
-        let max_total_position = self.cached_balance.read().await
-            .map(|b| b * 0.9)
-            .unwrap_or(9000.0);
-
-        if total_position_value > max_total_position {
-            log::warn!(
-                "[{}] Total position limit exceeded: total_value={}, max={}",
-                self.id, total_position_value, max_total_position
-            );
-            return false;
-        }
 
         // ========== 风险规则检查 ==========
 
+        let today_pnl = match &self.pnl_service {
+            Some(pnl_service) => pnl_service
+                .today_pnl(&self.user_id, &PnlScope::Instance(self.id.clone()))
+                .await
+                .unwrap_or_else(|e| {
+                    log::warn!("[{}] Failed to compute today's PnL: {}", self.id, e);
+                    0.0
+                }),
+            None => 0.0,
+        };
+
         // 构建风险上下文
         let risk_context = RiskContext {
             positions: positions.clone(),
             orders: Vec::new(), // 可从 exchange 获取
             balance: self.cached_balance.read().await.unwrap_or(0.0),
-            today_pnl: 0.0, // 可从数据库计算
+            today_pnl,
             instance_id: self.id.clone(),
+            user_id: self.user_id.clone(),
+            // 实例只在一个账户上交易
+            position_accounts: match self.trade_service.as_ref().and_then(|ts| ts.exchange_id()) {
+                Some(exchange_id) => positions.iter().map(|p| (p.id.clone(), exchange_id.to_string())).collect(),
+                None => HashMap::new(),
+            },
+            timestamp: None,
         };
 
         // 检查所有启用的风控规则
-        for rule in &self.risk_rules {
+        let risk_rules = self.risk_rules.read().await;
+        for rule in risk_rules.iter() {
             if !rule.config().enabled {
                 continue;
             }
 
             match rule.check(&risk_context).await {
                 Ok(triggered) => {
-                    if triggered {
-                        log::warn!(
-                            "[{}] Risk rule '{}' triggered, rejecting signal",
-                            self.id, rule.name()
-                        );
-                        return false;
+                    if !triggered {
+                        continue;
+                    }
+                    // 仅告警的规则不拦截信号
+                    if !rule.config().action.stops_trading() {
+                        log::warn!("[{}] Risk rule '{}' triggered", self.id, rule.name());
+                        continue;
                     }
+                    log::warn!(
+                        "[{}] Risk rule '{}' triggered, rejecting signal",
+                        self.id, rule.name()
+                    );
+                    return false;
                 }
                 Err(e) => {
                     log::error!("[{}] Risk rule '{}' check error: {}", self.id, rule.name(), e);
@@ -504,18 +526,6 @@ impl RunningInstance {
         }
     }
 
-    /// 暂停策略实例
-    pub fn pause(&self) {
-        self.paused.store(true, std::sync::atomic::Ordering::SeqCst);
-        log::info!("Strategy instance {} paused", self.id);
-    }
-
-    /// 恢复策略实例
-    pub fn resume(&self) {
-        self.paused.store(false, std::sync::atomic::Ordering::SeqCst);
-        log::info!("Strategy instance {} resumed", self.id);
-    }
-
     /// 获取实例信息
     pub fn info(&self) -> InstanceInfo {
         log::debug!("[info] Starting info() for instance {}", self.id);
@@ -562,9 +572,14 @@ impl RunningInstance {
 /// 策略引擎
 pub struct StrategyEngine {
     instances: Arc<RwLock<HashMap<String, Arc<RwLock<RunningInstance>>>>>,
+    handles: Arc<RwLock<HashMap<String, InstanceHandle>>>,
     event_bus: Arc<EventBus>,
     exchange: Arc<dyn Exchange>,
     instance_repo: Arc<StrategyInstanceRepository>,
+    exchange_sessions: Option<Arc<ExchangeSessionRegistry>>,
+    pnl_service: Option<Arc<PnlService>>,
+    /// 读取 `risk_rules` 表和规则所需行情的连接池，未设置时实例不检查风控规则
+    risk_rule_pool: Option<SqlitePool>,
 }
 
 impl StrategyEngine {
@@ -576,12 +591,51 @@ impl StrategyEngine {
     ) -> Self {
         Self {
             instances: Arc::new(RwLock::new(HashMap::new())),
+            handles: Arc::new(RwLock::new(HashMap::new())),
             event_bus,
             exchange,
             instance_repo,
+            exchange_sessions: None,
+            pnl_service: None,
+            risk_rule_pool: None,
         }
     }
 
+    /// 为实例风控检查提供今日盈亏
+    pub fn with_pnl_service(mut self, pnl_service: Arc<PnlService>) -> Self {
+        self.pnl_service = Some(pnl_service);
+        self
+    }
+
+    /// 从 `risk_rules` 表加载实例的风控规则
+    pub fn with_risk_rules(mut self, pool: SqlitePool) -> Self {
+        self.risk_rule_pool = Some(pool);
+        self
+    }
+
+    /// 按实例的交易所账户路由下单、余额和持仓请求
+    pub fn with_exchange_sessions(mut self, sessions: Arc<ExchangeSessionRegistry>) -> Self {
+        self.exchange_sessions = Some(sessions);
+        self
+    }
+
+    /// 解析实例所用交易所账户的客户端和会话
+    async fn resolve_account(
+        &self,
+        user_id: &str,
+        exchange_id: &str,
+    ) -> Result<(Arc<dyn Exchange>, Option<Arc<ExchangeSession>>)> {
+        let Some(sessions) = &self.exchange_sessions else {
+            return Ok((self.exchange.clone(), None));
+        };
+
+        let session = sessions
+            .for_user(user_id, Some(exchange_id))
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Exchange account {} not found", exchange_id))?;
+        Ok((session.exchange.clone(), Some(session)))
+    }
+
     /// 启动策略实例
     pub async fn start_instance(&self, config: StrategyConfig, user_id: String, exchange_id: Option<String>) -> Result<String> {
         log::info!("[start_instance] ===== START =====");
@@ -608,6 +662,7 @@ impl StrategyEngine {
         let exchange_id = exchange_id.ok_or_else(|| {
             anyhow::anyhow!("exchange_id is required but not provided")
         })?;
+        let (exchange, session) = self.resolve_account(&user_id, &exchange_id).await?;
         let symbol = config.symbols.first().cloned().unwrap_or_else(|| {
             log::warn!("Strategy {} has no symbols, using default", id);
             "BTCUSDT".to_string()
@@ -653,25 +708,35 @@ impl StrategyEngine {
 
         log::info!("[start_instance] Updated strategy instance {} status to running", instance_id);
 
-        // 创建默认风控规则
-        log::info!("[start_instance] Creating risk rules...");
-        let risk_rules: Vec<Box<dyn RiskRule>> = vec![
-            // TODO: 从配置加载风控规则
-        ];
+        // 从数据库加载风控规则
+        log::info!("[start_instance] Loading risk rules...");
+        let risk_rules = self.load_risk_rules().await;
 
         // 创建运行实例
         log::info!("[start_instance] Creating RunningInstance...");
-        let instance = RunningInstance::new(
+        let mut instance = RunningInstance::new(
             instance_id.clone(),
             config,
             self.event_bus.clone(),
-            self.exchange.clone(),
+            exchange,
             user_id,
             self.instance_repo.clone(),
             risk_rules,
         )?;
+        instance.trade_service = session.as_ref().map(|s| s.trade_service.clone());
+        instance.execution = session.as_ref().map(|s| s.execution.clone());
+        instance.pnl_service = self.pnl_service.clone();
         log::info!("[start_instance] RunningInstance created successfully");
 
+        let handle = InstanceHandle {
+            user_id: instance.user_id.clone(),
+            symbols: instance.config.symbols.clone(),
+            trade_service: instance.trade_service.clone(),
+            paused: instance.paused.clone(),
+            busy_since: instance.busy_since.clone(),
+            risk_rules: instance.risk_rules.clone(),
+        };
+
         // 启动策略循环
         log::info!("[start_instance] Spawning strategy run loop...");
         let instance_ref = Arc::new(RwLock::new(instance));
@@ -698,6 +763,7 @@ impl StrategyEngine {
         let mut instances = self.instances.write().await;
         instances.insert(instance_id.clone(), instance_ref.clone());
         drop(instances);
+        self.handles.write().await.insert(instance_id.clone(), handle);
         log::info!("[start_instance] Instance saved to memory");
 
         log::info!("[start_instance] ===== COMPLETE - returning instance_id: {} =====", instance_id);
@@ -717,6 +783,7 @@ impl StrategyEngine {
             return Err(anyhow::anyhow!("Strategy instance {} not found", id));
         }
         drop(instances);
+        self.handles.write().await.remove(id);
 
         // 更新数据库状态为 stopped
         self.instance_repo
@@ -735,15 +802,7 @@ impl StrategyEngine {
     pub async fn pause_instance(&self, id: &str) -> Result<()> {
         log::info!("Pausing strategy instance: {}", id);
 
-        let instances = self.instances.read().await;
-        if let Some(instance) = instances.get(id) {
-            let instance = instance.read().await;
-            instance.pause();
-            drop(instance);
-        } else {
-            return Err(anyhow::anyhow!("Strategy instance {} not found", id));
-        }
-        drop(instances);
+        self.set_paused(id, true).await?;
 
         // 更新数据库状态为 paused
         self.instance_repo
@@ -762,15 +821,7 @@ impl StrategyEngine {
     pub async fn resume_instance(&self, id: &str) -> Result<()> {
         log::info!("Resuming strategy instance: {}", id);
 
-        let instances = self.instances.read().await;
-        if let Some(instance) = instances.get(id) {
-            let instance = instance.read().await;
-            instance.resume();
-            drop(instance);
-        } else {
-            return Err(anyhow::anyhow!("Strategy instance {} not found", id));
-        }
-        drop(instances);
+        self.set_paused(id, false).await?;
 
         // 更新数据库状态为 running
         self.instance_repo
@@ -785,6 +836,87 @@ impl StrategyEngine {
         Ok(())
     }
 
+    /// 设置实例的暂停标志，不等待运行循环释放实例锁
+    async fn set_paused(&self, id: &str, paused: bool) -> Result<()> {
+        let handles = self.handles.read().await;
+        let handle = handles
+            .get(id)
+            .ok_or_else(|| anyhow::anyhow!("Strategy instance {} not found", id))?;
+        handle.paused.store(paused, std::sync::atomic::Ordering::SeqCst);
+        log::info!("Strategy instance {} {}", id, if paused { "paused" } else { "resumed" });
+        Ok(())
+    }
+
+    /// 构建一套新的风控规则
+    ///
+    /// 规则按实例记录状态，每个实例使用各自的规则对象。
+    async fn load_risk_rules(&self) -> Vec<Box<dyn RiskRule>> {
+        let Some(pool) = &self.risk_rule_pool else {
+            return Vec::new();
+        };
+        crate::core::risk::load_rules(pool).await.unwrap_or_else(|e| {
+            log::error!("Failed to load risk rules: {}", e);
+            Vec::new()
+        })
+    }
+
+    /// 规则修改后重建所有运行实例的该条风控规则，返回更新的实例数
+    ///
+    /// 新规则从下一个信号起生效，只有被修改的规则状态重新开始，其他规则
+    /// 记录的状态（峰值权益、亏损计数）保留。规则无法构建时返回错误。
+    pub async fn reload_risk_rule(&self, name: &str) -> Result<usize> {
+        let Some(pool) = &self.risk_rule_pool else {
+            return Ok(0);
+        };
+
+        let Some(row) = RiskRuleRepository::new(pool.clone()).find_by_name(name).await? else {
+            return Ok(0);
+        };
+        if crate::core::risk::build_rule(&row, Some(pool))?.is_none() {
+            return Ok(0);
+        }
+
+        let handles = self.handles.read().await;
+        for (id, handle) in handles.iter() {
+            if let Some(rule) = crate::core::risk::build_rule(&row, Some(pool))? {
+                crate::core::risk::replace_rule(&mut *handle.risk_rules.write().await, rule);
+                log::info!("Strategy instance {} reloaded risk rule {}", id, name);
+            }
+        }
+        Ok(handles.len())
+    }
+
+    /// 运行中实例的风控目标，供风控监控按实例检查规则
+    pub async fn risk_targets(&self) -> Vec<InstanceRiskTarget> {
+        self.handles
+            .read()
+            .await
+            .iter()
+            .map(|(id, handle)| InstanceRiskTarget {
+                instance_id: id.clone(),
+                user_id: handle.user_id.clone(),
+                symbols: handle.symbols.clone(),
+                trade_service: handle.trade_service.clone(),
+                paused: handle.paused.load(std::sync::atomic::Ordering::SeqCst),
+            })
+            .collect()
+    }
+
+    /// 处理单根 K 线超过 `max_busy_ms` 仍未返回的实例
+    pub async fn stalled_instances(&self, max_busy_ms: i64) -> Vec<String> {
+        let now = chrono::Utc::now().timestamp_millis();
+        self.handles
+            .read()
+            .await
+            .iter()
+            .filter(|(_, handle)| {
+                let since = handle.busy_since.load(std::sync::atomic::Ordering::SeqCst);
+                since > 0 && now - since > max_busy_ms
+            })
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
     /// 获取所有实例信息
     pub async fn list_instances(&self) -> Vec<InstanceInfo> {
         log::debug!("[list_instances] Starting to list instances");
@@ -839,6 +971,7 @@ impl StrategyEngine {
         let mut instances = self.instances.write().await;
         let count = instances.len();
         let instance_ids: Vec<String> = instances.keys().cloned().collect();
+        self.handles.write().await.clear();
 
         // Stop each instance
         let mut stopped_count = 0;
@@ -4,6 +4,6 @@ pub mod indicators;
 pub mod debug;
 
 pub use script::ScriptExecutor;
-pub use engine::{StrategyEngine, StrategyConfig, InstanceInfo, InstanceStatus};
+pub use engine::{StrategyEngine, StrategyConfig, InstanceInfo, InstanceRiskTarget, InstanceStatus};
 pub use indicators::{IndicatorCalculator, MacdResult, BollingerBandsResult, KeltnerChannelsResult};
 pub use debug::{DebugContext, DebugLog, LogLevel, PerformanceMetrics, get_debug_context};
@@ -106,7 +106,9 @@ impl MarketDataConverter for BinanceConverter {
             status,
             commission: helpers::parse_f64(raw.get("commission").unwrap_or(&Value::Null), "commission").unwrap_or(0.0),
             created_at: helpers::normalize_timestamp(raw.get("time").unwrap_or(&Value::Null), "time")?,
+            commission_asset: raw.get("commissionAsset").and_then(|v| v.as_str()).map(|s| s.to_string()),
             filled_at: raw.get("updateTime").and_then(|v| helpers::normalize_timestamp(v, "updateTime").ok()),
+            updated_at: raw.get("updateTime").and_then(|v| helpers::normalize_timestamp(v, "updateTime").ok()),
         })
     }
 
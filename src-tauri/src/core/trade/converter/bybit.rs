@@ -195,7 +195,9 @@ impl MarketDataConverter for BybitConverter {
             created_at: helpers::normalize_timestamp(
                 result.get("createdTime").unwrap_or(&Value::Null), "createdTime"
             )?,
+            commission_asset: None,
             filled_at: result.get("updatedTime").and_then(|v| helpers::normalize_timestamp(v, "updatedTime").ok()),
+            updated_at: result.get("updatedTime").and_then(|v| helpers::normalize_timestamp(v, "updatedTime").ok()),
         })
     }
 
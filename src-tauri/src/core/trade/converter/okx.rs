@@ -134,7 +134,9 @@ impl MarketDataConverter for OkxConverter {
             commission: helpers::parse_f64(data.get("fee").unwrap_or(&Value::Null), "fee")
                 .or(Ok::<f64, ConversionError>(0.0))?,
             created_at: helpers::normalize_timestamp(data.get("cTime").unwrap_or(&Value::Null), "cTime")?,
+            commission_asset: data.get("feeCcy").and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string()),
             filled_at: data.get("uTime").and_then(|v| helpers::normalize_timestamp(v, "uTime").ok()),
+            updated_at: data.get("uTime").and_then(|v| helpers::normalize_timestamp(v, "uTime").ok()),
         })
     }
 
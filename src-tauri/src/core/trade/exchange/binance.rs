@@ -1,8 +1,11 @@
 use super::super::types::*;
 use super::r#trait::{Exchange, ExchangeName};
-use super::client::BinanceClient;
+use super::client::{binance_request_weight, BinanceClient};
+use super::rate_limit::RateLimiter;
+use super::http_client;
 use async_trait::async_trait;
 use anyhow::{anyhow, Result};
+use futures_util::future::join_all;
 use futures_util::{StreamExt, SinkExt};
 use reqwest::Client;
 use serde_json::Value;
@@ -20,6 +23,7 @@ pub struct BinanceExchange {
     ticker_tx: broadcast::Sender<Ticker>,
     kline_tx: broadcast::Sender<Kline>,
     order_tx: broadcast::Sender<Order>,
+    fill_tx: broadcast::Sender<Fill>,
     connection_state: Arc<RwLock<bool>>,
     ws_task_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
     listen_key: Arc<Mutex<Option<String>>>,
@@ -30,14 +34,16 @@ impl BinanceExchange {
         let (ticker_tx, _) = broadcast::channel(1000);
         let (kline_tx, _) = broadcast::channel(1000);
         let (order_tx, _) = broadcast::channel(1000);
+        let (fill_tx, _) = broadcast::channel(1000);
 
         Self {
             api_key,
             api_secret,
-            client: Client::new(),
+            client: http_client(),
             ticker_tx,
             kline_tx,
             order_tx,
+            fill_tx,
             connection_state: Arc::new(RwLock::new(false)),
             ws_task_handle: Arc::new(Mutex::new(None)),
             listen_key: Arc::new(Mutex::new(None)),
@@ -46,7 +52,12 @@ impl BinanceExchange {
 
     async fn get(&self, path: &str) -> Result<Value> {
         let url = format!("{}{}", REST_API_BASE, path);
+
+        let limiter = RateLimiter::for_exchange(ExchangeName::Binance);
+        limiter.acquire(binance_request_weight(&reqwest::Method::GET, path, &[])).await?;
+
         let response: reqwest::Response = self.client.get(&url).send().await?;
+        limiter.record_response(response.status(), response.headers());
 
         if !response.status().is_success() {
             return Err(anyhow!("HTTP error: {}", response.status()));
@@ -313,9 +324,13 @@ impl BinanceExchange {
 
     /// 从 Binance API 响应解析订单
     fn parse_order(&self, json: &Value, request: &OrderRequest) -> Result<Order> {
+        // orderId 是数字
+        let order_id = json["orderId"].as_i64().map(|id| id.to_string())
+            .or_else(|| json["orderId"].as_str().map(str::to_string));
+
         Ok(Order {
-            id: json["orderId"].as_str().unwrap_or_else(|| json["clientOrderId"].as_str().unwrap_or("")).to_string(),
-            exchange_order_id: Some(json["orderId"].as_str().unwrap_or("").to_string()),
+            id: order_id.clone().unwrap_or_else(|| json["clientOrderId"].as_str().unwrap_or("").to_string()),
+            exchange_order_id: Some(order_id.unwrap_or_default()),
             client_order_id: json["clientOrderId"].as_str().map(|s| s.to_string()),
             symbol: json["symbol"].as_str().unwrap_or(&request.symbol).to_string(),
             side: request.side,
@@ -332,7 +347,65 @@ impl BinanceExchange {
             status: Self::parse_order_state(json["status"].as_str().unwrap_or("UNKNOWN")),
             commission: 0.0, // 需要从交易历史获取
             created_at: json["time"].as_i64().unwrap_or(0),
+            commission_asset: None,
             filled_at: json["updateTime"].as_i64(),
+            updated_at: json["updateTime"].as_i64(),
+        })
+    }
+
+    /// 从订单查询结果还原下单请求（用于 parse_order）
+    fn request_from_json(json: &Value) -> OrderRequest {
+        OrderRequest {
+            symbol: json["symbol"].as_str().unwrap_or("").to_string(),
+            side: match json["side"].as_str().unwrap_or("") {
+                "BUY" => OrderSide::Buy,
+                "SELL" => OrderSide::Sell,
+                _ => OrderSide::Buy,
+            },
+            order_type: match json["type"].as_str().unwrap_or("") {
+                "LIMIT" => OrderType::Limit,
+                "MARKET" => OrderType::Market,
+                "STOP_LOSS_LIMIT" => OrderType::StopLimit,
+                _ => OrderType::Limit,
+            },
+            price: json["price"].as_str().and_then(|s| s.parse().ok()),
+            stop_price: None,
+            quantity: json["origQty"].as_str().unwrap_or("0").parse().unwrap_or(0.0),
+            client_order_id: json["clientOrderId"].as_str().map(str::to_string),
+            time_in_force: None,
+        }
+    }
+
+    /// 解析 exchangeInfo 中的单个交易对规则
+    fn parse_instrument(json: &Value) -> Option<Instrument> {
+        let filters = json["filters"].as_array()?;
+        let filter = |filter_type: &str| filters.iter().find(|f| f["filterType"] == filter_type);
+        let num = |v: &Value| v.as_str().and_then(|s| s.parse::<f64>().ok());
+
+        let price_filter = filter("PRICE_FILTER")?;
+        let lot_size = filter("LOT_SIZE")?;
+        // 新版接口使用 NOTIONAL，旧版为 MIN_NOTIONAL
+        let min_notional = filter("NOTIONAL")
+            .or_else(|| filter("MIN_NOTIONAL"))
+            .and_then(|f| num(&f["minNotional"]))
+            .unwrap_or(0.0);
+
+        let status = match json["status"].as_str().unwrap_or("") {
+            "TRADING" => InstrumentStatus::Trading,
+            "BREAK" => InstrumentStatus::Closed,
+            _ => InstrumentStatus::Halted,
+        };
+
+        Some(Instrument {
+            symbol: json["symbol"].as_str()?.to_string(),
+            base_asset: json["baseAsset"].as_str().unwrap_or("").to_string(),
+            quote_asset: json["quoteAsset"].as_str().unwrap_or("").to_string(),
+            status,
+            tick_size: num(&price_filter["tickSize"]).unwrap_or(0.0),
+            step_size: num(&lot_size["stepSize"]).unwrap_or(0.0),
+            min_qty: num(&lot_size["minQty"]).unwrap_or(0.0),
+            max_qty: num(&lot_size["maxQty"]),
+            min_notional,
         })
     }
 
@@ -426,6 +499,9 @@ impl BinanceExchange {
                         if let Some(event_type) = json.get("e").and_then(|e| e.as_str()) {
                             match event_type {
                                 "executionReport" => {
+                                    if let Some(fill) = Self::parse_execution_fill_static(&json) {
+                                        let _ = self.fill_tx.send(fill);
+                                    }
                                     if let Ok(order) = self.parse_execution_report(&json) {
                                         let _ = self.order_tx.send(order);
                                     }
@@ -513,7 +589,43 @@ impl BinanceExchange {
             status: Self::parse_order_state(json.get("X").and_then(|x| x.as_str()).unwrap_or("NEW")),
             commission: json.get("n").and_then(|n| n.as_f64()).unwrap_or(0.0),
             created_at: json.get("T").and_then(|t| t.as_i64()).unwrap_or(0),
+            commission_asset: json.get("N").and_then(|n| n.as_str()).map(|s| s.to_string()),
             filled_at: json.get("T").and_then(|t| t.as_i64()),
+            updated_at: json.get("E").and_then(|e| e.as_i64()),
+        })
+    }
+
+    /// 从 executionReport 解析单笔成交
+    ///
+    /// 仅 `x == "TRADE"` 的事件代表成交，其余（NEW/CANCELED 等）返回 None。
+    /// 字段：t = 成交ID, L = 成交价, l = 成交量, n = 手续费, N = 手续费资产, m = 是否 maker
+    fn parse_execution_fill_static(json: &Value) -> Option<Fill> {
+        if json.get("x").and_then(|x| x.as_str()) != Some("TRADE") {
+            return None;
+        }
+
+        let num = |key: &str| json.get(key).and_then(|v| v.as_str()).and_then(|s| s.parse::<f64>().ok());
+        let quantity = num("l")?;
+        if quantity <= 0.0 {
+            return None;
+        }
+
+        Some(Fill {
+            exchange_order_id: json.get("i")?.as_i64()?.to_string(),
+            exchange_trade_id: json.get("t").and_then(|t| t.as_i64()).map(|t| t.to_string()),
+            client_order_id: json.get("c").and_then(|c| c.as_str()).map(|s| s.to_string()),
+            symbol: json.get("s")?.as_str()?.to_string(),
+            side: match json.get("S")?.as_str()? {
+                "BUY" => OrderSide::Buy,
+                "SELL" => OrderSide::Sell,
+                _ => return None,
+            },
+            price: num("L")?,
+            quantity,
+            commission: num("n").unwrap_or(0.0),
+            commission_asset: json.get("N").and_then(|n| n.as_str()).map(|s| s.to_string()),
+            is_maker: json.get("m").and_then(|m| m.as_bool()).unwrap_or(false),
+            timestamp: json.get("T").and_then(|t| t.as_i64()).unwrap_or(0),
         })
     }
 }
@@ -567,6 +679,23 @@ impl Exchange for BinanceExchange {
         })
     }
 
+    async fn get_order_book(&self, symbol: &str, limit: usize) -> Result<OrderBook> {
+        // Binance only accepts these depth limits
+        let limit = [5, 10, 20, 50, 100, 500, 1000, 5000]
+            .into_iter()
+            .find(|l| *l >= limit)
+            .unwrap_or(5000);
+        let path = format!("/depth?symbol={}&limit={}", symbol.to_uppercase(), limit);
+        let json = self.get(&path).await?;
+
+        Ok(OrderBook {
+            symbol: symbol.to_uppercase(),
+            bids: super::parse_book_levels(&json["bids"]),
+            asks: super::parse_book_levels(&json["asks"]),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        })
+    }
+
     async fn get_klines(
         &self,
         symbol: &str,
@@ -603,6 +732,27 @@ impl Exchange for BinanceExchange {
         Ok(klines)
     }
 
+    async fn get_server_time(&self) -> Result<i64> {
+        let json = self.get("/api/v3/time").await?;
+
+        json["serverTime"]
+            .as_i64()
+            .ok_or_else(|| anyhow!("Invalid server time response"))
+    }
+
+    async fn get_instruments(&self) -> Result<Vec<Instrument>> {
+        let json = self.get("/api/v3/exchangeInfo?permissions=SPOT").await?;
+
+        let instruments = json["symbols"]
+            .as_array()
+            .ok_or_else(|| anyhow!("Invalid exchangeInfo response"))?
+            .iter()
+            .filter_map(Self::parse_instrument)
+            .collect();
+
+        Ok(instruments)
+    }
+
     async fn subscribe_ticker(&self, symbols: Vec<String>) -> Result<()> {
         if symbols.is_empty() {
             return Ok(());
@@ -657,6 +807,10 @@ impl Exchange for BinanceExchange {
         self.order_tx.subscribe()
     }
 
+    fn fill_stream(&self) -> broadcast::Receiver<Fill> {
+        self.fill_tx.subscribe()
+    }
+
     async fn subscribe_user_data(&self) -> Result<()> {
         // 如果还没有 listen key，创建一个
         let mut guard = self.listen_key.lock().await;
@@ -676,6 +830,7 @@ impl Exchange for BinanceExchange {
         let connection_state = self.connection_state.clone();
         let listen_key_ref = self.listen_key.clone();
         let order_tx = self.order_tx.clone();
+        let fill_tx = self.fill_tx.clone();
 
         let handle = tokio::spawn(async move {
             // 重新创建 WebSocket 连接需要的组件
@@ -735,6 +890,9 @@ impl Exchange for BinanceExchange {
                             if let Some(event_type) = json.get("e").and_then(|e| e.as_str()) {
                                 match event_type {
                                     "executionReport" => {
+                                        if let Some(fill) = Self::parse_execution_fill_static(&json) {
+                                            let _ = fill_tx.send(fill);
+                                        }
                                         if let Ok(order) = Self::parse_execution_report_static(&json) {
                                             let _ = order_tx.send(order);
                                         }
@@ -799,6 +957,9 @@ impl Exchange for BinanceExchange {
         if let Some(ref price) = price_str {
             params.push(("price", price.as_str()));
         }
+        if let Some(ref client_order_id) = request.client_order_id {
+            params.push(("newClientOrderId", client_order_id.as_str()));
+        }
 
         // MARKET 订单不需要 timeInForce
         if request.order_type != OrderType::Market {
@@ -822,35 +983,123 @@ impl Exchange for BinanceExchange {
         Ok(())
     }
 
-    async fn get_order(&self, order_id: &str) -> Result<Order> {
+    /// Spot has no batch order endpoint; orders go out concurrently and
+    /// the client rate limiter paces them
+    async fn place_orders(&self, requests: &[OrderRequest]) -> Result<Vec<Result<Order>>> {
+        Ok(join_all(requests.iter().map(|request| self.place_order(request))).await)
+    }
+
+    async fn cancel_orders(&self, orders: &[Order]) -> Result<Vec<Result<()>>> {
+        let client = &self.rest_client()?;
+        let cancels = orders.iter().map(|order| async move {
+            let exchange_order_id = order.exchange_order_id.as_deref()
+                .ok_or_else(|| anyhow!("Order {} has no exchange order id", order.id))?;
+            let symbol = order.symbol.to_uppercase();
+            let params = vec![("symbol", symbol.as_str()), ("orderId", exchange_order_id)];
+
+            client.delete_signed("/api/v3/order", &params).await
+                .map_err(|e| anyhow!("Cancel order failed: {}", e))?;
+            Ok(())
+        });
+
+        Ok(join_all(cancels).await)
+    }
+
+    /// 单次请求撤销某交易对的全部挂单
+    async fn cancel_all_orders(&self, symbol: &str) -> Result<usize> {
         let client = self.rest_client()?;
 
-        let params = vec![("orderId", order_id)];
+        let symbol = symbol.to_uppercase();
+        let params = vec![("symbol", symbol.as_str())];
+
+        let response = client.delete_signed("/api/v3/openOrders", &params).await
+            .map_err(|e| anyhow!("Cancel all orders failed: {}", e))?;
+
+        Ok(response.as_array().map(|orders| orders.len()).unwrap_or(0))
+    }
+
+    /// Binance has no price amend for spot; cancel-replace swaps the order
+    /// atomically and the replacement gets a new order id
+    async fn amend_order(&self, order: &Order, amend: &AmendRequest) -> Result<AmendOutcome> {
+        let client = self.rest_client()?;
+        let exchange_order_id = order.exchange_order_id.as_deref()
+            .ok_or_else(|| anyhow!("Order {} has no exchange order id", order.id))?;
+
+        let amended = amend.apply(order);
+        let price = amended.price.ok_or_else(|| anyhow!("Order {} has no limit price", order.id))?;
+
+        let symbol = order.symbol.to_uppercase();
+        let side = order.side.to_string().to_uppercase();
+        let order_type = order.order_type.to_string().to_uppercase();
+        // The replacement only carries what is still unfilled
+        let remaining = amended.quantity - order.filled_quantity;
+        let quantity = remaining.to_string();
+        let price = price.to_string();
+
+        let params = vec![
+            ("symbol", symbol.as_str()),
+            ("side", side.as_str()),
+            ("type", order_type.as_str()),
+            ("cancelReplaceMode", "STOP_ON_FAILURE"),
+            ("cancelOrderId", exchange_order_id),
+            ("quantity", quantity.as_str()),
+            ("price", price.as_str()),
+            ("timeInForce", "GTC"),
+        ];
+
+        let response = match client.post_signed("/api/v3/order/cancelReplace", &params).await {
+            Ok(response) => response,
+            // -2021: with STOP_ON_FAILURE, the cancel went through but the new order didn't
+            Err(e) if e.contains("-2021") => return Ok(AmendOutcome::ReplaceFailed(e)),
+            Err(e) => return Err(anyhow!("Cancel-replace failed: {}", e)),
+        };
+
+        let new_order = &response["newOrderResponse"];
+        let new_id = new_order["orderId"].as_i64().map(|id| id.to_string())
+            .or_else(|| new_order["orderId"].as_str().map(str::to_string))
+            .ok_or_else(|| anyhow!("Cancel-replace returned no new order: {}", response))?;
+
+        // Fills of the replacement arrive on the user data stream under the new id
+        Ok(AmendOutcome::Replaced(Order {
+            exchange_order_id: Some(new_id),
+            client_order_id: new_order["clientOrderId"].as_str().map(str::to_string),
+            quantity: remaining,
+            filled_quantity: 0.0,
+            avg_price: None,
+            status: OrderState::Open,
+            commission: 0.0,
+            commission_asset: None,
+            created_at: chrono::Utc::now().timestamp_millis(),
+            filled_at: None,
+            updated_at: None,
+            ..amended
+        }))
+    }
+
+    async fn get_order(&self, symbol: &str, order_id: &str) -> Result<Order> {
+        let client = self.rest_client()?;
+
+        let symbol = symbol.to_uppercase();
+        let params = vec![("symbol", symbol.as_str()), ("orderId", order_id)];
 
         let response = client.get_signed("/api/v3/order", &params).await
             .map_err(|e| anyhow!("Get order failed: {}", e))?;
 
-        let dummy_request = OrderRequest {
-            symbol: response["symbol"].as_str().unwrap_or("").to_string(),
-            side: match response["side"].as_str().unwrap_or("") {
-                "BUY" => OrderSide::Buy,
-                "SELL" => OrderSide::Sell,
-                _ => OrderSide::Buy,
-            },
-            order_type: match response["type"].as_str().unwrap_or("") {
-                "LIMIT" => OrderType::Limit,
-                "MARKET" => OrderType::Market,
-                "STOP_LOSS_LIMIT" => OrderType::StopLimit,
-                _ => OrderType::Limit,
-            },
-            price: response["price"].as_str().and_then(|s| s.parse().ok()),
-            stop_price: None,
-            quantity: response["origQty"].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-            client_order_id: None,
-            time_in_force: None,
-        };
+        self.parse_order(&response, &Self::request_from_json(&response))
+    }
+
+    async fn get_order_by_client_id(&self, symbol: &str, client_order_id: &str) -> Result<Option<Order>> {
+        let client = self.rest_client()?;
 
-        self.parse_order(&response, &dummy_request)
+        let symbol = symbol.to_uppercase();
+        let params = vec![("symbol", symbol.as_str()), ("origClientOrderId", client_order_id)];
+
+        match client.get_signed("/api/v3/order", &params).await {
+            Ok(response) => Ok(Some(self.parse_order(&response, &Self::request_from_json(&response))?)),
+            // -2013: Order does not exist
+            Err(e) if e.contains("-2013") => Ok(None),
+            Err(e) => Err(anyhow!("Get order failed: {}", e)),
+        }
     }
 
     async fn get_open_orders(&self, symbol: Option<&str>) -> Result<Vec<Order>> {
@@ -868,28 +1117,7 @@ impl Exchange for BinanceExchange {
         let orders = response.as_array()
             .ok_or_else(|| anyhow!("Invalid response format"))?
             .iter()
-            .map(|item| {
-                let dummy_request = OrderRequest {
-                    symbol: item["symbol"].as_str().unwrap_or("").to_string(),
-                    side: match item["side"].as_str().unwrap_or("") {
-                        "BUY" => OrderSide::Buy,
-                        "SELL" => OrderSide::Sell,
-                        _ => OrderSide::Buy,
-                    },
-                    order_type: match item["type"].as_str().unwrap_or("") {
-                        "LIMIT" => OrderType::Limit,
-                        "MARKET" => OrderType::Market,
-                        "STOP_LOSS_LIMIT" => OrderType::StopLimit,
-                        _ => OrderType::Limit,
-                    },
-                    price: item["price"].as_str().and_then(|s| s.parse().ok()),
-                    stop_price: None,
-                    quantity: item["origQty"].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-                    client_order_id: None,
-                    time_in_force: None,
-                };
-                self.parse_order(item, &dummy_request)
-            })
+            .map(|item| self.parse_order(item, &Self::request_from_json(item)))
             .collect::<Result<Vec<Order>>>()?;
 
         Ok(orders)
@@ -930,3 +1158,65 @@ impl Exchange for BinanceExchange {
         Ok(Vec::new())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_instrument() {
+        let json: Value = serde_json::json!({
+            "symbol": "BTCUSDT",
+            "status": "TRADING",
+            "baseAsset": "BTC",
+            "quoteAsset": "USDT",
+            "filters": [
+                {"filterType": "PRICE_FILTER", "minPrice": "0.01", "maxPrice": "1000000.00", "tickSize": "0.01"},
+                {"filterType": "LOT_SIZE", "minQty": "0.00001", "maxQty": "9000.00000", "stepSize": "0.00001"},
+                {"filterType": "NOTIONAL", "minNotional": "5.00000000"}
+            ]
+        });
+
+        let instrument = BinanceExchange::parse_instrument(&json).unwrap();
+        assert_eq!(instrument.symbol, "BTCUSDT");
+        assert_eq!(instrument.status, InstrumentStatus::Trading);
+        assert_eq!(instrument.tick_size, 0.01);
+        assert_eq!(instrument.step_size, 0.00001);
+        assert_eq!(instrument.max_qty, Some(9000.0));
+        assert_eq!(instrument.min_notional, 5.0);
+    }
+
+    #[test]
+    fn test_parse_execution_fill() {
+        let json: Value = serde_json::json!({
+            "e": "executionReport", "s": "BTCUSDT", "c": "my-order-1", "S": "BUY",
+            "o": "LIMIT", "q": "1.00000000", "p": "50000.00", "x": "TRADE", "X": "PARTIALLY_FILLED",
+            "i": 4293153, "l": "0.25000000", "z": "0.25000000", "L": "49999.50",
+            "n": "0.00025000", "N": "BTC", "T": 1499405658657_i64, "t": 98765, "m": true
+        });
+
+        let fill = BinanceExchange::parse_execution_fill_static(&json).unwrap();
+        assert_eq!(fill.exchange_order_id, "4293153");
+        assert_eq!(fill.exchange_trade_id.as_deref(), Some("98765"));
+        assert_eq!(fill.side, OrderSide::Buy);
+        assert_eq!(fill.price, 49999.5);
+        assert_eq!(fill.quantity, 0.25);
+        assert_eq!(fill.commission, 0.00025);
+        assert_eq!(fill.commission_asset.as_deref(), Some("BTC"));
+        assert!(fill.is_maker);
+    }
+
+    #[test]
+    fn test_parse_execution_fill_ignores_non_trade() {
+        let json: Value = serde_json::json!({
+            "e": "executionReport", "s": "BTCUSDT", "S": "BUY", "x": "NEW", "i": 1, "l": "0.00000000"
+        });
+        assert!(BinanceExchange::parse_execution_fill_static(&json).is_none());
+    }
+
+    #[test]
+    fn test_parse_instrument_requires_filters() {
+        let json: Value = serde_json::json!({"symbol": "BTCUSDT", "status": "BREAK", "filters": []});
+        assert!(BinanceExchange::parse_instrument(&json).is_none());
+    }
+}
@@ -10,6 +10,9 @@
 
 use super::super::types::*;
 use super::r#trait::{Exchange, ExchangeName};
+use super::rate_limit::RateLimiter;
+use super::{http_client, BatchFailure};
+use super::time_sync::ClockSync;
 use async_trait::async_trait;
 use anyhow::{anyhow, Result};
 use reqwest::Client;
@@ -22,13 +25,12 @@ use futures_util::{StreamExt, SinkExt};
 const REST_API_BASE: &str = "https://api.bybit.com";
 const WS_API_PUBLIC: &str = "wss://stream.bybit.com/v5/public/spot";
 
-/// Private WebSocket API endpoint
-///
-/// # Note
-/// Reserved for future private WebSocket streaming implementation.
-#[allow(dead_code)]
+/// Private WebSocket API endpoint (order / execution topics)
 const WS_API_PRIVATE: &str = "wss://stream.bybit.com/v5/private";
 
+/// Most spot orders `create-batch` and `cancel-batch` accept per request
+const BATCH_LIMIT: usize = 10;
+
 /// Bybit-specific error codes
 #[derive(Debug)]
 pub enum BybitError {
@@ -61,6 +63,7 @@ struct BybitClient {
     api_secret: String,
     client: Client,
     is_testnet: bool,
+    rate_limiter: Arc<RateLimiter>,
 }
 
 impl BybitClient {
@@ -68,8 +71,18 @@ impl BybitClient {
         Self {
             api_key,
             api_secret,
-            client: Client::new(),
+            client: http_client(),
             is_testnet,
+            rate_limiter: RateLimiter::for_exchange(ExchangeName::Bybit),
+        }
+    }
+
+    /// Wait for rate-limit budget; order endpoints also count against the order limit
+    async fn throttle(&self, method: &str, path: &str) -> Result<()> {
+        if method == "POST" && path.starts_with("/v5/order/") {
+            self.rate_limiter.acquire_order(1).await
+        } else {
+            self.rate_limiter.acquire(1).await
         }
     }
 
@@ -97,12 +110,10 @@ impl BybitClient {
     }
 
     /// Make authenticated GET request
-    ///
-    /// # Note
-    /// Reserved for future authenticated API requests.
-    #[allow(dead_code)]
     async fn get_signed(&self, path: &str, params: &str) -> Result<Value> {
-        let timestamp = chrono::Utc::now().timestamp_millis().to_string();
+        self.throttle("GET", path).await?;
+
+        let timestamp = ClockSync::for_exchange(ExchangeName::Bybit).now_ms().to_string();
         let sign = self.sign_request(&timestamp, params);
 
         let url = if params.is_empty() {
@@ -119,6 +130,7 @@ impl BybitClient {
             .header("X-BAPI-RECV-WINDOW", "5000")
             .send()
             .await?;
+        self.rate_limiter.record_response(response.status(), response.headers());
 
         let json: Value = response.json().await?;
 
@@ -132,7 +144,9 @@ impl BybitClient {
 
     /// Make authenticated POST request
     async fn post_signed(&self, path: &str, body: &str) -> Result<Value> {
-        let timestamp = chrono::Utc::now().timestamp_millis().to_string();
+        self.throttle("POST", path).await?;
+
+        let timestamp = ClockSync::for_exchange(ExchangeName::Bybit).now_ms().to_string();
         let sign = self.sign_request(&timestamp, body);
 
         let response = self.client
@@ -145,6 +159,7 @@ impl BybitClient {
             .body(body.to_string())
             .send()
             .await?;
+        self.rate_limiter.record_response(response.status(), response.headers());
 
         let json: Value = response.json().await?;
 
@@ -165,6 +180,7 @@ pub struct BybitExchange {
     ticker_tx: broadcast::Sender<Ticker>,
     kline_tx: broadcast::Sender<Kline>,
     order_tx: broadcast::Sender<Order>,
+    fill_tx: broadcast::Sender<Fill>,
     connection_state: Arc<RwLock<bool>>,
     ws_task_handles: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>,
 }
@@ -178,15 +194,17 @@ impl BybitExchange {
         let (ticker_tx, _) = broadcast::channel(1000);
         let (kline_tx, _) = broadcast::channel(1000);
         let (order_tx, _) = broadcast::channel(1000);
+        let (fill_tx, _) = broadcast::channel(1000);
 
         Self {
             api_key,
             api_secret,
             is_testnet: false,
-            client: Client::new(),
+            client: http_client(),
             ticker_tx,
             kline_tx,
             order_tx,
+            fill_tx,
             connection_state: Arc::new(RwLock::new(false)),
             ws_task_handles: Arc::new(Mutex::new(Vec::new())),
         }
@@ -208,6 +226,19 @@ impl BybitExchange {
         Ok(BybitClient::new(api_key, api_secret, self.is_testnet))
     }
 
+    /// Unauthenticated GET through the shared rate limiter
+    async fn public_get(&self, path: &str) -> Result<Value> {
+        let limiter = RateLimiter::for_exchange(ExchangeName::Bybit);
+        limiter.acquire(1).await?;
+
+        let response = self.client.get(format!("{}{}", REST_API_BASE, path))
+            .send()
+            .await?;
+        limiter.record_response(response.status(), response.headers());
+
+        Ok(response.json::<Value>().await?)
+    }
+
     /// Bybit uses same format as Binance (BTCUSDT)
     fn normalize_symbol(&self, symbol: &str) -> String {
         symbol.to_uppercase()
@@ -279,10 +310,73 @@ impl BybitExchange {
         }
     }
 
+    /// Request body of one spot order, without `category`
+    fn order_body(&self, request: &OrderRequest) -> serde_json::Map<String, Value> {
+        let mut body_map = serde_json::Map::new();
+        body_map.insert("symbol".to_string(), serde_json::json!(self.normalize_symbol(&request.symbol)));
+        body_map.insert("side".to_string(), serde_json::json!(Self::side_to_bybit(request.side)));
+        body_map.insert("orderType".to_string(), serde_json::json!(Self::order_type_to_bybit(request.order_type)));
+        body_map.insert("qty".to_string(), serde_json::json!(request.quantity.to_string()));
+        body_map.insert("timeInForce".to_string(), serde_json::json!("GTC"));
+
+        if let Some(price) = request.price {
+            body_map.insert("price".to_string(), serde_json::json!(price.to_string()));
+        }
+        if let Some(client_order_id) = &request.client_order_id {
+            body_map.insert("orderLinkId".to_string(), serde_json::json!(client_order_id));
+        }
+
+        body_map
+    }
+
+    /// Rebuild the request an order was placed with from its order details
+    fn request_from_result(data: &Value) -> OrderRequest {
+        let num = |key: &str| data[key].as_str().and_then(|s| s.parse::<f64>().ok());
+
+        OrderRequest {
+            symbol: data["symbol"].as_str().unwrap_or("").to_string(),
+            side: match data["side"].as_str().unwrap_or("") {
+                "Sell" => OrderSide::Sell,
+                _ => OrderSide::Buy,
+            },
+            order_type: match data["orderType"].as_str().unwrap_or("") {
+                "Market" => OrderType::Market,
+                _ => OrderType::Limit,
+            },
+            price: num("price").filter(|price| *price > 0.0),
+            stop_price: None,
+            quantity: num("qty").unwrap_or(0.0),
+            client_order_id: data["orderLinkId"].as_str().filter(|s| !s.is_empty()).map(str::to_string),
+            time_in_force: None,
+        }
+    }
+
+    /// Per-order results of a batch response, in request order
+    ///
+    /// Results are in `result.list` and per-order codes in
+    /// `retExtInfo.list`; `retCode` is 0 even when some orders failed.
+    fn parse_batch_acks(json: &Value, count: usize) -> Vec<Result<String>> {
+        let results = json["result"]["list"].as_array().cloned().unwrap_or_default();
+        let codes = json["retExtInfo"]["list"].as_array().cloned().unwrap_or_default();
+
+        (0..count)
+            .map(|i| match (results.get(i), codes.get(i)) {
+                (Some(item), Some(code)) if code["code"].as_i64() == Some(0) => {
+                    Ok(item["orderId"].as_str().unwrap_or("").to_string())
+                }
+                (_, Some(code)) => Err(anyhow!("Bybit order rejected: {} {}", code["code"], code["msg"])),
+                _ => Err(anyhow!("Bybit batch response has no result for order {}", i)),
+            })
+            .collect()
+    }
+
     /// Parse Bybit order response
     fn parse_order(&self, json: &Value, request: &OrderRequest) -> Result<Order> {
-        let data = &json["result"];
+        self.order_from_result(&json["result"], request)
+    }
 
+    /// Build an order from an order result object
+    fn order_from_result(&self, data: &Value, request: &OrderRequest) -> Result<Order> {
         Ok(Order {
             id: data["orderId"].as_str().unwrap_or("").to_string(),
             exchange_order_id: Some(data["orderId"].as_str().unwrap_or("").to_string()),
@@ -297,7 +391,9 @@ impl BybitExchange {
             status: self.parse_order_state(data["orderStatus"].as_str().unwrap_or("Created")),
             commission: 0.0,
             created_at: chrono::Utc::now().timestamp_millis(),
+            commission_asset: None,
             filled_at: None,
+            updated_at: data["updatedTime"].as_str().and_then(|s| s.parse().ok()),
         })
     }
 
@@ -312,16 +408,36 @@ impl BybitExchange {
         }
     }
 
+    /// Parse a single entry of /v5/market/instruments-info
+    fn parse_instrument(data: &Value) -> Option<Instrument> {
+        let num = |v: &Value| v.as_str().and_then(|s| s.parse::<f64>().ok());
+        let lot = &data["lotSizeFilter"];
+
+        let status = match data["status"].as_str().unwrap_or("") {
+            "Trading" => InstrumentStatus::Trading,
+            "Closed" => InstrumentStatus::Closed,
+            _ => InstrumentStatus::Halted,
+        };
+
+        Some(Instrument {
+            symbol: data["symbol"].as_str()?.to_string(),
+            base_asset: data["baseCoin"].as_str().unwrap_or("").to_string(),
+            quote_asset: data["quoteCoin"].as_str().unwrap_or("").to_string(),
+            status,
+            tick_size: num(&data["priceFilter"]["tickSize"])?,
+            step_size: num(&lot["basePrecision"])?,
+            min_qty: num(&lot["minOrderQty"]).unwrap_or(0.0),
+            max_qty: num(&lot["maxOrderQty"]),
+            min_notional: num(&lot["minOrderAmt"]).unwrap_or(0.0),
+        })
+    }
+
     /// Static helper to normalize symbol
     fn normalize_symbol_static(symbol: &str) -> String {
         symbol.to_uppercase()
     }
 
     /// Static helper to parse order state
-    ///
-    /// # Note
-    /// Reserved for future order processing.
-    #[allow(dead_code)]
     fn parse_order_state_static(state: &str) -> OrderState {
         match state {
             "New" | "PartiallyFilled" => OrderState::Open,
@@ -391,10 +507,6 @@ impl BybitExchange {
     }
 
     /// Parse WebSocket order message
-    ///
-    /// # Note
-    /// Reserved for future WebSocket order streaming.
-    #[allow(dead_code)]
     fn parse_ws_order_from_value(data: &Value) -> Result<Order> {
         let symbol = data["symbol"].as_str().unwrap_or("");
         let symbol = Self::normalize_symbol_static(symbol);
@@ -421,10 +533,144 @@ impl BybitExchange {
             status: Self::parse_order_state_static(data["orderStatus"].as_str().unwrap_or("")),
             commission: 0.0,
             created_at: data["createdTime"].as_i64().unwrap_or(0),
+            commission_asset: None,
             filled_at: None,
+            updated_at: data["updatedTime"].as_str().and_then(|s| s.parse().ok()),
+        })
+    }
+
+    /// Parse a single entry of the `execution` topic
+    ///
+    /// Only `execType == "Trade"` entries are fills; funding/settlement
+    /// entries are skipped.
+    fn parse_ws_fill_static(data: &Value) -> Option<Fill> {
+        if data["execType"].as_str() != Some("Trade") {
+            return None;
+        }
+
+        let num = |key: &str| data[key].as_str().and_then(|s| s.parse::<f64>().ok());
+        let quantity = num("execQty")?;
+        if quantity <= 0.0 {
+            return None;
+        }
+
+        Some(Fill {
+            exchange_order_id: data["orderId"].as_str()?.to_string(),
+            exchange_trade_id: data["execId"].as_str().map(|s| s.to_string()),
+            client_order_id: data["orderLinkId"].as_str().filter(|s| !s.is_empty()).map(|s| s.to_string()),
+            symbol: Self::normalize_symbol_static(data["symbol"].as_str()?),
+            side: match data["side"].as_str()? {
+                "Buy" => OrderSide::Buy,
+                "Sell" => OrderSide::Sell,
+                _ => return None,
+            },
+            price: num("execPrice")?,
+            quantity,
+            commission: num("execFee").unwrap_or(0.0),
+            commission_asset: data["feeCurrency"].as_str().filter(|s| !s.is_empty()).map(|s| s.to_string()),
+            is_maker: data["isMaker"].as_bool().unwrap_or(false),
+            timestamp: data["execTime"].as_str().and_then(|s| s.parse().ok()).unwrap_or(0),
         })
     }
 
+    /// Private WebSocket auth signature: HMAC_SHA256(secret, "GET/realtime" + expires)
+    fn ws_auth_signature(api_secret: &str, expires: i64) -> String {
+        use hmac::Mac;
+        use sha2::Sha256;
+
+        let mut mac = hmac::Hmac::<Sha256>::new_from_slice(api_secret.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(format!("GET/realtime{}", expires).as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// Private WebSocket loop for order and execution updates
+    async fn ws_private_loop_impl(
+        order_tx: &broadcast::Sender<Order>,
+        fill_tx: &broadcast::Sender<Fill>,
+        api_key: &str,
+        api_secret: &str,
+    ) -> Result<()> {
+        log::info!("Connecting to Bybit private WebSocket");
+
+        let (ws_stream, _) = tokio_tungstenite::connect_async(WS_API_PRIVATE).await?;
+        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+
+        let expires = ClockSync::for_exchange(ExchangeName::Bybit).now_ms() + 10_000;
+        let auth_msg = serde_json::json!({
+            "op": "auth",
+            "args": [api_key, expires, Self::ws_auth_signature(api_secret, expires)]
+        });
+        ws_sender.send(Message::Text(auth_msg.to_string())).await?;
+
+        if let Some(Ok(Message::Text(text))) = ws_receiver.next().await {
+            let json: Value = serde_json::from_str(&text)?;
+            if json["op"] != "auth" || json["success"] != true {
+                return Err(anyhow!("Bybit WebSocket auth failed: {}", json));
+            }
+            log::info!("Bybit WebSocket auth successful");
+        }
+
+        let sub_msg = serde_json::json!({
+            "op": "subscribe",
+            "args": ["order", "execution"]
+        });
+        ws_sender.send(Message::Text(sub_msg.to_string())).await?;
+
+        // Bybit drops private connections without a ping every ~20s
+        let mut heartbeat = tokio::time::interval(tokio::time::Duration::from_secs(20));
+
+        loop {
+            tokio::select! {
+                _ = heartbeat.tick() => {
+                    let ping = serde_json::json!({"op": "ping"});
+                    ws_sender.send(Message::Text(ping.to_string())).await?;
+                }
+                msg = ws_receiver.next() => {
+                    let Some(msg) = msg else { break };
+                    match msg {
+                        Ok(Message::Text(text)) => {
+                            let Ok(json) = serde_json::from_str::<Value>(&text) else { continue };
+                            let Some(data) = json.get("data").and_then(|d| d.as_array()) else { continue };
+
+                            match json.get("topic").and_then(|t| t.as_str()) {
+                                Some("order") => {
+                                    for item in data {
+                                        if let Ok(order) = Self::parse_ws_order_from_value(item) {
+                                            let _ = order_tx.send(order);
+                                        }
+                                    }
+                                }
+                                Some("execution") => {
+                                    for item in data {
+                                        if let Some(fill) = Self::parse_ws_fill_static(item) {
+                                            let _ = fill_tx.send(fill);
+                                        }
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                        Ok(Message::Ping(data)) => {
+                            let _ = ws_sender.send(Message::Pong(data)).await;
+                        }
+                        Ok(Message::Close(_)) => {
+                            log::info!("Bybit private WebSocket closed");
+                            break;
+                        }
+                        Err(e) => {
+                            log::error!("Bybit private WebSocket error: {}", e);
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Start public WebSocket for tickers and klines
     ///
     /// # Note
@@ -565,11 +811,7 @@ impl Exchange for BybitExchange {
         let bybit_symbol = self.normalize_symbol(symbol);
         let path = format!("/v5/market/tickers?category=spot&symbol={}", bybit_symbol);
 
-        let response = self.client.get(format!("{}{}", REST_API_BASE, path))
-            .send()
-            .await?
-            .json::<Value>()
-            .await?;
+        let response = self.public_get(&path).await?;
 
         if response["retCode"] != 0 {
             return Err(anyhow!("Bybit ticker error: {}", response["retMsg"]));
@@ -578,6 +820,29 @@ impl Exchange for BybitExchange {
         self.parse_ticker(&response, symbol)
     }
 
+    async fn get_order_book(&self, symbol: &str, limit: usize) -> Result<OrderBook> {
+        let bybit_symbol = self.normalize_symbol(symbol);
+        let path = format!(
+            "/v5/market/orderbook?category=spot&symbol={}&limit={}",
+            bybit_symbol,
+            limit.clamp(1, 200)
+        );
+
+        let response = self.public_get(&path).await?;
+
+        if response["retCode"] != 0 {
+            return Err(anyhow!("Bybit order book error: {}", response["retMsg"]));
+        }
+
+        let data = &response["result"];
+        Ok(OrderBook {
+            symbol: bybit_symbol,
+            bids: super::parse_book_levels(&data["b"]),
+            asks: super::parse_book_levels(&data["a"]),
+            timestamp: data["ts"].as_i64().unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
+        })
+    }
+
     async fn get_klines(
         &self,
         symbol: &str,
@@ -592,11 +857,7 @@ impl Exchange for BybitExchange {
             bybit_symbol, bybit_interval, limit
         );
 
-        let response = self.client.get(format!("{}{}", REST_API_BASE, path))
-            .send()
-            .await?
-            .json::<Value>()
-            .await?;
+        let response = self.public_get(&path).await?;
 
         if response["retCode"] != 0 {
             return Err(anyhow!("Bybit klines error: {}", response["retMsg"]));
@@ -610,6 +871,40 @@ impl Exchange for BybitExchange {
             .collect()
     }
 
+    async fn get_server_time(&self) -> Result<i64> {
+        let response = self.public_get("/v5/market/time").await?;
+
+        if response["retCode"] != 0 {
+            return Err(anyhow!("Bybit server time error: {}", response["retMsg"]));
+        }
+
+        // Top-level `time` is in ms; `result.timeNano` is the precise fallback
+        response["time"]
+            .as_i64()
+            .or_else(|| {
+                response["result"]["timeNano"]
+                    .as_str()
+                    .and_then(|ns| ns.parse::<i64>().ok())
+                    .map(|ns| ns / 1_000_000)
+            })
+            .ok_or_else(|| anyhow!("Invalid server time response"))
+    }
+
+    async fn get_instruments(&self) -> Result<Vec<Instrument>> {
+        let path = "/v5/market/instruments-info?category=spot";
+
+        let response = self.public_get(path).await?;
+
+        if response["retCode"] != 0 {
+            return Err(anyhow!("Bybit instruments error: {}", response["retMsg"]));
+        }
+
+        let data = response["result"]["list"].as_array()
+            .ok_or_else(|| anyhow!("Invalid instruments response"))?;
+
+        Ok(data.iter().filter_map(Self::parse_instrument).collect())
+    }
+
     async fn subscribe_ticker(&self, symbols: Vec<String>) -> Result<()> {
         if symbols.is_empty() {
             return Ok(());
@@ -726,31 +1021,60 @@ impl Exchange for BybitExchange {
         self.order_tx.subscribe()
     }
 
+    fn fill_stream(&self) -> broadcast::Receiver<Fill> {
+        self.fill_tx.subscribe()
+    }
+
     async fn subscribe_user_data(&self) -> Result<()> {
-        // TODO: Implement Bybit private WebSocket for order updates
-        log::warn!("Bybit user data stream not yet implemented");
+        log::info!("Subscribing to Bybit user data stream");
+
+        let (api_key, api_secret) = match (self.api_key.clone(), self.api_secret.clone()) {
+            (Some(key), Some(secret)) => (key, secret),
+            _ => return Err(anyhow!("API credentials not configured for Bybit user data stream")),
+        };
+
+        let tx_order = self.order_tx.clone();
+        let tx_fill = self.fill_tx.clone();
+        let connection_state = self.connection_state.clone();
+
+        let handle = tokio::spawn(async move {
+            log::info!("Bybit user data WebSocket task started");
+
+            let mut retry_count = 0;
+            const MAX_RETRIES: u32 = 5;
+
+            while *connection_state.read().await {
+                if retry_count >= MAX_RETRIES {
+                    log::error!("Bybit user data WebSocket max retries reached");
+                    break;
+                }
+
+                match Self::ws_private_loop_impl(&tx_order, &tx_fill, &api_key, &api_secret).await {
+                    Ok(_) => {
+                        retry_count = 0;
+                    }
+                    Err(e) => {
+                        retry_count += 1;
+                        log::error!("Bybit user data WebSocket error (retry {}): {}", retry_count, e);
+                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                    }
+                }
+            }
+
+            log::info!("Bybit user data WebSocket task stopped");
+        });
+
+        let mut handles = self.ws_task_handles.lock().await;
+        handles.push(handle);
+
         Ok(())
     }
 
     async fn place_order(&self, request: &OrderRequest) -> Result<Order> {
         let client = self.rest_client()?;
 
-        let bybit_symbol = self.normalize_symbol(&request.symbol);
-
-        // Build order request body
-        let body = serde_json::json!({
-            "category": "spot",
-            "symbol": bybit_symbol,
-            "side": Self::side_to_bybit(request.side),
-            "orderType": Self::order_type_to_bybit(request.order_type),
-            "qty": request.quantity.to_string(),
-            "timeInForce": "GTC"
-        });
-
-        let mut body_map = body.as_object().unwrap().clone();
-        if let Some(price) = request.price {
-            body_map.insert("price".to_string(), serde_json::json!(price.to_string()));
-        }
+        let mut body_map = self.order_body(request);
+        body_map.insert("category".to_string(), serde_json::json!("spot"));
 
         let body_str = serde_json::to_string(&body_map)?;
         let response = client.post_signed("/v5/order/create", &body_str).await?;
@@ -772,37 +1096,187 @@ impl Exchange for BybitExchange {
         Ok(())
     }
 
-    async fn get_order(&self, order_id: &str) -> Result<Order> {
+    async fn place_orders(&self, requests: &[OrderRequest]) -> Result<Vec<Result<Order>>> {
+        let client = self.rest_client()?;
+
+        let mut results = Vec::with_capacity(requests.len());
+        for chunk in requests.chunks(BATCH_LIMIT) {
+            let body = serde_json::json!({
+                "category": "spot",
+                "request": chunk.iter().map(|request| self.order_body(request)).collect::<Vec<_>>(),
+            });
+
+            // A failed chunk fails its own orders; earlier chunks are already live
+            match client.post_signed("/v5/order/create-batch", &serde_json::to_string(&body)?).await {
+                Ok(response) => {
+                    let acks = Self::parse_batch_acks(&response, chunk.len());
+                    for (i, (ack, request)) in acks.into_iter().zip(chunk).enumerate() {
+                        results.push(ack.and_then(|_| self.order_from_result(&response["result"]["list"][i], request)));
+                    }
+                }
+                Err(e) => {
+                    let failure = BatchFailure::new("Batch order failed", e);
+                    results.extend(chunk.iter().map(|_| Err(failure.error())));
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    async fn cancel_orders(&self, orders: &[Order]) -> Result<Vec<Result<()>>> {
+        let client = self.rest_client()?;
+
+        let mut results = Vec::with_capacity(orders.len());
+        for chunk in orders.chunks(BATCH_LIMIT) {
+            let body = serde_json::json!({
+                "category": "spot",
+                "request": chunk
+                    .iter()
+                    .map(|order| serde_json::json!({
+                        "symbol": self.normalize_symbol(&order.symbol),
+                        "orderId": order.exchange_order_id,
+                    }))
+                    .collect::<Vec<_>>(),
+            });
+
+            match client.post_signed("/v5/order/cancel-batch", &serde_json::to_string(&body)?).await {
+                Ok(response) => {
+                    results.extend(Self::parse_batch_acks(&response, chunk.len()).into_iter().map(|ack| ack.map(|_| ())))
+                }
+                Err(e) => {
+                    let failure = BatchFailure::new("Batch cancel failed", e);
+                    results.extend(chunk.iter().map(|_| Err(failure.error())));
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    async fn cancel_all_orders(&self, symbol: &str) -> Result<usize> {
         let client = self.rest_client()?;
 
         let body = serde_json::json!({
             "category": "spot",
-            "orderId": order_id,
+            "symbol": self.normalize_symbol(symbol),
         });
 
         let body_str = serde_json::to_string(&body)?;
-        let _response = client.post_signed("/v5/order/query", &body_str).await?;
+        let response = client.post_signed("/v5/order/cancel-all", &body_str).await?;
 
-        // Parse response - need proper OrderRequest context
-        todo!("Bybit get_order parsing - need context for symbol/side/price")
+        Ok(response["result"]["list"].as_array().map(|list| list.len()).unwrap_or(0))
     }
 
-    async fn get_open_orders(&self, symbol: Option<&str>) -> Result<Vec<Order>> {
+    /// Bybit's disconnect-cancel-all protection cancels the orders once the
+    /// private stream stays disconnected for the window (3-300 s). It can
+    /// only be switched off in the account settings, so `0` leaves it as is.
+    async fn cancel_all_after(&self, timeout_secs: u64) -> Result<bool> {
+        if timeout_secs == 0 {
+            return Ok(false);
+        }
         let client = self.rest_client()?;
 
-        let mut body = serde_json::json!({
-            "category": "spot",
+        let body = serde_json::json!({
+            "product": "SPOT",
+            "timeWindow": timeout_secs.clamp(3, 300),
         });
 
+        let body_str = serde_json::to_string(&body)?;
+        client.post_signed("/v5/order/disconnected-cancel-all", &body_str).await?;
+        Ok(true)
+    }
+
+    async fn amend_order(&self, order: &Order, amend: &AmendRequest) -> Result<AmendOutcome> {
+        let client = self.rest_client()?;
+        let exchange_order_id = order.exchange_order_id.as_deref()
+            .ok_or_else(|| anyhow!("Order {} has no exchange order id", order.id))?;
+
+        let mut body_map = serde_json::Map::new();
+        body_map.insert("category".to_string(), serde_json::json!("spot"));
+        body_map.insert("symbol".to_string(), serde_json::json!(self.normalize_symbol(&order.symbol)));
+        body_map.insert("orderId".to_string(), serde_json::json!(exchange_order_id));
+        if let Some(quantity) = amend.quantity {
+            body_map.insert("qty".to_string(), serde_json::json!(quantity.to_string()));
+        }
+        if let Some(price) = amend.price {
+            body_map.insert("price".to_string(), serde_json::json!(price.to_string()));
+        }
+
+        let body_str = serde_json::to_string(&body_map)?;
+        client.post_signed("/v5/order/amend", &body_str).await?;
+
+        Ok(AmendOutcome::Amended(amend.apply(order)))
+    }
+
+    async fn get_order(&self, symbol: &str, order_id: &str) -> Result<Order> {
+        let client = self.rest_client()?;
+
+        let params = format!(
+            "category=spot&symbol={}&orderId={}",
+            self.normalize_symbol(symbol),
+            order_id
+        );
+
+        // Live orders are in realtime, closed ones only in history
+        for path in ["/v5/order/realtime", "/v5/order/history"] {
+            let response = client.get_signed(path, &params).await?;
+            if let Some(data) = response["result"]["list"].as_array().and_then(|list| list.first()) {
+                return self.order_from_result(data, &Self::request_from_result(data));
+            }
+        }
+
+        Err(anyhow!("Bybit order {} not found", order_id))
+    }
+
+    async fn get_order_by_client_id(&self, symbol: &str, client_order_id: &str) -> Result<Option<Order>> {
+        let client = self.rest_client()?;
+
+        let params = format!(
+            "category=spot&symbol={}&orderLinkId={}",
+            self.normalize_symbol(symbol),
+            client_order_id
+        );
+
+        // Live orders are in realtime, closed ones only in history
+        for path in ["/v5/order/realtime", "/v5/order/history"] {
+            let response = client.get_signed(path, &params).await?;
+            if let Some(data) = response["result"]["list"].as_array().and_then(|list| list.first()) {
+                return Ok(Some(self.order_from_result(data, &Self::request_from_result(data))?));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn get_open_orders(&self, symbol: Option<&str>) -> Result<Vec<Order>> {
+        let client = self.rest_client()?;
+
+        let mut query = "category=spot&openOnly=0&limit=50".to_string();
         if let Some(sym) = symbol {
-            body["symbol"] = serde_json::json!(self.normalize_symbol(sym));
+            query.push_str(&format!("&symbol={}", self.normalize_symbol(sym)));
         }
 
-        let body_str = serde_json::to_string(&body)?;
-        let _response = client.post_signed("/v5/order/open", &body_str).await?;
+        let mut orders = Vec::new();
+        let mut cursor = String::new();
+        loop {
+            let params = if cursor.is_empty() {
+                query.clone()
+            } else {
+                format!("{}&cursor={}", query, cursor)
+            };
+            let response = client.get_signed("/v5/order/realtime", &params).await?;
+            let page = response["result"]["list"].as_array()
+                .ok_or_else(|| anyhow!("Invalid open orders response"))?;
+
+            for data in page {
+                orders.push(self.order_from_result(data, &Self::request_from_result(data))?);
+            }
+            cursor = response["result"]["nextPageCursor"].as_str().unwrap_or("").to_string();
+            if page.is_empty() || cursor.is_empty() {
+                break;
+            }
+        }
 
-        // Parse response
-        todo!("Bybit get_open_orders parsing")
+        Ok(orders)
     }
 
     async fn get_balance(&self) -> Result<Vec<Balance>> {
@@ -889,4 +1363,95 @@ mod tests {
         assert_eq!(exchange.parse_order_state("Filled"), OrderState::Filled);
         assert_eq!(exchange.parse_order_state("Cancelled"), OrderState::Canceled);
     }
+
+    #[test]
+    fn test_parse_batch_acks() {
+        let response = serde_json::json!({
+            "retCode": 0,
+            "retMsg": "OK",
+            "result": {"list": [
+                {"category": "spot", "symbol": "BTCUSDT", "orderId": "1666800494330512128", "orderLinkId": ""},
+                {"category": "spot", "symbol": "ETHUSDT", "orderId": "", "orderLinkId": ""}
+            ]},
+            "retExtInfo": {"list": [
+                {"code": 0, "msg": "OK"},
+                {"code": 170131, "msg": "Insufficient balance."}
+            ]}
+        });
+
+        let acks = BybitExchange::parse_batch_acks(&response, 3);
+        assert_eq!(acks[0].as_ref().unwrap(), "1666800494330512128");
+        assert!(acks[1].as_ref().unwrap_err().to_string().contains("Insufficient balance"));
+        assert!(acks[2].is_err());
+    }
+
+    #[test]
+    fn test_parse_instrument() {
+        let data = serde_json::json!({
+            "symbol": "BTCUSDT",
+            "baseCoin": "BTC",
+            "quoteCoin": "USDT",
+            "status": "Trading",
+            "lotSizeFilter": {
+                "basePrecision": "0.000001",
+                "quotePrecision": "0.00000001",
+                "minOrderQty": "0.000048",
+                "maxOrderQty": "71.73956243",
+                "minOrderAmt": "1",
+                "maxOrderAmt": "2000000"
+            },
+            "priceFilter": {"tickSize": "0.01"}
+        });
+
+        let instrument = BybitExchange::parse_instrument(&data).unwrap();
+        assert_eq!(instrument.symbol, "BTCUSDT");
+        assert_eq!(instrument.status, InstrumentStatus::Trading);
+        assert_eq!(instrument.tick_size, 0.01);
+        assert_eq!(instrument.step_size, 0.000001);
+        assert_eq!(instrument.min_notional, 1.0);
+    }
+
+    #[test]
+    fn test_parse_ws_fill() {
+        let data = serde_json::json!({
+            "category": "spot",
+            "symbol": "BTCUSDT",
+            "execFee": "0.0000150",
+            "feeCurrency": "BTC",
+            "execId": "2100000000007764263",
+            "execPrice": "30000.5",
+            "execQty": "0.015",
+            "execType": "Trade",
+            "execTime": "1672364174443",
+            "isMaker": false,
+            "orderId": "1321052653536515584",
+            "orderLinkId": "",
+            "side": "Buy"
+        });
+
+        let fill = BybitExchange::parse_ws_fill_static(&data).unwrap();
+        assert_eq!(fill.exchange_order_id, "1321052653536515584");
+        assert_eq!(fill.exchange_trade_id.as_deref(), Some("2100000000007764263"));
+        assert!(fill.client_order_id.is_none());
+        assert_eq!(fill.price, 30000.5);
+        assert_eq!(fill.quantity, 0.015);
+        assert_eq!(fill.commission_asset.as_deref(), Some("BTC"));
+        assert_eq!(fill.timestamp, 1672364174443);
+    }
+
+    #[test]
+    fn test_parse_ws_fill_skips_funding() {
+        let data = serde_json::json!({
+            "symbol": "BTCUSDT", "execType": "Funding", "execQty": "1", "execPrice": "1",
+            "orderId": "1", "side": "Buy"
+        });
+        assert!(BybitExchange::parse_ws_fill_static(&data).is_none());
+    }
+
+    #[test]
+    fn test_ws_auth_signature_is_hex_sha256() {
+        let signature = BybitExchange::ws_auth_signature("secret", 1_700_000_000_000);
+        assert_eq!(signature.len(), 64);
+        assert!(signature.chars().all(|c| c.is_ascii_hexdigit()));
+    }
 }
@@ -1,14 +1,45 @@
-use reqwest::Client;
+use reqwest::{Client, Method};
 use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 use serde_json::Value;
+use std::sync::Arc;
+use super::rate_limit::RateLimiter;
+use super::http_client;
+use super::r#trait::ExchangeName;
 use super::signature::BinanceSignature;
 
+/// Binance 请求权重 (参考 https://binance-docs.github.io/apidocs/spot/en/)
+///
+/// 未列出的端点按 1 计算
+pub fn binance_request_weight(method: &Method, endpoint: &str, params: &[(&str, &str)]) -> u32 {
+    let has_symbol = params.iter().any(|(k, _)| *k == "symbol") || endpoint.contains("symbol=");
+
+    match (method.as_str(), endpoint.split('?').next().unwrap_or(endpoint)) {
+        ("GET", "/api/v3/order") => 4,
+        ("GET", "/api/v3/openOrders") => if has_symbol { 6 } else { 80 },
+        ("GET", "/api/v3/allOrders") => 20,
+        ("GET", "/api/v3/myTrades") => 20,
+        ("GET", "/api/v3/account") => 20,
+        ("GET", "/api/v3/exchangeInfo") => 20,
+        ("GET", "/api/v3/ticker/24hr") => if has_symbol { 2 } else { 80 },
+        ("GET", "/api/v3/klines") => 2,
+        ("DELETE", "/api/v3/openOrders") => 1,
+        (_, "/api/v3/userDataStream") => 2,
+        _ => 1,
+    }
+}
+
+/// 是否为会计入下单次数的请求
+pub fn is_binance_order_request(method: &Method, endpoint: &str) -> bool {
+    *method == Method::POST && endpoint.starts_with("/api/v3/order")
+}
+
 /// Binance REST API 客户端
 pub struct BinanceClient {
     client: Client,
     api_key: String,
     api_secret: String,
     base_url: String,
+    rate_limiter: Arc<RateLimiter>,
 }
 
 impl BinanceClient {
@@ -21,13 +52,25 @@ impl BinanceClient {
         }.to_string();
 
         Self {
-            client: Client::new(),
+            client: http_client(),
             api_key,
             api_secret,
             base_url,
+            rate_limiter: RateLimiter::for_exchange(ExchangeName::Binance),
         }
     }
 
+    /// 等待限频额度
+    async fn throttle(&self, method: &Method, endpoint: &str, params: &[(&str, &str)]) -> Result<(), String> {
+        let weight = binance_request_weight(method, endpoint, params);
+        let result = if is_binance_order_request(method, endpoint) {
+            self.rate_limiter.acquire_order(weight).await
+        } else {
+            self.rate_limiter.acquire(weight).await
+        };
+        result.map_err(|e| e.to_string())
+    }
+
     /// 构建请求头
     fn build_headers(&self) -> HeaderMap {
         let mut headers = HeaderMap::new();
@@ -47,18 +90,21 @@ impl BinanceClient {
         };
         let url = format!("{}{}{}", self.base_url, endpoint, query);
 
-        self.client
+        self.throttle(&Method::GET, endpoint, params).await?;
+
+        let response = self.client
             .get(&url)
             .send()
             .await
-            .map_err(|e| format!("GET request failed: {}", e))?
-            .json()
-            .await
-            .map_err(|e| format!("Response parse failed: {}", e))
+            .map_err(|e| format!("GET request failed: {}", e))?;
+
+        self.handle_response(response).await
     }
 
     /// 发送 GET 请求（需要签名）
     pub async fn get_signed(&self, endpoint: &str, params: &[(&str, &str)]) -> Result<Value, String> {
+        self.throttle(&Method::GET, endpoint, params).await?;
+
         let signature = BinanceSignature::new(self.api_key.clone(), self.api_secret.clone());
         let timestamp = BinanceSignature::timestamp();
         let timestamp_str = timestamp.to_string();
@@ -87,6 +133,8 @@ impl BinanceClient {
 
     /// 发送 POST 请求（需要签名）
     pub async fn post_signed(&self, endpoint: &str, params: &[(&str, &str)]) -> Result<Value, String> {
+        self.throttle(&Method::POST, endpoint, params).await?;
+
         let signature = BinanceSignature::new(self.api_key.clone(), self.api_secret.clone());
         let timestamp = BinanceSignature::timestamp();
         let timestamp_str = timestamp.to_string();
@@ -115,6 +163,8 @@ impl BinanceClient {
 
     /// 发送 DELETE 请求（需要签名）
     pub async fn delete_signed(&self, endpoint: &str, params: &[(&str, &str)]) -> Result<Value, String> {
+        self.throttle(&Method::DELETE, endpoint, params).await?;
+
         let signature = BinanceSignature::new(self.api_key.clone(), self.api_secret.clone());
         let timestamp = BinanceSignature::timestamp();
         let timestamp_str = timestamp.to_string();
@@ -143,6 +193,8 @@ impl BinanceClient {
 
     /// 发送 PUT 请求（需要签名）
     pub async fn put_signed(&self, endpoint: &str, params: &[(&str, &str)]) -> Result<Value, String> {
+        self.throttle(&Method::PUT, endpoint, params).await?;
+
         let signature = BinanceSignature::new(self.api_key.clone(), self.api_secret.clone());
         let timestamp = BinanceSignature::timestamp();
         let timestamp_str = timestamp.to_string();
@@ -172,6 +224,7 @@ impl BinanceClient {
     /// 处理 API 响应，检查错误代码
     async fn handle_response(&self, response: reqwest::Response) -> Result<Value, String> {
         let status = response.status();
+        self.rate_limiter.record_response(status, response.headers());
 
         if status.is_success() {
             response
@@ -229,4 +282,21 @@ mod tests {
 
         assert_eq!(client.base_url, "https://api.binance.com");
     }
+
+    #[test]
+    fn test_request_weight() {
+        assert_eq!(binance_request_weight(&Method::GET, "/api/v3/account", &[]), 20);
+        assert_eq!(binance_request_weight(&Method::GET, "/api/v3/openOrders", &[]), 80);
+        assert_eq!(binance_request_weight(&Method::GET, "/api/v3/openOrders", &[("symbol", "BTCUSDT")]), 6);
+        assert_eq!(binance_request_weight(&Method::GET, "/api/v3/ticker/24hr?symbol=BTCUSDT", &[]), 2);
+        assert_eq!(binance_request_weight(&Method::POST, "/api/v3/order", &[]), 1);
+    }
+
+    #[test]
+    fn test_order_request_detection() {
+        assert!(is_binance_order_request(&Method::POST, "/api/v3/order"));
+        assert!(is_binance_order_request(&Method::POST, "/api/v3/order/cancelReplace"));
+        assert!(!is_binance_order_request(&Method::DELETE, "/api/v3/order"));
+        assert!(!is_binance_order_request(&Method::GET, "/api/v3/order"));
+    }
 }
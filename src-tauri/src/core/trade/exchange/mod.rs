@@ -4,13 +4,119 @@ pub mod okx;
 pub mod bybit;
 pub mod signature;
 pub mod client;
+pub mod rate_limit;
+pub mod time_sync;
 
+use std::fmt;
 use std::sync::Arc;
+use std::time::Duration;
+
+use crate::core::trade::types::OrderBookLevel;
 
 pub use r#trait::{Exchange, ExchangeName};
 pub use binance::BinanceExchange;
 pub use okx::OkxExchange;
 pub use bybit::BybitExchange;
+pub use rate_limit::{RateLimitConfig, RateLimitUsage, RateLimiter};
+pub use time_sync::{ClockSync, ClockSyncStatus};
+
+/// Longest an exchange REST request may take
+///
+/// Pending orders are settled once their placement answer is overdue, so a
+/// request must give up well before that or a live order could be settled as
+/// lost.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// HTTP client for exchange REST calls
+pub fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .expect("HTTP client with default TLS settings")
+}
+
+/// Whether a failed request may still have reached the exchange
+///
+/// Timeouts, dropped connections, unreadable responses and 5xx answers leave
+/// an order's fate unknown; it has to be looked up by client order id before
+/// it is sent again.
+pub fn is_outcome_unknown(err: &anyhow::Error) -> bool {
+    if let Some(e) = err.chain().find_map(|cause| cause.downcast_ref::<reqwest::Error>()) {
+        return e.is_timeout()
+            || e.is_connect()
+            || e.is_request()
+            || e.is_body()
+            || e.is_decode()
+            || e.status().is_some_and(|status| status.is_server_error());
+    }
+
+    // The Binance client reports transport errors as strings
+    let message = err.to_string();
+    message.contains("request failed")
+        || message.contains("Response parse failed")
+        || message.contains("timed out")
+        || message.contains("HTTP error 5")
+}
+
+/// A failed batch request, reported for every order of the batch
+///
+/// `anyhow::Error` can't be cloned, so each order gets its own error with
+/// the request's error as its source; `is_outcome_unknown` still finds a
+/// transport failure behind it.
+#[derive(Debug, Clone)]
+pub struct BatchFailure {
+    context: &'static str,
+    error: Arc<anyhow::Error>,
+}
+
+impl BatchFailure {
+    pub fn new(context: &'static str, error: anyhow::Error) -> Self {
+        Self { context, error: Arc::new(error) }
+    }
+
+    /// The error for one order of the batch
+    pub fn error(&self) -> anyhow::Error {
+        anyhow::Error::new(self.clone())
+    }
+}
+
+impl fmt::Display for BatchFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.context, self.error)
+    }
+}
+
+impl std::error::Error for BatchFailure {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&**self.error)
+    }
+}
+
+/// Parse `[[price, quantity, ...], ...]` depth levels as the venues send them
+///
+/// Prices and quantities may be strings or numbers; malformed and empty
+/// levels are dropped.
+pub(crate) fn parse_book_levels(levels: &serde_json::Value) -> Vec<OrderBookLevel> {
+    let number = |v: Option<&serde_json::Value>| {
+        v.and_then(|v| v.as_str().and_then(|s| s.parse().ok()).or_else(|| v.as_f64()))
+    };
+    levels
+        .as_array()
+        .map(|levels| {
+            levels
+                .iter()
+                .filter_map(|level| {
+                    let level = level.as_array()?;
+                    Some(OrderBookLevel {
+                        price: number(level.first())?,
+                        quantity: number(level.get(1))?,
+                    })
+                })
+                .filter(|level| level.price > 0.0 && level.quantity > 0.0)
+                .collect()
+        })
+        .unwrap_or_default()
+}
 
 /// Factory for creating exchange instances
 pub struct ExchangeFactory;
@@ -22,11 +128,28 @@ impl ExchangeFactory {
         api_key: Option<String>,
         api_secret: Option<String>,
         passphrase: Option<String>,
+    ) -> Arc<dyn Exchange> {
+        Self::create_for_network(name, api_key, api_secret, passphrase, false)
+    }
+
+    /// Create exchange by name, selecting the testnet where supported
+    ///
+    /// Binance has no testnet endpoint configured and always uses the live API.
+    pub fn create_for_network(
+        name: ExchangeName,
+        api_key: Option<String>,
+        api_secret: Option<String>,
+        passphrase: Option<String>,
+        is_testnet: bool,
     ) -> Arc<dyn Exchange> {
         match name {
             ExchangeName::Binance => Arc::new(BinanceExchange::new(api_key, api_secret)),
-            ExchangeName::OKX => Arc::new(OkxExchange::new(api_key, api_secret, passphrase)),
-            ExchangeName::Bybit => Arc::new(BybitExchange::new(api_key, api_secret, passphrase)),
+            ExchangeName::OKX => Arc::new(
+                OkxExchange::new(api_key, api_secret, passphrase).with_testnet(is_testnet),
+            ),
+            ExchangeName::Bybit => Arc::new(
+                BybitExchange::new(api_key, api_secret, passphrase).with_testnet(is_testnet),
+            ),
         }
     }
 
@@ -52,6 +175,51 @@ impl ExchangeFactory {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_outcome_unknown() {
+        assert!(is_outcome_unknown(&anyhow::anyhow!(
+            "Place order failed: Signed POST request failed: error sending request for url"
+        )));
+        assert!(is_outcome_unknown(&anyhow::anyhow!("Place order failed: HTTP error 503: Service Unavailable")));
+        assert!(!is_outcome_unknown(&anyhow::anyhow!(
+            "Place order failed: Binance API error -2010: Account has insufficient balance"
+        )));
+        assert!(!is_outcome_unknown(&anyhow::anyhow!("OKX API error: \"Parameter sz error\"")));
+    }
+
+    #[tokio::test]
+    async fn test_batch_failure_keeps_transport_error() {
+        // Nothing listens on the discard port
+        let transport = reqwest::Client::new().get("http://127.0.0.1:9").send().await.unwrap_err();
+        let failure = BatchFailure::new("Batch order failed", transport.into());
+        let errors = [failure.error(), failure.error()];
+        for error in &errors {
+            assert!(error.to_string().starts_with("Batch order failed: "));
+            assert!(is_outcome_unknown(error));
+        }
+
+        let rejected = BatchFailure::new("Batch order failed", anyhow::anyhow!("OKX API error: \"Parameter sz error\""));
+        assert!(!is_outcome_unknown(&rejected.error()));
+    }
+
+    #[test]
+    fn test_parse_book_levels() {
+        let levels = parse_book_levels(&serde_json::json!([
+            ["100.5", "2.0", "0", "3"],
+            [100.4, 1.5],
+            ["100.3", "0"],
+            ["bad"]
+        ]));
+        assert_eq!(
+            levels,
+            vec![
+                OrderBookLevel { price: 100.5, quantity: 2.0 },
+                OrderBookLevel { price: 100.4, quantity: 1.5 },
+            ]
+        );
+        assert!(parse_book_levels(&serde_json::Value::Null).is_empty());
+    }
+
     #[test]
     fn test_factory_create_binance() {
         let exchange = ExchangeFactory::create(
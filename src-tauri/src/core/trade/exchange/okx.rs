@@ -10,6 +10,9 @@
 
 use super::super::types::*;
 use super::r#trait::{Exchange, ExchangeName};
+use super::rate_limit::RateLimiter;
+use super::{http_client, BatchFailure};
+use super::time_sync::ClockSync;
 use async_trait::async_trait;
 use anyhow::{anyhow, Result};
 use reqwest::Client;
@@ -23,6 +26,12 @@ const REST_API_BASE: &str = "https://www.okx.com";
 const WS_API_PUBLIC: &str = "wss://ws.okx.com:8443/ws/v5/public";
 const WS_API_PRIVATE: &str = "wss://ws.okx.com:8443/ws/v5/private";
 
+/// Most orders `batch-orders` and `cancel-batch-orders` accept per request
+const BATCH_LIMIT: usize = 20;
+
+/// Most orders `orders-pending` returns per page
+const OKX_PAGE_LIMIT: usize = 100;
+
 /// OKX-specific error codes
 #[derive(Debug)]
 pub enum OkxError {
@@ -56,6 +65,7 @@ struct OkxClient {
     passphrase: String,
     client: Client,
     is_testnet: bool,
+    rate_limiter: Arc<RateLimiter>,
 }
 
 impl OkxClient {
@@ -64,8 +74,18 @@ impl OkxClient {
             api_key,
             api_secret,
             passphrase,
-            client: Client::new(),
+            client: http_client(),
             is_testnet,
+            rate_limiter: RateLimiter::for_exchange(ExchangeName::OKX),
+        }
+    }
+
+    /// Wait for rate-limit budget; order endpoints also count against the order limit
+    async fn throttle(&self, method: &str, path: &str) -> Result<()> {
+        if method == "POST" && path.starts_with("/api/v5/trade/") {
+            self.rate_limiter.acquire_order(1).await
+        } else {
+            self.rate_limiter.acquire(1).await
         }
     }
 
@@ -93,12 +113,21 @@ impl OkxClient {
     }
 
     /// Make authenticated GET request
-    ///
-    /// # Note
-    /// Reserved for future authenticated API endpoints.
-    #[allow(dead_code)]
     async fn get_signed(&self, path: &str) -> Result<Value> {
-        let timestamp = chrono::Utc::now().timestamp_millis().to_string();
+        let json = self.get_signed_raw(path).await?;
+
+        if json["code"] != "0" {
+            return Err(anyhow!("OKX API error: {}", json["msg"]));
+        }
+
+        Ok(json)
+    }
+
+    /// Authenticated GET that leaves the response code to the caller
+    async fn get_signed_raw(&self, path: &str) -> Result<Value> {
+        self.throttle("GET", path).await?;
+
+        let timestamp = ClockSync::for_exchange(ExchangeName::OKX).now_ms().to_string();
         let sign = self.sign_request(&timestamp, "GET", path, "");
 
         let response = self.client
@@ -109,10 +138,15 @@ impl OkxClient {
             .header("OK-ACCESS-PASSPHRASE", &self.passphrase)
             .send()
             .await?;
+        self.rate_limiter.record_response(response.status(), response.headers());
 
-        let json: Value = response.json().await?;
+        Ok(response.json().await?)
+    }
+
+    /// Make authenticated POST request
+    async fn post_signed(&self, path: &str, body: &str) -> Result<Value> {
+        let json = self.post_signed_raw(path, body).await?;
 
-        // Check OKX response format
         if json["code"] != "0" {
             return Err(anyhow!("OKX API error: {}", json["msg"]));
         }
@@ -120,9 +154,14 @@ impl OkxClient {
         Ok(json)
     }
 
-    /// Make authenticated POST request
-    async fn post_signed(&self, path: &str, body: &str) -> Result<Value> {
-        let timestamp = chrono::Utc::now().timestamp_millis().to_string();
+    /// Authenticated POST that leaves the response code to the caller
+    ///
+    /// Batch endpoints answer code 1 or 2 when some of the orders failed;
+    /// the per-order results are still in `data`.
+    async fn post_signed_raw(&self, path: &str, body: &str) -> Result<Value> {
+        self.throttle("POST", path).await?;
+
+        let timestamp = ClockSync::for_exchange(ExchangeName::OKX).now_ms().to_string();
         let sign = self.sign_request(&timestamp, "POST", path, body);
 
         let response = self.client
@@ -135,14 +174,9 @@ impl OkxClient {
             .body(body.to_string())
             .send()
             .await?;
+        self.rate_limiter.record_response(response.status(), response.headers());
 
-        let json: Value = response.json().await?;
-
-        if json["code"] != "0" {
-            return Err(anyhow!("OKX API error: {}", json["msg"]));
-        }
-
-        Ok(json)
+        Ok(response.json().await?)
     }
 
     /// Make authenticated DELETE request
@@ -151,6 +185,8 @@ impl OkxClient {
     /// Reserved for future DELETE API endpoints.
     #[allow(dead_code)]
     async fn delete_signed(&self, path: &str, params: &[(&str, &str)]) -> Result<Value> {
+        self.throttle("DELETE", path).await?;
+
         let query_string = if params.is_empty() {
             String::new()
         } else {
@@ -166,7 +202,7 @@ impl OkxClient {
             format!("{}{}", path, query_string)
         };
 
-        let timestamp = chrono::Utc::now().timestamp_millis().to_string();
+        let timestamp = ClockSync::for_exchange(ExchangeName::OKX).now_ms().to_string();
         let sign = self.sign_request(&timestamp, "DELETE", &full_path, "");
 
         let response = self.client
@@ -177,6 +213,7 @@ impl OkxClient {
             .header("OK-ACCESS-PASSPHRASE", &self.passphrase)
             .send()
             .await?;
+        self.rate_limiter.record_response(response.status(), response.headers());
 
         let json: Value = response.json().await?;
 
@@ -198,6 +235,7 @@ pub struct OkxExchange {
     ticker_tx: broadcast::Sender<Ticker>,
     kline_tx: broadcast::Sender<Kline>,
     order_tx: broadcast::Sender<Order>,
+    fill_tx: broadcast::Sender<Fill>,
     connection_state: Arc<RwLock<bool>>,
     ws_task_handles: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>,
 }
@@ -211,16 +249,18 @@ impl OkxExchange {
         let (ticker_tx, _) = broadcast::channel(1000);
         let (kline_tx, _) = broadcast::channel(1000);
         let (order_tx, _) = broadcast::channel(1000);
+        let (fill_tx, _) = broadcast::channel(1000);
 
         Self {
             api_key,
             api_secret,
             passphrase,
             is_testnet: false,
-            client: Client::new(),
+            client: http_client(),
             ticker_tx,
             kline_tx,
             order_tx,
+            fill_tx,
             connection_state: Arc::new(RwLock::new(false)),
             ws_task_handles: Arc::new(Mutex::new(Vec::new())),
         }
@@ -244,6 +284,19 @@ impl OkxExchange {
         Ok(OkxClient::new(api_key, api_secret, passphrase, self.is_testnet))
     }
 
+    /// Unauthenticated GET through the shared rate limiter
+    async fn public_get(&self, path: &str) -> Result<Value> {
+        let limiter = RateLimiter::for_exchange(ExchangeName::OKX);
+        limiter.acquire(1).await?;
+
+        let response = self.client.get(format!("{}{}", REST_API_BASE, path))
+            .send()
+            .await?;
+        limiter.record_response(response.status(), response.headers());
+
+        Ok(response.json::<Value>().await?)
+    }
+
     /// Convert OKX symbol format (e.g., BTC-USDT to BTCUSDT)
     fn normalize_symbol(&self, symbol: &str) -> String {
         symbol.replace("-", "").to_uppercase()
@@ -332,10 +385,102 @@ impl OkxExchange {
         }
     }
 
+    /// Request body of one order, as sent to `order` and `batch-orders`
+    fn order_body(&self, request: &OrderRequest) -> serde_json::Map<String, Value> {
+        let td_mode = "cash"; // Trading mode: cash, cross, isolated
+
+        let mut body_map = serde_json::Map::new();
+        body_map.insert("instId".to_string(), serde_json::json!(self.to_okx_symbol(&request.symbol)));
+        body_map.insert("tdMode".to_string(), serde_json::json!(td_mode));
+        body_map.insert("side".to_string(), serde_json::json!(Self::side_to_okx(request.side)));
+        body_map.insert("ordType".to_string(), serde_json::json!(Self::order_type_to_okx(request.order_type)));
+        body_map.insert("sz".to_string(), serde_json::json!(request.quantity.to_string()));
+
+        // Add price for limit orders
+        if let Some(price) = request.price {
+            body_map.insert("px".to_string(), serde_json::json!(price.to_string()));
+        }
+        if let Some(client_order_id) = &request.client_order_id {
+            body_map.insert("clOrdId".to_string(), serde_json::json!(client_order_id));
+        }
+
+        body_map
+    }
+
+    /// Rebuild the request an order was placed with from its order details
+    fn request_from_data(&self, data: &Value) -> OrderRequest {
+        let num = |key: &str| data[key].as_str().and_then(|s| s.parse::<f64>().ok());
+
+        OrderRequest {
+            symbol: self.normalize_symbol(data["instId"].as_str().unwrap_or("")),
+            side: match data["side"].as_str().unwrap_or("") {
+                "sell" => OrderSide::Sell,
+                _ => OrderSide::Buy,
+            },
+            order_type: match data["ordType"].as_str().unwrap_or("") {
+                "market" => OrderType::Market,
+                _ => OrderType::Limit,
+            },
+            price: num("px"),
+            stop_price: None,
+            quantity: num("sz").unwrap_or(0.0),
+            client_order_id: data["clOrdId"].as_str().filter(|s| !s.is_empty()).map(str::to_string),
+            time_in_force: None,
+        }
+    }
+
+    /// Per-order results of a batch response, in request order
+    ///
+    /// Each entry is the OKX order id, or the order's `sMsg` when it was
+    /// rejected.
+    fn parse_batch_acks(json: &Value, count: usize) -> Result<Vec<Result<String>>> {
+        if !matches!(json["code"].as_str(), Some("0" | "1" | "2")) {
+            return Err(anyhow!("OKX API error: {}", json["msg"]));
+        }
+
+        let data = json["data"].as_array().cloned().unwrap_or_default();
+        Ok((0..count)
+            .map(|i| match data.get(i) {
+                Some(item) if item["sCode"].as_str() == Some("0") => {
+                    Ok(item["ordId"].as_str().unwrap_or("").to_string())
+                }
+                Some(item) => Err(anyhow!("OKX order rejected: {} {}", item["sCode"], item["sMsg"])),
+                None => Err(anyhow!("OKX batch response has no result for order {}", i)),
+            })
+            .collect())
+    }
+
+    /// Cancel orders by `(instId, ordId)` in batches of `BATCH_LIMIT`
+    async fn cancel_batch(client: &OkxClient, orders: &[(String, String)]) -> Result<Vec<Result<()>>> {
+        let mut results = Vec::with_capacity(orders.len());
+        for chunk in orders.chunks(BATCH_LIMIT) {
+            let body: Vec<Value> = chunk
+                .iter()
+                .map(|(inst_id, ord_id)| serde_json::json!({ "instId": inst_id, "ordId": ord_id }))
+                .collect();
+
+            let acks = client
+                .post_signed_raw("/api/v5/trade/cancel-batch-orders", &serde_json::to_string(&body)?)
+                .await
+                .and_then(|response| Self::parse_batch_acks(&response, chunk.len()));
+            match acks {
+                Ok(acks) => results.extend(acks.into_iter().map(|ack| ack.map(|_| ()))),
+                Err(e) => {
+                    let failure = BatchFailure::new("Batch cancel failed", e);
+                    results.extend(chunk.iter().map(|_| Err(failure.error())));
+                }
+            }
+        }
+        Ok(results)
+    }
+
     /// Parse OKX order response
     fn parse_order(&self, json: &Value, request: &OrderRequest) -> Result<Order> {
-        let data = &json["data"][0];
+        self.order_from_data(&json["data"][0], request)
+    }
 
+    /// Build an order from one entry of an order response's `data`
+    fn order_from_data(&self, data: &Value, request: &OrderRequest) -> Result<Order> {
         Ok(Order {
             id: data["ordId"].as_str().unwrap_or("").to_string(),
             exchange_order_id: Some(data["ordId"].as_str().unwrap_or("").to_string()),
@@ -345,12 +490,16 @@ impl OkxExchange {
             order_type: request.order_type,
             price: request.price,
             quantity: request.quantity,
-            filled_quantity: data["fillSz"].as_str().unwrap_or("0").parse().unwrap_or(0.0),
+            // Order details carry the cumulative fill in accFillSz
+            filled_quantity: data["accFillSz"].as_str().or(data["fillSz"].as_str())
+                .unwrap_or("0").parse().unwrap_or(0.0),
             avg_price: data["avgPx"].as_str().and_then(|s| s.parse().ok()),
             status: self.parse_order_state(data["state"].as_str().unwrap_or("live")),
             commission: 0.0,
             created_at: chrono::Utc::now().timestamp_millis(),
+            commission_asset: None,
             filled_at: None,
+            updated_at: data["uTime"].as_str().and_then(|s| s.parse().ok()),
         })
     }
 
@@ -366,6 +515,30 @@ impl OkxExchange {
         }
     }
 
+    /// Parse a single entry of /api/v5/public/instruments
+    fn parse_instrument(data: &Value) -> Option<Instrument> {
+        let num = |v: &Value| v.as_str().and_then(|s| s.parse::<f64>().ok());
+
+        let status = match data["state"].as_str().unwrap_or("") {
+            "live" => InstrumentStatus::Trading,
+            "expired" => InstrumentStatus::Closed,
+            _ => InstrumentStatus::Halted,
+        };
+
+        Some(Instrument {
+            symbol: Self::normalize_symbol_static(data["instId"].as_str()?),
+            base_asset: data["baseCcy"].as_str().unwrap_or("").to_string(),
+            quote_asset: data["quoteCcy"].as_str().unwrap_or("").to_string(),
+            status,
+            tick_size: num(&data["tickSz"])?,
+            step_size: num(&data["lotSz"])?,
+            min_qty: num(&data["minSz"]).unwrap_or(0.0),
+            max_qty: num(&data["maxLmtSz"]),
+            // OKX spot has no minimum notional
+            min_notional: 0.0,
+        })
+    }
+
     /// Start public WebSocket for tickers and klines
     ///
     /// # Note
@@ -497,6 +670,7 @@ impl OkxExchange {
     async fn ws_private_loop(&self) -> Result<()> {
         OkxExchange::ws_private_loop_impl(
             &self.order_tx,
+            &self.fill_tx,
             self.api_key.clone(),
             self.api_secret.clone(),
             self.passphrase.clone(),
@@ -509,9 +683,11 @@ impl OkxExchange {
         api_secret: &str,
         passphrase: &str,
         tx_order: broadcast::Sender<Order>,
+        tx_fill: broadcast::Sender<Fill>,
     ) -> Result<()> {
         OkxExchange::ws_private_loop_impl(
             &tx_order,
+            &tx_fill,
             Some(api_key.to_string()),
             Some(api_secret.to_string()),
             Some(passphrase.to_string()),
@@ -521,6 +697,7 @@ impl OkxExchange {
     /// Implementation of private WebSocket loop
     async fn ws_private_loop_impl(
         order_tx: &broadcast::Sender<Order>,
+        fill_tx: &broadcast::Sender<Fill>,
         api_key: Option<String>,
         api_secret: Option<String>,
         passphrase: Option<String>,
@@ -538,7 +715,7 @@ impl OkxExchange {
         let temp_client = OkxClient::new(api_key.clone(), api_secret.clone(), passphrase.clone(), false);
 
         // Login and subscribe
-        let timestamp = chrono::Utc::now().timestamp_millis().to_string();
+        let timestamp = ClockSync::for_exchange(ExchangeName::OKX).now_ms().to_string();
         let sign = temp_client.sign_request(&timestamp, "GET", "/users/self/verify", "");
 
         let login_msg = serde_json::json!({
@@ -578,6 +755,9 @@ impl OkxExchange {
                     if let Ok(json) = serde_json::from_str::<Value>(&text) {
                         if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
                             for item in data {
+                                if let Some(fill) = Self::parse_ws_fill_static(item) {
+                                    let _ = fill_tx.send(fill);
+                                }
                                 if let Ok(order) = Self::parse_ws_order_from_value(item) {
                                     let _ = order_tx.send(order);
                                 }
@@ -693,13 +873,48 @@ impl OkxExchange {
             filled_quantity: data["fillSz"].as_str().unwrap_or("0").parse().unwrap_or(0.0),
             avg_price: data["avgPx"].as_str().and_then(|s| s.parse().ok()),
             status: Self::parse_order_state_static(data["state"].as_str().unwrap_or("")),
-            commission: 0.0,
+            commission: -data["fee"].as_str().and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0),
             created_at: data["cTime"].as_i64().unwrap_or(0),
+            commission_asset: data["feeCcy"].as_str().filter(|s| !s.is_empty()).map(|s| s.to_string()),
             filled_at: None,
+            updated_at: data["uTime"].as_str().and_then(|s| s.parse().ok()),
             client_order_id: data["clOrdId"].as_str().map(|s| s.to_string()),
         })
     }
 
+    /// Extract the execution carried by an orders-channel update
+    ///
+    /// OKX reports the latest fill on the order push itself (`tradeId`,
+    /// `fillPx`, `fillSz`, `fillFee`); updates without a `tradeId` are pure
+    /// state changes. `fillFee` is negative when charged.
+    fn parse_ws_fill_static(data: &Value) -> Option<Fill> {
+        let trade_id = data["tradeId"].as_str().filter(|id| !id.is_empty())?;
+        let num = |key: &str| data[key].as_str().and_then(|s| s.parse::<f64>().ok());
+
+        let quantity = num("fillSz")?;
+        if quantity <= 0.0 {
+            return None;
+        }
+
+        Some(Fill {
+            exchange_order_id: data["ordId"].as_str()?.to_string(),
+            exchange_trade_id: Some(trade_id.to_string()),
+            client_order_id: data["clOrdId"].as_str().filter(|s| !s.is_empty()).map(|s| s.to_string()),
+            symbol: Self::normalize_symbol_static(data["instId"].as_str()?),
+            side: match data["side"].as_str()? {
+                "buy" => OrderSide::Buy,
+                "sell" => OrderSide::Sell,
+                _ => return None,
+            },
+            price: num("fillPx")?,
+            quantity,
+            commission: -num("fillFee").unwrap_or(0.0),
+            commission_asset: data["fillFeeCcy"].as_str().filter(|s| !s.is_empty()).map(|s| s.to_string()),
+            is_maker: data["execType"].as_str() == Some("M"),
+            timestamp: data["fillTime"].as_str().and_then(|s| s.parse().ok()).unwrap_or(0),
+        })
+    }
+
     /// Static helper to normalize symbol (used in static async functions)
     fn normalize_symbol_static(symbol: &str) -> String {
         symbol.replace("-", "").to_uppercase()
@@ -754,11 +969,7 @@ impl Exchange for OkxExchange {
         let okx_symbol = self.to_okx_symbol(symbol);
         let path = format!("/api/v5/market/ticker?instId={}", okx_symbol);
 
-        let response = self.client.get(format!("{}{}", REST_API_BASE, path))
-            .send()
-            .await?
-            .json::<Value>()
-            .await?;
+        let response = self.public_get(&path).await?;
 
         if response["code"] != "0" {
             return Err(anyhow!("OKX ticker error: {}", response["msg"]));
@@ -767,6 +978,28 @@ impl Exchange for OkxExchange {
         self.parse_ticker(&response, symbol)
     }
 
+    async fn get_order_book(&self, symbol: &str, limit: usize) -> Result<OrderBook> {
+        let okx_symbol = self.to_okx_symbol(symbol);
+        let path = format!("/api/v5/market/books?instId={}&sz={}", okx_symbol, limit.clamp(1, 400));
+
+        let response = self.public_get(&path).await?;
+
+        if response["code"] != "0" {
+            return Err(anyhow!("OKX order book error: {}", response["msg"]));
+        }
+
+        let data = &response["data"][0];
+        Ok(OrderBook {
+            symbol: self.normalize_symbol(symbol),
+            bids: super::parse_book_levels(&data["bids"]),
+            asks: super::parse_book_levels(&data["asks"]),
+            timestamp: data["ts"]
+                .as_str()
+                .and_then(|ts| ts.parse().ok())
+                .unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
+        })
+    }
+
     async fn get_klines(
         &self,
         symbol: &str,
@@ -781,11 +1014,7 @@ impl Exchange for OkxExchange {
             okx_symbol, bar_interval, limit
         );
 
-        let response = self.client.get(format!("{}{}", REST_API_BASE, path))
-            .send()
-            .await?
-            .json::<Value>()
-            .await?;
+        let response = self.public_get(&path).await?;
 
         if response["code"] != "0" {
             return Err(anyhow!("OKX klines error: {}", response["msg"]));
@@ -799,6 +1028,34 @@ impl Exchange for OkxExchange {
             .collect()
     }
 
+    async fn get_server_time(&self) -> Result<i64> {
+        let response = self.public_get("/api/v5/public/time").await?;
+
+        if response["code"] != "0" {
+            return Err(anyhow!("OKX server time error: {}", response["msg"]));
+        }
+
+        response["data"][0]["ts"]
+            .as_str()
+            .and_then(|ts| ts.parse().ok())
+            .ok_or_else(|| anyhow!("Invalid server time response"))
+    }
+
+    async fn get_instruments(&self) -> Result<Vec<Instrument>> {
+        let path = "/api/v5/public/instruments?instType=SPOT";
+
+        let response = self.public_get(path).await?;
+
+        if response["code"] != "0" {
+            return Err(anyhow!("OKX instruments error: {}", response["msg"]));
+        }
+
+        let data = response["data"].as_array()
+            .ok_or_else(|| anyhow!("Invalid instruments response"))?;
+
+        Ok(data.iter().filter_map(Self::parse_instrument).collect())
+    }
+
     async fn subscribe_ticker(&self, symbols: Vec<String>) -> Result<()> {
         if symbols.is_empty() {
             return Ok(());
@@ -921,10 +1178,15 @@ impl Exchange for OkxExchange {
         self.order_tx.subscribe()
     }
 
+    fn fill_stream(&self) -> broadcast::Receiver<Fill> {
+        self.fill_tx.subscribe()
+    }
+
     async fn subscribe_user_data(&self) -> Result<()> {
         log::info!("Subscribing to OKX user data stream");
 
         let tx_order = self.order_tx.clone();
+        let tx_fill = self.fill_tx.clone();
         let connection_state = self.connection_state.clone();
         let api_key = self.api_key.clone();
         let api_secret = self.api_secret.clone();
@@ -951,6 +1213,7 @@ impl Exchange for OkxExchange {
                     api_secret.as_ref().unwrap(),
                     passphrase.as_ref().unwrap(),
                     tx_order.clone(),
+                    tx_fill.clone(),
                 ).await {
                     Ok(_) => {
                         retry_count = 0;
@@ -975,22 +1238,7 @@ impl Exchange for OkxExchange {
     async fn place_order(&self, request: &OrderRequest) -> Result<Order> {
         let client = self.rest_client()?;
 
-        let okx_symbol = self.to_okx_symbol(&request.symbol);
-        let td_mode = "cash"; // Trading mode: cash, cross, isolated
-
-        // Build order request body
-        let mut body_map = serde_json::Map::new();
-        body_map.insert("instId".to_string(), serde_json::json!(okx_symbol));
-        body_map.insert("tdMode".to_string(), serde_json::json!(td_mode));
-        body_map.insert("side".to_string(), serde_json::json!(Self::side_to_okx(request.side)));
-        body_map.insert("ordType".to_string(), serde_json::json!(Self::order_type_to_okx(request.order_type)));
-        body_map.insert("sz".to_string(), serde_json::json!(request.quantity.to_string()));
-
-        // Add price for limit orders
-        if let Some(price) = request.price {
-            body_map.insert("px".to_string(), serde_json::json!(price.to_string()));
-        }
-
+        let body_map = self.order_body(request);
         let body_str = serde_json::to_string(&body_map)?;
         let response = client.post_signed("/api/v5/trade/order", &body_str).await?;
 
@@ -1012,38 +1260,192 @@ impl Exchange for OkxExchange {
         Ok(())
     }
 
-    async fn get_order(&self, order_id: &str) -> Result<Order> {
+    async fn place_orders(&self, requests: &[OrderRequest]) -> Result<Vec<Result<Order>>> {
+        let client = self.rest_client()?;
+
+        let mut results = Vec::with_capacity(requests.len());
+        for chunk in requests.chunks(BATCH_LIMIT) {
+            let body: Vec<_> = chunk.iter().map(|request| self.order_body(request)).collect();
+            let acks = client
+                .post_signed_raw("/api/v5/trade/batch-orders", &serde_json::to_string(&body)?)
+                .await
+                .and_then(|response| Ok((Self::parse_batch_acks(&response, chunk.len())?, response)));
+
+            // A failed chunk fails its own orders; earlier chunks are already live
+            match acks {
+                Ok((acks, response)) => {
+                    for (i, (ack, request)) in acks.into_iter().zip(chunk).enumerate() {
+                        results.push(ack.and_then(|_| self.order_from_data(&response["data"][i], request)));
+                    }
+                }
+                Err(e) => {
+                    let failure = BatchFailure::new("Batch order failed", e);
+                    results.extend(chunk.iter().map(|_| Err(failure.error())));
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    async fn cancel_orders(&self, orders: &[Order]) -> Result<Vec<Result<()>>> {
+        let client = self.rest_client()?;
+
+        let ids: Vec<_> = orders
+            .iter()
+            .filter_map(|order| {
+                let ord_id = order.exchange_order_id.clone()?;
+                Some((self.to_okx_symbol(&order.symbol), ord_id))
+            })
+            .collect();
+        let mut acks = Self::cancel_batch(&client, &ids).await?.into_iter();
+
+        // Orders without an exchange id never reached OKX
+        Ok(orders
+            .iter()
+            .map(|order| match order.exchange_order_id {
+                Some(_) => acks.next().unwrap_or_else(|| Err(anyhow!("No cancel result for {}", order.id))),
+                None => Err(anyhow!("Order {} has no exchange order id", order.id)),
+            })
+            .collect())
+    }
+
+    /// OKX has no spot cancel-all; pending orders are listed and canceled
+    /// in batches
+    async fn cancel_all_orders(&self, symbol: &str) -> Result<usize> {
+        let client = self.rest_client()?;
+        let inst_id = self.to_okx_symbol(symbol);
+
+        let response = client
+            .get_signed(&format!("/api/v5/trade/orders-pending?instType=SPOT&instId={}", inst_id))
+            .await?;
+        let ids: Vec<_> = response["data"]
+            .as_array()
+            .map(|data| {
+                data.iter()
+                    .filter_map(|item| item["ordId"].as_str())
+                    .map(|ord_id| (inst_id.clone(), ord_id.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let results = Self::cancel_batch(&client, &ids).await?;
+        Ok(results.iter().filter(|r| r.is_ok()).count())
+    }
+
+    /// OKX accepts 10-120 s, or 0 to cancel the timer
+    async fn cancel_all_after(&self, timeout_secs: u64) -> Result<bool> {
         let client = self.rest_client()?;
+        let timeout = if timeout_secs == 0 { 0 } else { timeout_secs.clamp(10, 120) };
 
         let body = serde_json::json!({
-            "ordId": order_id,
+            "timeOut": timeout.to_string(),
         });
 
         let body_str = serde_json::to_string(&body)?;
-        let _response = client.post_signed("/api/v5/trade/order", &body_str).await?;
+        client.post_signed("/api/v5/trade/cancel-all-after", &body_str).await?;
+        Ok(true)
+    }
+
+    async fn amend_order(&self, order: &Order, amend: &AmendRequest) -> Result<AmendOutcome> {
+        let client = self.rest_client()?;
+        let exchange_order_id = order.exchange_order_id.as_deref()
+            .ok_or_else(|| anyhow!("Order {} has no exchange order id", order.id))?;
+
+        let mut body_map = serde_json::Map::new();
+        body_map.insert("instId".to_string(), serde_json::json!(self.to_okx_symbol(&order.symbol)));
+        body_map.insert("ordId".to_string(), serde_json::json!(exchange_order_id));
+        if let Some(quantity) = amend.quantity {
+            body_map.insert("newSz".to_string(), serde_json::json!(quantity.to_string()));
+        }
+        if let Some(price) = amend.price {
+            body_map.insert("newPx".to_string(), serde_json::json!(price.to_string()));
+        }
 
-        // Parse response - need proper OrderRequest context
-        // For now, return placeholder
-        todo!("OKX get_order parsing - need context for symbol/side/price")
+        let body_str = serde_json::to_string(&body_map)?;
+        let response = client.post_signed("/api/v5/trade/amend-order", &body_str).await?;
+
+        // The envelope code is 0 even when the amendment itself is rejected
+        let data = &response["data"][0];
+        if data["sCode"].as_str().unwrap_or("0") != "0" {
+            return Err(anyhow!("OKX amend rejected: {}", data["sMsg"]));
+        }
+
+        Ok(AmendOutcome::Amended(amend.apply(order)))
+    }
+
+    async fn get_order(&self, symbol: &str, order_id: &str) -> Result<Order> {
+        let client = self.rest_client()?;
+
+        let response = client
+            .get_signed(&format!(
+                "/api/v5/trade/order?instId={}&ordId={}",
+                self.to_okx_symbol(symbol),
+                order_id
+            ))
+            .await?;
+
+        let data = &response["data"][0];
+        if data.is_null() {
+            return Err(anyhow!("OKX order {} not found", order_id));
+        }
+        self.order_from_data(data, &self.request_from_data(data))
+    }
+
+    async fn get_order_by_client_id(&self, symbol: &str, client_order_id: &str) -> Result<Option<Order>> {
+        let client = self.rest_client()?;
+
+        let response = client
+            .get_signed_raw(&format!(
+                "/api/v5/trade/order?instId={}&clOrdId={}",
+                self.to_okx_symbol(symbol),
+                client_order_id
+            ))
+            .await?;
+
+        match response["code"].as_str() {
+            Some("0") => {
+                let data = &response["data"][0];
+                Ok(Some(self.order_from_data(data, &self.request_from_data(data))?))
+            }
+            // 51603: Order does not exist
+            Some("51603") => Ok(None),
+            _ => Err(anyhow!("OKX API error: {}", response["msg"])),
+        }
     }
 
     async fn get_open_orders(&self, symbol: Option<&str>) -> Result<Vec<Order>> {
         let client = self.rest_client()?;
 
-        let body = if let Some(sym) = symbol {
-            serde_json::json!({
-                "instId": self.to_okx_symbol(sym),
-            })
-        } else {
-            serde_json::json!({})
-        };
+        let mut query = format!("/api/v5/trade/orders-pending?instType=SPOT&limit={}", OKX_PAGE_LIMIT);
+        if let Some(sym) = symbol {
+            query.push_str(&format!("&instId={}", self.to_okx_symbol(sym)));
+        }
 
-        let body_str = serde_json::to_string(&body)?;
-        let _response = client.post_signed("/api/v5/trade/orders-pending", &body_str).await?;
+        // Pages run newest first; `after` continues past the last order id
+        let mut orders = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            let path = match &after {
+                Some(id) => format!("{}&after={}", query, id),
+                None => query.clone(),
+            };
+            let response = client.get_signed(&path).await?;
+            let page = response["data"].as_array()
+                .ok_or_else(|| anyhow!("Invalid open orders response"))?;
+
+            for data in page {
+                orders.push(self.order_from_data(data, &self.request_from_data(data))?);
+            }
+            if page.len() < OKX_PAGE_LIMIT {
+                break;
+            }
+            after = page.last().and_then(|data| data["ordId"].as_str()).map(str::to_string);
+            if after.is_none() {
+                break;
+            }
+        }
 
-        // Parse response
-        // For now, return placeholder
-        todo!("OKX get_open_orders parsing")
+        Ok(orders)
     }
 
     async fn get_balance(&self) -> Result<Vec<Balance>> {
@@ -1171,4 +1573,84 @@ mod tests {
         assert_eq!(exchange.parse_order_state("canceled"), OrderState::Canceled);
         assert_eq!(exchange.parse_order_state("failed"), OrderState::Rejected);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_instrument() {
+        let data = serde_json::json!({
+            "instId": "BTC-USDT",
+            "baseCcy": "BTC",
+            "quoteCcy": "USDT",
+            "tickSz": "0.1",
+            "lotSz": "0.00000001",
+            "minSz": "0.00001",
+            "maxLmtSz": "9999999999",
+            "state": "live"
+        });
+
+        let instrument = OkxExchange::parse_instrument(&data).unwrap();
+        assert_eq!(instrument.symbol, "BTCUSDT");
+        assert_eq!(instrument.status, InstrumentStatus::Trading);
+        assert_eq!(instrument.tick_size, 0.1);
+        assert_eq!(instrument.step_size, 0.00000001);
+        assert_eq!(instrument.min_qty, 0.00001);
+        assert_eq!(instrument.min_notional, 0.0);
+    }
+
+    #[test]
+    fn test_parse_ws_fill() {
+        let data = serde_json::json!({
+            "instId": "ETH-USDT",
+            "ordId": "312269865356374016",
+            "clOrdId": "",
+            "side": "sell",
+            "state": "partially_filled",
+            "tradeId": "1234567",
+            "fillPx": "2500.5",
+            "fillSz": "0.2",
+            "fillFee": "-0.50010",
+            "fillFeeCcy": "USDT",
+            "execType": "T",
+            "fillTime": "1597026383085"
+        });
+
+        let fill = OkxExchange::parse_ws_fill_static(&data).unwrap();
+        assert_eq!(fill.symbol, "ETHUSDT");
+        assert_eq!(fill.exchange_trade_id.as_deref(), Some("1234567"));
+        assert!(fill.client_order_id.is_none());
+        assert_eq!(fill.side, OrderSide::Sell);
+        assert_eq!(fill.quantity, 0.2);
+        assert_eq!(fill.commission, 0.5001);
+        assert_eq!(fill.commission_asset.as_deref(), Some("USDT"));
+        assert!(!fill.is_maker);
+        assert_eq!(fill.timestamp, 1597026383085);
+    }
+
+    #[test]
+    fn test_parse_batch_acks() {
+        let response = serde_json::json!({
+            "code": "2",
+            "msg": "",
+            "data": [
+                {"ordId": "101", "clOrdId": "", "sCode": "0", "sMsg": ""},
+                {"ordId": "", "clOrdId": "", "sCode": "51008", "sMsg": "Insufficient balance"}
+            ]
+        });
+
+        let acks = OkxExchange::parse_batch_acks(&response, 3).unwrap();
+        assert_eq!(acks[0].as_ref().unwrap(), "101");
+        assert!(acks[1].as_ref().unwrap_err().to_string().contains("Insufficient balance"));
+        assert!(acks[2].is_err());
+
+        let failed = serde_json::json!({"code": "50011", "msg": "Rate limit reached", "data": []});
+        assert!(OkxExchange::parse_batch_acks(&failed, 1).is_err());
+    }
+
+    #[test]
+    fn test_parse_ws_fill_skips_state_updates() {
+        let data = serde_json::json!({
+            "instId": "ETH-USDT", "ordId": "1", "side": "buy", "state": "live",
+            "tradeId": "", "fillSz": "0"
+        });
+        assert!(OkxExchange::parse_ws_fill_static(&data).is_none());
+    }
+}
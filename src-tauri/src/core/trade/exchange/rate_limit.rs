@@ -0,0 +1,498 @@
+//! Exchange REST rate-limit governor
+//!
+//! Every REST call goes through a per-exchange `RateLimiter` before it is
+//! sent. The limiter keeps token buckets for request weight and order count,
+//! re-syncs them from the usage headers the exchange returns
+//! (`X-MBX-USED-WEIGHT-1M`, `X-MBX-ORDER-COUNT-10S`, `X-Bapi-Limit-Status`),
+//! and backs off entirely after a 429/418 response.
+//!
+//! Calls that would exceed the budget are queued if the wait is short
+//! (`max_wait`) and rejected otherwise.
+
+use super::r#trait::ExchangeName;
+use anyhow::{bail, Result};
+use chrono::{Datelike, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Rate-limit budget for one exchange
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Request weight allowed per `weight_window`
+    pub weight_limit: u32,
+    pub weight_window: Duration,
+    /// Orders allowed per `order_window`
+    pub order_limit: u32,
+    pub order_window: Duration,
+    /// Orders allowed per UTC day
+    pub daily_order_limit: Option<u32>,
+    /// Longest a call may be queued before it is rejected instead
+    pub max_wait: Duration,
+    /// Backoff after a 429 without a Retry-After header
+    pub default_backoff: Duration,
+}
+
+impl RateLimitConfig {
+    /// Published limits with ~10% headroom for other clients on the same IP/key
+    pub fn for_exchange(name: ExchangeName) -> Self {
+        match name {
+            // 6000 weight/min per IP, 100 orders/10s and 200k orders/day per account
+            ExchangeName::Binance => Self {
+                weight_limit: 5400,
+                weight_window: Duration::from_secs(60),
+                order_limit: 90,
+                order_window: Duration::from_secs(10),
+                daily_order_limit: Some(180_000),
+                max_wait: Duration::from_secs(5),
+                default_backoff: Duration::from_secs(60),
+            },
+            // Most REST endpoints are 20 req/2s; order placement is 60/2s
+            ExchangeName::OKX => Self {
+                weight_limit: 18,
+                weight_window: Duration::from_secs(2),
+                order_limit: 54,
+                order_window: Duration::from_secs(2),
+                daily_order_limit: None,
+                max_wait: Duration::from_secs(5),
+                default_backoff: Duration::from_secs(2),
+            },
+            // 600 req/5s per IP; spot order creation is 20/s per UID
+            ExchangeName::Bybit => Self {
+                weight_limit: 540,
+                weight_window: Duration::from_secs(5),
+                order_limit: 18,
+                order_window: Duration::from_secs(1),
+                daily_order_limit: None,
+                max_wait: Duration::from_secs(5),
+                default_backoff: Duration::from_secs(5),
+            },
+        }
+    }
+}
+
+/// Snapshot of limiter usage, reported by `market_get_status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitUsage {
+    pub exchange: String,
+    pub used_weight: u32,
+    pub weight_limit: u32,
+    pub weight_window_secs: u64,
+    pub orders_in_window: u32,
+    pub order_limit: u32,
+    pub orders_today: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_order_limit: Option<u32>,
+    /// Calls that had to wait for budget
+    pub throttled_requests: u64,
+    /// Calls rejected because the wait exceeded `max_wait`
+    pub rejected_requests: u64,
+    /// Remaining backoff after a 429/418, 0 when not backing off
+    pub backoff_remaining_ms: u64,
+    /// Last usage reported by the exchange itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_used_weight: Option<u32>,
+}
+
+/// Continuously refilling token bucket
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: u32, window: Duration) -> Self {
+        let capacity = capacity.max(1) as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: capacity / window.as_secs_f64().max(0.001),
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Time until `amount` tokens are available (zero if available now)
+    fn wait_time(&self, amount: f64) -> Duration {
+        let amount = amount.min(self.capacity);
+        if self.tokens >= amount {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((amount - self.tokens) / self.refill_per_sec)
+        }
+    }
+
+    fn take(&mut self, amount: f64) {
+        self.tokens = (self.tokens - amount.min(self.capacity)).max(0.0);
+    }
+
+    fn used(&self) -> u32 {
+        (self.capacity - self.tokens).round() as u32
+    }
+
+    fn exhaust(&mut self) {
+        self.tokens = 0.0;
+    }
+
+    /// Align the bucket with server-reported usage; never gives tokens back
+    fn sync_used(&mut self, used: f64) {
+        self.tokens = self.tokens.min((self.capacity - used).max(0.0));
+    }
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    weight: TokenBucket,
+    orders: TokenBucket,
+    orders_today: u32,
+    day: u32,
+    backoff_until: Option<Instant>,
+    throttled: u64,
+    rejected: u64,
+    server_used_weight: Option<u32>,
+}
+
+impl LimiterState {
+    fn roll_day(&mut self) {
+        let today = Utc::now().ordinal();
+        if today != self.day {
+            self.day = today;
+            self.orders_today = 0;
+        }
+    }
+}
+
+/// Token-bucket governor for one exchange
+pub struct RateLimiter {
+    exchange: ExchangeName,
+    config: RateLimitConfig,
+    state: Mutex<LimiterState>,
+}
+
+lazy_static::lazy_static! {
+    /// One limiter per exchange, shared by every client in the process
+    static ref RATE_LIMITERS: Mutex<HashMap<ExchangeName, Arc<RateLimiter>>> =
+        Mutex::new(HashMap::new());
+}
+
+impl RateLimiter {
+    /// Create a standalone limiter
+    pub fn new(exchange: ExchangeName, config: RateLimitConfig) -> Self {
+        let state = LimiterState {
+            weight: TokenBucket::new(config.weight_limit, config.weight_window),
+            orders: TokenBucket::new(config.order_limit, config.order_window),
+            orders_today: 0,
+            day: Utc::now().ordinal(),
+            backoff_until: None,
+            throttled: 0,
+            rejected: 0,
+            server_used_weight: None,
+        };
+
+        Self {
+            exchange,
+            config,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Get the process-wide limiter for an exchange
+    ///
+    /// Exchange limits are per IP / per account, so all clients for the
+    /// same exchange must draw from the same budget.
+    pub fn for_exchange(exchange: ExchangeName) -> Arc<Self> {
+        let mut limiters = RATE_LIMITERS.lock().unwrap_or_else(|e| e.into_inner());
+        limiters
+            .entry(exchange)
+            .or_insert_with(|| Arc::new(Self::new(exchange, RateLimitConfig::for_exchange(exchange))))
+            .clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LimiterState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Wait for budget for a non-order request
+    pub async fn acquire(&self, weight: u32) -> Result<()> {
+        self.acquire_inner(weight, false).await
+    }
+
+    /// Wait for budget for a request that creates an order
+    pub async fn acquire_order(&self, weight: u32) -> Result<()> {
+        self.acquire_inner(weight, true).await
+    }
+
+    async fn acquire_inner(&self, weight: u32, is_order: bool) -> Result<()> {
+        let mut queued = false;
+
+        loop {
+            let wait = {
+                let mut state = self.lock();
+                let now = Instant::now();
+                state.weight.refill(now);
+                state.orders.refill(now);
+                state.roll_day();
+
+                if is_order {
+                    if let Some(limit) = self.config.daily_order_limit {
+                        if state.orders_today >= limit {
+                            state.rejected += 1;
+                            bail!(
+                                "{} daily order limit reached ({}/{})",
+                                self.exchange,
+                                state.orders_today,
+                                limit
+                            );
+                        }
+                    }
+                }
+
+                let backoff = state
+                    .backoff_until
+                    .map(|until| until.saturating_duration_since(now))
+                    .unwrap_or(Duration::ZERO);
+                let mut wait = backoff.max(state.weight.wait_time(weight as f64));
+                if is_order {
+                    wait = wait.max(state.orders.wait_time(1.0));
+                }
+
+                if wait.is_zero() {
+                    state.weight.take(weight as f64);
+                    if is_order {
+                        state.orders.take(1.0);
+                        state.orders_today += 1;
+                    }
+                    return Ok(());
+                }
+
+                if wait > self.config.max_wait {
+                    state.rejected += 1;
+                    bail!(
+                        "{} rate limit exceeded, retry in {}ms",
+                        self.exchange,
+                        wait.as_millis()
+                    );
+                }
+
+                if !queued {
+                    state.throttled += 1;
+                    queued = true;
+                }
+                wait
+            };
+
+            log::debug!("{} rate limit: queuing request for {}ms", self.exchange, wait.as_millis());
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Update the limiter from a response's status and usage headers
+    pub fn record_response(&self, status: StatusCode, headers: &HeaderMap) {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u32>().ok())
+        };
+
+        let mut state = self.lock();
+        let now = Instant::now();
+        state.weight.refill(now);
+        state.orders.refill(now);
+
+        // Binance
+        if let Some(used) = header("x-mbx-used-weight-1m") {
+            state.weight.sync_used(used as f64);
+            state.server_used_weight = Some(used);
+        }
+        if let Some(count) = header("x-mbx-order-count-10s") {
+            state.orders.sync_used(count as f64);
+        }
+        if let Some(count) = header("x-mbx-order-count-1d") {
+            state.roll_day();
+            state.orders_today = state.orders_today.max(count);
+        }
+
+        // Bybit reports per-endpoint limit and remaining
+        if let (Some(limit), Some(remaining)) = (header("x-bapi-limit"), header("x-bapi-limit-status")) {
+            state.server_used_weight = Some(limit.saturating_sub(remaining));
+        }
+
+        // 429 = too many requests, 418 = IP banned after ignoring 429s
+        if status == StatusCode::TOO_MANY_REQUESTS || status.as_u16() == 418 {
+            let backoff = header("retry-after")
+                .map(|secs| Duration::from_secs(secs as u64))
+                .unwrap_or(self.config.default_backoff);
+            state.backoff_until = Some(now + backoff);
+            state.weight.exhaust();
+            log::warn!(
+                "{} returned HTTP {}, backing off for {}s",
+                self.exchange,
+                status.as_u16(),
+                backoff.as_secs()
+            );
+        }
+    }
+
+    /// Current usage snapshot
+    pub fn usage(&self) -> RateLimitUsage {
+        let mut state = self.lock();
+        let now = Instant::now();
+        state.weight.refill(now);
+        state.orders.refill(now);
+        state.roll_day();
+
+        RateLimitUsage {
+            exchange: self.exchange.to_string(),
+            used_weight: state.weight.used(),
+            weight_limit: self.config.weight_limit,
+            weight_window_secs: self.config.weight_window.as_secs(),
+            orders_in_window: state.orders.used(),
+            order_limit: self.config.order_limit,
+            orders_today: state.orders_today,
+            daily_order_limit: self.config.daily_order_limit,
+            throttled_requests: state.throttled,
+            rejected_requests: state.rejected,
+            backoff_remaining_ms: state
+                .backoff_until
+                .map(|until| until.saturating_duration_since(now).as_millis() as u64)
+                .unwrap_or(0),
+            server_used_weight: state.server_used_weight,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn test_config(max_wait: Duration) -> RateLimitConfig {
+        RateLimitConfig {
+            weight_limit: 10,
+            weight_window: Duration::from_secs(1),
+            order_limit: 2,
+            order_window: Duration::from_secs(1),
+            daily_order_limit: Some(3),
+            max_wait,
+            default_backoff: Duration::from_secs(30),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_acquire_within_budget() {
+        let limiter = RateLimiter::new(ExchangeName::Binance, test_config(Duration::ZERO));
+
+        limiter.acquire(4).await.unwrap();
+        limiter.acquire(4).await.unwrap();
+
+        let usage = limiter.usage();
+        assert_eq!(usage.used_weight, 8);
+        assert_eq!(usage.throttled_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_reject_when_wait_exceeds_max() {
+        let limiter = RateLimiter::new(ExchangeName::Binance, test_config(Duration::ZERO));
+
+        limiter.acquire(10).await.unwrap();
+        assert!(limiter.acquire(5).await.is_err());
+        assert_eq!(limiter.usage().rejected_requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_queue_until_refilled() {
+        let limiter = RateLimiter::new(ExchangeName::Binance, test_config(Duration::from_secs(1)));
+
+        limiter.acquire(10).await.unwrap();
+        let start = Instant::now();
+        // 2 tokens at 10/s refill => ~200ms
+        limiter.acquire(2).await.unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(150));
+        assert_eq!(limiter.usage().throttled_requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_order_count_limits() {
+        let limiter = RateLimiter::new(ExchangeName::Binance, test_config(Duration::ZERO));
+
+        limiter.acquire_order(1).await.unwrap();
+        limiter.acquire_order(1).await.unwrap();
+        // 2 orders per window
+        assert!(limiter.acquire_order(1).await.is_err());
+        // Plain requests are unaffected
+        limiter.acquire(1).await.unwrap();
+
+        assert_eq!(limiter.usage().orders_today, 2);
+    }
+
+    #[tokio::test]
+    async fn test_daily_order_limit() {
+        let limiter = RateLimiter::new(ExchangeName::Binance, test_config(Duration::from_secs(1)));
+
+        for _ in 0..3 {
+            limiter.acquire_order(1).await.unwrap();
+        }
+        let err = limiter.acquire_order(1).await.unwrap_err();
+        assert!(err.to_string().contains("daily order limit"));
+    }
+
+    #[test]
+    fn test_sync_from_binance_headers() {
+        let limiter = RateLimiter::new(ExchangeName::Binance, test_config(Duration::ZERO));
+        let mut headers = HeaderMap::new();
+        headers.insert("x-mbx-used-weight-1m", HeaderValue::from_static("7"));
+        headers.insert("x-mbx-order-count-1d", HeaderValue::from_static("2"));
+
+        limiter.record_response(StatusCode::OK, &headers);
+
+        let usage = limiter.usage();
+        assert_eq!(usage.used_weight, 7);
+        assert_eq!(usage.server_used_weight, Some(7));
+        assert_eq!(usage.orders_today, 2);
+    }
+
+    #[test]
+    fn test_sync_from_bybit_headers() {
+        let limiter = RateLimiter::new(ExchangeName::Bybit, test_config(Duration::ZERO));
+        let mut headers = HeaderMap::new();
+        headers.insert("x-bapi-limit", HeaderValue::from_static("20"));
+        headers.insert("x-bapi-limit-status", HeaderValue::from_static("15"));
+
+        limiter.record_response(StatusCode::OK, &headers);
+
+        assert_eq!(limiter.usage().server_used_weight, Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_backoff_after_429() {
+        let limiter = RateLimiter::new(ExchangeName::Binance, test_config(Duration::from_secs(1)));
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("120"));
+
+        limiter.record_response(StatusCode::TOO_MANY_REQUESTS, &headers);
+
+        assert!(limiter.usage().backoff_remaining_ms > 100_000);
+        assert!(limiter.acquire(1).await.is_err());
+    }
+
+    #[test]
+    fn test_for_exchange_is_shared() {
+        let a = RateLimiter::for_exchange(ExchangeName::OKX);
+        let b = RateLimiter::for_exchange(ExchangeName::OKX);
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+}
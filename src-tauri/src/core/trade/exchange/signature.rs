@@ -1,6 +1,7 @@
 use hmac::{Hmac, Mac};
 use sha2::Sha256;
-use chrono::Utc;
+use super::r#trait::ExchangeName;
+use super::time_sync::ClockSync;
 use std::collections::HashMap;
 
 /// Binance API 签名器
@@ -23,9 +24,9 @@ impl BinanceSignature {
         hex::encode(result.into_bytes())
     }
 
-    /// 生成请求时间戳（毫秒）
+    /// 生成请求时间戳（毫秒），已按交易所服务器时间校准
     pub fn timestamp() -> u64 {
-        Utc::now().timestamp_millis() as u64
+        ClockSync::for_exchange(ExchangeName::Binance).now_ms() as u64
     }
 
     /// 构建查询字符串
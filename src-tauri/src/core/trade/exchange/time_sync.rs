@@ -0,0 +1,180 @@
+//! Exchange server time synchronisation
+//!
+//! Signed requests carry a millisecond timestamp that the exchange checks
+//! against its own clock (Binance rejects anything outside `recvWindow` with
+//! `-1021`). A drifting local clock therefore breaks every signed call.
+//!
+//! `ClockSync` keeps a per-exchange offset measured from the exchange's
+//! server-time endpoint. All signing code takes its timestamp from
+//! `ClockSync::now_ms` instead of the local clock.
+
+use super::r#trait::{Exchange, ExchangeName};
+use anyhow::Result;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Sentinel for "never measured"
+const UNSET: i64 = i64::MIN;
+
+/// Measured clock offset for one exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockSyncStatus {
+    pub exchange: String,
+    /// Server time minus local time (ms); positive means the local clock is behind
+    pub offset_ms: i64,
+    /// Round trip of the last sync request (ms)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rtt_ms: Option<i64>,
+    /// Local time of the last successful sync (ms)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_sync_at: Option<i64>,
+}
+
+/// Local-to-server clock offset for one exchange
+pub struct ClockSync {
+    exchange: ExchangeName,
+    offset_ms: AtomicI64,
+    rtt_ms: AtomicI64,
+    last_sync_at: AtomicI64,
+}
+
+lazy_static::lazy_static! {
+    /// One clock per exchange, shared by every signer in the process
+    static ref CLOCKS: Mutex<HashMap<ExchangeName, Arc<ClockSync>>> =
+        Mutex::new(HashMap::new());
+}
+
+impl ClockSync {
+    /// Create a standalone clock with zero offset
+    pub fn new(exchange: ExchangeName) -> Self {
+        Self {
+            exchange,
+            offset_ms: AtomicI64::new(0),
+            rtt_ms: AtomicI64::new(UNSET),
+            last_sync_at: AtomicI64::new(UNSET),
+        }
+    }
+
+    /// Get the process-wide clock for an exchange
+    pub fn for_exchange(exchange: ExchangeName) -> Arc<Self> {
+        let mut clocks = CLOCKS.lock().unwrap_or_else(|e| e.into_inner());
+        clocks
+            .entry(exchange)
+            .or_insert_with(|| Arc::new(Self::new(exchange)))
+            .clone()
+    }
+
+    /// Estimated exchange server time (ms)
+    pub fn now_ms(&self) -> i64 {
+        Utc::now().timestamp_millis() + self.offset_ms()
+    }
+
+    /// Current offset (server - local, ms)
+    pub fn offset_ms(&self) -> i64 {
+        self.offset_ms.load(Ordering::Relaxed)
+    }
+
+    /// Record a server time sample
+    ///
+    /// The server stamped its reply somewhere between the two local
+    /// timestamps, so it is compared against their midpoint.
+    pub fn record_sample(&self, local_before: i64, server_ms: i64, local_after: i64) -> i64 {
+        let rtt = (local_after - local_before).max(0);
+        let offset = server_ms - (local_before + rtt / 2);
+
+        self.offset_ms.store(offset, Ordering::Relaxed);
+        self.rtt_ms.store(rtt, Ordering::Relaxed);
+        self.last_sync_at.store(local_after, Ordering::Relaxed);
+        offset
+    }
+
+    /// Query the exchange's server time and update the offset
+    ///
+    /// Returns the new offset.
+    pub async fn sync_with(&self, exchange: &dyn Exchange) -> Result<i64> {
+        let local_before = Utc::now().timestamp_millis();
+        let server_ms = exchange.get_server_time().await?;
+        let local_after = Utc::now().timestamp_millis();
+
+        let offset = self.record_sample(local_before, server_ms, local_after);
+        log::debug!(
+            "{} clock offset {}ms (rtt {}ms)",
+            self.exchange,
+            offset,
+            local_after - local_before
+        );
+        Ok(offset)
+    }
+
+    /// Statuses of every exchange clock that has been synced at least once
+    pub fn synced_statuses() -> Vec<ClockSyncStatus> {
+        let clocks = CLOCKS.lock().unwrap_or_else(|e| e.into_inner());
+        let mut statuses: Vec<ClockSyncStatus> = clocks
+            .values()
+            .map(|clock| clock.status())
+            .filter(|status| status.last_sync_at.is_some())
+            .collect();
+        statuses.sort_by(|a, b| a.exchange.cmp(&b.exchange));
+        statuses
+    }
+
+    /// Snapshot of the measured offset
+    pub fn status(&self) -> ClockSyncStatus {
+        let optional = |v: i64| if v == UNSET { None } else { Some(v) };
+
+        ClockSyncStatus {
+            exchange: self.exchange.to_string(),
+            offset_ms: self.offset_ms(),
+            rtt_ms: optional(self.rtt_ms.load(Ordering::Relaxed)),
+            last_sync_at: optional(self.last_sync_at.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_clock_has_no_offset() {
+        let clock = ClockSync::new(ExchangeName::Binance);
+        let status = clock.status();
+
+        assert_eq!(status.offset_ms, 0);
+        assert!(status.rtt_ms.is_none());
+        assert!(status.last_sync_at.is_none());
+    }
+
+    #[test]
+    fn test_record_sample_uses_rtt_midpoint() {
+        let clock = ClockSync::new(ExchangeName::Binance);
+
+        // Request took 100ms, server stamped 1500ms past the midpoint
+        let offset = clock.record_sample(10_000, 11_550, 10_100);
+
+        assert_eq!(offset, 1_500);
+        let status = clock.status();
+        assert_eq!(status.rtt_ms, Some(100));
+        assert_eq!(status.last_sync_at, Some(10_100));
+    }
+
+    #[test]
+    fn test_now_ms_applies_offset() {
+        let clock = ClockSync::new(ExchangeName::OKX);
+        clock.record_sample(0, -5_000, 0);
+
+        let drift = Utc::now().timestamp_millis() - clock.now_ms();
+        assert!((4_900..=5_100).contains(&drift));
+    }
+
+    #[test]
+    fn test_for_exchange_is_shared() {
+        let a = ClockSync::for_exchange(ExchangeName::Bybit);
+        let b = ClockSync::for_exchange(ExchangeName::Bybit);
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+}
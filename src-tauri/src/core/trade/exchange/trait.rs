@@ -1,11 +1,12 @@
 use super::super::types::*;
+use super::rate_limit::{RateLimitUsage, RateLimiter};
 use async_trait::async_trait;
 use anyhow::Result;
 use tokio::sync::broadcast;
 use std::fmt;
 
 /// 交易所名称
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ExchangeName {
     Binance,
     OKX,
@@ -50,6 +51,11 @@ pub trait Exchange: Send + Sync {
     fn name(&self) -> ExchangeName;
     fn is_connected(&self) -> bool;
 
+    /// Current REST rate-limit usage for this exchange
+    fn rate_limit_usage(&self) -> RateLimitUsage {
+        RateLimiter::for_exchange(self.name()).usage()
+    }
+
     // ========== 连接管理 ==========
     async fn connect(&self) -> Result<()>;
     async fn disconnect(&self) -> Result<()>;
@@ -63,6 +69,18 @@ pub trait Exchange: Send + Sync {
         limit: usize,
     ) -> Result<Vec<Kline>>;
 
+    /// Order book depth, up to `limit` levels per side
+    async fn get_order_book(&self, symbol: &str, limit: usize) -> Result<OrderBook> {
+        let _ = limit;
+        Err(anyhow::anyhow!("{} does not provide order book depth for {}", self.name(), symbol))
+    }
+
+    /// Get the exchange server time (ms since epoch)
+    async fn get_server_time(&self) -> Result<i64>;
+
+    /// Get trading rules (tick size, lot size, min notional) for all spot symbols
+    async fn get_instruments(&self) -> Result<Vec<Instrument>>;
+
     // ========== 行情数据 (WebSocket订阅) ==========
     async fn subscribe_ticker(&self, symbols: Vec<String>) -> Result<()>;
     async fn subscribe_kline(
@@ -75,6 +93,8 @@ pub trait Exchange: Send + Sync {
     fn ticker_stream(&self) -> broadcast::Receiver<Ticker>;
     fn kline_stream(&self) -> broadcast::Receiver<Kline>;
     fn order_stream(&self) -> broadcast::Receiver<Order>;
+    /// Individual executions from the user data stream
+    fn fill_stream(&self) -> broadcast::Receiver<Fill>;
 
     // ========== 用户数据流订阅 ==========
     /// Subscribe to user data stream for real-time order/account updates
@@ -87,8 +107,72 @@ pub trait Exchange: Send + Sync {
     /// Cancel an existing order
     async fn cancel_order(&self, order_id: &str) -> Result<()>;
 
-    /// Get order details by order ID
-    async fn get_order(&self, order_id: &str) -> Result<Order>;
+    /// Place several orders, through the venue's batch endpoint where it has one
+    ///
+    /// Results are in request order; one rejected order doesn't fail the
+    /// others. The outer error means the batch as a whole didn't go out.
+    async fn place_orders(&self, requests: &[OrderRequest]) -> Result<Vec<Result<Order>>> {
+        let mut results = Vec::with_capacity(requests.len());
+        for request in requests {
+            results.push(self.place_order(request).await);
+        }
+        Ok(results)
+    }
+
+    /// Cancel several orders, through the venue's batch endpoint where it has one
+    ///
+    /// Orders are identified by `exchange_order_id` and `symbol`. Results are
+    /// in input order.
+    async fn cancel_orders(&self, orders: &[Order]) -> Result<Vec<Result<()>>> {
+        let mut results = Vec::with_capacity(orders.len());
+        for order in orders {
+            results.push(match order.exchange_order_id.as_deref() {
+                Some(exchange_order_id) => self.cancel_order(exchange_order_id).await,
+                None => Err(anyhow::anyhow!("Order {} has no exchange order id", order.id)),
+            });
+        }
+        Ok(results)
+    }
+
+    /// Cancel every open order on a symbol
+    ///
+    /// Returns how many orders the venue canceled.
+    async fn cancel_all_orders(&self, symbol: &str) -> Result<usize> {
+        let open = self.get_open_orders(Some(symbol)).await?;
+        let results = self.cancel_orders(&open).await?;
+        Ok(results.iter().filter(|r| r.is_ok()).count())
+    }
+
+    /// Arm the venue's cancel-all-after timer
+    ///
+    /// Unless armed again within `timeout_secs`, the venue cancels every
+    /// open order of the account; `0` turns the timer off. Venues clamp the
+    /// timeout to the range they accept. Returns `Ok(false)` when the venue
+    /// has no such timer, leaving the orders to the local watchdog.
+    async fn cancel_all_after(&self, timeout_secs: u64) -> Result<bool> {
+        let _ = timeout_secs;
+        Ok(false)
+    }
+
+    /// Change the price and/or quantity of a resting order
+    ///
+    /// `order` is the order as currently known; its `exchange_order_id` and
+    /// `symbol` identify it on the venue. Venues without native amend cancel
+    /// the order and place a new one for the unfilled quantity, reported as
+    /// `AmendOutcome::Replaced`.
+    async fn amend_order(&self, order: &Order, amend: &AmendRequest) -> Result<AmendOutcome>;
+
+    /// Get order details by the exchange order id
+    ///
+    /// Venues look orders up per symbol, so the order's symbol is needed too.
+    async fn get_order(&self, symbol: &str, order_id: &str) -> Result<Order>;
+
+    /// Look up an order by the client order id it was placed with
+    ///
+    /// Returns None when the venue has no such order. Used to find out
+    /// whether an order whose placement response was lost reached the
+    /// exchange.
+    async fn get_order_by_client_id(&self, symbol: &str, client_order_id: &str) -> Result<Option<Order>>;
 
     /// Get all open orders
     async fn get_open_orders(&self, symbol: Option<&str>) -> Result<Vec<Order>>;
@@ -0,0 +1,398 @@
+//! Execution algorithm engine
+//!
+//! Runs parent orders of one exchange account: records the parent, works
+//! its child orders on a background task, and rolls child fills up into the
+//! parent after every child.
+
+use super::schedule::{
+    child_quantity, iceberg_clips, twap_schedule, volume_profile, vwap_weights, weighted_schedule,
+    AlgoOrderRequest, ChildSlice, ExecutionAlgo, ExecutionReport,
+};
+use crate::core::trade::exchange::Exchange;
+use crate::core::trade::types::{Interval, Order, OrderRequest, OrderState};
+use crate::core::{AppError, AppResult};
+use crate::services::TradeService;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Hourly klines used for the VWAP volume profile (one week)
+const VWAP_PROFILE_HOURS: usize = 168;
+
+/// How often a resting iceberg clip is checked
+const ICEBERG_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Child quantities below this are not worth sending
+const MIN_CHILD_QTY: f64 = 1e-12;
+
+/// Attempts at an exchange or database call before a child gives up on it
+const CALL_ATTEMPTS: u32 = 3;
+
+/// Wait between attempts
+const CALL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Iceberg clips that may end unfilled in a row before the algo stops
+const MAX_UNFILLED_CLIPS: u32 = 3;
+
+/// Run `call`, retrying failures that may pass
+///
+/// Validation and risk refusals come back straight away; sending the same
+/// request again won't change them.
+async fn with_retries<T, F, Fut>(what: &str, mut call: F) -> AppResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = AppResult<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match call().await {
+            Ok(value) => return Ok(value),
+            Err(e @ (AppError::Validation(_) | AppError::RiskLimit(_))) => return Err(e),
+            Err(e) if attempt < CALL_ATTEMPTS => {
+                log::warn!("{} failed (attempt {}), retrying: {}", what, attempt, e);
+                tokio::time::sleep(CALL_RETRY_DELAY).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Works execution-algo parent orders on one exchange account
+pub struct ExecutionAlgoEngine {
+    exchange: Arc<dyn Exchange>,
+    trade_service: Arc<TradeService>,
+    running: Mutex<HashMap<String, JoinHandle<()>>>,
+}
+
+impl ExecutionAlgoEngine {
+    /// Create an engine for an exchange account
+    pub fn new(exchange: Arc<dyn Exchange>, trade_service: Arc<TradeService>) -> Self {
+        Self {
+            exchange,
+            trade_service,
+            running: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Submit a parent order and start working it
+    ///
+    /// Returns the parent order; child orders follow in the background.
+    pub async fn submit(
+        self: &Arc<Self>,
+        user_id: &str,
+        request: AlgoOrderRequest,
+        instance_id: Option<&str>,
+    ) -> AppResult<Order> {
+        request.validate()?;
+
+        let arrival_price = match self.exchange.get_ticker(&request.symbol).await {
+            Ok(ticker) => Some(ticker.price),
+            Err(e) => {
+                log::warn!("No arrival price for {}, slippage won't be reported: {}", request.symbol, e);
+                None
+            }
+        };
+        let schedule = self.schedule(&request).await;
+        let min_qty = self.min_child_qty(&request, arrival_price).await;
+
+        let parent = self
+            .trade_service
+            .create_parent_order(request.parent_request(), request.algo.name(), arrival_price, user_id, instance_id)
+            .await?;
+        log::info!(
+            "Execution algo started: {} {} {} {} {} in {} children",
+            parent.id, request.algo.name(), request.side, parent.quantity, request.symbol, schedule.len()
+        );
+
+        let engine = self.clone();
+        let parent_id = parent.id.clone();
+        let user_id = user_id.to_string();
+        let instance_id = instance_id.map(str::to_string);
+        let handle = tokio::spawn(async move {
+            let instance_id = instance_id.as_deref();
+            let result = match request.algo {
+                ExecutionAlgo::Iceberg { clip_size } => {
+                    engine.run_iceberg(&parent_id, &user_id, instance_id, &request, clip_size, min_qty).await
+                }
+                _ => engine.run_timed(&parent_id, &user_id, instance_id, &request, &schedule, min_qty).await,
+            };
+            if let Err(e) = result {
+                log::error!("Execution algo {} stopped: {}", parent_id, e);
+            }
+            engine.finish(&parent_id).await;
+        });
+        self.running.lock().unwrap().insert(parent.id.clone(), handle);
+
+        Ok(parent)
+    }
+
+    /// Stop working a parent order and cancel its resting children
+    pub async fn cancel(&self, user_id: &str, parent_order_id: &str) -> AppResult<ExecutionReport> {
+        // Ownership check
+        let report = self.trade_service.get_execution_report(parent_order_id, user_id).await?;
+        if report.status.is_terminal() {
+            return Ok(report);
+        }
+
+        if let Some(handle) = self.running.lock().unwrap().remove(parent_order_id) {
+            handle.abort();
+        }
+        for child in self.trade_service.get_child_orders(parent_order_id).await? {
+            if child.status.is_active() {
+                if let Err(e) = self.trade_service.cancel_order(&child.id, user_id).await {
+                    log::warn!("Failed to cancel child order {}: {}", child.id, e);
+                }
+            }
+        }
+
+        self.trade_service.refresh_parent_order(parent_order_id, true).await?;
+        self.trade_service.get_execution_report(parent_order_id, user_id).await
+    }
+
+    /// Close out parent orders left running by a previous session
+    ///
+    /// Their background tasks died with the process, so they are settled
+    /// with whatever their children filled.
+    pub async fn recover(&self) -> AppResult<usize> {
+        let orphans = self.trade_service.get_active_parent_orders().await?;
+        for parent in &orphans {
+            self.trade_service.refresh_parent_order(&parent.id, true).await?;
+            log::info!("Execution algo {} closed after restart", parent.id);
+        }
+        Ok(orphans.len())
+    }
+
+    /// Stop all running parent orders without settling them
+    pub fn abort_all(&self) {
+        for (_, handle) in self.running.lock().unwrap().drain() {
+            handle.abort();
+        }
+    }
+
+    /// Child slices for a request
+    ///
+    /// Iceberg clips carry no due time; they are placed back to back.
+    async fn schedule(&self, request: &AlgoOrderRequest) -> Vec<ChildSlice> {
+        let now = Utc::now().timestamp_millis();
+        match request.algo {
+            ExecutionAlgo::Twap { duration_secs, slices } => twap_schedule(request.quantity, now, duration_secs, slices),
+            ExecutionAlgo::Vwap { duration_secs, slices } => {
+                let klines = self
+                    .exchange
+                    .get_klines(&request.symbol, Interval::OneHour, VWAP_PROFILE_HOURS)
+                    .await
+                    .unwrap_or_else(|e| {
+                        log::warn!("No volume history for {}, VWAP falls back to TWAP: {}", request.symbol, e);
+                        Vec::new()
+                    });
+                let weights = vwap_weights(&volume_profile(&klines), now, duration_secs, slices);
+                weighted_schedule(request.quantity, now, duration_secs, &weights)
+            }
+            ExecutionAlgo::Iceberg { clip_size } => iceberg_clips(request.quantity, clip_size)
+                .into_iter()
+                .map(|quantity| ChildSlice { due_at: now, quantity })
+                .collect(),
+        }
+    }
+
+    /// Smallest child order the venue accepts for the request
+    ///
+    /// The minimum notional is converted at the limit price or, for market
+    /// children, the arrival price. Without exchange rules nothing is
+    /// assumed and the venue has the last word.
+    async fn min_child_qty(&self, request: &AlgoOrderRequest, arrival_price: Option<f64>) -> f64 {
+        let instrument = match self.trade_service.instruments().get(&request.symbol).await {
+            Ok(Some(instrument)) => instrument,
+            Ok(None) => return MIN_CHILD_QTY,
+            Err(e) => {
+                log::warn!("No trading rules for {}, child orders aren't sized to them: {}", request.symbol, e);
+                return MIN_CHILD_QTY;
+            }
+        };
+        let by_notional = match request.limit_price.or(arrival_price) {
+            Some(price) if price > 0.0 => instrument.min_notional / price,
+            _ => 0.0,
+        };
+        instrument.min_qty.max(by_notional).max(MIN_CHILD_QTY)
+    }
+
+    /// TWAP/VWAP: send each slice at its due time
+    ///
+    /// A slice that can't be placed or is below the venue minimum rolls into
+    /// the next one; the last slice picks up whatever is left.
+    async fn run_timed(
+        &self,
+        parent_id: &str,
+        user_id: &str,
+        instance_id: Option<&str>,
+        request: &AlgoOrderRequest,
+        schedule: &[ChildSlice],
+        min_qty: f64,
+    ) -> AppResult<()> {
+        let mut children: Vec<String> = Vec::with_capacity(schedule.len());
+        let mut carried = 0.0;
+        for (i, slice) in schedule.iter().enumerate() {
+            let wait = slice.due_at - Utc::now().timestamp_millis();
+            if wait > 0 {
+                tokio::time::sleep(Duration::from_millis(wait as u64)).await;
+            }
+
+            let last = i == schedule.len() - 1;
+            // Not every venue reports fills in the placement answer, so the
+            // children are synced before the top-up is sized
+            let mut outstanding = 0.0;
+            if last {
+                for child_id in &children {
+                    let child = with_retries("Child order sync", || {
+                        self.trade_service.sync_order_status(child_id, user_id)
+                    })
+                    .await?;
+                    if !child.status.is_terminal() {
+                        outstanding += child.quantity - child.filled_quantity;
+                    }
+                }
+            }
+
+            let parent =
+                with_retries("Parent order refresh", || self.trade_service.refresh_parent_order(parent_id, false))
+                    .await?;
+            if !parent.status.is_active() {
+                return Ok(());
+            }
+            // The last slice picks up what earlier slices left unfilled,
+            // leaving children still working on the book to fill
+            let remaining = parent.quantity - parent.filled_quantity - outstanding;
+            let planned = slice.quantity + carried;
+            let Some(quantity) = child_quantity(planned, remaining, min_qty, last) else {
+                if last && remaining > MIN_CHILD_QTY {
+                    log::warn!(
+                        "Execution algo {}: {} left is below the venue minimum of {}, not sent",
+                        parent_id, remaining, min_qty
+                    );
+                }
+                carried = planned;
+                continue;
+            };
+
+            match self.place_child(parent_id, user_id, instance_id, request, quantity).await {
+                Some(child) => {
+                    children.push(child.id);
+                    carried = 0.0;
+                }
+                None => carried = quantity,
+            }
+        }
+        Ok(())
+    }
+
+    /// Iceberg: keep one clip on the book until the parent is filled
+    ///
+    /// Whatever a clip leaves unfilled goes into the next one.
+    async fn run_iceberg(
+        &self,
+        parent_id: &str,
+        user_id: &str,
+        instance_id: Option<&str>,
+        request: &AlgoOrderRequest,
+        clip_size: f64,
+        min_qty: f64,
+    ) -> AppResult<()> {
+        let mut unfilled = 0;
+        loop {
+            let parent =
+                with_retries("Parent order refresh", || self.trade_service.refresh_parent_order(parent_id, false))
+                    .await?;
+            if !parent.status.is_active() {
+                return Ok(());
+            }
+            let remaining = parent.quantity - parent.filled_quantity;
+            let Some(quantity) = child_quantity(clip_size, remaining, min_qty, false) else {
+                if remaining > MIN_CHILD_QTY {
+                    log::warn!(
+                        "Execution algo {}: {} left is below the venue minimum of {}, not sent",
+                        parent_id, remaining, min_qty
+                    );
+                }
+                return Ok(());
+            };
+
+            let status = match self.place_child(parent_id, user_id, instance_id, request, quantity).await {
+                Some(child) => {
+                    let mut status = child.status;
+                    while !status.is_terminal() {
+                        tokio::time::sleep(ICEBERG_POLL_INTERVAL).await;
+                        match self.trade_service.sync_order_status(&child.id, user_id).await {
+                            Ok(child) => status = child.status,
+                            Err(e) => log::warn!("Iceberg clip {} not synced: {}", child.id, e),
+                        }
+                    }
+                    status
+                }
+                None => OrderState::Rejected,
+            };
+            if status == OrderState::Filled {
+                unfilled = 0;
+                continue;
+            }
+            unfilled += 1;
+            if unfilled >= MAX_UNFILLED_CLIPS {
+                return Err(AppError::Exchange(format!("{} iceberg clips in a row ended unfilled", unfilled)));
+            }
+            log::warn!("Execution algo {}: iceberg clip ended {:?}, placing the rest again", parent_id, status);
+            tokio::time::sleep(ICEBERG_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Place a child order, retrying under one client order id
+    ///
+    /// Returns `None` if it couldn't be placed. A child whose outcome is
+    /// unknown is returned as recorded; syncing it settles it.
+    async fn place_child(
+        &self,
+        parent_id: &str,
+        user_id: &str,
+        instance_id: Option<&str>,
+        request: &AlgoOrderRequest,
+        quantity: f64,
+    ) -> Option<Order> {
+        let client_order_id = OrderRequest::new_client_order_id("algo");
+        let child = OrderRequest {
+            client_order_id: Some(client_order_id.clone()),
+            ..request.child_request(quantity)
+        };
+        let placed = with_retries("Child order", || {
+            self.trade_service.place_child_order(child.clone(), user_id, instance_id, parent_id)
+        })
+        .await;
+
+        let error = match placed {
+            Ok(child) => return Some(child),
+            Err(e) => e,
+        };
+        match self.trade_service.find_order_by_client_id(&client_order_id, user_id).await {
+            Ok(Some(recorded)) if recorded.status == OrderState::Pending => {
+                log::warn!("Execution algo {}: child {} has an unknown outcome: {}", parent_id, recorded.id, error);
+                Some(recorded)
+            }
+            _ => {
+                log::warn!("Execution algo {}: child order of {} failed: {}", parent_id, quantity, error);
+                None
+            }
+        }
+    }
+
+    async fn finish(&self, parent_id: &str) {
+        self.running.lock().unwrap().remove(parent_id);
+        match self.trade_service.refresh_parent_order(parent_id, true).await {
+            Ok(parent) => log::info!(
+                "Execution algo {} finished: {:?}, filled {} of {} @ {:?}",
+                parent.id, parent.status, parent.filled_quantity, parent.quantity, parent.avg_price
+            ),
+            Err(e) => log::error!("Failed to settle parent order {}: {}", parent_id, e),
+        }
+    }
+}
@@ -0,0 +1,15 @@
+//! Execution algorithms
+//!
+//! Slices a large parent order into child orders: evenly over time (TWAP),
+//! along the historical intraday volume profile (VWAP), or as a series of
+//! visible limit clips (iceberg). Parent and children are rows of the
+//! `orders` table linked by `parent_order_id`.
+
+pub mod schedule;
+pub mod engine;
+
+pub use schedule::{
+    child_quantity, iceberg_clips, slippage_bps, twap_schedule, volume_profile, vwap_weights, weighted_schedule,
+    AlgoOrderRequest, ChildSlice, ExecutionAlgo, ExecutionPolicy, ExecutionReport,
+};
+pub use engine::ExecutionAlgoEngine;
@@ -0,0 +1,398 @@
+//! Child order schedules
+//!
+//! Pure functions that split a parent quantity into child slices and
+//! measure execution quality against the arrival price.
+
+use crate::core::trade::types::{Kline, OrderRequest, OrderSide, OrderState, OrderType, TimeInForce};
+use crate::core::{AppError, AppResult};
+use chrono::{TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Slicing algorithm of a parent order
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "algo", rename_all = "snake_case", rename_all_fields = "camelCase")]
+pub enum ExecutionAlgo {
+    /// Equal slices at a fixed interval over `duration_secs`
+    Twap { duration_secs: u64, slices: usize },
+    /// Slices weighted by the average traded volume of each hour of day
+    Vwap { duration_secs: u64, slices: usize },
+    /// Limit clips of `clip_size`, each placed once the previous one filled
+    Iceberg { clip_size: f64 },
+}
+
+impl ExecutionAlgo {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Twap { .. } => "twap",
+            Self::Vwap { .. } => "vwap",
+            Self::Iceberg { .. } => "iceberg",
+        }
+    }
+
+    /// Check the algo parameters for a parent order
+    pub fn validate(&self, quantity: f64, limit_price: Option<f64>) -> AppResult<()> {
+        match *self {
+            Self::Twap { duration_secs, slices } | Self::Vwap { duration_secs, slices } => {
+                if slices == 0 || duration_secs == 0 {
+                    return Err(AppError::validation("Duration and slice count must be positive"));
+                }
+            }
+            Self::Iceberg { clip_size } => {
+                if clip_size <= 0.0 || clip_size > quantity {
+                    return Err(AppError::validation("Clip size must be positive and not above the order quantity"));
+                }
+                if limit_price.is_none() {
+                    return Err(AppError::validation("Iceberg orders require a limit price"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// When a strategy routes its signals through an execution algo
+///
+/// Read from the `execution` strategy parameter, e.g.
+/// `{"algo": "twap", "durationSecs": 300, "slices": 5, "minQuantity": 1.0}`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionPolicy {
+    #[serde(flatten)]
+    pub algo: ExecutionAlgo,
+    /// Signals below this quantity go out as a single order
+    #[serde(default)]
+    pub min_quantity: f64,
+}
+
+impl ExecutionPolicy {
+    /// Parse the policy from strategy parameters, if configured
+    pub fn from_parameters(parameters: &serde_json::Value) -> Option<Self> {
+        let value = parameters.get("execution")?;
+        match serde_json::from_value(value.clone()) {
+            Ok(policy) => Some(policy),
+            Err(e) => {
+                log::warn!("Ignoring invalid execution parameter: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn applies_to(&self, quantity: f64) -> bool {
+        quantity >= self.min_quantity
+    }
+}
+
+/// Parameters for a new parent order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlgoOrderRequest {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: f64,
+    /// Limit price of the child orders; market children if absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_price: Option<f64>,
+    pub algo: ExecutionAlgo,
+}
+
+impl AlgoOrderRequest {
+    pub fn validate(&self) -> AppResult<()> {
+        if self.quantity <= 0.0 {
+            return Err(AppError::validation("Order quantity must be positive"));
+        }
+        if self.limit_price.is_some_and(|p| p <= 0.0) {
+            return Err(AppError::validation("Limit price must be positive"));
+        }
+        self.algo.validate(self.quantity, self.limit_price)
+    }
+
+    /// The parent order as recorded in the `orders` table
+    pub fn parent_request(&self) -> OrderRequest {
+        OrderRequest {
+            symbol: self.symbol.clone(),
+            side: self.side,
+            order_type: if self.limit_price.is_some() { OrderType::Limit } else { OrderType::Market },
+            price: self.limit_price,
+            stop_price: None,
+            quantity: self.quantity,
+            client_order_id: None,
+            time_in_force: None,
+        }
+    }
+
+    /// A child order of `quantity`
+    ///
+    /// Timed slices are IOC so an unfilled remainder doesn't linger; iceberg
+    /// clips rest on the book until filled.
+    pub fn child_request(&self, quantity: f64) -> OrderRequest {
+        let time_in_force = match (self.algo, self.limit_price) {
+            (ExecutionAlgo::Iceberg { .. }, _) => Some(TimeInForce::GTC),
+            (_, Some(_)) => Some(TimeInForce::IOC),
+            (_, None) => None,
+        };
+        OrderRequest {
+            quantity,
+            time_in_force,
+            ..self.parent_request()
+        }
+    }
+}
+
+/// One scheduled child order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChildSlice {
+    /// When to submit (ms since epoch)
+    pub due_at: i64,
+    pub quantity: f64,
+}
+
+/// Split `quantity` over `duration_secs` in proportion to `weights`
+///
+/// Slice `i` is due at `start + i * duration / n`. The last slice takes the
+/// rounding remainder so the slices sum to `quantity`. Non-positive or empty
+/// weights fall back to equal slices.
+pub fn weighted_schedule(quantity: f64, start_ms: i64, duration_secs: u64, weights: &[f64]) -> Vec<ChildSlice> {
+    let n = weights.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let total: f64 = weights.iter().filter(|w| **w > 0.0).sum();
+    let step_ms = (duration_secs as i64 * 1000) / n as i64;
+
+    let mut slices = Vec::with_capacity(n);
+    let mut allocated = 0.0;
+    for (i, weight) in weights.iter().enumerate() {
+        let qty = if i == n - 1 {
+            quantity - allocated
+        } else if total > 0.0 {
+            quantity * weight.max(0.0) / total
+        } else {
+            quantity / n as f64
+        };
+        allocated += qty;
+        slices.push(ChildSlice {
+            due_at: start_ms + i as i64 * step_ms,
+            quantity: qty,
+        });
+    }
+    slices
+}
+
+/// Equal slices over `duration_secs`
+pub fn twap_schedule(quantity: f64, start_ms: i64, duration_secs: u64, slices: usize) -> Vec<ChildSlice> {
+    weighted_schedule(quantity, start_ms, duration_secs, &vec![1.0; slices])
+}
+
+/// Average volume traded in each UTC hour of day
+pub fn volume_profile(klines: &[Kline]) -> [f64; 24] {
+    let mut volume = [0.0; 24];
+    let mut samples = [0usize; 24];
+    for kline in klines {
+        if let Some(time) = Utc.timestamp_millis_opt(kline.timestamp).single() {
+            let hour = time.hour() as usize;
+            volume[hour] += kline.volume;
+            samples[hour] += 1;
+        }
+    }
+    for hour in 0..24 {
+        if samples[hour] > 0 {
+            volume[hour] /= samples[hour] as f64;
+        }
+    }
+    volume
+}
+
+/// Weight of each slice: the profile volume of the hour the slice starts in
+pub fn vwap_weights(profile: &[f64; 24], start_ms: i64, duration_secs: u64, slices: usize) -> Vec<f64> {
+    if slices == 0 {
+        return Vec::new();
+    }
+    let step_ms = (duration_secs as i64 * 1000) / slices as i64;
+    (0..slices)
+        .map(|i| {
+            let due_at = start_ms + i as i64 * step_ms;
+            Utc.timestamp_millis_opt(due_at)
+                .single()
+                .map(|t| profile[t.hour() as usize])
+                .unwrap_or(0.0)
+        })
+        .collect()
+}
+
+/// Split `quantity` into clips of at most `clip_size`
+pub fn iceberg_clips(quantity: f64, clip_size: f64) -> Vec<f64> {
+    let mut clips = Vec::new();
+    let mut remaining = quantity;
+    while remaining > 1e-12 {
+        let clip = clip_size.min(remaining);
+        clips.push(clip);
+        remaining -= clip;
+    }
+    clips
+}
+
+/// Size of the next child order; `None` when it isn't worth sending
+///
+/// `planned` is the slice's share plus anything rolled over from slices
+/// that weren't sent, `remaining` what the parent still needs. The last
+/// child takes everything remaining. A remainder below the venue minimum
+/// `min_qty` goes with this child rather than being left for later, and a
+/// child below the minimum isn't sent.
+pub fn child_quantity(planned: f64, remaining: f64, min_qty: f64, last: bool) -> Option<f64> {
+    let mut quantity = if last { remaining } else { planned.min(remaining) };
+    if remaining - quantity < min_qty {
+        quantity = remaining;
+    }
+    (quantity >= min_qty && quantity > 1e-12).then_some(quantity)
+}
+
+/// Slippage of the average fill versus the arrival price, in basis points
+///
+/// Positive values are a cost: paying above arrival on a buy, receiving
+/// below it on a sell.
+pub fn slippage_bps(side: OrderSide, arrival_price: f64, avg_price: f64) -> f64 {
+    if arrival_price <= 0.0 {
+        return 0.0;
+    }
+    let diff = match side {
+        OrderSide::Buy => avg_price - arrival_price,
+        OrderSide::Sell => arrival_price - avg_price,
+    };
+    diff / arrival_price * 10_000.0
+}
+
+/// Execution quality of a parent order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionReport {
+    pub parent_order_id: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub algo: String,
+    pub status: OrderState,
+    pub quantity: f64,
+    pub filled_quantity: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arrival_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slippage_bps: Option<f64>,
+    pub commission: f64,
+    pub child_orders: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: i64 = 1_767_787_200_000; // Wed 2026-01-07 12:00 UTC
+    const HOUR_MS: i64 = 3_600_000;
+
+    fn kline(timestamp: i64, volume: f64) -> Kline {
+        Kline {
+            symbol: "BTCUSDT".to_string(),
+            timeframe: "1h".to_string(),
+            timestamp,
+            open: 100.0,
+            high: 100.0,
+            low: 100.0,
+            close: 100.0,
+            volume,
+            quote_volume: None,
+        }
+    }
+
+    #[test]
+    fn test_twap_schedule_even_slices() {
+        let slices = twap_schedule(1.0, START, 600, 4);
+        assert_eq!(slices.len(), 4);
+        assert_eq!(slices[1].due_at - slices[0].due_at, 150_000);
+        assert!((slices.iter().map(|s| s.quantity).sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!((slices[0].quantity - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_vwap_follows_volume_profile() {
+        // Yesterday: 12:00 traded 3x the volume of 13:00
+        let profile = volume_profile(&[kline(START - 24 * HOUR_MS, 300.0), kline(START - 23 * HOUR_MS, 100.0)]);
+        assert_eq!(profile[12], 300.0);
+        assert_eq!(profile[13], 100.0);
+
+        let weights = vwap_weights(&profile, START, 7200, 2);
+        let slices = weighted_schedule(8.0, START, 7200, &weights);
+        assert!((slices[0].quantity - 6.0).abs() < 1e-9);
+        assert!((slices[1].quantity - 2.0).abs() < 1e-9);
+        assert_eq!(slices[1].due_at, START + HOUR_MS);
+
+        // No volume history: equal slices
+        let flat = weighted_schedule(8.0, START, 7200, &vwap_weights(&[0.0; 24], START, 7200, 2));
+        assert!((flat[0].quantity - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_iceberg_clips_and_validation() {
+        let clips = iceberg_clips(2.5, 1.0);
+        assert_eq!(clips, vec![1.0, 1.0, 0.5]);
+
+        let algo = ExecutionAlgo::Iceberg { clip_size: 1.0 };
+        assert!(algo.validate(2.5, Some(100.0)).is_ok());
+        assert!(algo.validate(2.5, None).is_err());
+        assert!(ExecutionAlgo::Iceberg { clip_size: 3.0 }.validate(2.5, Some(100.0)).is_err());
+        assert!(ExecutionAlgo::Twap { duration_secs: 60, slices: 0 }.validate(1.0, None).is_err());
+    }
+
+    #[test]
+    fn test_child_quantity_respects_venue_minimum() {
+        // Slices go out as planned, the last one takes the rest
+        assert_eq!(child_quantity(1.0, 3.0, 0.1, false), Some(1.0));
+        assert_eq!(child_quantity(1.0, 1.4, 0.1, true), Some(1.4));
+        // A remainder below the minimum goes with this child
+        assert_eq!(child_quantity(1.0, 1.05, 0.1, false), Some(1.05));
+        // A child below the minimum isn't sent
+        assert_eq!(child_quantity(0.05, 3.0, 0.1, false), None);
+        assert_eq!(child_quantity(1.0, 0.05, 0.1, true), None);
+        assert_eq!(child_quantity(1.0, 0.0, 0.0, true), None);
+    }
+
+    #[test]
+    fn test_slippage_sign_by_side() {
+        assert!((slippage_bps(OrderSide::Buy, 100.0, 100.5) - 50.0).abs() < 1e-9);
+        assert!((slippage_bps(OrderSide::Sell, 100.0, 100.5) + 50.0).abs() < 1e-9);
+        assert_eq!(slippage_bps(OrderSide::Buy, 0.0, 100.0), 0.0);
+    }
+
+    #[test]
+    fn test_execution_policy_from_parameters() {
+        let params = serde_json::json!({
+            "period": 14,
+            "execution": {"algo": "vwap", "durationSecs": 600, "slices": 3, "minQuantity": 2.0}
+        });
+        let policy = ExecutionPolicy::from_parameters(&params).unwrap();
+        assert_eq!(policy.algo, ExecutionAlgo::Vwap { duration_secs: 600, slices: 3 });
+        assert!(policy.applies_to(2.0));
+        assert!(!policy.applies_to(1.0));
+
+        assert!(ExecutionPolicy::from_parameters(&serde_json::json!({"period": 14})).is_none());
+        assert!(ExecutionPolicy::from_parameters(&serde_json::json!({"execution": {"algo": "pov"}})).is_none());
+    }
+
+    #[test]
+    fn test_child_request_time_in_force() {
+        let mut request = AlgoOrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            side: OrderSide::Buy,
+            quantity: 2.0,
+            limit_price: None,
+            algo: ExecutionAlgo::Twap { duration_secs: 60, slices: 2 },
+        };
+        let child = request.child_request(1.0);
+        assert_eq!(child.order_type, OrderType::Market);
+        assert_eq!(child.quantity, 1.0);
+
+        request.limit_price = Some(100.0);
+        assert_eq!(request.child_request(1.0).time_in_force, Some(TimeInForce::IOC));
+        request.algo = ExecutionAlgo::Iceberg { clip_size: 1.0 };
+        assert_eq!(request.child_request(1.0).time_in_force, Some(TimeInForce::GTC));
+    }
+}
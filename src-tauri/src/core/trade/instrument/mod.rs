@@ -0,0 +1,10 @@
+//! Instrument metadata module
+//!
+//! This module provides exchange trading rules (tick size, lot size,
+//! min notional) and order precision handling.
+
+pub mod rules;
+pub mod registry;
+
+pub use rules::{decimals_of, floor_to_step, round_to_step};
+pub use registry::InstrumentRegistry;
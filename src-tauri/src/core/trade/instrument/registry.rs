@@ -0,0 +1,236 @@
+//! Instrument registry
+//!
+//! Caches exchange trading rules fetched through `Exchange::get_instruments`
+//! and refreshes them once the cache goes stale. If a refresh fails, the
+//! stale rules keep being served and the refresh is retried later.
+
+use crate::core::trade::exchange::Exchange;
+use crate::core::trade::types::Instrument;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Default cache lifetime; exchange info changes rarely (listings, tick changes)
+const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Wait before retrying a failed refresh of a stale cache
+const REFRESH_RETRY: Duration = Duration::from_secs(60);
+
+struct CachedInstruments {
+    instruments: HashMap<String, Instrument>,
+    loaded_at: Instant,
+    /// Set after a failed refresh; no new attempt before this time
+    retry_at: Option<Instant>,
+}
+
+/// Cached instrument metadata for a single exchange
+pub struct InstrumentRegistry {
+    exchange: Arc<dyn Exchange>,
+    ttl: Duration,
+    cache: RwLock<Option<CachedInstruments>>,
+}
+
+impl InstrumentRegistry {
+    /// Create a registry with the default cache lifetime
+    pub fn new(exchange: Arc<dyn Exchange>) -> Self {
+        Self::with_ttl(exchange, DEFAULT_TTL)
+    }
+
+    /// Create a registry with a custom cache lifetime
+    pub fn with_ttl(exchange: Arc<dyn Exchange>, ttl: Duration) -> Self {
+        Self {
+            exchange,
+            ttl,
+            cache: RwLock::new(None),
+        }
+    }
+
+    /// Normalize a symbol to the registry key format (BTC-USDT -> BTCUSDT)
+    fn key(symbol: &str) -> String {
+        symbol.replace(['-', '_', '/'], "").to_uppercase()
+    }
+
+    /// Replace the cached instruments
+    pub async fn load(&self, instruments: Vec<Instrument>) {
+        let instruments = instruments
+            .into_iter()
+            .map(|i| (Self::key(&i.symbol), i))
+            .collect();
+
+        *self.cache.write().await = Some(CachedInstruments {
+            instruments,
+            loaded_at: Instant::now(),
+            retry_at: None,
+        });
+    }
+
+    /// Fetch instruments from the exchange and replace the cache
+    ///
+    /// Returns the number of instruments loaded.
+    pub async fn refresh(&self) -> Result<usize> {
+        let instruments = self.exchange.get_instruments().await?;
+        let count = instruments.len();
+        self.load(instruments).await;
+
+        log::info!(
+            "Loaded {} instruments from {}",
+            count,
+            self.exchange.name()
+        );
+        Ok(count)
+    }
+
+    /// Returns true if the cache is empty or older than the TTL
+    pub async fn is_stale(&self) -> bool {
+        match self.cache.read().await.as_ref() {
+            Some(cache) => cache.loaded_at.elapsed() >= self.ttl,
+            None => true,
+        }
+    }
+
+    /// Refresh a stale cache
+    ///
+    /// Fails only when nothing was ever loaded; otherwise a failed refresh
+    /// leaves the stale rules in place until the retry.
+    async fn ensure_fresh(&self) -> Result<()> {
+        if !self.is_stale().await {
+            return Ok(());
+        }
+        let retry_pending = self
+            .cache
+            .read()
+            .await
+            .as_ref()
+            .and_then(|c| c.retry_at)
+            .is_some_and(|at| Instant::now() < at);
+        if retry_pending {
+            return Ok(());
+        }
+
+        let Err(e) = self.refresh().await else {
+            return Ok(());
+        };
+        match self.cache.write().await.as_mut() {
+            Some(cache) => {
+                log::warn!(
+                    "Failed to refresh {} instruments, using rules loaded {:?} ago: {}",
+                    self.exchange.name(),
+                    cache.loaded_at.elapsed(),
+                    e
+                );
+                cache.retry_at = Some(Instant::now() + REFRESH_RETRY);
+                Ok(())
+            }
+            None => Err(e),
+        }
+    }
+
+    /// Get the trading rules for a symbol
+    ///
+    /// Returns `Ok(None)` if the exchange doesn't list the symbol.
+    pub async fn get(&self, symbol: &str) -> Result<Option<Instrument>> {
+        self.ensure_fresh().await?;
+
+        let cache = self.cache.read().await;
+        Ok(cache
+            .as_ref()
+            .and_then(|c| c.instruments.get(&Self::key(symbol)))
+            .cloned())
+    }
+
+    /// Get all instruments, sorted by symbol
+    pub async fn list(&self) -> Result<Vec<Instrument>> {
+        self.ensure_fresh().await?;
+
+        let cache = self.cache.read().await;
+        let mut instruments: Vec<Instrument> = cache
+            .as_ref()
+            .map(|c| c.instruments.values().cloned().collect())
+            .unwrap_or_default();
+        instruments.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        Ok(instruments)
+    }
+
+    /// Get the symbols currently open for trading, sorted
+    pub async fn trading_symbols(&self) -> Result<Vec<String>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|i| i.is_trading())
+            .map(|i| i.symbol)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::trade::exchange::BinanceExchange;
+    use crate::core::trade::types::InstrumentStatus;
+
+    fn instrument(symbol: &str, status: InstrumentStatus) -> Instrument {
+        Instrument {
+            symbol: symbol.to_string(),
+            base_asset: symbol.trim_end_matches("USDT").to_string(),
+            quote_asset: "USDT".to_string(),
+            status,
+            tick_size: 0.01,
+            step_size: 0.001,
+            min_qty: 0.001,
+            max_qty: None,
+            min_notional: 5.0,
+        }
+    }
+
+    fn registry() -> InstrumentRegistry {
+        InstrumentRegistry::new(Arc::new(BinanceExchange::new(None, None)))
+    }
+
+    #[tokio::test]
+    async fn test_empty_registry_is_stale() {
+        assert!(registry().is_stale().await);
+    }
+
+    #[tokio::test]
+    async fn test_lookup_normalizes_symbol() {
+        let registry = registry();
+        registry
+            .load(vec![instrument("BTCUSDT", InstrumentStatus::Trading)])
+            .await;
+
+        assert!(!registry.is_stale().await);
+        assert!(registry.get("BTC-USDT").await.unwrap().is_some());
+        assert!(registry.get("btcusdt").await.unwrap().is_some());
+        assert!(registry.get("ETHUSDT").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stale_cache_is_served_when_refresh_fails() {
+        let registry = InstrumentRegistry::with_ttl(Arc::new(BinanceExchange::new(None, None)), Duration::ZERO);
+        registry
+            .load(vec![instrument("BTCUSDT", InstrumentStatus::Trading)])
+            .await;
+
+        // Whether or not the exchange answers, the symbol stays known
+        assert!(registry.get("BTCUSDT").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_trading_symbols_skips_halted() {
+        let registry = registry();
+        registry
+            .load(vec![
+                instrument("ETHUSDT", InstrumentStatus::Trading),
+                instrument("LUNAUSDT", InstrumentStatus::Closed),
+                instrument("BTCUSDT", InstrumentStatus::Trading),
+            ])
+            .await;
+
+        let symbols = registry.trading_symbols().await.unwrap();
+        assert_eq!(symbols, vec!["BTCUSDT", "ETHUSDT"]);
+    }
+}
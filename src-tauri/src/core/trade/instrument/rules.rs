@@ -0,0 +1,252 @@
+//! Order precision rules
+//!
+//! Rounds order prices and quantities to the exchange grid and validates
+//! them against the instrument's trading limits.
+
+use crate::core::trade::types::{Instrument, InstrumentStatus, OrderRequest};
+use anyhow::{bail, Result};
+
+/// Tolerance applied before flooring, absorbs f64 representation error
+/// (e.g. `0.3 / 0.1 = 2.9999999999999996`)
+const STEP_EPSILON: f64 = 1e-9;
+
+/// Returns the number of decimal places implied by a step size
+///
+/// `0.001` -> 3, `0.5` -> 1, `1.0` -> 0
+pub fn decimals_of(step: f64) -> u32 {
+    if step <= 0.0 {
+        return 0;
+    }
+    // f64 Display never uses exponent notation, so this is always "int.frac"
+    let repr = step.to_string();
+    repr.split('.')
+        .nth(1)
+        .map(|frac| frac.trim_end_matches('0').len() as u32)
+        .unwrap_or(0)
+}
+
+/// Rounds a value to the nearest multiple of `step`
+///
+/// A non-positive step leaves the value untouched.
+pub fn round_to_step(value: f64, step: f64) -> f64 {
+    if step <= 0.0 {
+        return value;
+    }
+    trim_decimals((value / step).round() * step, decimals_of(step))
+}
+
+/// Rounds a value down to a multiple of `step`
+///
+/// A non-positive step leaves the value untouched.
+pub fn floor_to_step(value: f64, step: f64) -> f64 {
+    if step <= 0.0 {
+        return value;
+    }
+    trim_decimals((value / step + STEP_EPSILON).floor() * step, decimals_of(step))
+}
+
+fn trim_decimals(value: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    (value * factor).round() / factor
+}
+
+impl Instrument {
+    /// Returns true if the instrument currently accepts orders
+    pub fn is_trading(&self) -> bool {
+        self.status == InstrumentStatus::Trading
+    }
+
+    /// Rounds a price to the nearest tick
+    pub fn round_price(&self, price: f64) -> f64 {
+        round_to_step(price, self.tick_size)
+    }
+
+    /// Rounds a quantity down to the lot size
+    ///
+    /// Quantities are never rounded up so an order can't exceed what the
+    /// caller asked for.
+    pub fn round_quantity(&self, quantity: f64) -> f64 {
+        floor_to_step(quantity, self.step_size)
+    }
+
+    /// Rounds an order request in place and checks it against the trading rules
+    ///
+    /// The min notional check needs a reference price, so it is only applied
+    /// when the request carries a price or stop price. Market orders without
+    /// one are left to the exchange.
+    pub fn normalize_order(&self, request: &mut OrderRequest) -> Result<()> {
+        if !self.is_trading() {
+            bail!("{} is not trading (status: {})", self.symbol, self.status);
+        }
+
+        request.quantity = self.round_quantity(request.quantity);
+        if request.quantity <= 0.0 || request.quantity < self.min_qty {
+            bail!(
+                "Order quantity {} is below the minimum {} for {}",
+                request.quantity,
+                self.min_qty,
+                self.symbol
+            );
+        }
+        if let Some(max_qty) = self.max_qty {
+            if request.quantity > max_qty {
+                bail!(
+                    "Order quantity {} exceeds the maximum {} for {}",
+                    request.quantity,
+                    max_qty,
+                    self.symbol
+                );
+            }
+        }
+
+        if let Some(price) = request.price {
+            let rounded = self.round_price(price);
+            if rounded <= 0.0 {
+                bail!("Order price {} is below the tick size {}", price, self.tick_size);
+            }
+            request.price = Some(rounded);
+        }
+
+        if let Some(stop_price) = request.stop_price {
+            let rounded = self.round_price(stop_price);
+            if rounded <= 0.0 {
+                bail!("Stop price {} is below the tick size {}", stop_price, self.tick_size);
+            }
+            request.stop_price = Some(rounded);
+        }
+
+        if self.min_notional > 0.0 {
+            if let Some(reference_price) = request.price.or(request.stop_price) {
+                let notional = request.quantity * reference_price;
+                if notional < self.min_notional {
+                    bail!(
+                        "Order value {:.8} is below the minimum notional {} for {}",
+                        notional,
+                        self.min_notional,
+                        self.symbol
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::trade::types::{OrderSide, OrderType};
+
+    fn btc_instrument() -> Instrument {
+        Instrument {
+            symbol: "BTCUSDT".to_string(),
+            base_asset: "BTC".to_string(),
+            quote_asset: "USDT".to_string(),
+            status: InstrumentStatus::Trading,
+            tick_size: 0.01,
+            step_size: 0.00001,
+            min_qty: 0.00001,
+            max_qty: Some(9000.0),
+            min_notional: 5.0,
+        }
+    }
+
+    fn limit_request(quantity: f64, price: f64) -> OrderRequest {
+        OrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            price: Some(price),
+            stop_price: None,
+            quantity,
+            client_order_id: None,
+            time_in_force: None,
+        }
+    }
+
+    #[test]
+    fn test_decimals_of() {
+        assert_eq!(decimals_of(0.001), 3);
+        assert_eq!(decimals_of(0.00000001), 8);
+        assert_eq!(decimals_of(0.5), 1);
+        assert_eq!(decimals_of(1.0), 0);
+        assert_eq!(decimals_of(10.0), 0);
+        assert_eq!(decimals_of(0.0), 0);
+    }
+
+    #[test]
+    fn test_round_to_step() {
+        assert_eq!(round_to_step(50000.123, 0.01), 50000.12);
+        assert_eq!(round_to_step(50000.1251, 0.01), 50000.13);
+        assert_eq!(round_to_step(0.3, 0.1), 0.3);
+        assert_eq!(round_to_step(103.0, 5.0), 105.0);
+        assert_eq!(round_to_step(1.23456, 0.0), 1.23456);
+    }
+
+    #[test]
+    fn test_floor_to_step() {
+        assert_eq!(floor_to_step(0.123456, 0.001), 0.123);
+        assert_eq!(floor_to_step(0.3, 0.1), 0.3);
+        assert_eq!(floor_to_step(0.999, 0.01), 0.99);
+        assert_eq!(floor_to_step(7.0, 5.0), 5.0);
+    }
+
+    #[test]
+    fn test_normalize_order_rounds_values() {
+        let instrument = btc_instrument();
+        let mut request = limit_request(0.123456789, 50000.129);
+
+        instrument.normalize_order(&mut request).unwrap();
+
+        assert_eq!(request.quantity, 0.12345);
+        assert_eq!(request.price, Some(50000.13));
+    }
+
+    #[test]
+    fn test_normalize_order_rejects_below_min_qty() {
+        let instrument = btc_instrument();
+        let mut request = limit_request(0.000001, 50000.0);
+
+        let err = instrument.normalize_order(&mut request).unwrap_err();
+        assert!(err.to_string().contains("below the minimum"));
+    }
+
+    #[test]
+    fn test_normalize_order_rejects_above_max_qty() {
+        let instrument = btc_instrument();
+        let mut request = limit_request(10000.0, 50000.0);
+
+        assert!(instrument.normalize_order(&mut request).is_err());
+    }
+
+    #[test]
+    fn test_normalize_order_rejects_below_min_notional() {
+        let instrument = btc_instrument();
+        // 0.00005 * 50000 = 2.5 USDT < 5 USDT
+        let mut request = limit_request(0.00005, 50000.0);
+
+        let err = instrument.normalize_order(&mut request).unwrap_err();
+        assert!(err.to_string().contains("minimum notional"));
+    }
+
+    #[test]
+    fn test_normalize_market_order_skips_notional() {
+        let instrument = btc_instrument();
+        let mut request = limit_request(0.00005, 0.0);
+        request.order_type = OrderType::Market;
+        request.price = None;
+
+        assert!(instrument.normalize_order(&mut request).is_ok());
+    }
+
+    #[test]
+    fn test_normalize_order_rejects_halted_instrument() {
+        let mut instrument = btc_instrument();
+        instrument.status = InstrumentStatus::Halted;
+        let mut request = limit_request(0.1, 50000.0);
+
+        let err = instrument.normalize_order(&mut request).unwrap_err();
+        assert!(err.to_string().contains("not trading"));
+    }
+}
@@ -1,10 +1,13 @@
 pub mod types;
 pub mod exchange;
 pub mod order;
+pub mod execution;
 pub mod position;
 pub mod converter;
+pub mod instrument;
 
 pub use types::*;
 pub use order::*;
 pub use position::*;
 pub use converter::*;
+pub use instrument::InstrumentRegistry;
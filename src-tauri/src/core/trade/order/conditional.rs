@@ -0,0 +1,904 @@
+//! Client-side conditional orders
+//!
+//! Stop, take-profit, trailing-stop and OCO orders that the venue can't hold
+//! natively are kept here and checked against the account's ticker stream.
+//! When a trigger condition is met the engine submits a market order (or a
+//! limit order if a limit price was given) through the account's
+//! `TradeService`; if the submission fails the order stays armed and fires
+//! again after a backoff. Pending orders are persisted and reloaded when the
+//! account session is opened again.
+
+use crate::core::trade::exchange::Exchange;
+use crate::core::trade::types::{OrderRequest, OrderSide, OrderState, OrderType, MAX_CLIENT_ORDER_ID_LEN};
+use crate::core::{AppError, AppResult};
+use crate::repository::ConditionalOrderRepository;
+use crate::services::TradeService;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// Distance a trailing stop keeps from the best price seen
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "unit", content = "value", rename_all = "lowercase")]
+pub enum TrailingOffset {
+    /// Percent of the best price, e.g. `2.0` for 2%
+    Percent(f64),
+    /// Fixed price distance in quote currency
+    Absolute(f64),
+}
+
+impl TrailingOffset {
+    /// Price distance from the reference price
+    pub fn distance(&self, reference: f64) -> f64 {
+        match self {
+            Self::Percent(pct) => reference * pct / 100.0,
+            Self::Absolute(value) => *value,
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Self::Percent(_) => "percent",
+            Self::Absolute(_) => "absolute",
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        match self {
+            Self::Percent(value) | Self::Absolute(value) => *value,
+        }
+    }
+
+    pub fn from_parts(unit: &str, value: f64) -> anyhow::Result<Self> {
+        match unit {
+            "percent" => Ok(Self::Percent(value)),
+            "absolute" => Ok(Self::Absolute(value)),
+            _ => anyhow::bail!("Invalid trailing offset unit: {}", unit),
+        }
+    }
+}
+
+/// Condition that fires a conditional order
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", rename_all_fields = "camelCase")]
+pub enum ConditionalTrigger {
+    /// Fires when price moves against the order: a sell at or below the
+    /// trigger price, a buy at or above it
+    Stop { trigger_price: f64 },
+    /// Fires when price moves in favour: a sell at or above the trigger
+    /// price, a buy at or below it
+    TakeProfit { trigger_price: f64 },
+    /// Stop that follows the best price seen since placement by `offset`
+    TrailingStop {
+        offset: TrailingOffset,
+        #[serde(default)]
+        extreme_price: Option<f64>,
+    },
+}
+
+impl ConditionalTrigger {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Stop { .. } => "stop",
+            Self::TakeProfit { .. } => "take_profit",
+            Self::TrailingStop { .. } => "trailing_stop",
+        }
+    }
+}
+
+/// Conditional order lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionalStatus {
+    /// Waiting for the trigger
+    Pending,
+    /// Order submitted to the exchange
+    Triggered,
+    Canceled,
+    /// Trigger fired but the order request was invalid
+    Failed,
+}
+
+impl ConditionalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Triggered => "triggered",
+            Self::Canceled => "canceled",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+impl std::str::FromStr for ConditionalStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(Self::Pending),
+            "triggered" => Ok(Self::Triggered),
+            "canceled" => Ok(Self::Canceled),
+            "failed" => Ok(Self::Failed),
+            _ => anyhow::bail!("Invalid conditional order status: {}", s),
+        }
+    }
+}
+
+/// Parameters for a new conditional order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConditionalOrderRequest {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: f64,
+    /// Limit price of the order submitted on trigger; market if absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_price: Option<f64>,
+    pub trigger: ConditionalTrigger,
+}
+
+impl ConditionalOrderRequest {
+    /// Check quantities and trigger parameters
+    pub fn validate(&self) -> AppResult<()> {
+        if self.quantity <= 0.0 {
+            return Err(AppError::validation("Order quantity must be positive"));
+        }
+        if self.limit_price.is_some_and(|p| p <= 0.0) {
+            return Err(AppError::validation("Limit price must be positive"));
+        }
+        match self.trigger {
+            ConditionalTrigger::Stop { trigger_price } | ConditionalTrigger::TakeProfit { trigger_price } => {
+                if trigger_price <= 0.0 {
+                    return Err(AppError::validation("Trigger price must be positive"));
+                }
+            }
+            ConditionalTrigger::TrailingStop { offset, .. } => {
+                let valid = match offset {
+                    TrailingOffset::Percent(pct) => pct > 0.0 && pct < 100.0,
+                    TrailingOffset::Absolute(value) => value > 0.0,
+                };
+                if !valid {
+                    return Err(AppError::validation("Trailing offset must be positive (percent below 100)"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A conditional order held client-side
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConditionalOrder {
+    pub id: String,
+    pub user_id: String,
+    /// Exchange account (`exchanges.id`)
+    pub exchange_id: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_price: Option<f64>,
+    pub trigger: ConditionalTrigger,
+    /// Legs of one OCO pair share a group id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oco_group_id: Option<String>,
+    pub status: ConditionalStatus,
+    /// Order submitted when the trigger fired
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub triggered_at: Option<i64>,
+}
+
+impl ConditionalOrder {
+    /// Create a pending order from a request
+    pub fn new(
+        user_id: &str,
+        exchange_id: &str,
+        request: ConditionalOrderRequest,
+        oco_group_id: Option<String>,
+    ) -> Self {
+        let now = Utc::now().timestamp_millis();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            exchange_id: exchange_id.to_string(),
+            symbol: request.symbol,
+            side: request.side,
+            quantity: request.quantity,
+            limit_price: request.limit_price,
+            trigger: request.trigger,
+            oco_group_id,
+            status: ConditionalStatus::Pending,
+            order_id: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+            triggered_at: None,
+        }
+    }
+
+    /// Current trigger level
+    ///
+    /// `None` for a trailing stop that hasn't seen a price yet.
+    pub fn trigger_price(&self) -> Option<f64> {
+        match self.trigger {
+            ConditionalTrigger::Stop { trigger_price } | ConditionalTrigger::TakeProfit { trigger_price } => {
+                Some(trigger_price)
+            }
+            ConditionalTrigger::TrailingStop { offset, extreme_price } => extreme_price.map(|best| match self.side {
+                OrderSide::Sell => best - offset.distance(best),
+                OrderSide::Buy => best + offset.distance(best),
+            }),
+        }
+    }
+
+    /// Feed a market price into a trailing stop
+    ///
+    /// A sell stop follows the highest price, a buy stop the lowest.
+    /// Returns true if the reference price moved.
+    pub fn observe(&mut self, price: f64) -> bool {
+        let ConditionalTrigger::TrailingStop { extreme_price, .. } = &mut self.trigger else {
+            return false;
+        };
+        let improved = match (*extreme_price, self.side) {
+            (None, _) => true,
+            (Some(best), OrderSide::Sell) => price > best,
+            (Some(best), OrderSide::Buy) => price < best,
+        };
+        if improved {
+            *extreme_price = Some(price);
+        }
+        improved
+    }
+
+    /// Whether the trigger condition holds at `price`
+    pub fn is_triggered(&self, price: f64) -> bool {
+        let Some(level) = self.trigger_price() else {
+            return false;
+        };
+        let take_profit = matches!(self.trigger, ConditionalTrigger::TakeProfit { .. });
+        match (self.side, take_profit) {
+            (OrderSide::Sell, false) | (OrderSide::Buy, true) => price <= level,
+            (OrderSide::Buy, false) | (OrderSide::Sell, true) => price >= level,
+        }
+    }
+
+    /// Order submitted when the trigger fires
+    ///
+    /// Every attempt uses the same client order id, so a retry after a lost
+    /// answer settles the first attempt instead of sending a second order.
+    pub fn order_request(&self) -> OrderRequest {
+        OrderRequest {
+            symbol: self.symbol.clone(),
+            side: self.side,
+            order_type: if self.limit_price.is_some() { OrderType::Limit } else { OrderType::Market },
+            price: self.limit_price,
+            stop_price: None,
+            quantity: self.quantity,
+            client_order_id: Some(self.client_order_id()),
+            time_in_force: None,
+        }
+    }
+
+    /// Client order id of the order submitted on trigger
+    pub fn client_order_id(&self) -> String {
+        let id: String = self.id.chars().filter(char::is_ascii_alphanumeric).collect();
+        let len = id.len().min(MAX_CLIENT_ORDER_ID_LEN - 4);
+        format!("cond{}", &id[..len])
+    }
+
+    pub fn mark_triggered(&mut self, order_id: String) {
+        let now = Utc::now().timestamp_millis();
+        self.status = ConditionalStatus::Triggered;
+        self.order_id = Some(order_id);
+        self.error = None;
+        self.triggered_at = Some(now);
+        self.updated_at = now;
+    }
+
+    pub fn mark_failed(&mut self, error: String) {
+        let now = Utc::now().timestamp_millis();
+        self.status = ConditionalStatus::Failed;
+        self.error = Some(error);
+        self.triggered_at = Some(now);
+        self.updated_at = now;
+    }
+
+    pub fn mark_canceled(&mut self) {
+        self.status = ConditionalStatus::Canceled;
+        self.updated_at = Utc::now().timestamp_millis();
+    }
+}
+
+/// Outcome of one price update
+#[derive(Debug, Default)]
+pub struct PriceUpdate {
+    /// Orders whose trigger fired; they have left the book
+    pub triggered: Vec<ConditionalOrder>,
+    /// Trailing stops whose reference price moved
+    pub moved: Vec<ConditionalOrder>,
+}
+
+/// Pending conditional orders of one account
+#[derive(Debug, Default)]
+pub struct ConditionalOrderBook {
+    orders: HashMap<String, ConditionalOrder>,
+}
+
+impl ConditionalOrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, order: ConditionalOrder) {
+        self.orders.insert(order.id.clone(), order);
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<ConditionalOrder> {
+        self.orders.remove(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    /// Remove the remaining legs of an OCO group
+    pub fn take_group(&mut self, group_id: &str) -> Vec<ConditionalOrder> {
+        let ids: Vec<String> = self
+            .orders
+            .values()
+            .filter(|o| o.oco_group_id.as_deref() == Some(group_id))
+            .map(|o| o.id.clone())
+            .collect();
+        ids.iter().filter_map(|id| self.orders.remove(id)).collect()
+    }
+
+    /// Apply a price to every order on `symbol`
+    ///
+    /// At most one leg of an OCO group fires per update; the other legs stay
+    /// in the book until the fired order is accepted.
+    pub fn on_price(&mut self, symbol: &str, price: f64) -> PriceUpdate {
+        let mut update = PriceUpdate::default();
+        let mut fired_groups = HashSet::new();
+
+        let mut ids: Vec<String> = self
+            .orders
+            .values()
+            .filter(|o| o.symbol == symbol)
+            .map(|o| o.id.clone())
+            .collect();
+        // Oldest first, so the earlier leg wins when both legs of a pair trigger
+        ids.sort_by_key(|id| self.orders[id].created_at);
+
+        for id in ids {
+            let Some(order) = self.orders.get_mut(&id) else { continue };
+            if order.observe(price) {
+                update.moved.push(order.clone());
+            }
+            if !order.is_triggered(price) {
+                continue;
+            }
+            if let Some(group) = &order.oco_group_id {
+                if !fired_groups.insert(group.clone()) {
+                    continue;
+                }
+            }
+            if let Some(order) = self.orders.remove(&id) {
+                update.moved.retain(|o| o.id != order.id);
+                update.triggered.push(order);
+            }
+        }
+
+        update
+    }
+}
+
+/// Least time (ms) between writes of a moving trailing stop's reference
+///
+/// The book holds the live reference; the stored one only matters after a
+/// restart, and is at most this stale.
+const TRAILING_PERSIST_INTERVAL_MS: i64 = 5_000;
+
+/// Wait (ms) before a fired order whose submission failed may fire again;
+/// doubles with each attempt up to `FIRE_RETRY_MAX_MS`
+const FIRE_RETRY_BASE_MS: i64 = 1_000;
+const FIRE_RETRY_MAX_MS: i64 = 60_000;
+
+/// Wait before the next attempt after `attempts` failed submissions
+fn fire_retry_delay(attempts: u32) -> i64 {
+    FIRE_RETRY_BASE_MS
+        .saturating_mul(1 << attempts.saturating_sub(1).min(16))
+        .min(FIRE_RETRY_MAX_MS)
+}
+
+/// Failed submissions of a re-armed order
+#[derive(Debug, Clone, Copy)]
+struct FireRetry {
+    attempts: u32,
+    /// Time (ms) before which the order doesn't fire again
+    not_before: i64,
+}
+
+/// Watches one account's ticker stream and fires its conditional orders
+pub struct ConditionalOrderEngine {
+    exchange_id: String,
+    exchange: Arc<dyn Exchange>,
+    trade_service: Arc<TradeService>,
+    repo: ConditionalOrderRepository,
+    book: Mutex<ConditionalOrderBook>,
+    subscribed: Mutex<HashSet<String>>,
+    /// Order id -> time (ms) its trailing reference was last written
+    trailing_persisted_at: Mutex<HashMap<String, i64>>,
+    /// Orders re-armed after a failed submission
+    retries: Mutex<HashMap<String, FireRetry>>,
+}
+
+impl ConditionalOrderEngine {
+    /// Create an engine for an exchange account
+    pub fn new(
+        exchange_id: impl Into<String>,
+        exchange: Arc<dyn Exchange>,
+        trade_service: Arc<TradeService>,
+        pool: SqlitePool,
+    ) -> Self {
+        Self {
+            exchange_id: exchange_id.into(),
+            exchange,
+            trade_service,
+            repo: ConditionalOrderRepository::new(pool),
+            book: Mutex::new(ConditionalOrderBook::new()),
+            subscribed: Mutex::new(HashSet::new()),
+            trailing_persisted_at: Mutex::new(HashMap::new()),
+            retries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Load the account's pending orders from the database
+    pub async fn load(&self) -> AppResult<usize> {
+        let pending = self.repo.find_pending(&self.exchange_id).await?;
+        let count = pending.len();
+        for order in pending {
+            if let Err(e) = self.subscribe(&order.symbol).await {
+                log::warn!("Conditional order {} not watched: {}", order.id, e);
+            }
+            self.book.lock().await.insert(order);
+        }
+        if count > 0 {
+            log::info!("Loaded {} pending conditional orders for account {}", count, self.exchange_id);
+        }
+        Ok(count)
+    }
+
+    /// Place a conditional order
+    ///
+    /// The order is held and triggered client-side.
+    pub async fn place(&self, user_id: &str, request: ConditionalOrderRequest) -> AppResult<ConditionalOrder> {
+        request.validate()?;
+        let order = ConditionalOrder::new(user_id, &self.exchange_id, request, None);
+
+        self.subscribe(&order.symbol).await?;
+        self.repo.insert(&order).await?;
+        self.book.lock().await.insert(order.clone());
+        log::info!(
+            "Conditional order placed: {} {} {} {} @ {:?}",
+            order.id, order.trigger.kind(), order.side, order.symbol, order.trigger_price()
+        );
+        Ok(order)
+    }
+
+    /// Place an OCO pair: when one leg fires the other is canceled
+    pub async fn place_oco(
+        &self,
+        user_id: &str,
+        first: ConditionalOrderRequest,
+        second: ConditionalOrderRequest,
+    ) -> AppResult<Vec<ConditionalOrder>> {
+        first.validate()?;
+        second.validate()?;
+        if first.symbol != second.symbol || first.side != second.side {
+            return Err(AppError::validation("OCO legs must have the same symbol and side"));
+        }
+
+        let group_id = Uuid::new_v4().to_string();
+        let legs = vec![
+            ConditionalOrder::new(user_id, &self.exchange_id, first, Some(group_id.clone())),
+            ConditionalOrder::new(user_id, &self.exchange_id, second, Some(group_id.clone())),
+        ];
+
+        self.subscribe(&legs[0].symbol).await?;
+        for leg in &legs {
+            self.repo.insert(leg).await?;
+        }
+        let mut book = self.book.lock().await;
+        for leg in &legs {
+            book.insert(leg.clone());
+        }
+        log::info!("OCO order placed: group {} on {}", group_id, legs[0].symbol);
+        Ok(legs)
+    }
+
+    /// Cancel a pending order, and the other leg if it is part of an OCO pair
+    pub async fn cancel(&self, user_id: &str, id: &str) -> AppResult<Vec<ConditionalOrder>> {
+        let order = self
+            .repo
+            .find_by_id(id)
+            .await?
+            .filter(|o| o.user_id == user_id && o.exchange_id == self.exchange_id)
+            .ok_or_else(|| AppError::validation(format!("Conditional order not found: {}", id)))?;
+        if order.status != ConditionalStatus::Pending {
+            return Err(AppError::validation(format!(
+                "Conditional order {} is already {}",
+                id,
+                order.status.as_str()
+            )));
+        }
+
+        let mut canceled = {
+            let mut book = self.book.lock().await;
+            match &order.oco_group_id {
+                Some(group) => book.take_group(group),
+                None => book.remove(id).into_iter().collect(),
+            }
+        };
+        // Not in the book: it fired concurrently
+        if canceled.is_empty() {
+            return Err(AppError::validation(format!("Conditional order {} has already fired", id)));
+        }
+
+        for order in &mut canceled {
+            order.mark_canceled();
+            self.trailing_persisted_at.lock().await.remove(&order.id);
+            self.retries.lock().await.remove(&order.id);
+            self.repo.update(order).await?;
+        }
+        Ok(canceled)
+    }
+
+    /// Conditional orders of a user on this account, newest first
+    pub async fn list(
+        &self,
+        user_id: &str,
+        status: Option<ConditionalStatus>,
+        limit: usize,
+    ) -> AppResult<Vec<ConditionalOrder>> {
+        let mut orders = self.repo.find_by_user(user_id, Some(&self.exchange_id), status, limit).await?;
+
+        // Trailing references are persisted at most every few seconds; the
+        // book is authoritative for pending orders
+        let book = self.book.lock().await;
+        for order in orders.iter_mut().filter(|o| o.status == ConditionalStatus::Pending) {
+            if let Some(live) = book.orders.get(&order.id) {
+                order.trigger = live.trigger;
+            }
+        }
+        Ok(orders)
+    }
+
+    /// Number of orders being watched
+    pub async fn pending_count(&self) -> usize {
+        self.book.lock().await.len()
+    }
+
+    /// Apply a market price and fire any triggered orders
+    pub async fn on_price(&self, symbol: &str, price: f64) {
+        let update = self.book.lock().await.on_price(symbol, price);
+
+        let now = Utc::now().timestamp_millis();
+        for order in &update.moved {
+            {
+                let mut persisted_at = self.trailing_persisted_at.lock().await;
+                let last = persisted_at.get(&order.id).copied().unwrap_or(i64::MIN);
+                if now.saturating_sub(last) < TRAILING_PERSIST_INTERVAL_MS {
+                    continue;
+                }
+                persisted_at.insert(order.id.clone(), now);
+            }
+            if let Err(e) = self.repo.update(order).await {
+                log::warn!("Failed to persist trailing stop {}: {}", order.id, e);
+            }
+        }
+
+        // Re-armed orders wait out their backoff on the book
+        let (waiting, due): (Vec<_>, Vec<_>) = {
+            let retries = self.retries.lock().await;
+            update
+                .triggered
+                .into_iter()
+                .partition(|order| retries.get(&order.id).is_some_and(|retry| retry.not_before > now))
+        };
+        if !waiting.is_empty() {
+            let mut book = self.book.lock().await;
+            for order in waiting {
+                book.insert(order);
+            }
+        }
+        for order in due {
+            self.fire(order, price).await;
+        }
+    }
+
+    /// Start watching the account's ticker stream
+    pub fn start(self: &Arc<Self>) -> JoinHandle<()> {
+        let engine = self.clone();
+        let mut ticker_rx = self.exchange.ticker_stream();
+
+        tokio::spawn(async move {
+            loop {
+                match ticker_rx.recv().await {
+                    Ok(ticker) => engine.on_price(&ticker.symbol, ticker.price).await,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("Conditional order engine lagged, skipped {} tickers", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    async fn fire(&self, mut order: ConditionalOrder, price: f64) {
+        log::info!(
+            "Conditional order {} triggered at {} ({} {} {})",
+            order.id, price, order.trigger.kind(), order.side, order.symbol
+        );
+        self.trailing_persisted_at.lock().await.remove(&order.id);
+
+        match self.trade_service.place_conditional_order(order.order_request(), &order.user_id).await {
+            Ok(placed) => order.mark_triggered(placed.id),
+            Err(e) => {
+                let recorded = self
+                    .trade_service
+                    .find_order_by_client_id(&order.client_order_id(), &order.user_id)
+                    .await
+                    .unwrap_or_else(|lookup| {
+                        log::warn!("Failed to look up the order of conditional order {}: {}", order.id, lookup);
+                        None
+                    });
+                match recorded {
+                    // The order may be live; reconciliation settles it
+                    Some(recorded) if recorded.status == OrderState::Pending => {
+                        log::warn!(
+                            "Conditional order {} submitted as {} with an unknown outcome: {}",
+                            order.id, recorded.id, e
+                        );
+                        order.mark_triggered(recorded.id);
+                    }
+                    _ if matches!(e, AppError::Validation(_)) => {
+                        log::error!("Conditional order {} can't be submitted: {}", order.id, e);
+                        order.mark_failed(e.to_string());
+                    }
+                    // Network and venue errors, rate limits and a halt may
+                    // pass; the order stays armed rather than leaving the
+                    // position unprotected
+                    _ => {
+                        self.rearm(order, e).await;
+                        return;
+                    }
+                }
+            }
+        }
+        self.retries.lock().await.remove(&order.id);
+        if let Err(e) = self.repo.update(&order).await {
+            log::error!("Failed to persist conditional order {}: {}", order.id, e);
+        }
+
+        // A rejected leg leaves the other leg of its OCO pair in place
+        if order.status != ConditionalStatus::Triggered {
+            return;
+        }
+        if let Some(group) = &order.oco_group_id {
+            let siblings = self.book.lock().await.take_group(group);
+            for mut sibling in siblings {
+                sibling.mark_canceled();
+                if let Err(e) = self.repo.update(&sibling).await {
+                    log::error!("Failed to cancel OCO leg {}: {}", sibling.id, e);
+                }
+            }
+        }
+    }
+
+    /// Put a fired order back on the book after a failed submission
+    ///
+    /// It fires again on the first price that still triggers it once the
+    /// backoff has passed.
+    async fn rearm(&self, mut order: ConditionalOrder, error: AppError) {
+        let now = Utc::now().timestamp_millis();
+        let retry = {
+            let mut retries = self.retries.lock().await;
+            let retry = retries.entry(order.id.clone()).or_insert(FireRetry { attempts: 0, not_before: now });
+            retry.attempts += 1;
+            retry.not_before = now + fire_retry_delay(retry.attempts);
+            *retry
+        };
+        log::error!(
+            "Conditional order {} failed to submit (attempt {}), armed again in {} ms: {}",
+            order.id,
+            retry.attempts,
+            retry.not_before - now,
+            error
+        );
+
+        order.error = Some(error.to_string());
+        order.updated_at = now;
+        if let Err(e) = self.repo.update(&order).await {
+            log::error!("Failed to persist conditional order {}: {}", order.id, e);
+        }
+        self.book.lock().await.insert(order);
+    }
+
+    async fn subscribe(&self, symbol: &str) -> AppResult<()> {
+        let mut subscribed = self.subscribed.lock().await;
+        if subscribed.contains(symbol) {
+            return Ok(());
+        }
+
+        if !self.exchange.is_connected() {
+            self.exchange.connect().await.map_err(|e| AppError::Exchange(e.to_string()))?;
+        }
+        self.exchange
+            .subscribe_ticker(vec![symbol.to_string()])
+            .await
+            .map_err(|e| AppError::Exchange(e.to_string()))?;
+        subscribed.insert(symbol.to_string());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(side: OrderSide, trigger: ConditionalTrigger) -> ConditionalOrder {
+        ConditionalOrder::new(
+            "user-1",
+            "account-1",
+            ConditionalOrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side,
+                quantity: 0.1,
+                limit_price: None,
+                trigger,
+            },
+            None,
+        )
+    }
+
+    #[test]
+    fn test_stop_and_take_profit_directions() {
+        let stop = order(OrderSide::Sell, ConditionalTrigger::Stop { trigger_price: 100.0 });
+        assert!(!stop.is_triggered(101.0));
+        assert!(stop.is_triggered(100.0));
+        assert!(stop.is_triggered(99.0));
+
+        let buy_stop = order(OrderSide::Buy, ConditionalTrigger::Stop { trigger_price: 100.0 });
+        assert!(!buy_stop.is_triggered(99.0));
+        assert!(buy_stop.is_triggered(100.5));
+
+        let take_profit = order(OrderSide::Sell, ConditionalTrigger::TakeProfit { trigger_price: 120.0 });
+        assert!(!take_profit.is_triggered(119.0));
+        assert!(take_profit.is_triggered(121.0));
+
+        let buy_take_profit = order(OrderSide::Buy, ConditionalTrigger::TakeProfit { trigger_price: 80.0 });
+        assert!(buy_take_profit.is_triggered(79.0));
+        assert!(!buy_take_profit.is_triggered(81.0));
+    }
+
+    #[test]
+    fn test_trailing_stop_follows_best_price() {
+        let mut sell = order(
+            OrderSide::Sell,
+            ConditionalTrigger::TrailingStop { offset: TrailingOffset::Percent(5.0), extreme_price: None },
+        );
+        assert!(!sell.is_triggered(100.0));
+
+        assert!(sell.observe(100.0));
+        assert_eq!(sell.trigger_price(), Some(95.0));
+        assert!(sell.observe(120.0));
+        assert!(!sell.observe(110.0));
+        assert_eq!(sell.trigger_price(), Some(114.0));
+        assert!(!sell.is_triggered(115.0));
+        assert!(sell.is_triggered(114.0));
+
+        let mut buy = order(
+            OrderSide::Buy,
+            ConditionalTrigger::TrailingStop { offset: TrailingOffset::Absolute(10.0), extreme_price: None },
+        );
+        buy.observe(100.0);
+        buy.observe(80.0);
+        assert_eq!(buy.trigger_price(), Some(90.0));
+        assert!(!buy.is_triggered(89.0));
+        assert!(buy.is_triggered(90.0));
+    }
+
+    #[test]
+    fn test_book_fires_one_oco_leg() {
+        let mut book = ConditionalOrderBook::new();
+        let mut take_profit = order(OrderSide::Sell, ConditionalTrigger::TakeProfit { trigger_price: 110.0 });
+        let mut stop = order(OrderSide::Sell, ConditionalTrigger::Stop { trigger_price: 90.0 });
+        take_profit.oco_group_id = Some("group-1".to_string());
+        stop.oco_group_id = Some("group-1".to_string());
+        let stop_id = stop.id.clone();
+        book.insert(take_profit);
+        book.insert(stop);
+
+        assert!(book.on_price("BTCUSDT", 100.0).triggered.is_empty());
+        assert!(book.on_price("ETHUSDT", 50.0).triggered.is_empty());
+
+        let update = book.on_price("BTCUSDT", 85.0);
+        assert_eq!(update.triggered.len(), 1);
+        assert_eq!(update.triggered[0].id, stop_id);
+        assert_eq!(book.len(), 1);
+
+        let remaining = book.take_group("group-1");
+        assert_eq!(remaining.len(), 1);
+        assert!(book.is_empty());
+    }
+
+    #[test]
+    fn test_fired_order_keeps_its_client_order_id() {
+        let stop = order(OrderSide::Sell, ConditionalTrigger::Stop { trigger_price: 95.0 });
+        let client_order_id = stop.order_request().client_order_id.unwrap();
+        assert!(OrderRequest::is_valid_client_order_id(&client_order_id));
+        assert_eq!(stop.order_request().client_order_id.unwrap(), client_order_id);
+        assert_ne!(order(OrderSide::Sell, ConditionalTrigger::Stop { trigger_price: 95.0 }).client_order_id(), client_order_id);
+    }
+
+    #[test]
+    fn test_fire_retry_delay_backs_off() {
+        assert_eq!(fire_retry_delay(1), 1_000);
+        assert_eq!(fire_retry_delay(2), 2_000);
+        assert_eq!(fire_retry_delay(4), 8_000);
+        assert_eq!(fire_retry_delay(10), FIRE_RETRY_MAX_MS);
+        assert_eq!(fire_retry_delay(u32::MAX), FIRE_RETRY_MAX_MS);
+    }
+
+    #[test]
+    fn test_request_validation() {
+        let mut request = ConditionalOrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            side: OrderSide::Sell,
+            quantity: 1.0,
+            limit_price: Some(95.0),
+            trigger: ConditionalTrigger::Stop { trigger_price: 96.0 },
+        };
+        assert!(request.validate().is_ok());
+
+        let stop = ConditionalOrder::new("user-1", "account-1", request.clone(), None);
+        assert_eq!(stop.order_request().order_type, OrderType::Limit);
+
+        request.trigger = ConditionalTrigger::TrailingStop { offset: TrailingOffset::Percent(100.0), extreme_price: None };
+        assert!(request.validate().is_err());
+        request.trigger = ConditionalTrigger::TakeProfit { trigger_price: 0.0 };
+        assert!(request.validate().is_err());
+    }
+
+    #[test]
+    fn test_trigger_serde_shape() {
+        let trigger: ConditionalTrigger = serde_json::from_str(
+            r#"{"kind":"trailing_stop","offset":{"unit":"percent","value":2.5}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            trigger,
+            ConditionalTrigger::TrailingStop { offset: TrailingOffset::Percent(2.5), extreme_price: None }
+        );
+
+        let json = serde_json::to_value(ConditionalTrigger::TakeProfit { trigger_price: 10.0 }).unwrap();
+        assert_eq!(json["kind"], "take_profit");
+        assert_eq!(json["triggerPrice"], 10.0);
+    }
+}
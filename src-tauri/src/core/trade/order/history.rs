@@ -0,0 +1,269 @@
+//! Order event history
+//!
+//! Every change recorded on an order (creation, exchange updates, fills,
+//! amendments) is appended to the `order_events` table, together with
+//! updates the state machine refused, so an order's timeline can be
+//! replayed when debugging fills.
+
+use super::OrderStateMachine;
+use crate::core::trade::types::{Order, OrderState, TradeRecord};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// What happened to an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderEventKind {
+    /// Order recorded locally
+    Created,
+    /// Status moved forward
+    StatusChanged,
+    /// Execution recorded
+    Fill,
+    /// Price and/or quantity changed
+    Amended,
+    /// Rejected order sent again under the same client order id
+    Resubmitted,
+    /// Update dropped as stale or as an invalid transition
+    Ignored,
+}
+
+impl OrderEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::StatusChanged => "status_changed",
+            Self::Fill => "fill",
+            Self::Amended => "amended",
+            Self::Resubmitted => "resubmitted",
+            Self::Ignored => "ignored",
+        }
+    }
+}
+
+impl std::str::FromStr for OrderEventKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "created" => Ok(Self::Created),
+            "status_changed" => Ok(Self::StatusChanged),
+            "fill" => Ok(Self::Fill),
+            "amended" => Ok(Self::Amended),
+            "resubmitted" => Ok(Self::Resubmitted),
+            "ignored" => Ok(Self::Ignored),
+            _ => anyhow::bail!("Invalid order event kind: {}", s),
+        }
+    }
+}
+
+/// Where an order update came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderEventSource {
+    /// Action taken by the app
+    Local,
+    /// REST response from the exchange
+    Exchange,
+    /// User data stream
+    Stream,
+    /// Reconciliation against the exchange
+    Reconcile,
+}
+
+impl OrderEventSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Exchange => "exchange",
+            Self::Stream => "stream",
+            Self::Reconcile => "reconcile",
+        }
+    }
+}
+
+impl std::str::FromStr for OrderEventSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "local" => Ok(Self::Local),
+            "exchange" => Ok(Self::Exchange),
+            "stream" => Ok(Self::Stream),
+            "reconcile" => Ok(Self::Reconcile),
+            _ => anyhow::bail!("Invalid order event source: {}", s),
+        }
+    }
+}
+
+/// One entry of an order's history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderEvent {
+    /// Insertion order; 0 until stored
+    pub id: i64,
+    pub order_id: String,
+    pub kind: OrderEventKind,
+    pub source: OrderEventSource,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_status: Option<OrderState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_status: Option<OrderState>,
+    /// Cumulative filled quantity after the event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filled_quantity: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Exchange time of the update (ms), when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exchange_time: Option<i64>,
+    /// Local time the event was recorded (ms)
+    pub created_at: i64,
+}
+
+impl OrderEvent {
+    /// Create an event recorded now
+    pub fn new(order_id: &str, kind: OrderEventKind, source: OrderEventSource) -> Self {
+        Self {
+            id: 0,
+            order_id: order_id.to_string(),
+            kind,
+            source,
+            from_status: None,
+            to_status: None,
+            filled_quantity: None,
+            detail: None,
+            exchange_time: None,
+            created_at: Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// Status before and after the event
+    pub fn with_status(mut self, from: Option<OrderState>, to: OrderState) -> Self {
+        self.from_status = from;
+        self.to_status = Some(to);
+        self
+    }
+
+    /// Cumulative filled quantity after the event
+    pub fn with_filled(mut self, filled_quantity: f64) -> Self {
+        self.filled_quantity = Some(filled_quantity);
+        self
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Exchange time of the update
+    pub fn with_exchange_time(mut self, exchange_time: Option<i64>) -> Self {
+        self.exchange_time = exchange_time;
+        self
+    }
+}
+
+/// An order with everything recorded about it, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderTimeline {
+    pub order: Order,
+    pub events: Vec<OrderEvent>,
+    pub fills: Vec<TradeRecord>,
+}
+
+/// How an exchange-reported status applies to a stored order
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpdateCheck {
+    /// Apply the update; `changed` is false if the status is unchanged
+    Apply { changed: bool },
+    /// Older than the last exchange update already applied
+    Stale,
+    /// Refused by the state machine
+    Refused(String),
+}
+
+/// Check an exchange-reported status against the stored order
+///
+/// `last_update_at` is the exchange time of the last update applied and
+/// `update_time` that of the incoming one; updates without a time are only
+/// checked against the state machine.
+pub fn check_update(
+    current: OrderState,
+    last_update_at: Option<i64>,
+    status: OrderState,
+    update_time: Option<i64>,
+) -> UpdateCheck {
+    if let (Some(last), Some(time)) = (last_update_at, update_time) {
+        if time < last {
+            return UpdateCheck::Stale;
+        }
+    }
+
+    match OrderStateMachine::from_state(current).advance_to(status) {
+        Ok(changed) => UpdateCheck::Apply { changed },
+        Err(e) => UpdateCheck::Refused(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_update_drops_stale() {
+        // Open from the stream arriving after the PartiallyFilled update
+        assert_eq!(
+            check_update(OrderState::PartiallyFilled, Some(2_000), OrderState::Open, Some(1_000)),
+            UpdateCheck::Stale
+        );
+        // Same status, older time: still stale
+        assert_eq!(
+            check_update(OrderState::Open, Some(2_000), OrderState::Open, Some(1_999)),
+            UpdateCheck::Stale
+        );
+    }
+
+    #[test]
+    fn test_check_update_uses_state_machine() {
+        assert_eq!(
+            check_update(OrderState::Pending, None, OrderState::Filled, Some(1_000)),
+            UpdateCheck::Apply { changed: true }
+        );
+        assert_eq!(
+            check_update(OrderState::Open, Some(1_000), OrderState::Open, Some(1_000)),
+            UpdateCheck::Apply { changed: false }
+        );
+        // No times to compare: a regression is still refused
+        assert!(matches!(
+            check_update(OrderState::PartiallyFilled, None, OrderState::Open, None),
+            UpdateCheck::Refused(_)
+        ));
+        assert!(matches!(
+            check_update(OrderState::Canceled, Some(1_000), OrderState::Filled, Some(2_000)),
+            UpdateCheck::Refused(_)
+        ));
+    }
+
+    #[test]
+    fn test_event_kind_round_trip() {
+        for kind in [
+            OrderEventKind::Created,
+            OrderEventKind::StatusChanged,
+            OrderEventKind::Fill,
+            OrderEventKind::Amended,
+            OrderEventKind::Resubmitted,
+            OrderEventKind::Ignored,
+        ] {
+            assert_eq!(kind.as_str().parse::<OrderEventKind>().unwrap(), kind);
+        }
+        for source in [
+            OrderEventSource::Local,
+            OrderEventSource::Exchange,
+            OrderEventSource::Stream,
+            OrderEventSource::Reconcile,
+        ] {
+            assert_eq!(source.as_str().parse::<OrderEventSource>().unwrap(), source);
+        }
+    }
+}
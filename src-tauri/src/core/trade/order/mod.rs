@@ -1,7 +1,19 @@
 //! Order management module
 //!
-//! This module provides order state machine and related functionality.
+//! This module provides order state machine, order event history, exchange
+//! reconciliation, client-side conditional orders and related functionality.
 
 pub mod state;
+pub mod history;
+pub mod reconcile;
+pub mod conditional;
 
 pub use state::OrderStateMachine;
+pub use history::{
+    check_update, OrderEvent, OrderEventKind, OrderEventSource, OrderTimeline, UpdateCheck,
+};
+pub use reconcile::{diff_order, fill_delta, FillDelta, OrderCorrection, ReconcileReport};
+pub use conditional::{
+    ConditionalOrder, ConditionalOrderBook, ConditionalOrderEngine, ConditionalOrderRequest,
+    ConditionalStatus, ConditionalTrigger, PriceUpdate, TrailingOffset,
+};
@@ -0,0 +1,203 @@
+//! Order reconciliation
+//!
+//! Compares a locally stored order with the exchange's view of it and
+//! describes the correction needed. The exchange is always treated as the
+//! source of truth; persisting the correction is left to the caller.
+
+use super::OrderStateMachine;
+use crate::core::trade::types::{Order, OrderState};
+use serde::{Deserialize, Serialize};
+
+/// Quantities below this are treated as rounding noise
+const QTY_EPSILON: f64 = 1e-12;
+
+/// Fill that happened on the exchange but was never recorded locally
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FillDelta {
+    pub quantity: f64,
+    /// Average price of the missed quantity
+    pub price: f64,
+}
+
+/// Correction to apply to a local order
+#[derive(Debug, Clone)]
+pub struct OrderCorrection {
+    /// Local order updated with the exchange state
+    pub order: Order,
+    pub previous_state: OrderState,
+    /// Missed fill, if the filled quantity grew
+    pub fill: Option<FillDelta>,
+}
+
+impl OrderCorrection {
+    /// Returns true if the state change skips the normal lifecycle
+    /// (e.g. Pending -> Filled), meaning local tracking fell behind
+    pub fn is_out_of_sequence(&self) -> bool {
+        !OrderStateMachine::from_state(self.previous_state).can_transition_to(&self.order.status)
+    }
+}
+
+/// Outcome of one reconciliation pass
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcileReport {
+    /// Local active orders compared against the exchange
+    pub checked: usize,
+    /// Local orders corrected from the exchange state
+    pub updated: usize,
+    /// Missed fills written to the trades table
+    pub fills_recorded: usize,
+    /// Exchange orders that were unknown locally and have been imported
+    pub imported: usize,
+    /// Orders that couldn't be checked (exchange or database error)
+    pub errors: usize,
+    pub started_at: i64,
+    pub finished_at: i64,
+}
+
+impl ReconcileReport {
+    /// Fold another pass (e.g. for a second account) into this report
+    pub fn merge(&mut self, other: &ReconcileReport) {
+        self.checked += other.checked;
+        self.updated += other.updated;
+        self.fills_recorded += other.fills_recorded;
+        self.imported += other.imported;
+        self.errors += other.errors;
+        if self.started_at == 0 || other.started_at < self.started_at {
+            self.started_at = other.started_at;
+        }
+        self.finished_at = self.finished_at.max(other.finished_at);
+    }
+}
+
+/// Compute the fill missed between the local and exchange order
+///
+/// Uses the cumulative average prices to back out the price of the
+/// missing quantity.
+pub fn fill_delta(local: &Order, remote: &Order) -> Option<FillDelta> {
+    let quantity = remote.filled_quantity - local.filled_quantity;
+    if quantity <= QTY_EPSILON {
+        return None;
+    }
+
+    let remote_avg = remote.avg_price.or(remote.price)?;
+    let local_notional = local.filled_quantity * local.avg_price.unwrap_or(remote_avg);
+    let price = (remote.filled_quantity * remote_avg - local_notional) / quantity;
+
+    // A bad local avg price can make the back-out meaningless
+    let price = if price.is_finite() && price > 0.0 { price } else { remote_avg };
+
+    Some(FillDelta { quantity, price })
+}
+
+/// Compare a local order with the exchange's copy
+///
+/// Returns `None` if they already agree.
+pub fn diff_order(local: &Order, remote: &Order) -> Option<OrderCorrection> {
+    let fill = fill_delta(local, remote);
+    let state_changed = local.status != remote.status;
+
+    if !state_changed && fill.is_none() {
+        return None;
+    }
+
+    let mut order = local.clone();
+    order.status = remote.status;
+    order.filled_quantity = remote.filled_quantity.max(local.filled_quantity);
+    order.avg_price = remote.avg_price.or(local.avg_price);
+    order.filled_at = remote.filled_at.or(local.filled_at);
+    order.updated_at = remote.updated_at.or(local.updated_at);
+    if remote.commission > 0.0 {
+        order.commission = remote.commission;
+    }
+
+    Some(OrderCorrection {
+        order,
+        previous_state: local.status,
+        fill,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::trade::types::{OrderSide, OrderType};
+
+    fn order(status: OrderState, filled_quantity: f64, avg_price: Option<f64>) -> Order {
+        Order {
+            id: "local-1".to_string(),
+            exchange_order_id: Some("12345".to_string()),
+            client_order_id: None,
+            symbol: "BTCUSDT".to_string(),
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            price: Some(50000.0),
+            quantity: 1.0,
+            filled_quantity,
+            avg_price,
+            status,
+            commission: 0.0,
+            created_at: 1_700_000_000_000,
+            commission_asset: None,
+            filled_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_no_drift() {
+        let local = order(OrderState::Open, 0.0, None);
+        let remote = order(OrderState::Open, 0.0, None);
+
+        assert!(diff_order(&local, &remote).is_none());
+    }
+
+    #[test]
+    fn test_external_cancel() {
+        let local = order(OrderState::Open, 0.0, None);
+        let remote = order(OrderState::Canceled, 0.0, None);
+
+        let correction = diff_order(&local, &remote).unwrap();
+        assert_eq!(correction.order.status, OrderState::Canceled);
+        assert_eq!(correction.order.id, "local-1");
+        assert!(correction.fill.is_none());
+        assert!(!correction.is_out_of_sequence());
+    }
+
+    #[test]
+    fn test_missed_partial_fill_price() {
+        // 0.4 @ 50000 known locally, exchange reports 1.0 @ avg 50600
+        let local = order(OrderState::PartiallyFilled, 0.4, Some(50000.0));
+        let remote = order(OrderState::Filled, 1.0, Some(50600.0));
+
+        let correction = diff_order(&local, &remote).unwrap();
+        let fill = correction.fill.unwrap();
+
+        assert!((fill.quantity - 0.6).abs() < 1e-9);
+        assert!((fill.price - 51000.0).abs() < 1e-6);
+        assert_eq!(correction.order.filled_quantity, 1.0);
+    }
+
+    #[test]
+    fn test_merge_reports() {
+        let mut report = ReconcileReport::default();
+        report.merge(&ReconcileReport { checked: 2, updated: 1, started_at: 100, finished_at: 200, ..Default::default() });
+        report.merge(&ReconcileReport { checked: 3, errors: 1, started_at: 150, finished_at: 300, ..Default::default() });
+
+        assert_eq!(report.checked, 5);
+        assert_eq!(report.updated, 1);
+        assert_eq!(report.errors, 1);
+        assert_eq!(report.started_at, 100);
+        assert_eq!(report.finished_at, 300);
+    }
+
+    #[test]
+    fn test_pending_to_filled_is_out_of_sequence() {
+        let local = order(OrderState::Pending, 0.0, None);
+        let remote = order(OrderState::Filled, 1.0, Some(50000.0));
+
+        let correction = diff_order(&local, &remote).unwrap();
+        assert!(correction.is_out_of_sequence());
+        assert_eq!(correction.fill.unwrap().price, 50000.0);
+    }
+}
@@ -35,6 +35,7 @@ impl OrderStateMachine {
     /// - Open -> Canceled
     /// - PartiallyFilled -> Filled
     /// - PartiallyFilled -> Canceled
+    /// - Open/PartiallyFilled -> same state on amend (see [`Self::amend`])
     ///
     /// # Errors
     /// Returns an error if the transition is invalid
@@ -85,6 +86,71 @@ impl OrderStateMachine {
         self.state.is_active()
     }
 
+    /// Check that the order can be amended (price or quantity change)
+    ///
+    /// Only resting orders can be amended; the amendment keeps the current
+    /// state (Open -> Open, PartiallyFilled -> PartiallyFilled).
+    ///
+    /// # Errors
+    /// Returns an error if the order is pending or terminal
+    pub fn amend(&mut self) -> Result<()> {
+        if !self.state.is_active() {
+            bail!("Order cannot be amended in state {:?}", self.state);
+        }
+        self.transition_to(self.state)
+    }
+
+    /// Move forward to a state reported by the exchange
+    ///
+    /// Exchange updates may skip intermediate states (a market order goes
+    /// Pending -> Filled in one response), so any forward move is accepted:
+    /// Pending -> Open -> PartiallyFilled -> terminal. Rejected is only
+    /// reachable from Pending.
+    ///
+    /// Returns false if the order is already in that state.
+    ///
+    /// # Errors
+    /// Returns an error for regressions (e.g. a late `Open` after
+    /// `PartiallyFilled`) and for any change after a terminal state
+    pub fn advance_to(&mut self, new_state: OrderState) -> Result<bool> {
+        if self.state == new_state {
+            return Ok(false);
+        }
+        if self.state.is_terminal() {
+            bail!("Order is already {:?}, ignoring {:?}", self.state, new_state);
+        }
+        if new_state == OrderState::Rejected && self.state != OrderState::Pending {
+            bail!("Invalid state transition: {:?} -> {:?}", self.state, new_state);
+        }
+        if Self::rank(new_state) <= Self::rank(self.state) {
+            bail!("Out-of-order state transition: {:?} -> {:?}", self.state, new_state);
+        }
+        self.state = new_state;
+        Ok(true)
+    }
+
+    /// Send a rejected order again under the same client order id
+    ///
+    /// # Errors
+    /// Returns an error unless the order is Rejected
+    pub fn resubmit(&mut self) -> Result<()> {
+        if self.state != OrderState::Rejected {
+            bail!("Only rejected orders can be resubmitted, order is {:?}", self.state);
+        }
+        self.state = OrderState::Pending;
+        Ok(())
+    }
+
+    /// Position of a state in the order lifecycle
+    fn rank(state: OrderState) -> u8 {
+        match state {
+            OrderState::Pending => 0,
+            OrderState::Open => 1,
+            OrderState::PartiallyFilled => 2,
+            OrderState::Filled | OrderState::Canceled | OrderState::Rejected => 3,
+        }
+    }
+
     /// Check if a transition to the target state is valid without performing it
     pub fn can_transition_to(&self, target_state: &OrderState) -> bool {
         self.validate_transition(&self.state, target_state).is_ok()
@@ -231,4 +297,94 @@ mod tests {
         // Invalid state
         assert!("invalid".parse::<OrderState>().is_err());
     }
+
+    #[test]
+    fn test_amend_keeps_state() {
+        let mut sm = OrderStateMachine::from_state(OrderState::Open);
+        assert!(sm.amend().is_ok());
+        assert_eq!(sm.state(), OrderState::Open);
+
+        let mut sm = OrderStateMachine::from_state(OrderState::PartiallyFilled);
+        assert!(sm.amend().is_ok());
+        assert_eq!(sm.state(), OrderState::PartiallyFilled);
+
+        for state in [OrderState::Pending, OrderState::Filled, OrderState::Canceled, OrderState::Rejected] {
+            assert!(OrderStateMachine::from_state(state).amend().is_err());
+        }
+    }
+
+    #[test]
+    fn test_amend_request_against_order() {
+        use crate::core::trade::types::{AmendRequest, Order, OrderSide, OrderType};
+
+        let order = Order {
+            id: "order-1".to_string(),
+            exchange_order_id: Some("123".to_string()),
+            client_order_id: None,
+            symbol: "BTCUSDT".to_string(),
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            price: Some(100.0),
+            quantity: 2.0,
+            filled_quantity: 0.5,
+            avg_price: Some(100.0),
+            status: OrderState::PartiallyFilled,
+            commission: 0.0,
+            commission_asset: None,
+            created_at: 0,
+            filled_at: None,
+            updated_at: None,
+        };
+
+        let amend = AmendRequest { price: Some(99.5), quantity: None };
+        assert!(amend.validate(&order).is_ok());
+        let amended = amend.apply(&order);
+        assert_eq!(amended.price, Some(99.5));
+        assert_eq!(amended.quantity, 2.0);
+
+        assert!(AmendRequest::default().validate(&order).is_err());
+        assert!(AmendRequest { price: None, quantity: Some(0.5) }.validate(&order).is_err());
+        assert!(AmendRequest { price: None, quantity: Some(1.0) }.validate(&order).is_ok());
+
+        let market = Order { order_type: OrderType::Market, ..order };
+        assert!(amend.validate(&market).is_err());
+    }
+
+    #[test]
+    fn test_advance_skips_forward() {
+        let mut sm = OrderStateMachine::new();
+        assert!(sm.advance_to(OrderState::Filled).unwrap());
+        assert_eq!(sm.state(), OrderState::Filled);
+
+        let mut sm = OrderStateMachine::new();
+        assert!(sm.advance_to(OrderState::PartiallyFilled).unwrap());
+        assert!(!sm.advance_to(OrderState::PartiallyFilled).unwrap());
+        assert!(sm.advance_to(OrderState::Canceled).unwrap());
+    }
+
+    #[test]
+    fn test_advance_refuses_regressions() {
+        let mut sm = OrderStateMachine::from_state(OrderState::PartiallyFilled);
+        assert!(sm.advance_to(OrderState::Open).is_err());
+        assert!(sm.advance_to(OrderState::Pending).is_err());
+        assert!(sm.advance_to(OrderState::Rejected).is_err());
+        assert_eq!(sm.state(), OrderState::PartiallyFilled);
+
+        // A late update after a terminal state changes nothing
+        let mut sm = OrderStateMachine::from_state(OrderState::Canceled);
+        assert!(sm.advance_to(OrderState::Filled).is_err());
+        assert!(sm.advance_to(OrderState::PartiallyFilled).is_err());
+        assert_eq!(sm.state(), OrderState::Canceled);
+    }
+
+    #[test]
+    fn test_resubmit_only_from_rejected() {
+        let mut sm = OrderStateMachine::from_state(OrderState::Rejected);
+        assert!(sm.resubmit().is_ok());
+        assert_eq!(sm.state(), OrderState::Pending);
+
+        for state in [OrderState::Pending, OrderState::Open, OrderState::Filled, OrderState::Canceled] {
+            assert!(OrderStateMachine::from_state(state).resubmit().is_err());
+        }
+    }
 }
@@ -1,11 +1,21 @@
 //! Position management module
 //!
 //! This module provides position tracking and management functionality.
+//!
+//! Fills are booked into open lots. In net mode a symbol has one position
+//! whose side follows the sign of the net quantity: a sell reduces a long
+//! (and flips it short once the long is used up). In hedge mode long and
+//! short legs are tracked separately. Realised PnL accumulates over the
+//! life of a position and is net of fees.
 
 use crate::core::trade::types::Position;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use uuid::Uuid;
 
+/// Quantities below this are treated as flat
+const QTY_EPSILON: f64 = 1e-12;
+
 /// Trade record for position updates
 #[derive(Debug, Clone)]
 pub struct Trade {
@@ -13,143 +23,388 @@ pub struct Trade {
     pub side: String,      // "buy" or "sell"
     pub quantity: f64,
     pub price: f64,
+    /// Fee in the quote asset
+    pub commission: f64,
+    /// Leg the trade belongs to in hedge mode ("long" or "short"); a buy
+    /// defaults to the long leg and a sell to the short leg
+    pub position_side: Option<String>,
     pub timestamp: i64,
 }
 
+/// How fills on both sides of a symbol are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionMode {
+    /// One position per symbol; opposite fills reduce it
+    #[default]
+    Net,
+    /// Separate long and short legs per symbol
+    Hedge,
+}
+
+/// How closing fills are matched against open lots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostMethod {
+    /// All open quantity shares one average entry price
+    #[default]
+    AverageCost,
+    /// Closing fills consume the oldest lots first
+    Fifo,
+}
+
+/// Quantity opened by one fill and not yet closed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lot {
+    pub quantity: f64,
+    pub price: f64,
+    pub opened_at: i64,
+}
+
+/// A position with the open lots behind it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackedPosition {
+    pub position: Position,
+    pub lots: VecDeque<Lot>,
+    /// Fees paid over the life of the position (quote asset)
+    pub fees: f64,
+}
+
+impl TrackedPosition {
+    fn open(symbol: &str, side: &str, timestamp: i64) -> Self {
+        Self {
+            position: Position {
+                id: Uuid::new_v4().to_string(),
+                symbol: symbol.to_string(),
+                side: side.to_string(),
+                quantity: 0.0,
+                entry_price: 0.0,
+                current_price: None,
+                unrealized_pnl: 0.0,
+                realized_pnl: 0.0,
+                opened_at: timestamp,
+            },
+            lots: VecDeque::new(),
+            fees: 0.0,
+        }
+    }
+
+    /// Whether the position has been closed out
+    pub fn is_closed(&self) -> bool {
+        self.position.quantity < QTY_EPSILON
+    }
+
+    fn is_long(&self) -> bool {
+        self.position.side == "long"
+    }
+
+    fn charge(&mut self, fee: f64) {
+        self.fees += fee;
+        self.position.realized_pnl -= fee;
+    }
+
+    fn add(&mut self, quantity: f64, price: f64, timestamp: i64, method: CostMethod) {
+        match (method, self.lots.front_mut()) {
+            (CostMethod::AverageCost, Some(lot)) => {
+                lot.price = (lot.quantity * lot.price + quantity * price) / (lot.quantity + quantity);
+                lot.quantity += quantity;
+            }
+            _ => self.lots.push_back(Lot { quantity, price, opened_at: timestamp }),
+        }
+        self.refresh(Some(price));
+    }
+
+    /// Close up to `quantity` against the open lots; returns the quantity closed
+    fn reduce(&mut self, quantity: f64, price: f64) -> f64 {
+        let long = self.is_long();
+        let mut remaining = quantity;
+        while remaining > QTY_EPSILON {
+            let Some(lot) = self.lots.front_mut() else {
+                break;
+            };
+            let closed = remaining.min(lot.quantity);
+            self.position.realized_pnl += if long {
+                (price - lot.price) * closed
+            } else {
+                (lot.price - price) * closed
+            };
+            lot.quantity -= closed;
+            remaining -= closed;
+            if lot.quantity < QTY_EPSILON {
+                self.lots.pop_front();
+            }
+        }
+        self.refresh(Some(price));
+        quantity - remaining.max(0.0)
+    }
+
+    /// Recompute quantity, entry price and unrealised PnL from the lots
+    fn refresh(&mut self, mark_price: Option<f64>) {
+        let quantity: f64 = self.lots.iter().map(|lot| lot.quantity).sum();
+        let cost: f64 = self.lots.iter().map(|lot| lot.quantity * lot.price).sum();
+        let long = self.is_long();
+
+        let pos = &mut self.position;
+        pos.quantity = if quantity < QTY_EPSILON { 0.0 } else { quantity };
+        if pos.quantity > 0.0 {
+            pos.entry_price = cost / quantity;
+        }
+        if mark_price.is_some() {
+            pos.current_price = mark_price;
+        }
+        pos.unrealized_pnl = match pos.current_price {
+            Some(mark) if pos.quantity > 0.0 => {
+                if long {
+                    (mark - pos.entry_price) * pos.quantity
+                } else {
+                    (pos.entry_price - mark) * pos.quantity
+                }
+            }
+            _ => 0.0,
+        };
+    }
+}
+
 /// Position manager for tracking trading positions
 pub struct PositionManager {
-    positions: HashMap<String, Position>,
+    mode: PositionMode,
+    method: CostMethod,
+    positions: HashMap<String, TrackedPosition>,
+    /// Realised PnL of positions already closed out
+    closed_realized_pnl: f64,
 }
 
 impl PositionManager {
-    /// Create a new position manager
+    /// Create a new position manager (net mode, average cost)
     pub fn new() -> Self {
+        Self::with_accounting(PositionMode::Net, CostMethod::AverageCost)
+    }
+
+    /// Create a position manager with the given accounting
+    pub fn with_accounting(mode: PositionMode, method: CostMethod) -> Self {
         Self {
+            mode,
+            method,
             positions: HashMap::new(),
+            closed_realized_pnl: 0.0,
         }
     }
 
+    /// Position mode in use
+    pub fn mode(&self) -> PositionMode {
+        self.mode
+    }
+
     /// Update position based on a trade
-    pub fn update_position(&mut self, trade: &Trade) {
-        let key = format!("{}_{}", trade.symbol, trade.side);
-
-        // Check if position exists
-        if let Some(pos) = self.positions.get_mut(&key) {
-            // Update existing position
-            if trade.side == "buy" || trade.side == "long" {
-                // Add to position (average up/down)
-                let total_cost = pos.quantity * pos.entry_price + trade.quantity * trade.price;
-                pos.quantity += trade.quantity;
-                pos.entry_price = if pos.quantity > 0.0 { total_cost / pos.quantity } else { trade.price };
-            } else if trade.side == "sell" || trade.side == "short" {
-                // Reduce position
-                pos.quantity -= trade.quantity;
+    ///
+    /// Returns the positions the trade touched; a position closed by the
+    /// trade is returned with zero quantity. A net position flipped by the
+    /// trade yields the closed position followed by the new one.
+    pub fn update_position(&mut self, trade: &Trade) -> Vec<TrackedPosition> {
+        if trade.quantity < QTY_EPSILON {
+            return Vec::new();
+        }
+        let buy = matches!(trade.side.as_str(), "buy" | "long");
+        let direction = if buy { "long" } else { "short" };
+        let fee_per_unit = trade.commission / trade.quantity;
+
+        let (key, side) = match self.mode {
+            PositionMode::Net => (trade.symbol.clone(), direction),
+            PositionMode::Hedge => {
+                let leg = trade.position_side.as_deref().map(normalize_side).unwrap_or(direction);
+                (position_key(&trade.symbol, leg), leg)
             }
-
-            // Update unrealized PnL based on current price (using trade price as current)
-            pos.current_price = Some(trade.price);
-            pos.unrealized_pnl = if pos.side == "buy" || pos.side == "long" {
-                // Long position: profit when price goes up
-                (trade.price - pos.entry_price) * pos.quantity
-            } else {
-                // Short position: profit when price goes down
-                (pos.entry_price - trade.price) * pos.quantity
-            };
-        } else {
-            // Create new position for first trade
-            let new_position = Position {
-                id: Uuid::new_v4().to_string(),
-                symbol: trade.symbol.clone(),
-                side: trade.side.clone(),
-                quantity: trade.quantity,
-                entry_price: trade.price,
-                current_price: Some(trade.price),
-                unrealized_pnl: 0.0,
-                realized_pnl: 0.0,
-                opened_at: trade.timestamp,
+        };
+
+        let mut touched = Vec::new();
+        let mut remaining = trade.quantity;
+
+        // Reduce a position held against the trade direction
+        let reducing = match self.mode {
+            PositionMode::Net => self.positions.get(&key).is_some_and(|tracked| tracked.is_long() != buy),
+            PositionMode::Hedge => side != direction,
+        };
+        if reducing {
+            let Some(tracked) = self.positions.get_mut(&key) else {
+                log::warn!("No {} {} position to reduce", trade.symbol, side);
+                return touched;
             };
-            self.positions.insert(key, new_position);
+            let closed = tracked.reduce(remaining, trade.price);
+            tracked.charge(fee_per_unit * closed);
+            remaining -= closed;
+            if !tracked.is_closed() {
+                touched.push(tracked.clone());
+                return touched;
+            }
+            if let Some(tracked) = self.positions.remove(&key) {
+                self.closed_realized_pnl += tracked.position.realized_pnl;
+                touched.push(tracked);
+            }
+            // A hedge leg has nothing to flip into
+            if self.mode == PositionMode::Hedge || remaining < QTY_EPSILON {
+                return touched;
+            }
+        }
+
+        // Open or add to a position in the trade direction
+        let tracked = self
+            .positions
+            .entry(key)
+            .or_insert_with(|| TrackedPosition::open(&trade.symbol, direction, trade.timestamp));
+        tracked.add(remaining, trade.price, trade.timestamp, self.method);
+        tracked.charge(fee_per_unit * remaining);
+        touched.push(tracked.clone());
+        touched
+    }
+
+    /// Revalue open positions in a symbol at a new mark price
+    ///
+    /// Returns the positions that were revalued.
+    pub fn update_mark_price(&mut self, symbol: &str, price: f64) -> Vec<TrackedPosition> {
+        self.positions
+            .values_mut()
+            .filter(|tracked| tracked.position.symbol == symbol)
+            .map(|tracked| {
+                tracked.refresh(Some(price));
+                tracked.clone()
+            })
+            .collect()
+    }
+
+    /// Load a position, e.g. from the database after a restart
+    ///
+    /// A position without lots is loaded as one lot at its entry price.
+    pub fn restore(&mut self, mut tracked: TrackedPosition) {
+        if tracked.lots.is_empty() && tracked.position.quantity > QTY_EPSILON {
+            tracked.lots.push_back(Lot {
+                quantity: tracked.position.quantity,
+                price: tracked.position.entry_price,
+                opened_at: tracked.position.opened_at,
+            });
         }
+        tracked.position.side = normalize_side(&tracked.position.side).to_string();
+        tracked.refresh(None);
+
+        let key = match self.mode {
+            PositionMode::Net => tracked.position.symbol.clone(),
+            PositionMode::Hedge => position_key(&tracked.position.symbol, &tracked.position.side),
+        };
+        self.positions.insert(key, tracked);
     }
 
     /// Get a position by symbol and side
     pub fn get_position(&self, symbol: &str, side: &str) -> Option<&Position> {
-        let key = format!("{}_{}", symbol, side);
-        self.positions.get(&key)
+        self.tracked(symbol, side).map(|tracked| &tracked.position)
+    }
+
+    /// Get a position with its open lots
+    pub fn get_tracked_position(&self, symbol: &str, side: &str) -> Option<&TrackedPosition> {
+        self.tracked(symbol, side)
     }
 
     /// Get a mutable reference to a position
     pub fn get_position_mut(&mut self, symbol: &str, side: &str) -> Option<&mut Position> {
-        let key = format!("{}_{}", symbol, side);
-        self.positions.get_mut(&key)
+        let side = normalize_side(side);
+        let key = self.key(symbol, side);
+        self.positions
+            .get_mut(&key)
+            .filter(|tracked| tracked.position.side == side)
+            .map(|tracked| &mut tracked.position)
     }
 
     /// Get all positions
     pub fn get_all_positions(&self) -> Vec<&Position> {
-        self.positions.values().collect()
+        self.positions.values().map(|tracked| &tracked.position).collect()
     }
 
     /// Get all open positions (quantity > 0)
     pub fn get_open_positions(&self) -> Vec<&Position> {
         self.positions
             .values()
+            .map(|tracked| &tracked.position)
             .filter(|p| p.quantity > 0.0)
             .collect()
     }
 
     /// Remove a position (when fully closed)
     pub fn remove_position(&mut self, symbol: &str, side: &str) -> Option<Position> {
-        let key = format!("{}_{}", symbol, side);
-        self.positions.remove(&key)
+        let side = normalize_side(side);
+        let key = self.key(symbol, side);
+        if self.positions.get(&key)?.position.side != side {
+            return None;
+        }
+        self.positions.remove(&key).map(|tracked| tracked.position)
     }
 
-    /// Calculate and realize PnL when closing a position
+    /// Close out a position at a price and realise its PnL
+    ///
+    /// Returns the PnL realised by this close; the position's realised PnL
+    /// accumulates it.
     pub fn close_position(&mut self, symbol: &str, side: &str, close_price: f64) -> f64 {
-        if let Some(pos) = self.get_position_mut(symbol, side) {
-            let realized_pnl = if pos.side == "buy" || pos.side == "long" {
-                (close_price - pos.entry_price) * pos.quantity
-            } else {
-                (pos.entry_price - close_price) * pos.quantity
-            };
-
-            // Update realized PnL
-            pos.realized_pnl = realized_pnl;
-            pos.quantity = 0.0; // Position closed
-
-            realized_pnl
-        } else {
-            0.0
+        let side = normalize_side(side);
+        let key = self.key(symbol, side);
+        let Some(tracked) = self.positions.get_mut(&key).filter(|t| t.position.side == side) else {
+            return 0.0;
+        };
+
+        let before = tracked.position.realized_pnl;
+        let quantity = tracked.position.quantity;
+        tracked.reduce(quantity, close_price);
+        let realized_pnl = tracked.position.realized_pnl - before;
+
+        if let Some(tracked) = self.positions.remove(&key) {
+            self.closed_realized_pnl += tracked.position.realized_pnl;
         }
+        realized_pnl
     }
 
     /// Get total unrealized PnL across all positions
     pub fn get_total_unrealized_pnl(&self) -> f64 {
         self.positions
             .values()
-            .map(|p| p.unrealized_pnl)
+            .map(|tracked| tracked.position.unrealized_pnl)
             .sum()
     }
 
-    /// Get total realized PnL across all positions
+    /// Get total realized PnL, including positions already closed
     pub fn get_total_realized_pnl(&self) -> f64 {
-        self.positions
-            .values()
-            .map(|p| p.realized_pnl)
-            .sum()
+        self.closed_realized_pnl
+            + self
+                .positions
+                .values()
+                .map(|tracked| tracked.position.realized_pnl)
+                .sum::<f64>()
     }
 
     /// Clear all positions
     pub fn clear(&mut self) {
         self.positions.clear();
+        self.closed_realized_pnl = 0.0;
     }
 
     /// Get the number of active positions
     pub fn active_position_count(&self) -> usize {
         self.positions
             .values()
-            .filter(|p| p.quantity > 0.0)
+            .filter(|tracked| !tracked.is_closed())
             .count()
     }
+
+    fn tracked(&self, symbol: &str, side: &str) -> Option<&TrackedPosition> {
+        let side = normalize_side(side);
+        self.positions
+            .get(&self.key(symbol, side))
+            .filter(|tracked| tracked.position.side == side)
+    }
+
+    fn key(&self, symbol: &str, side: &str) -> String {
+        match self.mode {
+            PositionMode::Net => symbol.to_string(),
+            PositionMode::Hedge => position_key(symbol, side),
+        }
+    }
 }
 
 impl Default for PositionManager {
@@ -158,6 +413,18 @@ impl Default for PositionManager {
     }
 }
 
+fn position_key(symbol: &str, side: &str) -> String {
+    format!("{}_{}", symbol, side)
+}
+
+/// Map order sides onto position sides ("buy" -> "long", "sell" -> "short")
+fn normalize_side(side: &str) -> &'static str {
+    match side.to_lowercase().as_str() {
+        "short" | "sell" => "short",
+        _ => "long",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -168,6 +435,8 @@ mod tests {
             side: side.to_string(),
             quantity,
             price,
+            commission: 0.0,
+            position_side: None,
             timestamp: chrono::Utc::now().timestamp(),
         }
     }
@@ -191,7 +460,7 @@ mod tests {
 
         let pos = &positions[0];
         assert_eq!(pos.symbol, "BTCUSDT");
-        assert_eq!(pos.side, "buy");
+        assert_eq!(pos.side, "long");
         assert_eq!(pos.quantity, 1.0);
         assert_eq!(pos.entry_price, 50000.0);
     }
@@ -221,9 +490,6 @@ mod tests {
         let trade1 = create_test_trade("BTCUSDT", "buy", 2.0, 50000.0);
         manager.update_position(&trade1);
 
-        // Reduce position by closing partially
-        // Note: close_position sets quantity to 0, so we test close_position separately
-        // Here we just verify the initial position is correct
         let pos = manager.get_position("BTCUSDT", "buy").unwrap();
         assert_eq!(pos.quantity, 2.0);
         assert_eq!(pos.entry_price, 50000.0);
@@ -236,6 +502,84 @@ mod tests {
         assert_eq!(manager.active_position_count(), 0);
     }
 
+    #[test]
+    fn test_sell_reduces_long() {
+        let mut manager = PositionManager::new();
+        manager.update_position(&create_test_trade("BTCUSDT", "buy", 2.0, 50000.0));
+
+        let touched = manager.update_position(&create_test_trade("BTCUSDT", "sell", 0.5, 52000.0));
+        assert_eq!(touched.len(), 1);
+
+        let pos = manager.get_position("BTCUSDT", "long").unwrap();
+        assert_eq!(pos.quantity, 1.5);
+        assert_eq!(pos.entry_price, 50000.0);
+        assert_eq!(pos.realized_pnl, 1000.0);
+        assert!(manager.get_position("BTCUSDT", "short").is_none());
+
+        // A second partial close adds to the realised PnL
+        manager.update_position(&create_test_trade("BTCUSDT", "sell", 0.5, 51000.0));
+        let pos = manager.get_position("BTCUSDT", "long").unwrap();
+        assert_eq!(pos.quantity, 1.0);
+        assert_eq!(pos.realized_pnl, 1500.0);
+        assert_eq!(manager.active_position_count(), 1);
+    }
+
+    #[test]
+    fn test_sell_through_long_flips_short() {
+        let mut manager = PositionManager::new();
+        manager.update_position(&create_test_trade("BTCUSDT", "buy", 1.0, 100.0));
+
+        let touched = manager.update_position(&create_test_trade("BTCUSDT", "sell", 3.0, 110.0));
+        assert_eq!(touched.len(), 2);
+        assert!(touched[0].is_closed());
+        assert_eq!(touched[0].position.realized_pnl, 10.0);
+        assert_ne!(touched[0].position.id, touched[1].position.id);
+
+        let short = manager.get_position("BTCUSDT", "short").unwrap();
+        assert_eq!(short.quantity, 2.0);
+        assert_eq!(short.entry_price, 110.0);
+        assert_eq!(short.realized_pnl, 0.0);
+        assert_eq!(manager.get_total_realized_pnl(), 10.0);
+    }
+
+    #[test]
+    fn test_fifo_consumes_oldest_lots() {
+        let mut manager = PositionManager::with_accounting(PositionMode::Net, CostMethod::Fifo);
+        manager.update_position(&create_test_trade("ETHUSDT", "buy", 1.0, 100.0));
+        manager.update_position(&create_test_trade("ETHUSDT", "buy", 1.0, 200.0));
+
+        manager.update_position(&create_test_trade("ETHUSDT", "sell", 1.0, 250.0));
+        let tracked = manager.get_tracked_position("ETHUSDT", "long").unwrap();
+        assert_eq!(tracked.position.realized_pnl, 150.0); // Closed the 100 lot
+        assert_eq!(tracked.lots.len(), 1);
+        assert_eq!(tracked.position.entry_price, 200.0);
+
+        // Average cost closes against the blended price instead
+        let mut manager = PositionManager::new();
+        manager.update_position(&create_test_trade("ETHUSDT", "buy", 1.0, 100.0));
+        manager.update_position(&create_test_trade("ETHUSDT", "buy", 1.0, 200.0));
+        manager.update_position(&create_test_trade("ETHUSDT", "sell", 1.0, 250.0));
+        let pos = manager.get_position("ETHUSDT", "long").unwrap();
+        assert_eq!(pos.realized_pnl, 100.0);
+        assert_eq!(pos.entry_price, 150.0);
+    }
+
+    #[test]
+    fn test_fees_reduce_realized_pnl() {
+        let mut manager = PositionManager::new();
+        let mut open = create_test_trade("BTCUSDT", "buy", 1.0, 100.0);
+        open.commission = 0.5;
+        manager.update_position(&open);
+
+        let mut close = create_test_trade("BTCUSDT", "sell", 1.0, 110.0);
+        close.commission = 0.5;
+        let touched = manager.update_position(&close);
+
+        assert_eq!(touched[0].fees, 1.0);
+        assert_eq!(touched[0].position.realized_pnl, 9.0);
+        assert_eq!(manager.get_total_realized_pnl(), 9.0);
+    }
+
     #[test]
     fn test_unrealized_pnl_long_position() {
         let mut manager = PositionManager::new();
@@ -244,11 +588,11 @@ mod tests {
         manager.update_position(&trade);
 
         // Update with current price
-        let update = create_test_trade("BTCUSDT", "buy", 0.0, 51000.0);
-        manager.update_position(&update);
+        manager.update_mark_price("BTCUSDT", 51000.0);
 
         let pos = manager.get_position("BTCUSDT", "buy").unwrap();
         assert!((pos.unrealized_pnl - 1000.0).abs() < 0.01); // (51000 - 50000) * 1
+        assert_eq!(pos.current_price, Some(51000.0));
     }
 
     #[test]
@@ -259,8 +603,7 @@ mod tests {
         manager.update_position(&trade);
 
         // Update with current price
-        let update = create_test_trade("BTCUSDT", "sell", 0.0, 49000.0);
-        manager.update_position(&update);
+        manager.update_mark_price("BTCUSDT", 49000.0);
 
         let pos = manager.get_position("BTCUSDT", "sell").unwrap();
         assert!((pos.unrealized_pnl - 1000.0).abs() < 0.01); // (50000 - 49000) * 1
@@ -281,6 +624,18 @@ mod tests {
         assert_eq!(manager.active_position_count(), 0);
     }
 
+    #[test]
+    fn test_close_position_accumulates_realized() {
+        let mut manager = PositionManager::new();
+        manager.update_position(&create_test_trade("BTCUSDT", "buy", 2.0, 100.0));
+        manager.update_position(&create_test_trade("BTCUSDT", "sell", 1.0, 110.0));
+
+        // Only the remaining quantity is realised by the close
+        let realized_pnl = manager.close_position("BTCUSDT", "long", 120.0);
+        assert_eq!(realized_pnl, 20.0);
+        assert_eq!(manager.get_total_realized_pnl(), 30.0);
+    }
+
     #[test]
     fn test_multiple_positions() {
         let mut manager = PositionManager::new();
@@ -311,8 +666,7 @@ mod tests {
         manager.update_position(&trade1);
 
         // Update price
-        let update = create_test_trade("BTCUSDT", "buy", 0.0, 51000.0);
-        manager.update_position(&update);
+        manager.update_mark_price("BTCUSDT", 51000.0);
 
         let total_unrealized = manager.get_total_unrealized_pnl();
         assert!((total_unrealized - 1000.0).abs() < 0.01);
@@ -351,8 +705,8 @@ mod tests {
     }
 
     #[test]
-    fn test_long_and_short_separate() {
-        let mut manager = PositionManager::new();
+    fn test_hedge_mode_keeps_long_and_short_separate() {
+        let mut manager = PositionManager::with_accounting(PositionMode::Hedge, CostMethod::AverageCost);
 
         // Open long
         let long_trade = create_test_trade("BTCUSDT", "buy", 1.0, 50000.0);
@@ -363,11 +717,32 @@ mod tests {
         manager.update_position(&short_trade);
 
         assert_eq!(manager.active_position_count(), 2);
+        assert!(manager.get_position("BTCUSDT", "buy").is_some());
+        assert!(manager.get_position("BTCUSDT", "sell").is_some());
+
+        // A sell on the long leg closes it without touching the short
+        let mut close_long = create_test_trade("BTCUSDT", "sell", 2.0, 51000.0);
+        close_long.position_side = Some("long".to_string());
+        let touched = manager.update_position(&close_long);
+        assert_eq!(touched.len(), 1);
+        assert!(touched[0].is_closed());
+        assert_eq!(touched[0].position.realized_pnl, 1000.0);
+        assert_eq!(manager.active_position_count(), 1);
+        assert_eq!(manager.get_position("BTCUSDT", "short").unwrap().quantity, 1.0);
+    }
 
-        let long_pos = manager.get_position("BTCUSDT", "buy");
-        let short_pos = manager.get_position("BTCUSDT", "sell");
-
-        assert!(long_pos.is_some());
-        assert!(short_pos.is_some());
+    #[test]
+    fn test_restore_loads_single_lot() {
+        let mut manager = PositionManager::new();
+        let mut tracked = TrackedPosition::open("BTCUSDT", "buy", 0);
+        tracked.position.quantity = 2.0;
+        tracked.position.entry_price = 100.0;
+        tracked.position.realized_pnl = 5.0;
+        manager.restore(tracked);
+
+        manager.update_position(&create_test_trade("BTCUSDT", "sell", 1.0, 110.0));
+        let pos = manager.get_position("BTCUSDT", "long").unwrap();
+        assert_eq!(pos.quantity, 1.0);
+        assert_eq!(pos.realized_pnl, 15.0);
     }
 }
@@ -3,5 +3,7 @@
 //! This module provides position tracking and management functionality.
 
 pub mod manager;
+pub mod pnl;
 
-pub use manager::{PositionManager, Trade};
+pub use manager::{CostMethod, Lot, PositionManager, PositionMode, TrackedPosition, Trade};
+pub use pnl::{commission_in_quote, CostBasis};
@@ -0,0 +1,131 @@
+//! Fill-based realised PnL
+//!
+//! Realised PnL is derived by replaying fills through an average-cost basis
+//! for the net position in a symbol. Order snapshots only carry cumulative
+//! averages, which lose the price of individual executions.
+
+use crate::core::trade::types::OrderSide;
+
+/// Quantities below this are treated as flat
+const QTY_EPSILON: f64 = 1e-12;
+
+/// Average-cost basis of a net position in one symbol
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CostBasis {
+    /// Signed quantity, positive for long and negative for short
+    pub quantity: f64,
+    pub avg_price: f64,
+}
+
+impl CostBasis {
+    /// Apply a fill and return the gross PnL realised by it
+    ///
+    /// Fills in the direction of the position move the average price;
+    /// fills against it realise PnL on the closed quantity. A fill larger
+    /// than the position flips it, with the remainder opened at the fill price.
+    pub fn apply(&mut self, side: OrderSide, quantity: f64, price: f64) -> f64 {
+        let signed = match side {
+            OrderSide::Buy => quantity,
+            OrderSide::Sell => -quantity,
+        };
+
+        let is_flat = self.quantity.abs() < QTY_EPSILON;
+        if is_flat || self.quantity.signum() == signed.signum() {
+            let held = self.quantity.abs();
+            self.avg_price = (held * self.avg_price + quantity * price) / (held + quantity);
+            self.quantity += signed;
+            return 0.0;
+        }
+
+        let closed = quantity.min(self.quantity.abs());
+        let realized = if self.quantity > 0.0 {
+            (price - self.avg_price) * closed
+        } else {
+            (self.avg_price - price) * closed
+        };
+
+        self.quantity += signed;
+        if self.quantity.abs() < QTY_EPSILON {
+            *self = Self::default();
+        } else if self.quantity.signum() == signed.signum() {
+            // Position flipped; the remainder was opened at this fill
+            self.avg_price = price;
+        }
+
+        realized
+    }
+}
+
+/// Value a commission in the quote asset of `symbol`
+///
+/// Fees charged in the quote asset count as-is and fees in the base asset
+/// are converted at the fill price. Fees in a third asset (e.g. BNB) can't
+/// be valued here and count as zero.
+pub fn commission_in_quote(symbol: &str, asset: Option<&str>, commission: f64, price: f64) -> f64 {
+    let Some(asset) = asset.filter(|a| !a.is_empty() && *a != symbol) else {
+        return 0.0;
+    };
+
+    if symbol.ends_with(asset) {
+        commission
+    } else if symbol.starts_with(asset) {
+        commission * price
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_and_average_long() {
+        let mut basis = CostBasis::default();
+        assert_eq!(basis.apply(OrderSide::Buy, 1.0, 100.0), 0.0);
+        assert_eq!(basis.apply(OrderSide::Buy, 1.0, 200.0), 0.0);
+
+        assert_eq!(basis.quantity, 2.0);
+        assert_eq!(basis.avg_price, 150.0);
+    }
+
+    #[test]
+    fn test_partial_close_realizes_pnl() {
+        let mut basis = CostBasis::default();
+        basis.apply(OrderSide::Buy, 2.0, 150.0);
+
+        let realized = basis.apply(OrderSide::Sell, 0.5, 170.0);
+        assert_eq!(realized, 10.0);
+        assert_eq!(basis.quantity, 1.5);
+        assert_eq!(basis.avg_price, 150.0);
+    }
+
+    #[test]
+    fn test_short_close() {
+        let mut basis = CostBasis::default();
+        basis.apply(OrderSide::Sell, 1.0, 100.0);
+
+        let realized = basis.apply(OrderSide::Buy, 1.0, 90.0);
+        assert_eq!(realized, 10.0);
+        assert_eq!(basis, CostBasis::default());
+    }
+
+    #[test]
+    fn test_flip_opens_remainder_at_fill_price() {
+        let mut basis = CostBasis::default();
+        basis.apply(OrderSide::Buy, 1.0, 100.0);
+
+        let realized = basis.apply(OrderSide::Sell, 3.0, 110.0);
+        assert_eq!(realized, 10.0);
+        assert_eq!(basis.quantity, -2.0);
+        assert_eq!(basis.avg_price, 110.0);
+    }
+
+    #[test]
+    fn test_commission_in_quote() {
+        assert_eq!(commission_in_quote("BTCUSDT", Some("USDT"), 1.5, 50000.0), 1.5);
+        assert_eq!(commission_in_quote("BTCUSDT", Some("BTC"), 0.0001, 50000.0), 5.0);
+        assert_eq!(commission_in_quote("BTCUSDT", Some("BNB"), 0.01, 50000.0), 0.0);
+        assert_eq!(commission_in_quote("BTCUSDT", None, 1.0, 50000.0), 0.0);
+    }
+}
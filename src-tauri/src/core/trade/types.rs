@@ -87,6 +87,42 @@ pub struct Kline {
     pub quote_volume: Option<f64>,
 }
 
+/// One price level of an order book
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBookLevel {
+    pub price: f64,
+    pub quantity: f64,
+}
+
+/// Order book depth snapshot; bids best (highest) first, asks best (lowest) first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBook {
+    pub symbol: String,
+    pub bids: Vec<OrderBookLevel>,
+    pub asks: Vec<OrderBookLevel>,
+    pub timestamp: i64,
+}
+
+impl OrderBook {
+    /// Levels an order on `side` takes liquidity from
+    pub fn levels_for(&self, side: OrderSide) -> &[OrderBookLevel] {
+        match side {
+            OrderSide::Buy => &self.asks,
+            OrderSide::Sell => &self.bids,
+        }
+    }
+
+    /// Midpoint of the best bid and ask
+    pub fn mid_price(&self) -> Option<f64> {
+        match (self.bids.first(), self.asks.first()) {
+            (Some(bid), Some(ask)) => Some((bid.price + ask.price) / 2.0),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Balance {
@@ -96,6 +132,50 @@ pub struct Balance {
     pub total: f64,
 }
 
+/// Instrument trading status as reported by the exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InstrumentStatus {
+    /// Open for trading
+    Trading,
+    /// Temporarily halted or in pre-trading
+    Halted,
+    /// Delisted / settled, no longer tradable
+    Closed,
+}
+
+impl std::fmt::Display for InstrumentStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Trading => write!(f, "trading"),
+            Self::Halted => write!(f, "halted"),
+            Self::Closed => write!(f, "closed"),
+        }
+    }
+}
+
+/// Exchange trading rules for a single symbol
+///
+/// Symbols are stored in the normalized form (e.g. `BTCUSDT`) regardless of
+/// the exchange-native format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Instrument {
+    pub symbol: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub status: InstrumentStatus,
+    /// Minimum price increment
+    pub tick_size: f64,
+    /// Minimum quantity increment (lot size)
+    pub step_size: f64,
+    pub min_qty: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_qty: Option<f64>,
+    /// Minimum order value in quote asset (0 when the exchange has no limit)
+    pub min_notional: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Position {
@@ -131,9 +211,59 @@ pub struct Order {
     pub avg_price: Option<f64>,
     pub status: OrderState,
     pub commission: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commission_asset: Option<String>,
     pub created_at: i64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub filled_at: Option<i64>,
+    /// Exchange time of the order's latest change (ms), when the venue reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<i64>,
+}
+
+/// A single execution (fill) reported by the exchange
+///
+/// One order can produce many fills; each carries its own price and fee.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fill {
+    pub exchange_order_id: String,
+    /// Exchange execution id, unique per exchange
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exchange_trade_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_order_id: Option<String>,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub price: f64,
+    pub quantity: f64,
+    /// Fee paid for this execution (negative for rebates)
+    pub commission: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commission_asset: Option<String>,
+    pub is_maker: bool,
+    pub timestamp: i64,
+}
+
+/// A fill as stored in the `trades` table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeRecord {
+    pub id: String,
+    pub order_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exchange_trade_id: Option<String>,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub price: f64,
+    pub quantity: f64,
+    pub commission: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commission_asset: Option<String>,
+    /// Realised PnL of this fill, net of fees paid in the quote asset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub realized_pnl: Option<f64>,
+    pub timestamp: i64,
 }
 
 /// Order type enum
@@ -221,6 +351,86 @@ pub struct OrderRequest {
     pub time_in_force: Option<TimeInForce>,
 }
 
+/// Longest client order id every supported venue accepts (OKX allows 32)
+pub const MAX_CLIENT_ORDER_ID_LEN: usize = 32;
+
+impl OrderRequest {
+    /// New client order id: an alphanumeric tag followed by random hex
+    ///
+    /// The tag is cut to 8 characters so the id stays within
+    /// `MAX_CLIENT_ORDER_ID_LEN` with 24 random hex digits.
+    pub fn new_client_order_id(tag: &str) -> String {
+        let tag: String = tag.chars().filter(|c| c.is_ascii_alphanumeric()).take(8).collect();
+        let random = uuid::Uuid::new_v4().simple().to_string();
+        format!("{}{}", tag, &random[..MAX_CLIENT_ORDER_ID_LEN - tag.len()])
+    }
+
+    /// Whether every venue accepts the client order id
+    pub fn is_valid_client_order_id(id: &str) -> bool {
+        !id.is_empty() && id.len() <= MAX_CLIENT_ORDER_ID_LEN && id.chars().all(|c| c.is_ascii_alphanumeric())
+    }
+}
+
+/// Changes to a resting order
+///
+/// `quantity` is the new total order quantity, including anything already
+/// filled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<f64>,
+}
+
+impl AmendRequest {
+    /// Check the amendment against the current order
+    pub fn validate(&self, order: &Order) -> anyhow::Result<()> {
+        if self.price.is_none() && self.quantity.is_none() {
+            anyhow::bail!("Amendment must change the price or the quantity");
+        }
+        if let Some(price) = self.price {
+            if price <= 0.0 {
+                anyhow::bail!("Amended price must be positive");
+            }
+            if !matches!(order.order_type, OrderType::Limit | OrderType::StopLimit) {
+                anyhow::bail!("Only limit orders have a price to amend");
+            }
+        }
+        if let Some(quantity) = self.quantity {
+            if quantity <= order.filled_quantity {
+                anyhow::bail!(
+                    "Amended quantity {} must exceed the filled quantity {}",
+                    quantity,
+                    order.filled_quantity
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// The order with the amendment applied
+    pub fn apply(&self, order: &Order) -> Order {
+        Order {
+            price: self.price.or(order.price),
+            quantity: self.quantity.unwrap_or(order.quantity),
+            ..order.clone()
+        }
+    }
+}
+
+/// What an amendment did on the venue
+#[derive(Debug, Clone)]
+pub enum AmendOutcome {
+    /// The order was changed in place
+    Amended(Order),
+    /// The order was canceled and this new order placed for its unfilled rest
+    Replaced(Order),
+    /// The order was canceled but its replacement was refused
+    ReplaceFailed(String),
+}
+
 /// Time in force for orders
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -290,3 +500,25 @@ impl Interval {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_client_order_id() {
+        let id = OrderRequest::new_client_order_id("risk-close");
+        assert!(id.starts_with("riskclos"));
+        assert_eq!(id.len(), MAX_CLIENT_ORDER_ID_LEN);
+        assert!(OrderRequest::is_valid_client_order_id(&id));
+        assert_ne!(id, OrderRequest::new_client_order_id("risk-close"));
+    }
+
+    #[test]
+    fn test_is_valid_client_order_id() {
+        assert!(OrderRequest::is_valid_client_order_id("client123"));
+        assert!(!OrderRequest::is_valid_client_order_id(""));
+        assert!(!OrderRequest::is_valid_client_order_id("close_1"));
+        assert!(!OrderRequest::is_valid_client_order_id(&"a".repeat(33)));
+    }
+}
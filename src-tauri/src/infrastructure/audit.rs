@@ -18,6 +18,9 @@ pub enum AuditEvent {
     StrategyDeleted { user_id: String, strategy_id: String, strategy_name: String },
     OrderPlaced { user_id: String, order_id: String, symbol: String, side: String, quantity: f64 },
     RiskAlertTriggered { user_id: String, alert_type: String, severity: String, message: String },
+    /// 交易熔断启用或升级；风控监控触发时 user_id 为空
+    TradingHaltEngaged { user_id: Option<String>, level: String, reason: String },
+    TradingHaltRearmed { user_id: String, level: String, reason: String },
     SystemStarted { version: String },
 }
 
@@ -84,7 +87,9 @@ impl AuditService {
             | AuditEvent::StrategyUpdated { user_id, .. }
             | AuditEvent::StrategyDeleted { user_id, .. }
             | AuditEvent::OrderPlaced { user_id, .. }
-            | AuditEvent::RiskAlertTriggered { user_id, .. } => Some(user_id.clone()),
+            | AuditEvent::RiskAlertTriggered { user_id, .. }
+            | AuditEvent::TradingHaltRearmed { user_id, .. } => Some(user_id.clone()),
+            AuditEvent::TradingHaltEngaged { user_id, .. } => user_id.clone(),
             AuditEvent::SystemStarted { .. } => None,
         }
     }
@@ -177,6 +182,16 @@ mod tests {
                 severity: "high".to_string(),
                 message: "Drawdown exceeds 10%".to_string(),
             },
+            AuditEvent::TradingHaltEngaged {
+                user_id: None,
+                level: "full_halt".to_string(),
+                reason: "Drawdown exceeds 10%".to_string(),
+            },
+            AuditEvent::TradingHaltRearmed {
+                user_id: "user123".to_string(),
+                level: "full_halt".to_string(),
+                reason: "Positions reviewed".to_string(),
+            },
             AuditEvent::SystemStarted {
                 version: "1.0.0".to_string(),
             },
@@ -5,21 +5,35 @@ use std::str::FromStr;
 use std::sync::Arc;
 use tauri::{AppHandle, Manager};
 
-use crate::repository::{UserRepository, StrategyRepository, StrategyInstanceRepository, RiskRuleRepository};
+use crate::repository::{UserRepository, StrategyRepository, StrategyInstanceRepository, RiskAlertRepository, RiskRuleRepository};
 use crate::infrastructure::audit::AuditService;
+use crate::infrastructure::DefaultNotificationService;
 use crate::core::EventBus;
+use crate::core::risk::RiskMonitor;
 use crate::core::strategy::StrategyEngine;
 use crate::core::trade::exchange::binance::BinanceExchange;
 use crate::core::trade::exchange::Exchange;
-use crate::services::TradeService;
+use crate::core::trade::execution::ExecutionAlgoEngine;
+use crate::core::trade::order::ConditionalOrderEngine;
+use crate::core::{AppError, AppResult};
+use crate::services::{
+    DeadManSwitch, ExchangeSessionRegistry, KillSwitchService, OrderReconciler, PnlService, PortfolioService, TradeService,
+};
 use tokio::sync::RwLock;
 
 pub struct Database {
     pub pool: SqlitePool,
     event_bus: Arc<EventBus>,
     strategy_engine: Arc<crate::core::strategy::StrategyEngine>,
+    risk_monitor: Arc<RiskMonitor>,
     exchange: Arc<dyn Exchange>,
+    exchange_sessions: Arc<ExchangeSessionRegistry>,
+    portfolio_service: Arc<PortfolioService>,
+    pnl_service: Arc<PnlService>,
+    kill_switch: Arc<KillSwitchService>,
+    dead_man_switch: Arc<DeadManSwitch>,
     trade_service: Arc<RwLock<Option<Arc<TradeService>>>>,
+    order_reconciler: Arc<RwLock<Option<Arc<OrderReconciler>>>>,
 }
 
 impl Database {
@@ -56,6 +70,11 @@ impl Database {
         let exchange: Arc<dyn Exchange> = Arc::new(BinanceExchange::new(None, None));
         log::info!("Exchange initialized (Binance)");
 
+        // 创建交易所账户会话注册表
+        let exchange_sessions = Arc::new(ExchangeSessionRegistry::new(pool.clone(), event_bus.clone()));
+        let portfolio_service = Arc::new(PortfolioService::new(pool.clone(), exchange_sessions.clone()));
+        let pnl_service = Arc::new(PnlService::new(pool.clone(), portfolio_service.clone()));
+
         // 创建 StrategyInstanceRepository
         let instance_repo = Arc::new(StrategyInstanceRepository::new(pool.clone()));
         log::info!("StrategyInstanceRepository initialized");
@@ -65,9 +84,27 @@ impl Database {
             event_bus.clone(),
             exchange.clone(),
             instance_repo,
-        ));
+        )
+        .with_exchange_sessions(exchange_sessions.clone())
+        .with_pnl_service(pnl_service.clone())
+        .with_risk_rules(pool.clone()));
         log::info!("StrategyEngine initialized");
 
+        // 创建交易熔断开关，熔断状态保存在数据库中
+        let kill_switch = Arc::new(KillSwitchService::new(pool.clone(), exchange_sessions.clone(), strategy_engine.clone()));
+
+        // 创建失联保护，规则启用后才生效
+        let dead_man_switch = Arc::new(DeadManSwitch::new(pool.clone(), exchange_sessions.clone(), strategy_engine.clone()));
+
+        // 创建风控监控，规则在迁移完成后加载
+        let risk_monitor = Arc::new(
+            RiskMonitor::new(exchange_sessions.clone(), strategy_engine.clone(), Arc::new(DefaultNotificationService))
+                .with_pnl_service(pnl_service.clone())
+                .with_alert_repository(RiskAlertRepository::new(pool.clone()))
+                .with_event_bus(event_bus.clone())
+                .with_kill_switch(kill_switch.clone()),
+        );
+
         // TradeService will be initialized lazily when needed
         let trade_service = Arc::new(RwLock::new(None));
 
@@ -75,8 +112,15 @@ impl Database {
             pool,
             event_bus,
             strategy_engine,
+            risk_monitor,
             exchange,
+            exchange_sessions,
+            portfolio_service,
+            pnl_service,
+            kill_switch,
+            dead_man_switch,
             trade_service,
+            order_reconciler: Arc::new(RwLock::new(None)),
         })
     }
 
@@ -92,6 +136,11 @@ impl Database {
         let exchange: Arc<dyn Exchange> = Arc::new(BinanceExchange::new(None, None));
         log::info!("Exchange initialized (Binance)");
 
+        // 创建交易所账户会话注册表
+        let exchange_sessions = Arc::new(ExchangeSessionRegistry::new(pool.clone(), event_bus.clone()));
+        let portfolio_service = Arc::new(PortfolioService::new(pool.clone(), exchange_sessions.clone()));
+        let pnl_service = Arc::new(PnlService::new(pool.clone(), portfolio_service.clone()));
+
         // 创建 StrategyInstanceRepository
         let instance_repo = Arc::new(StrategyInstanceRepository::new(pool.clone()));
         log::info!("StrategyInstanceRepository initialized");
@@ -101,9 +150,27 @@ impl Database {
             event_bus.clone(),
             exchange.clone(),
             instance_repo,
-        ));
+        )
+        .with_exchange_sessions(exchange_sessions.clone())
+        .with_pnl_service(pnl_service.clone())
+        .with_risk_rules(pool.clone()));
         log::info!("StrategyEngine initialized");
 
+        // 创建交易熔断开关，熔断状态保存在数据库中
+        let kill_switch = Arc::new(KillSwitchService::new(pool.clone(), exchange_sessions.clone(), strategy_engine.clone()));
+
+        // 创建失联保护，规则启用后才生效
+        let dead_man_switch = Arc::new(DeadManSwitch::new(pool.clone(), exchange_sessions.clone(), strategy_engine.clone()));
+
+        // 创建风控监控，规则在迁移完成后加载
+        let risk_monitor = Arc::new(
+            RiskMonitor::new(exchange_sessions.clone(), strategy_engine.clone(), Arc::new(DefaultNotificationService))
+                .with_pnl_service(pnl_service.clone())
+                .with_alert_repository(RiskAlertRepository::new(pool.clone()))
+                .with_event_bus(event_bus.clone())
+                .with_kill_switch(kill_switch.clone()),
+        );
+
         // TradeService will be initialized lazily when needed
         let trade_service = Arc::new(RwLock::new(None));
 
@@ -111,8 +178,15 @@ impl Database {
             pool,
             event_bus,
             strategy_engine,
+            risk_monitor,
             exchange,
+            exchange_sessions,
+            portfolio_service,
+            pnl_service,
+            kill_switch,
+            dead_man_switch,
             trade_service,
+            order_reconciler: Arc::new(RwLock::new(None)),
         })
     }
 
@@ -164,22 +238,179 @@ impl Database {
         self.strategy_engine.clone()
     }
 
+    /// 获取风控监控
+    pub fn get_risk_monitor(&self) -> Arc<RiskMonitor> {
+        self.risk_monitor.clone()
+    }
+
+    /// 风控规则修改后重新加载风控监控和运行实例中的该条规则
+    pub async fn reload_risk_rule(&self, rule_name: &str) -> Result<()> {
+        self.risk_monitor.reload_rule(&self.pool, rule_name).await?;
+        let instances = self.strategy_engine.reload_risk_rule(rule_name).await?;
+        log::info!("Risk rule {} reloaded for monitor and {} running instances", rule_name, instances);
+        Ok(())
+    }
+
     /// 获取 Exchange
     pub fn get_exchange(&self) -> Arc<dyn Exchange> {
         self.exchange.clone()
     }
 
+    /// 获取交易所账户会话注册表
+    pub fn get_exchange_sessions(&self) -> Arc<ExchangeSessionRegistry> {
+        self.exchange_sessions.clone()
+    }
+
+    /// 获取跨交易所组合服务
+    pub fn get_portfolio_service(&self) -> Arc<PortfolioService> {
+        self.portfolio_service.clone()
+    }
+
+    /// 获取权益与盈亏服务
+    pub fn get_pnl_service(&self) -> Arc<PnlService> {
+        self.pnl_service.clone()
+    }
+
+    /// 获取交易熔断开关
+    pub fn get_kill_switch(&self) -> Arc<KillSwitchService> {
+        self.kill_switch.clone()
+    }
+
+    /// 获取失联保护
+    pub fn get_dead_man_switch(&self) -> Arc<DeadManSwitch> {
+        self.dead_man_switch.clone()
+    }
+
+    /// 获取用户指定交易所账户的 TradeService
+    ///
+    /// 未指定 `exchange_id` 时使用用户最早的活跃账户；用户没有活跃账户时
+    /// 返回错误，不会落到未认证的默认 TradeService。
+    pub async fn get_trade_service_for_user(
+        &self,
+        user_id: &str,
+        exchange_id: Option<&str>,
+    ) -> AppResult<Arc<TradeService>> {
+        match self.exchange_sessions.for_user(user_id, exchange_id).await? {
+            Some(session) => Ok(session.trade_service.clone()),
+            None => Err(AppError::validation(format!("No active exchange account for user {}", user_id))),
+        }
+    }
+
+    /// 获取订单所属交易所账户的 TradeService
+    pub async fn get_trade_service_for_order(
+        &self,
+        user_id: &str,
+        order_id: &str,
+    ) -> AppResult<Arc<TradeService>> {
+        let exchange_id: Option<String> = sqlx::query_scalar(
+            "SELECT exchange_id FROM orders WHERE id = ? AND user_id = ?"
+        )
+        .bind(order_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        self.get_trade_service_for_user(user_id, exchange_id.as_deref()).await
+    }
+
+    /// 获取用户交易所账户的条件单引擎
+    ///
+    /// 条件单依赖账户会话，用户没有活跃账户时返回错误。
+    pub async fn get_conditional_order_engine(
+        &self,
+        user_id: &str,
+        exchange_id: Option<&str>,
+    ) -> AppResult<Arc<ConditionalOrderEngine>> {
+        match self.exchange_sessions.for_user(user_id, exchange_id).await? {
+            Some(session) => Ok(session.conditional_orders.clone()),
+            None => Err(AppError::validation("No active exchange account")),
+        }
+    }
+
+    /// 获取条件单所属交易所账户的条件单引擎
+    pub async fn get_conditional_order_engine_for(
+        &self,
+        user_id: &str,
+        conditional_order_id: &str,
+    ) -> AppResult<Arc<ConditionalOrderEngine>> {
+        let exchange_id: Option<String> = sqlx::query_scalar(
+            "SELECT exchange_id FROM conditional_orders WHERE id = ? AND user_id = ?"
+        )
+        .bind(conditional_order_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let exchange_id = exchange_id.ok_or_else(|| {
+            AppError::validation(format!("Conditional order not found: {}", conditional_order_id))
+        })?;
+        self.get_conditional_order_engine(user_id, Some(&exchange_id)).await
+    }
+
+    /// 获取用户交易所账户的执行算法引擎
+    pub async fn get_execution_engine(
+        &self,
+        user_id: &str,
+        exchange_id: Option<&str>,
+    ) -> AppResult<Arc<ExecutionAlgoEngine>> {
+        match self.exchange_sessions.for_user(user_id, exchange_id).await? {
+            Some(session) => Ok(session.execution.clone()),
+            None => Err(AppError::validation("No active exchange account")),
+        }
+    }
+
+    /// 获取母单所属交易所账户的执行算法引擎
+    pub async fn get_execution_engine_for_order(
+        &self,
+        user_id: &str,
+        parent_order_id: &str,
+    ) -> AppResult<Arc<ExecutionAlgoEngine>> {
+        let exchange_id: Option<String> = sqlx::query_scalar(
+            "SELECT exchange_id FROM orders WHERE id = ? AND user_id = ? AND algo IS NOT NULL"
+        )
+        .bind(parent_order_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let exchange_id = exchange_id.ok_or_else(|| {
+            AppError::validation(format!("Parent order not found: {}", parent_order_id))
+        })?;
+        self.get_execution_engine(user_id, Some(&exchange_id)).await
+    }
+
     /// 获取或初始化 TradeService (async)
     pub async fn get_trade_service(&self) -> Arc<TradeService> {
         let mut service_guard = self.trade_service.write().await;
         if let Some(service) = &*service_guard {
             service.clone()
         } else {
-            let new_service = Arc::new(TradeService::new(self.exchange.clone(), self.pool.clone()));
+            let new_service = Arc::new(
+                TradeService::new(self.exchange.clone(), self.pool.clone())
+                    .with_event_bus(self.event_bus.clone()),
+            );
+            new_service.start_fill_recording();
+            new_service.start_order_updates();
+            new_service.start_mark_price_updates();
             *service_guard = Some(new_service.clone());
             new_service
         }
     }
+
+    /// 获取或初始化订单对账器 (async)
+    pub async fn get_order_reconciler(&self) -> Arc<OrderReconciler> {
+        if let Some(reconciler) = &*self.order_reconciler.read().await {
+            return reconciler.clone();
+        }
+
+        let trade_service = self.get_trade_service().await;
+        let mut guard = self.order_reconciler.write().await;
+        guard
+            .get_or_insert_with(|| {
+                Arc::new(OrderReconciler::new(trade_service, self.exchange_sessions.clone()))
+            })
+            .clone()
+    }
 }
 
 // 全局类型别名
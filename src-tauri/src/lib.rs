@@ -72,11 +72,32 @@ pub fn run() {
                     .await
                     .expect("Failed to run migrations");
 
+                // 重启前启用的交易熔断继续生效，直到被解除
+                match db.get_kill_switch().status().await {
+                    Ok(Some(halt)) => log::warn!("Trading is halted ({}): {}", halt.level, halt.reason),
+                    Ok(None) => {}
+                    Err(e) => log::error!("Failed to read trading halt: {}", e),
+                }
+
+                // 加载风控规则并启动风控监控
+                let risk_monitor = db.get_risk_monitor();
+                if let Err(e) = risk_monitor.reload_rules(&db.pool).await {
+                    log::warn!("Failed to load risk rules: {}", e);
+                }
+                risk_monitor.start().await;
+
+                // 恢复重启前未触发的条件单
+                match db.get_exchange_sessions().resume_conditional_orders().await {
+                    Ok(count) if count > 0 => log::info!("Resumed conditional orders on {} exchange accounts", count),
+                    Ok(_) => {}
+                    Err(e) => log::warn!("Failed to resume conditional orders: {}", e),
+                }
+
                 // 创建 MarketService (使用内部的 Database 结构)
                 let market_service = std::sync::Arc::new({
                     use crate::infrastructure::Database;
                     let ms_db = Database::new_with_pool(pool.clone()).await.expect("Failed to create MarketService Database");
-                    services::MarketService::new(ms_db)
+                    services::MarketService::new(ms_db).with_exchange_sessions(db.get_exchange_sessions())
                 });
 
                 // 初始化 Binance 交易所
@@ -86,6 +107,14 @@ pub fn run() {
                     log::info!("Binance exchange initialized successfully");
                 }
 
+                // 定期同步交易所服务器时间，供签名请求使用
+                market_service.start_clock_sync(std::time::Duration::from_secs(300)).await;
+
+                // 启动失联保护，行情中断也视为失联
+                let dead_man_switch = db.get_dead_man_switch();
+                dead_man_switch.watch_market_data(market_service.event_bus());
+                dead_man_switch.start().await;
+
                 // 创建 BacktestService
                 let backtest_service = std::sync::Arc::new({
                     use crate::infrastructure::Database;
@@ -149,6 +178,7 @@ pub fn run() {
             commands::market::market_subscribe_ticker,
             commands::market::market_get_klines,
             commands::market::market_get_symbols,
+            commands::market::market_get_instruments,
             commands::market::market_get_status,
             commands::market::market_unsubscribe_ticker,
             commands::strategy::strategy_list,
@@ -176,14 +206,42 @@ pub fn run() {
             // Trade commands
             commands::trade::trade_place_order,
             commands::trade::trade_cancel_order,
+            commands::trade::trade_amend_order,
             commands::trade::trade_get_order,
             commands::trade::trade_get_orders,
             commands::trade::trade_get_open_orders,
+            commands::trade::trade_get_trades,
             commands::trade::trade_sync_order_status,
+            commands::trade::trade_reconcile_orders,
+            commands::trade::trade_start_reconciliation,
+            commands::trade::trade_stop_reconciliation,
             commands::trade::trade_get_positions,
             commands::trade::trade_get_balance,
             commands::trade::trade_cancel_all_orders,
             commands::trade::trade_close_position,
+            commands::trade::trade_place_conditional_order,
+            commands::trade::trade_place_oco_order,
+            commands::trade::trade_cancel_conditional_order,
+            commands::trade::trade_get_conditional_orders,
+            commands::trade::trade_place_algo_order,
+            commands::trade::trade_cancel_algo_order,
+            commands::trade::trade_get_execution_report,
+            commands::trade::trade_get_child_orders,
+            commands::trade::trade_get_order_timeline,
+            commands::trade::trade_check_order,
+            // Portfolio commands
+            commands::portfolio::portfolio_get,
+            commands::portfolio::portfolio_snapshot,
+            commands::portfolio::portfolio_get_history,
+            commands::portfolio::portfolio_start_snapshots,
+            commands::portfolio::portfolio_stop_snapshots,
+            // PnL commands
+            commands::pnl::pnl_get_today,
+            commands::pnl::pnl_get_statements,
+            commands::pnl::pnl_get_equity_history,
+            commands::pnl::pnl_record_snapshot,
+            commands::pnl::pnl_start_snapshots,
+            commands::pnl::pnl_stop_snapshots,
             // Risk commands
             commands::risk::get_risk_overview,
             commands::risk::get_active_alerts,
@@ -195,8 +253,13 @@ pub fn run() {
             commands::risk::get_alert_detail,
             commands::risk::add_alert_note,
             commands::risk::delete_alert,
+            commands::risk::risk_replay_rules,
             // Emergency commands
             commands::emergency::emergency_stop,
+            commands::emergency::kill_switch_status,
+            commands::emergency::kill_switch_engage,
+            commands::emergency::kill_switch_rearm,
+            commands::emergency::dead_man_switch_status,
             // Config commands
             commands::config::config_get,
             commands::config::config_update,
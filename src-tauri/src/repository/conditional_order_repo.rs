@@ -0,0 +1,209 @@
+//! Conditional order repository
+//!
+//! Database operations for client-side conditional orders.
+
+use crate::core::trade::order::{ConditionalOrder, ConditionalStatus, ConditionalTrigger, TrailingOffset};
+use anyhow::{anyhow, Result};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+
+const COLUMNS: &str = "id, user_id, exchange_id, symbol, side, quantity, limit_price, kind, \
+                       trigger_price, trail_unit, trail_value, extreme_price, oco_group_id, status, \
+                       order_id, error, created_at, updated_at, triggered_at";
+
+/// Conditional order repository
+pub struct ConditionalOrderRepository {
+    pool: SqlitePool,
+}
+
+impl ConditionalOrderRepository {
+    /// Create a new conditional order repository
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Insert a new conditional order
+    pub async fn insert(&self, order: &ConditionalOrder) -> Result<()> {
+        let (trigger_price, trail_unit, trail_value, extreme_price) = trigger_columns(&order.trigger);
+
+        sqlx::query(&format!(
+            "INSERT INTO conditional_orders ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            COLUMNS
+        ))
+        .bind(&order.id)
+        .bind(&order.user_id)
+        .bind(&order.exchange_id)
+        .bind(&order.symbol)
+        .bind(order.side.to_string())
+        .bind(order.quantity)
+        .bind(order.limit_price)
+        .bind(order.trigger.kind())
+        .bind(trigger_price)
+        .bind(trail_unit)
+        .bind(trail_value)
+        .bind(extreme_price)
+        .bind(&order.oco_group_id)
+        .bind(order.status.as_str())
+        .bind(&order.order_id)
+        .bind(&order.error)
+        .bind(order.created_at)
+        .bind(order.updated_at)
+        .bind(order.triggered_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Update status, trailing reference and submitted order
+    pub async fn update(&self, order: &ConditionalOrder) -> Result<()> {
+        let (_, _, _, extreme_price) = trigger_columns(&order.trigger);
+
+        sqlx::query(
+            r#"
+            UPDATE conditional_orders
+            SET status = ?, extreme_price = ?, order_id = ?, error = ?,
+                updated_at = ?, triggered_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(order.status.as_str())
+        .bind(extreme_price)
+        .bind(&order.order_id)
+        .bind(&order.error)
+        .bind(order.updated_at)
+        .bind(order.triggered_at)
+        .bind(&order.id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get a conditional order by ID
+    pub async fn find_by_id(&self, id: &str) -> Result<Option<ConditionalOrder>> {
+        let row = sqlx::query(&format!("SELECT {} FROM conditional_orders WHERE id = ?", COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(row_to_order).transpose()
+    }
+
+    /// Pending orders of an exchange account, oldest first
+    pub async fn find_pending(&self, exchange_id: &str) -> Result<Vec<ConditionalOrder>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM conditional_orders WHERE exchange_id = ? AND status = 'pending' ORDER BY created_at",
+            COLUMNS
+        ))
+        .bind(exchange_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_order).collect()
+    }
+
+    /// Orders of a user, newest first
+    pub async fn find_by_user(
+        &self,
+        user_id: &str,
+        exchange_id: Option<&str>,
+        status: Option<ConditionalStatus>,
+        limit: usize,
+    ) -> Result<Vec<ConditionalOrder>> {
+        let status = status.map(|s| s.as_str());
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {} FROM conditional_orders
+            WHERE user_id = ?
+              AND (? IS NULL OR exchange_id = ?)
+              AND (? IS NULL OR status = ?)
+            ORDER BY created_at DESC
+            LIMIT ?
+            "#,
+            COLUMNS
+        ))
+        .bind(user_id)
+        .bind(exchange_id)
+        .bind(exchange_id)
+        .bind(status)
+        .bind(status)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_order).collect()
+    }
+
+    /// Exchange accounts that have pending orders
+    pub async fn pending_exchange_ids(&self) -> Result<Vec<String>> {
+        let ids = sqlx::query_scalar(
+            "SELECT DISTINCT exchange_id FROM conditional_orders WHERE status = 'pending'"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+}
+
+/// Split a trigger into (trigger_price, trail_unit, trail_value, extreme_price)
+fn trigger_columns(trigger: &ConditionalTrigger) -> (Option<f64>, Option<&'static str>, Option<f64>, Option<f64>) {
+    match trigger {
+        ConditionalTrigger::Stop { trigger_price } | ConditionalTrigger::TakeProfit { trigger_price } => {
+            (Some(*trigger_price), None, None, None)
+        }
+        ConditionalTrigger::TrailingStop { offset, extreme_price } => {
+            (None, Some(offset.unit()), Some(offset.value()), *extreme_price)
+        }
+    }
+}
+
+fn row_to_order(row: &SqliteRow) -> Result<ConditionalOrder> {
+    let kind: String = row.get("kind");
+    let trigger_price: Option<f64> = row.get("trigger_price");
+    let trigger = match kind.as_str() {
+        "stop" | "take_profit" => {
+            let trigger_price = trigger_price.ok_or_else(|| anyhow!("Missing trigger price"))?;
+            if kind == "stop" {
+                ConditionalTrigger::Stop { trigger_price }
+            } else {
+                ConditionalTrigger::TakeProfit { trigger_price }
+            }
+        }
+        "trailing_stop" => {
+            let unit: Option<String> = row.get("trail_unit");
+            let value: Option<f64> = row.get("trail_value");
+            let offset = match (unit, value) {
+                (Some(unit), Some(value)) => TrailingOffset::from_parts(&unit, value)?,
+                _ => return Err(anyhow!("Missing trailing offset")),
+            };
+            ConditionalTrigger::TrailingStop {
+                offset,
+                extreme_price: row.get("extreme_price"),
+            }
+        }
+        _ => return Err(anyhow!("Invalid conditional order kind: {}", kind)),
+    };
+
+    let side: String = row.get("side");
+    let status: String = row.get("status");
+
+    Ok(ConditionalOrder {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        exchange_id: row.get("exchange_id"),
+        symbol: row.get("symbol"),
+        side: side.parse()?,
+        quantity: row.get("quantity"),
+        limit_price: row.get("limit_price"),
+        trigger,
+        oco_group_id: row.get("oco_group_id"),
+        status: status.parse()?,
+        order_id: row.get("order_id"),
+        error: row.get("error"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        triggered_at: row.get("triggered_at"),
+    })
+}
@@ -0,0 +1,52 @@
+//! Kline repository
+//!
+//! Read access to the klines cached in the `klines` table.
+
+use crate::core::trade::types::Kline;
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+/// Kline repository
+pub struct KlineRepository {
+    pool: SqlitePool,
+}
+
+impl KlineRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Most recent `limit` klines of a symbol and timeframe, oldest first
+    pub async fn find_recent(&self, symbol: &str, timeframe: &str, limit: usize) -> Result<Vec<Kline>> {
+        let rows = sqlx::query_as::<_, (String, String, i64, f64, f64, f64, f64, f64)>(
+            r#"
+            SELECT symbol, timeframe, timestamp, open, high, low, close, volume
+            FROM klines
+            WHERE symbol = ? AND timeframe = ?
+            ORDER BY timestamp DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(symbol)
+        .bind(timeframe)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .rev()
+            .map(|(symbol, timeframe, timestamp, open, high, low, close, volume)| Kline {
+                symbol,
+                timeframe,
+                timestamp,
+                open,
+                high,
+                low,
+                close,
+                volume,
+                quote_volume: None,
+            })
+            .collect())
+    }
+}
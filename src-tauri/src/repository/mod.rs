@@ -17,6 +17,9 @@ pub mod strategy_instance_repo;
 pub mod risk_alert_repo;
 pub mod exchange_repo;
 pub mod risk_rule_repo;
+pub mod conditional_order_repo;
+pub mod kline_repo;
+pub mod trading_halt_repo;
 
 pub use user_repo::UserRepository;
 pub use strategy_repo::StrategyRepository;
@@ -24,3 +27,6 @@ pub use strategy_instance_repo::StrategyInstanceRepository;
 pub use risk_alert_repo::RiskAlertRepository;
 pub use exchange_repo::ExchangeRepository;
 pub use risk_rule_repo::{RiskRuleRepository, RiskRule, RiskRuleParams};
+pub use conditional_order_repo::ConditionalOrderRepository;
+pub use kline_repo::KlineRepository;
+pub use trading_halt_repo::TradingHaltRepository;
@@ -0,0 +1,74 @@
+//! Trading Halt Repository
+//!
+//! Database operations for the kill switch's halt state.
+
+use crate::core::risk::TradingHalt;
+use anyhow::{anyhow, Result};
+use sqlx::{FromRow, SqlitePool};
+
+/// Row of the `trading_halt` table
+#[derive(Debug, FromRow)]
+struct TradingHaltRow {
+    level: String,
+    reason: String,
+    engaged_by: Option<String>,
+    engaged_at: i64,
+    updated_at: i64,
+}
+
+/// Trading Halt Repository
+pub struct TradingHaltRepository {
+    pool: SqlitePool,
+}
+
+impl TradingHaltRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Get the engaged halt, if any
+    pub async fn find(&self) -> Result<Option<TradingHalt>> {
+        let row = sqlx::query_as::<_, TradingHaltRow>(
+            "SELECT level, reason, engaged_by, engaged_at, updated_at FROM trading_halt WHERE id = 1"
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            Ok(TradingHalt {
+                level: row.level.parse().map_err(|e| anyhow!("{}", e))?,
+                reason: row.reason,
+                engaged_by: row.engaged_by,
+                engaged_at: row.engaged_at,
+                updated_at: row.updated_at,
+            })
+        })
+        .transpose()
+    }
+
+    /// Store the halt, replacing the current one
+    pub async fn save(&self, halt: &TradingHalt) -> Result<()> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO trading_halt (id, level, reason, engaged_by, engaged_at, updated_at)
+             VALUES (1, ?, ?, ?, ?, ?)"
+        )
+        .bind(halt.level.as_str())
+        .bind(&halt.reason)
+        .bind(&halt.engaged_by)
+        .bind(halt.engaged_at)
+        .bind(halt.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Clear the halt; returns false if none was engaged
+    pub async fn delete(&self) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM trading_halt WHERE id = 1")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
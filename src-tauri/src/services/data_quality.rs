@@ -3,6 +3,7 @@
 //! Monitors the quality of market data including connection status,
 //! latency, message frequency, and data integrity.
 
+use crate::core::trade::exchange::{ClockSync, ClockSyncStatus};
 use crate::core::trade::types::Kline;
 use crate::infrastructure::Database;
 use serde::{Deserialize, Serialize};
@@ -71,6 +72,9 @@ pub struct DataQualityMetrics {
     pub quality_score: f64,
     /// Quality status
     pub status: DataQualityStatus,
+    /// Largest measured local-vs-exchange clock offset (ms, absolute)
+    #[serde(rename = "clockDriftMs", skip_serializing_if = "Option::is_none")]
+    pub clock_drift_ms: Option<i64>,
 }
 
 /// Data quality configuration
@@ -310,9 +314,20 @@ impl DataQualityMonitor {
             error_count: symbol_metrics.error_count,
             quality_score,
             status,
+            clock_drift_ms: Self::max_clock_drift(&ClockSync::synced_statuses()),
         })
     }
 
+    /// Get the measured clock offset for every synced exchange
+    pub fn get_clock_drift(&self) -> Vec<ClockSyncStatus> {
+        ClockSync::synced_statuses()
+    }
+
+    /// Largest absolute offset, `None` until at least one exchange has synced
+    fn max_clock_drift(statuses: &[ClockSyncStatus]) -> Option<i64> {
+        statuses.iter().map(|s| s.offset_ms.abs()).max()
+    }
+
     /// Get all metrics
     pub async fn get_all_metrics(&self) -> Vec<DataQualityMetrics> {
         let metrics = self.metrics.read().await;
@@ -411,6 +426,22 @@ mod tests {
         assert!(true);
     }
 
+    #[test]
+    fn test_max_clock_drift() {
+        let status = |exchange: &str, offset_ms: i64| ClockSyncStatus {
+            exchange: exchange.to_string(),
+            offset_ms,
+            rtt_ms: Some(50),
+            last_sync_at: Some(0),
+        };
+
+        assert_eq!(DataQualityMonitor::max_clock_drift(&[]), None);
+        assert_eq!(
+            DataQualityMonitor::max_clock_drift(&[status("binance", 120), status("okx", -800)]),
+            Some(800)
+        );
+    }
+
     #[test]
     fn test_timeframe_parsing() {
         // NOTE: parse_timeframe is a private method
@@ -0,0 +1,259 @@
+//! Dead-man's switch service
+//!
+//! Runs the dead-man's switch described in `core::risk::dead_man`. On every
+//! heartbeat it renews the cancel-all-after timer of each connected account
+//! whose venue has one, and checks that strategy instances and market data
+//! are still moving. When they are not, it cancels the open orders of every
+//! connected account once, and does so again only after the app has
+//! recovered and stalled anew.
+//!
+//! The switch is off until the `dead_man_switch` rule is enabled; disabling
+//! it turns the venue timers it armed off again.
+
+use crate::core::event::EventBus;
+use crate::core::risk::{unresponsive, DeadManConfig, DEAD_MAN_SWITCH_RULE};
+use crate::core::strategy::StrategyEngine;
+use crate::core::{AppError, AppResult};
+use crate::repository::RiskRuleRepository;
+use crate::services::ExchangeSessionRegistry;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// State of one account's venue timer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VenueTimer {
+    pub config_id: String,
+    pub exchange: String,
+    /// Whether the venue holds a timer; false when it has none
+    pub armed: bool,
+    /// Time (ms) of the last renewal attempt
+    pub renewed_at: i64,
+    /// Error of the last renewal, if it failed
+    pub error: Option<String>,
+}
+
+/// Dead-man's switch status for the frontend
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadManStatus {
+    pub enabled: bool,
+    pub timeout_secs: u64,
+    pub heartbeat_secs: u64,
+    /// Time (ms) of the last market event seen
+    pub last_market_event_at: Option<i64>,
+    pub venue_timers: Vec<VenueTimer>,
+    /// Time (ms) the switch last canceled orders, while still unresponsive
+    pub tripped_at: Option<i64>,
+    pub tripped_reason: Option<String>,
+}
+
+#[derive(Default)]
+struct DeadManState {
+    /// Time (ms) the switch was found enabled; the watchdog's baseline
+    enabled_since: Option<i64>,
+    /// Config id -> timer
+    venue_timers: HashMap<String, VenueTimer>,
+    tripped: Option<(i64, String)>,
+}
+
+/// Cancels open orders when the app stops responding
+pub struct DeadManSwitch {
+    pool: SqlitePool,
+    sessions: Arc<ExchangeSessionRegistry>,
+    strategy_engine: Arc<StrategyEngine>,
+    /// Time (ms) of the last market event; 0 before the first one
+    last_market_event: Arc<AtomicI64>,
+    state: Mutex<DeadManState>,
+}
+
+impl DeadManSwitch {
+    pub fn new(pool: SqlitePool, sessions: Arc<ExchangeSessionRegistry>, strategy_engine: Arc<StrategyEngine>) -> Self {
+        Self {
+            pool,
+            sessions,
+            strategy_engine,
+            last_market_event: Arc::new(AtomicI64::new(0)),
+            state: Mutex::new(DeadManState::default()),
+        }
+    }
+
+    /// Record the time of every market event published on `event_bus`
+    pub fn watch_market_data(&self, event_bus: Arc<EventBus>) {
+        let last_market_event = self.last_market_event.clone();
+        let mut market_rx = event_bus.subscribe_market();
+        tokio::spawn(async move {
+            loop {
+                match market_rx.recv().await {
+                    Ok(_) => last_market_event.store(Utc::now().timestamp_millis(), Ordering::SeqCst),
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Start the heartbeat loop
+    pub async fn start(self: Arc<Self>) {
+        tokio::spawn(async move {
+            loop {
+                let heartbeat = self.heartbeat().await;
+                tokio::time::sleep(heartbeat).await;
+            }
+        });
+
+        log::info!("Dead-man's switch started");
+    }
+
+    pub async fn status(&self) -> AppResult<DeadManStatus> {
+        let config = self.config().await?;
+        let state = self.state.lock().await;
+        let last_market_event = self.last_market_event.load(Ordering::SeqCst);
+
+        let mut venue_timers: Vec<VenueTimer> = state.venue_timers.values().cloned().collect();
+        venue_timers.sort_by(|a, b| a.config_id.cmp(&b.config_id));
+        let settings = config.unwrap_or_default();
+        Ok(DeadManStatus {
+            enabled: config.is_some(),
+            timeout_secs: settings.timeout_secs,
+            heartbeat_secs: settings.heartbeat_secs,
+            last_market_event_at: (last_market_event > 0).then_some(last_market_event),
+            venue_timers,
+            tripped_at: state.tripped.as_ref().map(|(at, _)| *at),
+            tripped_reason: state.tripped.as_ref().map(|(_, reason)| reason.clone()),
+        })
+    }
+
+    /// Run one heartbeat; returns the time until the next
+    async fn heartbeat(&self) -> Duration {
+        let config = match self.config().await {
+            Ok(config) => config,
+            Err(e) => {
+                log::error!("Failed to load dead-man's switch settings: {}", e);
+                None
+            }
+        };
+        let Some(config) = config else {
+            self.disarm().await;
+            return Duration::from_secs(DeadManConfig::default().heartbeat_secs);
+        };
+
+        self.renew_venue_timers(&config).await;
+        self.watch(&config).await;
+        Duration::from_secs(config.heartbeat_secs)
+    }
+
+    /// Settings of the `dead_man_switch` rule; `None` when it's missing or
+    /// disabled
+    async fn config(&self) -> AppResult<Option<DeadManConfig>> {
+        let rule = RiskRuleRepository::new(self.pool.clone())
+            .find_by_name(DEAD_MAN_SWITCH_RULE)
+            .await?;
+        match rule {
+            Some(rule) if rule.enabled => DeadManConfig::from_params(&rule.get_params()?)
+                .map(Some)
+                .map_err(AppError::validation),
+            _ => Ok(None),
+        }
+    }
+
+    /// Arm or renew the venue timer of every connected account
+    async fn renew_venue_timers(&self, config: &DeadManConfig) {
+        let sessions = self.sessions.open_sessions().await;
+        let now = Utc::now().timestamp_millis();
+
+        let mut timers = HashMap::with_capacity(sessions.len());
+        for session in sessions {
+            let (armed, error) = match session.exchange.cancel_all_after(config.timeout_secs).await {
+                Ok(armed) => (armed, None),
+                Err(e) => {
+                    log::warn!("Failed to renew cancel-all-after timer of account {}: {}", session.config_id, e);
+                    (false, Some(e.to_string()))
+                }
+            };
+            timers.insert(
+                session.config_id.clone(),
+                VenueTimer {
+                    config_id: session.config_id.clone(),
+                    exchange: session.exchange.name().to_string(),
+                    armed,
+                    renewed_at: now,
+                    error,
+                },
+            );
+        }
+
+        let mut state = self.state.lock().await;
+        state.enabled_since.get_or_insert(now);
+        state.venue_timers = timers;
+    }
+
+    /// Turn off the venue timers armed while the switch was enabled
+    async fn disarm(&self) {
+        let mut state = self.state.lock().await;
+        if state.enabled_since.take().is_none() {
+            return;
+        }
+        state.tripped = None;
+        let armed: Vec<String> = state
+            .venue_timers
+            .drain()
+            .filter(|(_, timer)| timer.armed)
+            .map(|(config_id, _)| config_id)
+            .collect();
+        drop(state);
+
+        for session in self.sessions.open_sessions().await {
+            if !armed.contains(&session.config_id) {
+                continue;
+            }
+            if let Err(e) = session.exchange.cancel_all_after(0).await {
+                log::warn!("Failed to turn off cancel-all-after timer of account {}: {}", session.config_id, e);
+            }
+        }
+        log::info!("Dead-man's switch disabled");
+    }
+
+    /// Cancel every open order once the app stops responding
+    async fn watch(&self, config: &DeadManConfig) {
+        let now = Utc::now().timestamp_millis();
+        let stalled = self.strategy_engine.stalled_instances(config.timeout_ms()).await;
+        let market_age = if self.strategy_engine.risk_targets().await.is_empty() {
+            None
+        } else {
+            let enabled_since = self.state.lock().await.enabled_since.unwrap_or(now);
+            Some(now - self.last_market_event.load(Ordering::SeqCst).max(enabled_since))
+        };
+
+        let Some(reason) = unresponsive(&stalled, market_age, config.timeout_ms()) else {
+            if self.state.lock().await.tripped.take().is_some() {
+                log::warn!("Dead-man's switch: app responsive again");
+            }
+            return;
+        };
+
+        {
+            let mut state = self.state.lock().await;
+            if state.tripped.is_some() {
+                return;
+            }
+            state.tripped = Some((now, reason.clone()));
+        }
+
+        log::error!("!!! DEAD-MAN'S SWITCH TRIPPED: {} - canceling all open orders !!!", reason);
+        let mut canceled = 0;
+        for session in self.sessions.open_sessions().await {
+            match session.trade_service.cancel_all_orders(&session.user_id, None).await {
+                Ok(count) => canceled += count,
+                Err(e) => log::error!("Dead-man's switch: failed to cancel orders of account {}: {}", session.config_id, e),
+            }
+        }
+        log::error!("Dead-man's switch canceled {} orders", canceled);
+    }
+}
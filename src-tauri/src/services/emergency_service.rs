@@ -51,7 +51,36 @@ impl EmergencyService {
             }
         }
 
-        // Step 2: Cancel all orders
+        // Steps 2 and 3: Cancel all orders and close all positions
+        self.flatten_into(&mut report, user_id, "ESTOP").await;
+
+        // Step 4: Send emergency alert
+        match self.send_emergency_alert(&report).await {
+            Ok(_) => {
+                report.alert_sent = true;
+                log::error!("EMERGENCY: Alert notification sent");
+            }
+            Err(e) => {
+                log::error!("EMERGENCY: Failed to send alert: {}", e);
+                report.errors.push(format!("Failed to send alert: {}", e));
+            }
+        }
+
+        log::error!("!!! EMERGENCY STOP COMPLETED {:?}", report);
+        Ok(report)
+    }
+
+    /// Cancel all of the user's orders and close all positions, leaving
+    /// strategies running
+    ///
+    /// Like `emergency_stop_all`, closing goes ahead even if canceling fails.
+    pub async fn flatten(&self, user_id: &str, tag: &str) -> EmergencyReport {
+        let mut report = EmergencyReport::default();
+        self.flatten_into(&mut report, user_id, tag).await;
+        report
+    }
+
+    async fn flatten_into(&self, report: &mut EmergencyReport, user_id: &str, tag: &str) {
         match self.cancel_all_orders(user_id).await {
             Ok(count) => {
                 report.orders_canceled = count;
@@ -63,8 +92,7 @@ impl EmergencyService {
             }
         }
 
-        // Step 3: Close all positions
-        match self.close_all_positions(user_id).await {
+        match self.close_all_positions(user_id, tag).await {
             Ok(count) => {
                 report.positions_closed = count;
                 log::error!("EMERGENCY: Closed {} positions", count);
@@ -74,77 +102,74 @@ impl EmergencyService {
                 report.errors.push(format!("Failed to close positions: {}", e));
             }
         }
-
-        // Step 4: Send emergency alert
-        match self.send_emergency_alert(&report).await {
-            Ok(_) => {
-                report.alert_sent = true;
-                log::error!("EMERGENCY: Alert notification sent");
-            }
-            Err(e) => {
-                log::error!("EMERGENCY: Failed to send alert: {}", e);
-                report.errors.push(format!("Failed to send alert: {}", e));
-            }
-        }
-
-        log::error!("!!! EMERGENCY STOP COMPLETED {:?}", report);
-        Ok(report)
     }
 
     /// Cancel all active orders for the user
     async fn cancel_all_orders(&self, user_id: &str) -> Result<usize> {
         log::error!("EMERGENCY: Canceling all orders for user: {}", user_id);
 
-        let orders = self.trade_service.get_open_orders(user_id).await?;
-        let mut canceled_count = 0;
-
-        for order in orders {
-            match self.trade_service.cancel_order(&order.id, user_id).await {
-                Ok(_) => {
-                    canceled_count += 1;
-                    log::error!("EMERGENCY: Canceled order {}", order.id);
-                }
-                Err(e) => {
-                    log::error!("EMERGENCY: Failed to cancel order {}: {}", order.id, e);
-                    // Continue with next order even if this one fails
-                }
-            }
-        }
+        // One cancel-all request per symbol instead of one per order
+        let canceled_count = self.trade_service.cancel_all_orders(user_id, None).await?;
 
         Ok(canceled_count)
     }
 
     /// Close all positions for the user using market orders
-    async fn close_all_positions(&self, user_id: &str) -> Result<usize> {
+    ///
+    /// The closing orders go out as one batch; `tag` prefixes their client
+    /// order ids so they can be told apart from strategy orders.
+    pub async fn close_all_positions(&self, user_id: &str, tag: &str) -> Result<usize> {
         log::error!("EMERGENCY: Closing all positions for user: {}", user_id);
-
         let positions = self.trade_service.get_positions(user_id).await?;
-        let mut closed_count = 0;
+        self.close_positions(user_id, &positions, tag).await
+    }
 
+    /// Close the positions built by one strategy instance
+    ///
+    /// Positions are netted per account. A position in one of the
+    /// instance's `symbols` that other instances or manual orders also
+    /// traded isn't the instance's to close, so it is left open and logged.
+    pub async fn close_instance_positions(
+        &self,
+        user_id: &str,
+        instance_id: &str,
+        symbols: &[String],
+        tag: &str,
+    ) -> Result<usize> {
+        let own = self.trade_service.get_instance_positions(user_id, instance_id).await?;
+        let account = self.trade_service.get_positions(user_id).await?;
+        for position in shared_positions(account, &own, symbols) {
+            log::warn!(
+                "Not closing position {} ({}) for strategy {}: it is shared with other orders",
+                position.symbol,
+                position.id,
+                instance_id
+            );
+        }
+        self.close_positions(user_id, &own, tag).await
+    }
+
+    /// Close the given positions with one batch of market orders
+    async fn close_positions(&self, user_id: &str, positions: &[Position], tag: &str) -> Result<usize> {
+        let mut closing = Vec::new();
+        let mut requests = Vec::new();
         for position in positions {
-            // Determine closing side: opposite of position side
-            let close_side = match position.side.as_str() {
-                "long" | "buy" => OrderSide::Sell,
-                "short" | "sell" => OrderSide::Buy,
-                _ => {
-                    log::error!("EMERGENCY: Unknown position side: {}", position.side);
-                    continue;
+            match close_request(position, tag) {
+                Some(request) => {
+                    closing.push(position);
+                    requests.push(request);
                 }
-            };
-
-            // Create market order to close position
-            let close_request = OrderRequest {
-                symbol: position.symbol.clone(),
-                side: close_side,
-                order_type: OrderType::Market,
-                price: None,
-                stop_price: None,
-                quantity: position.quantity.abs(), // Ensure positive quantity
-                client_order_id: Some(format!("EMERGENCY-CLOSE-{}", uuid::Uuid::new_v4())),
-                time_in_force: Some(TimeInForce::IOC),
-            };
-
-            match self.trade_service.place_order(close_request, user_id).await {
+                None => log::error!("EMERGENCY: Unknown position side: {}", position.side),
+            }
+        }
+        if requests.is_empty() {
+            return Ok(0);
+        }
+
+        let results = self.trade_service.place_emergency_orders(requests, user_id).await?;
+        let mut closed_count = 0;
+        for (position, result) in closing.into_iter().zip(results) {
+            match result {
                 Ok(_) => {
                     closed_count += 1;
                     log::error!(
@@ -160,7 +185,6 @@ impl EmergencyService {
                         position.id,
                         e
                     );
-                    // Continue with next position even if this one fails
                 }
             }
         }
@@ -198,6 +222,35 @@ impl EmergencyService {
     }
 }
 
+/// Positions in an instance's `symbols` that aren't among its own
+pub fn shared_positions(positions: Vec<Position>, own: &[Position], symbols: &[String]) -> Vec<Position> {
+    positions
+        .into_iter()
+        .filter(|p| symbols.contains(&p.symbol) && !own.iter().any(|o| o.id == p.id))
+        .collect()
+}
+
+/// Market order that flattens a position, or None for an unknown side
+pub fn close_request(position: &Position, tag: &str) -> Option<OrderRequest> {
+    // Closing side is the opposite of the position side
+    let close_side = match position.side.as_str() {
+        "long" | "buy" => OrderSide::Sell,
+        "short" | "sell" => OrderSide::Buy,
+        _ => return None,
+    };
+
+    Some(OrderRequest {
+        symbol: position.symbol.clone(),
+        side: close_side,
+        order_type: OrderType::Market,
+        price: None,
+        stop_price: None,
+        quantity: position.quantity.abs(), // Ensure positive quantity
+        client_order_id: Some(OrderRequest::new_client_order_id(tag)),
+        time_in_force: Some(TimeInForce::IOC),
+    })
+}
+
 /// Report of emergency stop execution
 #[derive(Debug, Clone, Default)]
 pub struct EmergencyReport {
@@ -243,6 +296,60 @@ mod tests {
         assert_eq!(report.errors.len(), 1);
     }
 
+    #[test]
+    fn test_shared_positions() {
+        let position = |id: &str, symbol: &str| Position {
+            id: id.to_string(),
+            symbol: symbol.to_string(),
+            side: "long".to_string(),
+            quantity: 1.0,
+            entry_price: 100.0,
+            current_price: None,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+            opened_at: 0,
+        };
+        let own = vec![position("p1", "ETHUSDT")];
+        let account = vec![position("p1", "ETHUSDT"), position("p2", "SOLUSDT"), position("p3", "BTCUSDT")];
+
+        let symbols = vec!["ETHUSDT".to_string(), "SOLUSDT".to_string()];
+        let shared: Vec<String> = shared_positions(account, &own, &symbols)
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(shared, vec!["p2"]);
+    }
+
+    #[test]
+    fn test_close_request_opposes_position() {
+        let mut position = Position {
+            id: "p1".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side: "long".to_string(),
+            quantity: 0.5,
+            entry_price: 50000.0,
+            current_price: None,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+            opened_at: 0,
+        };
+
+        let request = close_request(&position, "ESTOP").unwrap();
+        assert_eq!(request.side, OrderSide::Sell);
+        assert_eq!(request.order_type, OrderType::Market);
+        assert_eq!(request.quantity, 0.5);
+        assert!(request.client_order_id.unwrap().starts_with("ESTOP"));
+
+        position.side = "short".to_string();
+        position.quantity = -0.5;
+        let request = close_request(&position, "RISK").unwrap();
+        assert_eq!(request.side, OrderSide::Buy);
+        assert_eq!(request.quantity, 0.5);
+
+        position.side = "flat".to_string();
+        assert!(close_request(&position, "RISK").is_none());
+    }
+
     // Note: Integration tests for emergency_stop_all require:
     // - Mock TradeService
     // - Mock StrategyEngine
@@ -0,0 +1,314 @@
+//! Per-account exchange sessions
+//!
+//! A user can configure several exchange accounts (`exchanges` table), each
+//! with its own credentials. `ExchangeSessionRegistry` builds an
+//! authenticated client for an account on first use, from its decrypted
+//! credentials, and pairs it with a `TradeService` bound to that account so
+//! orders, balances and positions reach the right account. Each session
+//! also runs the account's client-side conditional orders and execution
+//! algorithms.
+
+use crate::core::trade::exchange::{ClockSync, Exchange, ExchangeFactory, ExchangeName};
+use crate::core::trade::execution::ExecutionAlgoEngine;
+use crate::core::trade::order::ConditionalOrderEngine;
+use crate::core::{AppError, AppResult, EventBus};
+use crate::models::exchange::ExchangeConfig;
+use crate::repository::{ConditionalOrderRepository, ExchangeRepository};
+use crate::services::TradeService;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+
+/// Authenticated connection to one exchange account
+pub struct ExchangeSession {
+    /// Exchange config id (`exchanges.id`)
+    pub config_id: String,
+    pub user_id: String,
+    pub exchange: Arc<dyn Exchange>,
+    pub trade_service: Arc<TradeService>,
+    pub conditional_orders: Arc<ConditionalOrderEngine>,
+    pub execution: Arc<ExecutionAlgoEngine>,
+    fill_task: JoinHandle<()>,
+    order_update_task: JoinHandle<()>,
+    mark_price_task: JoinHandle<()>,
+    conditional_task: JoinHandle<()>,
+}
+
+impl ExchangeSession {
+    async fn stop(&self) {
+        self.fill_task.abort();
+        self.order_update_task.abort();
+        self.mark_price_task.abort();
+        self.conditional_task.abort();
+        self.execution.abort_all();
+        if let Err(e) = self.exchange.disconnect().await {
+            log::warn!("Failed to disconnect exchange account {}: {}", self.config_id, e);
+        }
+    }
+}
+
+/// Lazily built exchange sessions keyed by exchange config id
+pub struct ExchangeSessionRegistry {
+    pool: SqlitePool,
+    event_bus: Arc<EventBus>,
+    sessions: RwLock<HashMap<String, Arc<ExchangeSession>>>,
+    /// One lock per account, held while its session connects
+    connecting: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+}
+
+impl ExchangeSessionRegistry {
+    /// Create an empty registry
+    pub fn new(pool: SqlitePool, event_bus: Arc<EventBus>) -> Self {
+        Self {
+            pool,
+            event_bus,
+            sessions: RwLock::new(HashMap::new()),
+            connecting: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the session for an exchange account, connecting on first use
+    ///
+    /// Connecting goes over the network, so it holds only the account's own
+    /// lock; sessions of other accounts stay usable meanwhile.
+    pub async fn get(&self, config_id: &str) -> AppResult<Arc<ExchangeSession>> {
+        if let Some(session) = self.sessions.read().await.get(config_id) {
+            return Ok(session.clone());
+        }
+
+        let gate = self.connecting.lock().await.entry(config_id.to_string()).or_default().clone();
+        let _connecting = gate.lock().await;
+        // Another caller may have connected while we waited
+        if let Some(session) = self.sessions.read().await.get(config_id) {
+            return Ok(session.clone());
+        }
+
+        let config = ExchangeRepository::new(self.pool.clone())
+            .find_by_id(config_id)
+            .await?
+            .ok_or_else(|| AppError::validation(format!("Exchange account not found: {}", config_id)))?;
+        if !config.is_active() {
+            return Err(AppError::validation(format!("Exchange account {} is not active", config_id)));
+        }
+
+        let session = Arc::new(self.connect(&config).await?);
+        self.sessions.write().await.insert(config_id.to_string(), session.clone());
+        log::info!(
+            "Exchange session opened: {} ({}, {}) for user {}",
+            config.id, config.exchange_name, config.display_name, config.user_id
+        );
+        Ok(session)
+    }
+
+    /// Resolve the session for a user
+    ///
+    /// With an explicit `config_id` the account must belong to the user;
+    /// otherwise the user's oldest active account is used. Returns `None`
+    /// if the user has no active account.
+    pub async fn for_user(
+        &self,
+        user_id: &str,
+        config_id: Option<&str>,
+    ) -> AppResult<Option<Arc<ExchangeSession>>> {
+        let config_id = match config_id {
+            Some(id) => id.to_string(),
+            None => {
+                let configs = ExchangeRepository::new(self.pool.clone()).find_by_user(user_id).await?;
+                // find_by_user returns newest first
+                match configs.into_iter().rev().find(|c| c.is_active()) {
+                    Some(config) => config.id,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        let session = self.get(&config_id).await?;
+        if session.user_id != user_id {
+            return Err(AppError::Permission(format!(
+                "Exchange account {} does not belong to user {}",
+                config_id, user_id
+            )));
+        }
+        Ok(Some(session))
+    }
+
+    /// Sessions for every active account of a user
+    pub async fn user_sessions(&self, user_id: &str) -> AppResult<Vec<Arc<ExchangeSession>>> {
+        let configs = ExchangeRepository::new(self.pool.clone()).find_by_user(user_id).await?;
+
+        let mut sessions = Vec::new();
+        for config in configs.iter().filter(|c| c.is_active()) {
+            match self.get(&config.id).await {
+                Ok(session) => sessions.push(session),
+                Err(e) => log::warn!("Skipping exchange account {}: {}", config.id, e),
+            }
+        }
+        Ok(sessions)
+    }
+
+    /// Sessions connected so far
+    pub async fn open_sessions(&self) -> Vec<Arc<ExchangeSession>> {
+        self.sessions.read().await.values().cloned().collect()
+    }
+
+    /// Drop a cached session so the next use reloads its config
+    ///
+    /// Call after an account's credentials or status change.
+    pub async fn invalidate(&self, config_id: &str) {
+        let session = self.sessions.write().await.remove(config_id);
+        if let Some(session) = session {
+            session.stop().await;
+            log::info!("Exchange session closed: {}", config_id);
+        }
+    }
+
+    /// Close all sessions
+    pub async fn shutdown(&self) {
+        let sessions: Vec<_> = self.sessions.write().await.drain().collect();
+        for (_, session) in sessions {
+            session.stop().await;
+        }
+    }
+
+    /// Open sessions for every account with pending conditional orders
+    ///
+    /// Called at startup so stops and take-profits placed before a restart
+    /// are watched again.
+    pub async fn resume_conditional_orders(&self) -> AppResult<usize> {
+        let config_ids = ConditionalOrderRepository::new(self.pool.clone())
+            .pending_exchange_ids()
+            .await?;
+
+        let mut resumed = 0;
+        for config_id in config_ids {
+            match self.get(&config_id).await {
+                Ok(_) => resumed += 1,
+                Err(e) => log::warn!("Conditional orders of account {} not resumed: {}", config_id, e),
+            }
+        }
+        Ok(resumed)
+    }
+
+    async fn connect(&self, config: &ExchangeConfig) -> AppResult<ExchangeSession> {
+        let exchange = build_exchange(config)?;
+        let trade_service = Arc::new(
+            TradeService::new(exchange.clone(), self.pool.clone())
+                .with_event_bus(self.event_bus.clone())
+                .with_exchange_id(config.id.clone()),
+        );
+        let fill_task = trade_service.start_fill_recording();
+        let order_update_task = trade_service.start_order_updates();
+        let mark_price_task = trade_service.start_mark_price_updates();
+
+        // The loops above subscribed first, so no event of the user data
+        // stream is missed. Without the stream the account still trades over
+        // REST and reconciliation catches up on its orders.
+        if let Err(e) = exchange.connect().await {
+            log::warn!("Failed to connect exchange account {}: {}", config.id, e);
+        }
+        // The periodic clock sync picks the venue up from now on; measure it
+        // once here so the first signed requests don't wait for it
+        let clock = ClockSync::for_exchange(exchange.name());
+        if clock.status().last_sync_at.is_none() {
+            if let Err(e) = clock.sync_with(exchange.as_ref()).await {
+                log::warn!("Failed to sync {} server time: {}", exchange.name(), e);
+            }
+        }
+        if let Err(e) = exchange.subscribe_user_data().await {
+            log::warn!("User data stream of account {} not subscribed: {}", config.id, e);
+        }
+        if let Err(e) = trade_service.load_positions(&config.user_id).await {
+            log::warn!("Failed to load positions of account {}: {}", config.id, e);
+        }
+
+        let conditional_orders = Arc::new(ConditionalOrderEngine::new(
+            config.id.clone(),
+            exchange.clone(),
+            trade_service.clone(),
+            self.pool.clone(),
+        ));
+        let conditional_task = conditional_orders.start();
+        if let Err(e) = conditional_orders.load().await {
+            log::warn!("Failed to load conditional orders of account {}: {}", config.id, e);
+        }
+
+        let execution = Arc::new(ExecutionAlgoEngine::new(exchange.clone(), trade_service.clone()));
+        if let Err(e) = execution.recover().await {
+            log::warn!("Failed to settle execution algos of account {}: {}", config.id, e);
+        }
+
+        Ok(ExchangeSession {
+            config_id: config.id.clone(),
+            user_id: config.user_id.clone(),
+            exchange,
+            trade_service,
+            conditional_orders,
+            execution,
+            fill_task,
+            order_update_task,
+            mark_price_task,
+            conditional_task,
+        })
+    }
+}
+
+/// Build an authenticated exchange client from an account config
+pub fn build_exchange(config: &ExchangeConfig) -> AppResult<Arc<dyn Exchange>> {
+    let name = ExchangeName::parse(&config.exchange_name)
+        .ok_or_else(|| AppError::validation(format!("Unsupported exchange: {}", config.exchange_name)))?;
+    let (api_key, api_secret) = config.get_decrypted_keys().map_err(AppError::Auth)?;
+    let passphrase = config.get_decrypted_passphrase().map_err(AppError::Auth)?;
+
+    if name == ExchangeName::Binance && config.is_testnet {
+        return Err(AppError::validation(format!(
+            "Binance testnet is not supported (account {})",
+            config.id
+        )));
+    }
+
+    Ok(ExchangeFactory::create_for_network(
+        name,
+        Some(api_key),
+        Some(api_secret),
+        passphrase,
+        config.is_testnet,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(exchange_name: &str) -> ExchangeConfig {
+        ExchangeConfig::create_encrypted(
+            "config-1".to_string(),
+            "user-1".to_string(),
+            exchange_name.to_string(),
+            "Test".to_string(),
+            "key",
+            "secret",
+            Some("pass"),
+            true,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_build_exchange_from_encrypted_config() {
+        assert_eq!(build_exchange(&config("okx")).unwrap().name(), ExchangeName::OKX);
+        assert_eq!(build_exchange(&config("bybit")).unwrap().name(), ExchangeName::Bybit);
+    }
+
+    #[test]
+    fn test_build_exchange_rejects_unknown_venue() {
+        assert!(build_exchange(&config("kraken")).is_err());
+    }
+
+    #[test]
+    fn test_build_exchange_rejects_binance_testnet() {
+        // The fixture account is on testnet
+        assert!(build_exchange(&config("binance")).is_err());
+    }
+}
@@ -0,0 +1,212 @@
+//! Kill switch service
+//!
+//! Engages, escalates and re-arms the app-wide trading halt described in
+//! `core::risk::kill_switch`. `TradeService` reads the halt from the
+//! database before every order, so it applies to every account at once
+//! and stays in force across restarts.
+//!
+//! Engaging a level runs its actions on every connected exchange account.
+//! Anyone may engage the switch, but re-arming needs a user with the
+//! `risk:write` permission and a reason. Both are written to the audit log.
+
+use crate::core::auth::{AuthService, PERM_RISK_WRITE};
+use crate::core::risk::{HaltLevel, TradingHalt};
+use crate::core::strategy::StrategyEngine;
+use crate::core::{AppError, AppResult};
+use crate::infrastructure::{AuditEvent, AuditService};
+use crate::repository::{TradingHaltRepository, UserRepository};
+use crate::services::{EmergencyReport, EmergencyService, ExchangeSessionRegistry};
+use chrono::Utc;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+/// Client order id prefix of the kill switch's closing orders
+const HALT_ORDER_TAG: &str = "HALT";
+
+/// Result of engaging the kill switch
+#[derive(Debug, Clone)]
+pub struct KillSwitchOutcome {
+    /// Halt in force after the request
+    pub halt: TradingHalt,
+    /// Whether the request engaged or raised the halt
+    pub escalated: bool,
+    /// What the level's actions did; empty when none ran
+    pub report: EmergencyReport,
+}
+
+/// App-wide trading halt
+pub struct KillSwitchService {
+    pool: SqlitePool,
+    sessions: Arc<ExchangeSessionRegistry>,
+    strategy_engine: Arc<StrategyEngine>,
+}
+
+impl KillSwitchService {
+    pub fn new(pool: SqlitePool, sessions: Arc<ExchangeSessionRegistry>, strategy_engine: Arc<StrategyEngine>) -> Self {
+        Self {
+            pool,
+            sessions,
+            strategy_engine,
+        }
+    }
+
+    /// The engaged halt, if any
+    pub async fn status(&self) -> AppResult<Option<TradingHalt>> {
+        Ok(self.repo().find().await?)
+    }
+
+    /// Halt trading at `level`, or raise the halt in force to it
+    ///
+    /// `user_id` is `None` when the risk monitor engages the switch. A level
+    /// below the one in force changes nothing; engaging the level in force
+    /// again runs its actions again.
+    pub async fn engage(&self, level: HaltLevel, reason: &str, user_id: Option<&str>) -> AppResult<KillSwitchOutcome> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(AppError::validation("A reason is required to halt trading"));
+        }
+
+        let repo = self.repo();
+        let now = Utc::now().timestamp_millis();
+        let engaged_by = user_id.map(str::to_string);
+        let (halt, escalated) = match repo.find().await? {
+            Some(halt) if halt.level > level => {
+                log::info!("Trading already halted at {}, ignoring {} request", halt.level, level);
+                return Ok(KillSwitchOutcome {
+                    halt,
+                    escalated: false,
+                    report: EmergencyReport::default(),
+                });
+            }
+            Some(mut halt) => {
+                let escalated = halt.escalate(level, reason, engaged_by, now);
+                (halt, escalated)
+            }
+            None => (TradingHalt::new(level, reason, engaged_by, now), true),
+        };
+
+        if escalated {
+            repo.save(&halt).await?;
+            log::error!(
+                "!!! TRADING HALT ENGAGED: {} by {}: {} !!!",
+                level,
+                user_id.unwrap_or("risk monitor"),
+                reason
+            );
+            // The halt stays in force even if it can't be audited
+            if let Err(e) = self
+                .audit(AuditEvent::TradingHaltEngaged {
+                    user_id: user_id.map(str::to_string),
+                    level: level.to_string(),
+                    reason: reason.to_string(),
+                })
+                .await
+            {
+                log::error!("Failed to audit trading halt: {}", e);
+            }
+        }
+
+        let report = self.enforce(level, user_id).await;
+        Ok(KillSwitchOutcome { halt, escalated, report })
+    }
+
+    /// Clear the halt so trading can resume
+    ///
+    /// Returns the halt that was cleared. The re-arm is audited before the
+    /// halt is lifted; if the audit log can't be written, trading stays
+    /// halted.
+    pub async fn rearm(&self, user_id: &str, reason: &str) -> AppResult<TradingHalt> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(AppError::validation("A reason is required to re-arm trading"));
+        }
+
+        let user = UserRepository::new(self.pool.clone())
+            .find_by_id_with_role(user_id)
+            .await?
+            .ok_or_else(|| AppError::auth(format!("User not found: {}", user_id)))?;
+        if !AuthService::has_permission(&user, PERM_RISK_WRITE) {
+            log::warn!("User {} tried to re-arm trading without permission", user.username);
+            return Err(AppError::permission(format!("User {} may not re-arm trading", user.username)));
+        }
+
+        let repo = self.repo();
+        let halt = repo
+            .find()
+            .await?
+            .ok_or_else(|| AppError::validation("Trading is not halted"))?;
+
+        self.audit(AuditEvent::TradingHaltRearmed {
+            user_id: user.id.clone(),
+            level: halt.level.to_string(),
+            reason: reason.to_string(),
+        })
+        .await?;
+        repo.delete().await?;
+
+        log::warn!("Trading halt ({}) re-armed by {}: {}", halt.level, user.username, reason);
+        Ok(halt)
+    }
+
+    /// Run a level's actions on every open exchange account, and on every
+    /// account of the user who engaged the switch
+    ///
+    /// Each step continues even if previous steps fail.
+    async fn enforce(&self, level: HaltLevel, user_id: Option<&str>) -> EmergencyReport {
+        let mut report = EmergencyReport::default();
+
+        if level.stops_strategies() {
+            match self.strategy_engine.stop_all().await {
+                Ok(count) => report.strategies_stopped = count,
+                Err(e) => {
+                    log::error!("HALT: Failed to stop strategies: {}", e);
+                    report.errors.push(format!("Failed to stop strategies: {}", e));
+                }
+            }
+        }
+
+        if level.flattens() {
+            let mut sessions = self.sessions.open_sessions().await;
+            if let Some(user_id) = user_id {
+                match self.sessions.user_sessions(user_id).await {
+                    Ok(user_sessions) => {
+                        for session in user_sessions {
+                            if !sessions.iter().any(|s| s.config_id == session.config_id) {
+                                sessions.push(session);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        log::error!("HALT: Failed to load accounts of user {}: {}", user_id, e);
+                        report.errors.push(format!("Failed to load accounts of user {}: {}", user_id, e));
+                    }
+                }
+            }
+
+            for session in sessions {
+                let flattened = EmergencyService::new(session.trade_service.clone(), self.strategy_engine.clone())
+                    .flatten(&session.user_id, HALT_ORDER_TAG)
+                    .await;
+                report.orders_canceled += flattened.orders_canceled;
+                report.positions_closed += flattened.positions_closed;
+                report
+                    .errors
+                    .extend(flattened.errors.into_iter().map(|e| format!("Account {}: {}", session.config_id, e)));
+            }
+        }
+
+        if level > HaltLevel::PauseEntries {
+            log::error!("HALT: {} completed: {:?}", level, report);
+        }
+        report
+    }
+
+    async fn audit(&self, event: AuditEvent) -> AppResult<()> {
+        AuditService::from_pool(self.pool.clone()).log(event).await?;
+        Ok(())
+    }
+
+    fn repo(&self) -> TradingHaltRepository {
+        TradingHaltRepository::new(self.pool.clone())
+    }
+}
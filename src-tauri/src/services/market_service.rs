@@ -1,33 +1,52 @@
-use crate::core::trade::exchange::{Exchange, ExchangeName, binance::BinanceExchange};
+use crate::core::trade::exchange::{ClockSync, ClockSyncStatus, Exchange, ExchangeName, RateLimitUsage, binance::BinanceExchange};
 use crate::core::trade::types::*;
 use crate::core::trade::converter::{MarketDataConverter, ConverterFactory};
+use crate::core::trade::instrument::InstrumentRegistry;
 use crate::core::event::EventBus;
 use crate::infrastructure::Database;
 use crate::infrastructure::cache::{get_klines, insert_klines};
+use crate::services::ExchangeSessionRegistry;
 use anyhow::{anyhow, Result};
 use sqlx::Row;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 pub struct MarketService {
     exchanges: Arc<RwLock<Vec<Arc<dyn Exchange>>>>,
+    instruments: Arc<RwLock<HashMap<ExchangeName, Arc<InstrumentRegistry>>>>,
     event_bus: Arc<EventBus>,
     db: Database,
     ws_handles: Arc<RwLock<Vec<JoinHandle<()>>>>,
+    clock_sync_handle: Arc<RwLock<Option<JoinHandle<()>>>>,
+    /// Connected exchange accounts, whose venues need their clocks synced too
+    exchange_sessions: Option<Arc<ExchangeSessionRegistry>>,
 }
 
+/// Offset above which signed requests risk falling outside `recvWindow`
+const CLOCK_DRIFT_WARN_MS: i64 = 1000;
+
 impl MarketService {
     pub fn new(db: Database) -> Self {
         Self {
             exchanges: Arc::new(RwLock::new(Vec::new())),
+            instruments: Arc::new(RwLock::new(HashMap::new())),
             event_bus: Arc::new(EventBus::new()),
             db,
             ws_handles: Arc::new(RwLock::new(Vec::new())),
+            clock_sync_handle: Arc::new(RwLock::new(None)),
+            exchange_sessions: None,
         }
     }
 
+    /// Also sync the clocks of the venues of connected exchange accounts
+    pub fn with_exchange_sessions(mut self, sessions: Arc<ExchangeSessionRegistry>) -> Self {
+        self.exchange_sessions = Some(sessions);
+        self
+    }
+
     /// Get the event bus for this market service
     pub fn event_bus(&self) -> Arc<EventBus> {
         self.event_bus.clone()
@@ -35,6 +54,11 @@ impl MarketService {
 
     /// Add an exchange to the service
     pub async fn add_exchange(&self, exchange: Arc<dyn Exchange>) {
+        self.instruments
+            .write()
+            .await
+            .insert(exchange.name(), Arc::new(InstrumentRegistry::new(exchange.clone())));
+
         let mut exchanges = self.exchanges.write().await;
         exchanges.push(exchange);
     }
@@ -49,6 +73,8 @@ impl MarketService {
             return Err(anyhow!("Exchange not found: {:?}", name));
         }
 
+        self.instruments.write().await.remove(&name);
+
         Ok(())
     }
 
@@ -64,6 +90,101 @@ impl MarketService {
         exchanges.iter().map(|e| e.name()).collect()
     }
 
+    /// Get REST rate-limit usage for every registered exchange
+    pub async fn rate_limit_usage(&self) -> Vec<RateLimitUsage> {
+        let exchanges = self.exchanges.read().await;
+        exchanges.iter().map(|e| e.rate_limit_usage()).collect()
+    }
+
+    /// Sync the signing clock of every registered exchange with its server time
+    pub async fn sync_clocks(&self) -> Vec<ClockSyncStatus> {
+        let exchanges = Self::clock_exchanges(&self.exchanges, self.exchange_sessions.as_deref()).await;
+        Self::sync_exchange_clocks(&exchanges).await
+    }
+
+    /// One exchange per venue among the registered exchanges and the
+    /// connected accounts
+    ///
+    /// Clocks are kept per venue, so syncing one client of a venue is enough.
+    async fn clock_exchanges(
+        exchanges: &RwLock<Vec<Arc<dyn Exchange>>>,
+        sessions: Option<&ExchangeSessionRegistry>,
+    ) -> Vec<Arc<dyn Exchange>> {
+        let mut clock_exchanges = exchanges.read().await.clone();
+        if let Some(sessions) = sessions {
+            for session in sessions.open_sessions().await {
+                if !clock_exchanges.iter().any(|e| e.name() == session.exchange.name()) {
+                    clock_exchanges.push(session.exchange.clone());
+                }
+            }
+        }
+        clock_exchanges
+    }
+
+    async fn sync_exchange_clocks(exchanges: &[Arc<dyn Exchange>]) -> Vec<ClockSyncStatus> {
+        let mut statuses = Vec::with_capacity(exchanges.len());
+
+        for exchange in exchanges {
+            let clock = ClockSync::for_exchange(exchange.name());
+            match clock.sync_with(exchange.as_ref()).await {
+                Ok(offset) if offset.abs() > CLOCK_DRIFT_WARN_MS => {
+                    log::warn!("{} clock drift is {}ms", exchange.name(), offset);
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("Failed to sync {} server time: {}", exchange.name(), e),
+            }
+            statuses.push(clock.status());
+        }
+
+        statuses
+    }
+
+    /// Start periodic server time sync for all registered exchanges and the
+    /// venues of connected exchange accounts
+    ///
+    /// Replaces any previously started sync task.
+    pub async fn start_clock_sync(&self, interval: Duration) {
+        let exchanges = self.exchanges.clone();
+        let sessions = self.exchange_sessions.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let snapshot = Self::clock_exchanges(&exchanges, sessions.as_deref()).await;
+                Self::sync_exchange_clocks(&snapshot).await;
+            }
+        });
+
+        if let Some(previous) = self.clock_sync_handle.write().await.replace(handle) {
+            previous.abort();
+        }
+        log::info!("Clock sync started (every {:?})", interval);
+    }
+
+    /// Get the instrument registry for an exchange
+    pub async fn instrument_registry(&self, name: ExchangeName) -> Option<Arc<InstrumentRegistry>> {
+        self.instruments.read().await.get(&name).cloned()
+    }
+
+    /// Get trading rules for all symbols listed on an exchange
+    pub async fn get_instruments(&self, name: ExchangeName) -> Result<Vec<Instrument>> {
+        let registry = self.instrument_registry(name)
+            .await
+            .ok_or_else(|| anyhow!("Exchange not found: {:?}", name))?;
+
+        registry.list().await
+    }
+
+    /// Get symbols currently open for trading on an exchange
+    pub async fn get_trading_symbols(&self, name: ExchangeName) -> Result<Vec<String>> {
+        let registry = self.instrument_registry(name)
+            .await
+            .ok_or_else(|| anyhow!("Exchange not found: {:?}", name))?;
+
+        registry.trading_symbols().await
+    }
+
     /// Initialize with default Binance exchange
     pub async fn init_binance(&self, api_key: Option<String>, api_secret: Option<String>) -> Result<()> {
         let binance = Arc::new(BinanceExchange::new(api_key, api_secret));
@@ -294,6 +415,10 @@ impl MarketService {
         // Stop event forwarding
         self.stop_event_forwarding().await?;
 
+        if let Some(handle) = self.clock_sync_handle.write().await.take() {
+            handle.abort();
+        }
+
         // Disconnect all exchanges
         let exchanges = self.exchanges.read().await;
         for exchange in exchanges.iter() {
@@ -1,6 +1,12 @@
 pub mod market_service;
 pub mod trade_service;
+pub mod order_reconciler;
+pub mod exchange_sessions;
+pub mod portfolio_service;
+pub mod pnl_service;
 pub mod emergency_service;
+pub mod kill_switch;
+pub mod dead_man_switch;
 pub mod backup_service;
 pub mod backtest_service;
 pub mod optimizer;
@@ -8,7 +14,13 @@ pub mod data_quality;
 
 pub use market_service::MarketService;
 pub use trade_service::TradeService;
+pub use order_reconciler::OrderReconciler;
+pub use exchange_sessions::{ExchangeSession, ExchangeSessionRegistry};
+pub use portfolio_service::{AssetExposure, ExchangeAllocation, PortfolioService, PortfolioSnapshot};
+pub use pnl_service::{EquitySnapshot, PnlScope, PnlService, PnlStatement, StatementPeriod};
 pub use emergency_service::{EmergencyService, EmergencyReport};
+pub use kill_switch::{KillSwitchOutcome, KillSwitchService};
+pub use dead_man_switch::{DeadManStatus, DeadManSwitch, VenueTimer};
 pub use backup_service::{BackupService, BackupInfo};
 pub use backtest_service::BacktestService;
 pub use optimizer::{ParameterOptimizer, OptimizationConfig, OptimizationResult, ParamRange};
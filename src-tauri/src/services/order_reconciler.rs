@@ -0,0 +1,141 @@
+//! Background order reconciliation
+//!
+//! Periodically runs `TradeService::reconcile_orders` on every exchange
+//! account of each user that has reconciliation enabled, so fills and cancels that happen while the app
+//! isn't listening (missed WebSocket messages, orders managed from the
+//! exchange UI) still reach the local database.
+
+use crate::core::trade::order::ReconcileReport;
+use crate::core::AppResult;
+use crate::services::{ExchangeSessionRegistry, TradeService};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+/// Default interval between reconciliation passes
+pub const DEFAULT_RECONCILE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Runs periodic order reconciliation per user
+pub struct OrderReconciler {
+    /// Used for users without a configured exchange account
+    trade_service: Arc<TradeService>,
+    sessions: Arc<ExchangeSessionRegistry>,
+    tasks: RwLock<HashMap<String, JoinHandle<()>>>,
+    last_reports: Arc<RwLock<HashMap<String, ReconcileReport>>>,
+}
+
+impl OrderReconciler {
+    /// Create a reconciler over the user's exchange accounts
+    pub fn new(trade_service: Arc<TradeService>, sessions: Arc<ExchangeSessionRegistry>) -> Self {
+        Self {
+            trade_service,
+            sessions,
+            tasks: RwLock::new(HashMap::new()),
+            last_reports: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Run one reconciliation pass immediately
+    pub async fn reconcile_now(&self, user_id: &str) -> AppResult<ReconcileReport> {
+        let report = reconcile_user(&self.trade_service, &self.sessions, user_id).await?;
+        self.last_reports
+            .write()
+            .await
+            .insert(user_id.to_string(), report.clone());
+        Ok(report)
+    }
+
+    /// Start periodic reconciliation for a user
+    ///
+    /// Restarts the task with the new interval if it is already running.
+    pub async fn start(&self, user_id: &str, interval: Duration) {
+        let trade_service = self.trade_service.clone();
+        let sessions = self.sessions.clone();
+        let last_reports = self.last_reports.clone();
+        let user = user_id.to_string();
+
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+                match reconcile_user(&trade_service, &sessions, &user).await {
+                    Ok(report) => {
+                        last_reports.write().await.insert(user.clone(), report);
+                    }
+                    Err(e) => log::warn!("Order reconciliation failed for {}: {}", user, e),
+                }
+            }
+        });
+
+        if let Some(previous) = self.tasks.write().await.insert(user_id.to_string(), handle) {
+            previous.abort();
+        }
+        log::info!("Order reconciliation started for {} (every {:?})", user_id, interval);
+    }
+
+    /// Stop periodic reconciliation for a user
+    ///
+    /// Returns false if it wasn't running.
+    pub async fn stop(&self, user_id: &str) -> bool {
+        match self.tasks.write().await.remove(user_id) {
+            Some(handle) => {
+                handle.abort();
+                log::info!("Order reconciliation stopped for {}", user_id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns true if periodic reconciliation is running for a user
+    pub async fn is_running(&self, user_id: &str) -> bool {
+        self.tasks
+            .read()
+            .await
+            .get(user_id)
+            .is_some_and(|handle| !handle.is_finished())
+    }
+
+    /// Result of the most recent pass for a user
+    pub async fn last_report(&self, user_id: &str) -> Option<ReconcileReport> {
+        self.last_reports.read().await.get(user_id).cloned()
+    }
+
+    /// Stop all reconciliation tasks
+    pub async fn shutdown(&self) {
+        for (_, handle) in self.tasks.write().await.drain() {
+            handle.abort();
+        }
+    }
+}
+
+/// Reconcile every active exchange account of a user into one report
+async fn reconcile_user(
+    default_service: &Arc<TradeService>,
+    sessions: &ExchangeSessionRegistry,
+    user_id: &str,
+) -> AppResult<ReconcileReport> {
+    let services: Vec<Arc<TradeService>> = match sessions.user_sessions(user_id).await? {
+        accounts if accounts.is_empty() => vec![default_service.clone()],
+        accounts => accounts.iter().map(|s| s.trade_service.clone()).collect(),
+    };
+
+    let mut report = ReconcileReport::default();
+    for service in services {
+        match service.reconcile_orders(user_id).await {
+            Ok(account_report) => report.merge(&account_report),
+            Err(e) => {
+                log::warn!(
+                    "Order reconciliation failed for {} on account {:?}: {}",
+                    user_id, service.exchange_id(), e
+                );
+                report.errors += 1;
+            }
+        }
+    }
+    Ok(report)
+}
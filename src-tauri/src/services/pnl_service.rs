@@ -0,0 +1,645 @@
+//! Equity and PnL history
+//!
+//! Records periodic equity snapshots per user, per exchange account and per
+//! strategy instance in `equity_snapshots`, and derives PnL from them and
+//! from the realised PnL of recorded fills (`trades.pnl`):
+//!
+//! - `today_pnl` feeds `RiskContext.today_pnl` in every risk check
+//! - `get_statements` builds daily, weekly and monthly PnL statements
+//!
+//! Day boundaries are UTC; weeks start on Monday.
+
+use crate::core::{AppError, AppResult};
+use crate::services::PortfolioService;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// Default interval between equity snapshots
+pub const DEFAULT_EQUITY_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// What a snapshot or statement covers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PnlScope {
+    /// All of a user's accounts
+    User,
+    /// One exchange account (`exchanges.id`)
+    Account(String),
+    /// One strategy instance
+    Instance(String),
+}
+
+impl PnlScope {
+    /// Build a scope from optional filters; an instance takes precedence
+    pub fn from_ids(exchange_id: Option<String>, instance_id: Option<String>) -> Self {
+        match (instance_id, exchange_id) {
+            (Some(instance_id), _) => Self::Instance(instance_id),
+            (None, Some(exchange_id)) => Self::Account(exchange_id),
+            (None, None) => Self::User,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Account(_) => "account",
+            Self::Instance(_) => "instance",
+        }
+    }
+
+    fn exchange_id(&self) -> Option<&str> {
+        match self {
+            Self::Account(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    fn instance_id(&self) -> Option<&str> {
+        match self {
+            Self::Instance(id) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+/// Statement granularity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementPeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl FromStr for StatementPeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "daily" | "day" => Ok(Self::Daily),
+            "weekly" | "week" => Ok(Self::Weekly),
+            "monthly" | "month" => Ok(Self::Monthly),
+            _ => Err(format!("Unknown statement period: {}", s)),
+        }
+    }
+}
+
+/// Recorded equity at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EquitySnapshot {
+    pub id: String,
+    pub scope: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exchange_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy_instance_id: Option<String>,
+    /// Account equity; for instances, realised plus unrealised PnL
+    pub equity: f64,
+    /// Cumulative realised PnL from fills
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    pub timestamp: i64,
+}
+
+/// PnL over one statement period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PnlStatement {
+    pub period_start: i64,
+    pub period_end: i64,
+    /// Realised PnL of fills in the period, net of fees
+    pub realized_pnl: f64,
+    pub trade_count: usize,
+    /// Change in unrealised PnL over the period, if snapshots cover it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unrealized_change: Option<f64>,
+    /// Realised PnL plus unrealised change
+    pub net_pnl: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opening_equity: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closing_equity: Option<f64>,
+}
+
+/// Records equity snapshots and computes PnL
+pub struct PnlService {
+    pool: SqlitePool,
+    portfolio: Arc<PortfolioService>,
+    tasks: RwLock<HashMap<String, JoinHandle<()>>>,
+}
+
+impl PnlService {
+    /// Create a PnL service valuing accounts through the portfolio service
+    pub fn new(pool: SqlitePool, portfolio: Arc<PortfolioService>) -> Self {
+        Self {
+            pool,
+            portfolio,
+            tasks: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// PnL since the start of the current UTC day
+    ///
+    /// Realised PnL of today's fills plus the change in unrealised PnL since
+    /// the last snapshot before midnight. Without such a snapshot all of the
+    /// current unrealised PnL counts as today's.
+    pub async fn today_pnl(&self, user_id: &str, scope: &PnlScope) -> AppResult<f64> {
+        let day_start = period_start(Utc::now().timestamp_millis(), StatementPeriod::Daily);
+
+        let realized = self.realized_pnl(user_id, scope, day_start).await?;
+        let unrealized = self.unrealized_pnl(user_id, scope).await?;
+        let opening_unrealized = self
+            .last_snapshot_before(user_id, scope, day_start)
+            .await?
+            .map(|s| s.unrealized_pnl)
+            .unwrap_or(0.0);
+
+        Ok(realized + unrealized - opening_unrealized)
+    }
+
+    /// Record user, account and running-instance snapshots for a user
+    pub async fn record_snapshots(&self, user_id: &str) -> AppResult<Vec<EquitySnapshot>> {
+        let portfolio = self.portfolio.get_portfolio(user_id).await?;
+        let now = Utc::now().timestamp_millis();
+        let mut snapshots = Vec::new();
+
+        if !portfolio.exchanges.is_empty() {
+            snapshots.push(self.build_snapshot(user_id, PnlScope::User, Some(portfolio.total_equity), now).await?);
+        }
+        for account in &portfolio.exchanges {
+            let scope = PnlScope::Account(account.exchange_id.clone());
+            snapshots.push(self.build_snapshot(user_id, scope, Some(account.equity), now).await?);
+        }
+
+        let instance_ids: Vec<String> = sqlx::query_scalar(
+            "SELECT id FROM strategy_instances WHERE user_id = ? AND status = 'running'"
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        for instance_id in instance_ids {
+            snapshots.push(self.build_snapshot(user_id, PnlScope::Instance(instance_id), None, now).await?);
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for snapshot in &snapshots {
+            sqlx::query(
+                r#"
+                INSERT INTO equity_snapshots (id, user_id, scope, exchange_id, strategy_instance_id,
+                                              equity, realized_pnl, unrealized_pnl, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&snapshot.id)
+            .bind(user_id)
+            .bind(&snapshot.scope)
+            .bind(&snapshot.exchange_id)
+            .bind(&snapshot.strategy_instance_id)
+            .bind(snapshot.equity)
+            .bind(snapshot.realized_pnl)
+            .bind(snapshot.unrealized_pnl)
+            .bind(snapshot.timestamp)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(snapshots)
+    }
+
+    /// Stored snapshots for a scope, newest first
+    pub async fn get_equity_history(
+        &self,
+        user_id: &str,
+        scope: &PnlScope,
+        since: Option<i64>,
+        limit: usize,
+    ) -> AppResult<Vec<EquitySnapshot>> {
+        let rows = sqlx::query(
+            "SELECT * FROM equity_snapshots WHERE user_id = ? AND scope = ? \
+             AND (? IS NULL OR exchange_id = ?) AND (? IS NULL OR strategy_instance_id = ?) \
+             AND created_at >= ? ORDER BY created_at DESC LIMIT ?"
+        )
+        .bind(user_id)
+        .bind(scope.as_str())
+        .bind(scope.exchange_id())
+        .bind(scope.exchange_id())
+        .bind(scope.instance_id())
+        .bind(scope.instance_id())
+        .bind(since.unwrap_or(0))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(row_to_snapshot).collect()
+    }
+
+    /// Realised PnL of each fill in `[from, to)` (ms) as `(timestamp, pnl)`, oldest first
+    pub async fn get_realized_fills(
+        &self,
+        user_id: &str,
+        scope: &PnlScope,
+        from: i64,
+        to: i64,
+    ) -> AppResult<Vec<(i64, f64)>> {
+        sqlx::query(
+            "SELECT t.timestamp, COALESCE(t.pnl, 0) AS pnl FROM trades t \
+             LEFT JOIN orders o ON o.id = t.order_id \
+             WHERE t.user_id = ? AND t.timestamp >= ? AND t.timestamp < ? \
+             AND (? IS NULL OR t.exchange_id = ?) AND (? IS NULL OR o.strategy_instance_id = ?) \
+             ORDER BY t.timestamp"
+        )
+        .bind(user_id)
+        .bind(from)
+        .bind(to)
+        .bind(scope.exchange_id())
+        .bind(scope.exchange_id())
+        .bind(scope.instance_id())
+        .bind(scope.instance_id())
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| Ok((row.try_get("timestamp")?, row.try_get("pnl")?)))
+        .collect()
+    }
+
+    /// Stored snapshots for a scope in `[from, to)` (ms), oldest first
+    pub async fn get_snapshots(
+        &self,
+        user_id: &str,
+        scope: &PnlScope,
+        from: i64,
+        to: i64,
+    ) -> AppResult<Vec<EquitySnapshot>> {
+        sqlx::query(
+            "SELECT * FROM equity_snapshots WHERE user_id = ? AND scope = ? \
+             AND (? IS NULL OR exchange_id = ?) AND (? IS NULL OR strategy_instance_id = ?) \
+             AND created_at >= ? AND created_at < ? ORDER BY created_at"
+        )
+        .bind(user_id)
+        .bind(scope.as_str())
+        .bind(scope.exchange_id())
+        .bind(scope.exchange_id())
+        .bind(scope.instance_id())
+        .bind(scope.instance_id())
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(row_to_snapshot)
+        .collect()
+    }
+
+    /// PnL statements for `[from, to)` (ms)
+    pub async fn get_statements(
+        &self,
+        user_id: &str,
+        scope: &PnlScope,
+        period: StatementPeriod,
+        from: i64,
+        to: i64,
+    ) -> AppResult<Vec<PnlStatement>> {
+        if to <= from {
+            return Err(AppError::validation("Statement range end must be after its start"));
+        }
+
+        let fills = self.get_realized_fills(user_id, scope, from, to).await?;
+        let snapshots = self.get_snapshots(user_id, scope, from, to).await?;
+
+        let opening = self.last_snapshot_before(user_id, scope, from).await?;
+        Ok(build_statements(period, &fills, &snapshots, opening.as_ref()))
+    }
+
+    /// Start periodic snapshots for a user
+    ///
+    /// Restarts the task with the new interval if it is already running.
+    pub async fn start(self: &Arc<Self>, user_id: &str, interval: Duration) {
+        let service = self.clone();
+        let user = user_id.to_string();
+
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+                if let Err(e) = service.record_snapshots(&user).await {
+                    log::warn!("Equity snapshot failed for {}: {}", user, e);
+                }
+            }
+        });
+
+        if let Some(previous) = self.tasks.write().await.insert(user_id.to_string(), handle) {
+            previous.abort();
+        }
+        log::info!("Equity snapshots started for {} (every {:?})", user_id, interval);
+    }
+
+    /// Stop periodic snapshots for a user
+    ///
+    /// Returns false if they weren't running.
+    pub async fn stop(&self, user_id: &str) -> bool {
+        match self.tasks.write().await.remove(user_id) {
+            Some(handle) => {
+                handle.abort();
+                log::info!("Equity snapshots stopped for {}", user_id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop all snapshot tasks
+    pub async fn shutdown(&self) {
+        for (_, handle) in self.tasks.write().await.drain() {
+            handle.abort();
+        }
+    }
+
+    async fn build_snapshot(
+        &self,
+        user_id: &str,
+        scope: PnlScope,
+        equity: Option<f64>,
+        timestamp: i64,
+    ) -> AppResult<EquitySnapshot> {
+        let realized_pnl = self.realized_pnl(user_id, &scope, 0).await?;
+        let unrealized_pnl = self.unrealized_pnl(user_id, &scope).await?;
+
+        Ok(EquitySnapshot {
+            id: Uuid::new_v4().to_string(),
+            scope: scope.as_str().to_string(),
+            exchange_id: scope.exchange_id().map(str::to_string),
+            strategy_instance_id: scope.instance_id().map(str::to_string),
+            equity: equity.unwrap_or(realized_pnl + unrealized_pnl),
+            realized_pnl,
+            unrealized_pnl,
+            timestamp,
+        })
+    }
+
+    /// Realised PnL of fills since `since` (ms)
+    async fn realized_pnl(&self, user_id: &str, scope: &PnlScope, since: i64) -> AppResult<f64> {
+        let pnl = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(t.pnl), 0) FROM trades t \
+             LEFT JOIN orders o ON o.id = t.order_id \
+             WHERE t.user_id = ? AND t.timestamp >= ? \
+             AND (? IS NULL OR t.exchange_id = ?) AND (? IS NULL OR o.strategy_instance_id = ?)"
+        )
+        .bind(user_id)
+        .bind(since)
+        .bind(scope.exchange_id())
+        .bind(scope.exchange_id())
+        .bind(scope.instance_id())
+        .bind(scope.instance_id())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(pnl)
+    }
+
+    /// Current unrealised PnL of open positions
+    async fn unrealized_pnl(&self, user_id: &str, scope: &PnlScope) -> AppResult<f64> {
+        let pnl = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(unrealized_pnl), 0) FROM positions \
+             WHERE user_id = ? AND quantity > 0 \
+             AND (? IS NULL OR exchange_id = ?) AND (? IS NULL OR strategy_instance_id = ?)"
+        )
+        .bind(user_id)
+        .bind(scope.exchange_id())
+        .bind(scope.exchange_id())
+        .bind(scope.instance_id())
+        .bind(scope.instance_id())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(pnl)
+    }
+
+    async fn last_snapshot_before(
+        &self,
+        user_id: &str,
+        scope: &PnlScope,
+        before: i64,
+    ) -> AppResult<Option<EquitySnapshot>> {
+        let row = sqlx::query(
+            "SELECT * FROM equity_snapshots WHERE user_id = ? AND scope = ? \
+             AND (? IS NULL OR exchange_id = ?) AND (? IS NULL OR strategy_instance_id = ?) \
+             AND created_at < ? ORDER BY created_at DESC LIMIT 1"
+        )
+        .bind(user_id)
+        .bind(scope.as_str())
+        .bind(scope.exchange_id())
+        .bind(scope.exchange_id())
+        .bind(scope.instance_id())
+        .bind(scope.instance_id())
+        .bind(before)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(row_to_snapshot).transpose()
+    }
+}
+
+fn row_to_snapshot(row: sqlx::sqlite::SqliteRow) -> AppResult<EquitySnapshot> {
+    Ok(EquitySnapshot {
+        id: row.try_get("id")?,
+        scope: row.try_get("scope")?,
+        exchange_id: row.try_get("exchange_id")?,
+        strategy_instance_id: row.try_get("strategy_instance_id")?,
+        equity: row.try_get("equity")?,
+        realized_pnl: row.try_get("realized_pnl")?,
+        unrealized_pnl: row.try_get("unrealized_pnl")?,
+        timestamp: row.try_get("created_at")?,
+    })
+}
+
+fn date_of(timestamp_ms: i64) -> NaiveDate {
+    DateTime::from_timestamp_millis(timestamp_ms)
+        .unwrap_or_default()
+        .date_naive()
+}
+
+fn date_to_ms(date: NaiveDate) -> i64 {
+    date.and_hms_opt(0, 0, 0)
+        .unwrap_or_default()
+        .and_utc()
+        .timestamp_millis()
+}
+
+/// Start (ms) of the period containing `timestamp_ms`
+pub fn period_start(timestamp_ms: i64, period: StatementPeriod) -> i64 {
+    let date = date_of(timestamp_ms);
+    let start = match period {
+        StatementPeriod::Daily => date,
+        StatementPeriod::Weekly => {
+            date - ChronoDuration::days(i64::from(date.weekday().num_days_from_monday()))
+        }
+        StatementPeriod::Monthly => date.with_day(1).unwrap_or(date),
+    };
+    date_to_ms(start)
+}
+
+/// End (ms, exclusive) of the period starting at `start_ms`
+pub fn period_end(start_ms: i64, period: StatementPeriod) -> i64 {
+    let start = date_of(start_ms);
+    let end = match period {
+        StatementPeriod::Daily => start + ChronoDuration::days(1),
+        StatementPeriod::Weekly => start + ChronoDuration::days(7),
+        StatementPeriod::Monthly => start + Months::new(1),
+    };
+    date_to_ms(end)
+}
+
+/// Bucket fills and snapshots into statements
+///
+/// Each period opens at the previous period's last snapshot (`opening` for
+/// the first one) and closes at its own last snapshot.
+fn build_statements(
+    period: StatementPeriod,
+    fills: &[(i64, f64)],
+    snapshots: &[EquitySnapshot],
+    opening: Option<&EquitySnapshot>,
+) -> Vec<PnlStatement> {
+    let mut statements: BTreeMap<i64, PnlStatement> = BTreeMap::new();
+
+    for &(timestamp, pnl) in fills {
+        let start = period_start(timestamp, period);
+        let statement = statements.entry(start).or_insert_with(|| empty_statement(start, period));
+        statement.realized_pnl += pnl;
+        statement.trade_count += 1;
+    }
+
+    // Opening and closing snapshot per period
+    let mut bounds: BTreeMap<i64, (&EquitySnapshot, &EquitySnapshot)> = BTreeMap::new();
+    let mut ordered: Vec<&EquitySnapshot> = snapshots.iter().collect();
+    ordered.sort_by_key(|s| s.timestamp);
+
+    let mut previous = opening;
+    for snapshot in ordered {
+        let start = period_start(snapshot.timestamp, period);
+        let open = previous.unwrap_or(snapshot);
+        bounds.entry(start).or_insert((open, snapshot)).1 = snapshot;
+        previous = Some(snapshot);
+    }
+
+    for (start, (open, close)) in bounds {
+        let statement = statements.entry(start).or_insert_with(|| empty_statement(start, period));
+        statement.opening_equity = Some(open.equity);
+        statement.closing_equity = Some(close.equity);
+        statement.unrealized_change = Some(close.unrealized_pnl - open.unrealized_pnl);
+    }
+
+    statements
+        .into_values()
+        .map(|mut statement| {
+            statement.net_pnl = statement.realized_pnl + statement.unrealized_change.unwrap_or(0.0);
+            statement
+        })
+        .collect()
+}
+
+fn empty_statement(start: i64, period: StatementPeriod) -> PnlStatement {
+    PnlStatement {
+        period_start: start,
+        period_end: period_end(start, period),
+        realized_pnl: 0.0,
+        trade_count: 0,
+        unrealized_change: None,
+        net_pnl: 0.0,
+        opening_equity: None,
+        closing_equity: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2026-01-07 (Wednesday) 12:00 UTC
+    const WED_NOON: i64 = 1_767_787_200_000;
+    const DAY_MS: i64 = 86_400_000;
+
+    fn snapshot(timestamp: i64, equity: f64, unrealized_pnl: f64) -> EquitySnapshot {
+        EquitySnapshot {
+            id: timestamp.to_string(),
+            scope: "user".to_string(),
+            exchange_id: None,
+            strategy_instance_id: None,
+            equity,
+            realized_pnl: 0.0,
+            unrealized_pnl,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_period_bounds() {
+        let day = period_start(WED_NOON, StatementPeriod::Daily);
+        assert_eq!(day, WED_NOON - DAY_MS / 2);
+        assert_eq!(period_end(day, StatementPeriod::Daily), day + DAY_MS);
+
+        // Week starts on Monday 2026-01-05
+        let week = period_start(WED_NOON, StatementPeriod::Weekly);
+        assert_eq!(week, day - 2 * DAY_MS);
+        assert_eq!(period_end(week, StatementPeriod::Weekly), week + 7 * DAY_MS);
+
+        let month = period_start(WED_NOON, StatementPeriod::Monthly);
+        assert_eq!(month, day - 6 * DAY_MS);
+        assert_eq!(period_end(month, StatementPeriod::Monthly), month + 31 * DAY_MS);
+    }
+
+    #[test]
+    fn test_daily_statements() {
+        let fills = vec![(WED_NOON, 50.0), (WED_NOON + 1_000, -20.0), (WED_NOON + DAY_MS, 10.0)];
+        let opening = snapshot(WED_NOON - DAY_MS, 1000.0, 5.0);
+        let snapshots = vec![
+            snapshot(WED_NOON + 2_000, 1040.0, 15.0),
+            snapshot(WED_NOON + DAY_MS + 2_000, 1030.0, -5.0),
+        ];
+
+        let statements = build_statements(StatementPeriod::Daily, &fills, &snapshots, Some(&opening));
+        assert_eq!(statements.len(), 2);
+
+        let wed = &statements[0];
+        assert_eq!(wed.trade_count, 2);
+        assert_eq!(wed.realized_pnl, 30.0);
+        assert_eq!(wed.unrealized_change, Some(10.0));
+        assert_eq!(wed.net_pnl, 40.0);
+        assert_eq!(wed.opening_equity, Some(1000.0));
+        assert_eq!(wed.closing_equity, Some(1040.0));
+
+        let thu = &statements[1];
+        assert_eq!(thu.opening_equity, Some(1040.0));
+        assert_eq!(thu.unrealized_change, Some(-20.0));
+        assert_eq!(thu.net_pnl, -10.0);
+    }
+
+    #[test]
+    fn test_statement_without_snapshots() {
+        let statements = build_statements(StatementPeriod::Monthly, &[(WED_NOON, 12.5)], &[], None);
+
+        assert_eq!(statements.len(), 1);
+        assert_eq!(statements[0].net_pnl, 12.5);
+        assert!(statements[0].unrealized_change.is_none());
+    }
+
+    #[test]
+    fn test_scope_from_ids() {
+        assert_eq!(PnlScope::from_ids(None, None), PnlScope::User);
+        assert_eq!(PnlScope::from_ids(Some("ex".into()), None), PnlScope::Account("ex".into()));
+        assert_eq!(
+            PnlScope::from_ids(Some("ex".into()), Some("inst".into())),
+            PnlScope::Instance("inst".into())
+        );
+    }
+}
@@ -0,0 +1,466 @@
+//! Cross-exchange portfolio
+//!
+//! Aggregates balances and positions from every active exchange account of a
+//! user, values them in a single quote currency using live tickers, and
+//! records periodic snapshots in the `portfolio_snapshots` table.
+
+use crate::core::trade::types::{Balance, Position};
+use crate::core::AppResult;
+use crate::repository::ExchangeRepository;
+use crate::services::ExchangeSessionRegistry;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// Currency portfolio values are expressed in unless configured otherwise
+pub const DEFAULT_QUOTE_CURRENCY: &str = "USDT";
+
+/// Default interval between stored snapshots
+pub const DEFAULT_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Equity below this is treated as zero when computing weights
+const EQUITY_EPSILON: f64 = 1e-9;
+
+/// Net exposure to one asset across all accounts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetExposure {
+    pub asset: String,
+    /// Balances plus signed position quantity (short positions count negative)
+    pub quantity: f64,
+    /// Value in the quote currency
+    pub value: f64,
+    /// Value as a share of total equity
+    pub weight: f64,
+}
+
+/// Equity held on one exchange account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeAllocation {
+    pub exchange_id: String,
+    pub exchange_name: String,
+    pub display_name: String,
+    pub equity: f64,
+    /// Equity as a share of total equity
+    pub weight: f64,
+}
+
+/// Aggregated portfolio at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioSnapshot {
+    pub quote_currency: String,
+    /// Balances valued in the quote currency plus unrealised position PnL
+    pub total_equity: f64,
+    /// Assets sorted by absolute value, largest first
+    pub assets: Vec<AssetExposure>,
+    pub exchanges: Vec<ExchangeAllocation>,
+    /// Assets with no ticker against the quote currency (excluded from equity)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unpriced_assets: Vec<String>,
+    /// Accounts that couldn't be queried
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+    pub timestamp: i64,
+}
+
+/// Balances and positions fetched from one account
+struct AccountHoldings {
+    exchange_id: String,
+    exchange_name: String,
+    display_name: String,
+    balances: Vec<Balance>,
+    positions: Vec<Position>,
+}
+
+/// Builds and records cross-exchange portfolio snapshots
+pub struct PortfolioService {
+    pool: SqlitePool,
+    sessions: Arc<ExchangeSessionRegistry>,
+    quote_currency: String,
+    tasks: RwLock<HashMap<String, JoinHandle<()>>>,
+}
+
+impl PortfolioService {
+    /// Create a portfolio service valued in `DEFAULT_QUOTE_CURRENCY`
+    pub fn new(pool: SqlitePool, sessions: Arc<ExchangeSessionRegistry>) -> Self {
+        Self {
+            pool,
+            sessions,
+            quote_currency: DEFAULT_QUOTE_CURRENCY.to_string(),
+            tasks: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Value the portfolio in another quote currency
+    pub fn with_quote_currency(mut self, quote_currency: impl Into<String>) -> Self {
+        self.quote_currency = quote_currency.into();
+        self
+    }
+
+    /// Aggregate the user's active accounts at current prices
+    ///
+    /// Accounts that fail to respond are listed in `errors` and left out.
+    pub async fn get_portfolio(&self, user_id: &str) -> AppResult<PortfolioSnapshot> {
+        let configs = ExchangeRepository::new(self.pool.clone()).find_by_user(user_id).await?;
+
+        let mut accounts = Vec::new();
+        let mut errors = Vec::new();
+        let mut prices: HashMap<String, f64> = HashMap::new();
+
+        for config in configs.iter().filter(|c| c.is_active()) {
+            let session = match self.sessions.get(&config.id).await {
+                Ok(session) => session,
+                Err(e) => {
+                    errors.push(format!("{}: {}", config.display_name, e));
+                    continue;
+                }
+            };
+
+            let balances = match session.exchange.get_balance().await {
+                Ok(balances) => balances,
+                Err(e) => {
+                    errors.push(format!("{}: {}", config.display_name, e));
+                    continue;
+                }
+            };
+            let positions = session.exchange.get_positions().await.unwrap_or_else(|e| {
+                errors.push(format!("{}: {}", config.display_name, e));
+                Vec::new()
+            });
+
+            // Price this account's assets on its own exchange
+            let needed: BTreeSet<String> = balances
+                .iter()
+                .filter(|b| b.total != 0.0)
+                .map(|b| b.asset.clone())
+                .chain(positions.iter().map(|p| base_asset(&p.symbol, &self.quote_currency).to_string()))
+                .filter(|asset| *asset != self.quote_currency && !prices.contains_key(asset))
+                .collect();
+            for asset in needed {
+                let symbol = format!("{}{}", asset, self.quote_currency);
+                match session.exchange.get_ticker(&symbol).await {
+                    Ok(ticker) if ticker.price > 0.0 => {
+                        prices.insert(asset, ticker.price);
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::debug!("No {} price on {}: {}", symbol, config.exchange_name, e),
+                }
+            }
+
+            accounts.push(AccountHoldings {
+                exchange_id: config.id.clone(),
+                exchange_name: config.exchange_name.clone(),
+                display_name: config.display_name.clone(),
+                balances,
+                positions,
+            });
+        }
+
+        let mut snapshot = aggregate(&self.quote_currency, &accounts, &prices, Utc::now().timestamp_millis());
+        snapshot.errors = errors;
+        Ok(snapshot)
+    }
+
+    /// Aggregate the portfolio and store it in the history table
+    ///
+    /// Nothing is stored if no account could be queried.
+    pub async fn snapshot(&self, user_id: &str) -> AppResult<PortfolioSnapshot> {
+        let snapshot = self.get_portfolio(user_id).await?;
+        if snapshot.exchanges.is_empty() {
+            return Ok(snapshot);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO portfolio_snapshots (id, user_id, quote_currency, total_equity,
+                                             assets_json, exchanges_json, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(user_id)
+        .bind(&snapshot.quote_currency)
+        .bind(snapshot.total_equity)
+        .bind(serde_json::to_string(&snapshot.assets)?)
+        .bind(serde_json::to_string(&snapshot.exchanges)?)
+        .bind(snapshot.timestamp)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(snapshot)
+    }
+
+    /// Stored snapshots for a user, newest first
+    pub async fn get_history(
+        &self,
+        user_id: &str,
+        since: Option<i64>,
+        limit: usize,
+    ) -> AppResult<Vec<PortfolioSnapshot>> {
+        let rows = sqlx::query(
+            "SELECT * FROM portfolio_snapshots WHERE user_id = ? AND created_at >= ? \
+             ORDER BY created_at DESC LIMIT ?"
+        )
+        .bind(user_id)
+        .bind(since.unwrap_or(0))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(PortfolioSnapshot {
+                    quote_currency: row.try_get("quote_currency")?,
+                    total_equity: row.try_get("total_equity")?,
+                    assets: serde_json::from_str(&row.try_get::<String, _>("assets_json")?)?,
+                    exchanges: serde_json::from_str(&row.try_get::<String, _>("exchanges_json")?)?,
+                    unpriced_assets: Vec::new(),
+                    errors: Vec::new(),
+                    timestamp: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Start periodic snapshots for a user
+    ///
+    /// Restarts the task with the new interval if it is already running.
+    pub async fn start(self: &Arc<Self>, user_id: &str, interval: Duration) {
+        let service = self.clone();
+        let user = user_id.to_string();
+
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+                if let Err(e) = service.snapshot(&user).await {
+                    log::warn!("Portfolio snapshot failed for {}: {}", user, e);
+                }
+            }
+        });
+
+        if let Some(previous) = self.tasks.write().await.insert(user_id.to_string(), handle) {
+            previous.abort();
+        }
+        log::info!("Portfolio snapshots started for {} (every {:?})", user_id, interval);
+    }
+
+    /// Stop periodic snapshots for a user
+    ///
+    /// Returns false if they weren't running.
+    pub async fn stop(&self, user_id: &str) -> bool {
+        match self.tasks.write().await.remove(user_id) {
+            Some(handle) => {
+                handle.abort();
+                log::info!("Portfolio snapshots stopped for {}", user_id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop all snapshot tasks
+    pub async fn shutdown(&self) {
+        for (_, handle) in self.tasks.write().await.drain() {
+            handle.abort();
+        }
+    }
+}
+
+/// Base asset of a position symbol, e.g. `BTC` for `BTCUSDT` or `BTC-USDT`
+fn base_asset<'a>(symbol: &'a str, quote: &str) -> &'a str {
+    symbol
+        .strip_suffix(quote)
+        .map(|base| base.trim_end_matches(['-', '/', '_']))
+        .filter(|base| !base.is_empty())
+        .unwrap_or(symbol)
+}
+
+/// Direction of a position: -1 for short, 1 otherwise
+fn position_sign(side: &str) -> f64 {
+    match side.to_lowercase().as_str() {
+        "short" | "sell" => -1.0,
+        _ => 1.0,
+    }
+}
+
+/// Combine account holdings into one snapshot
+///
+/// `prices` maps assets to their price in `quote`; the quote currency
+/// itself is always worth 1.
+fn aggregate(
+    quote: &str,
+    accounts: &[AccountHoldings],
+    prices: &HashMap<String, f64>,
+    timestamp: i64,
+) -> PortfolioSnapshot {
+    let price_of = |asset: &str| {
+        if asset == quote {
+            Some(1.0)
+        } else {
+            prices.get(asset).copied()
+        }
+    };
+
+    let mut assets: BTreeMap<String, (f64, f64)> = BTreeMap::new();
+    let mut unpriced = BTreeSet::new();
+    let mut exchanges = Vec::new();
+
+    for account in accounts {
+        let mut equity = 0.0;
+
+        for balance in account.balances.iter().filter(|b| b.total != 0.0) {
+            let Some(price) = price_of(&balance.asset) else {
+                unpriced.insert(balance.asset.clone());
+                continue;
+            };
+            let value = balance.total * price;
+            equity += value;
+
+            let entry = assets.entry(balance.asset.clone()).or_default();
+            entry.0 += balance.total;
+            entry.1 += value;
+        }
+
+        for position in account.positions.iter().filter(|p| p.quantity != 0.0) {
+            let base = base_asset(&position.symbol, quote);
+            let mark = position
+                .current_price
+                .or_else(|| price_of(base))
+                .unwrap_or(position.entry_price);
+            let quantity = position_sign(&position.side) * position.quantity;
+            equity += position.unrealized_pnl;
+
+            let entry = assets.entry(base.to_string()).or_default();
+            entry.0 += quantity;
+            entry.1 += quantity * mark;
+        }
+
+        exchanges.push(ExchangeAllocation {
+            exchange_id: account.exchange_id.clone(),
+            exchange_name: account.exchange_name.clone(),
+            display_name: account.display_name.clone(),
+            equity,
+            weight: 0.0,
+        });
+    }
+
+    let total_equity: f64 = exchanges.iter().map(|e| e.equity).sum();
+    let weight = |value: f64| {
+        if total_equity.abs() < EQUITY_EPSILON {
+            0.0
+        } else {
+            value / total_equity
+        }
+    };
+
+    for exchange in &mut exchanges {
+        exchange.weight = weight(exchange.equity);
+    }
+
+    let mut assets: Vec<AssetExposure> = assets
+        .into_iter()
+        .map(|(asset, (quantity, value))| AssetExposure {
+            asset,
+            quantity,
+            value,
+            weight: weight(value),
+        })
+        .collect();
+    assets.sort_by(|a, b| b.value.abs().total_cmp(&a.value.abs()));
+
+    PortfolioSnapshot {
+        quote_currency: quote.to_string(),
+        total_equity,
+        assets,
+        exchanges,
+        unpriced_assets: unpriced.into_iter().collect(),
+        errors: Vec::new(),
+        timestamp,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance(asset: &str, total: f64) -> Balance {
+        Balance {
+            asset: asset.to_string(),
+            free: total,
+            locked: 0.0,
+            total,
+        }
+    }
+
+    fn account(id: &str, balances: Vec<Balance>, positions: Vec<Position>) -> AccountHoldings {
+        AccountHoldings {
+            exchange_id: id.to_string(),
+            exchange_name: "binance".to_string(),
+            display_name: id.to_string(),
+            balances,
+            positions,
+        }
+    }
+
+    #[test]
+    fn test_base_asset() {
+        assert_eq!(base_asset("BTCUSDT", "USDT"), "BTC");
+        assert_eq!(base_asset("ETH-USDT", "USDT"), "ETH");
+        assert_eq!(base_asset("BTCUSD", "USDT"), "BTCUSD");
+    }
+
+    #[test]
+    fn test_aggregate_balances_across_exchanges() {
+        let accounts = vec![
+            account("a", vec![balance("USDT", 1000.0), balance("BTC", 0.1)], vec![]),
+            account("b", vec![balance("USDT", 500.0), balance("XYZ", 10.0)], vec![]),
+        ];
+        let prices = HashMap::from([("BTC".to_string(), 50000.0)]);
+
+        let snapshot = aggregate("USDT", &accounts, &prices, 0);
+
+        assert_eq!(snapshot.total_equity, 6500.0);
+        assert_eq!(snapshot.assets[0].asset, "BTC");
+        assert_eq!(snapshot.assets[0].value, 5000.0);
+        assert_eq!(snapshot.assets[1].asset, "USDT");
+        assert_eq!(snapshot.assets[1].quantity, 1500.0);
+        assert_eq!(snapshot.exchanges[0].equity, 6000.0);
+        assert!((snapshot.exchanges[1].weight - 500.0 / 6500.0).abs() < 1e-12);
+        assert_eq!(snapshot.unpriced_assets, vec!["XYZ".to_string()]);
+    }
+
+    #[test]
+    fn test_short_position_offsets_exposure() {
+        let short = Position {
+            id: "p1".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side: "short".to_string(),
+            quantity: 0.1,
+            entry_price: 52000.0,
+            current_price: Some(50000.0),
+            unrealized_pnl: 200.0,
+            realized_pnl: 0.0,
+            opened_at: 0,
+        };
+        let accounts = vec![account("a", vec![balance("USDT", 1000.0), balance("BTC", 0.1)], vec![short])];
+        let prices = HashMap::from([("BTC".to_string(), 50000.0)]);
+
+        let snapshot = aggregate("USDT", &accounts, &prices, 0);
+        let btc = snapshot.assets.iter().find(|a| a.asset == "BTC").unwrap();
+
+        assert!(btc.quantity.abs() < 1e-12);
+        assert!(btc.value.abs() < 1e-9);
+        assert_eq!(snapshot.total_equity, 6200.0);
+    }
+}
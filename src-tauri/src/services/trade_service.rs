@@ -3,59 +3,632 @@
 //! This module provides trading functionality including order management,
 //! position tracking, and account operations.
 
-use crate::core::trade::exchange::Exchange;
+use crate::core::risk::{
+    estimate_slippage, is_reduce_only, OrderOrigin, PreTradeLimits, PreTradeOrder, PreTradeRejection,
+    PreTradeSnapshot, RejectCode, SlippageDecision, SlippageEstimate, SlippageLimits, PRE_TRADE_RULE, SLIPPAGE_GUARD_RULE,
+};
+use crate::core::trade::exchange::{is_outcome_unknown, BatchFailure, Exchange};
+use crate::core::trade::instrument::InstrumentRegistry;
 use crate::core::trade::types::*;
-use crate::core::trade::position::PositionManager;
-use crate::core::trade::order::OrderStateMachine;
-use crate::core::{AppError, AppResult};
-use sqlx::SqlitePool;
+use crate::core::trade::position::{
+    commission_in_quote, CostBasis, CostMethod, PositionManager, PositionMode, TrackedPosition, Trade,
+};
+use crate::core::trade::execution::{slippage_bps, ExecutionReport};
+use crate::core::trade::order::{
+    check_update, diff_order, OrderCorrection, OrderEvent, OrderEventKind, OrderEventSource, OrderStateMachine,
+    OrderTimeline, ReconcileReport, UpdateCheck,
+};
+use crate::core::{AppError, AppResult, EventBus};
+use crate::repository::{RiskRuleRepository, TradingHaltRepository};
+use crate::services::pnl_service::{period_start, StatementPeriod};
+use sqlx::{SqliteConnection, SqlitePool};
 use chrono::Utc;
 use sqlx::Row;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
+/// Tolerance when comparing recorded fill quantity with the order quantity
+const FILL_QTY_TOLERANCE: f64 = 1e-9;
+
+/// How long a pending order may wait for its placement answer before
+/// reconciliation looks it up (ms); well past the exchange request timeout
+/// plus the rate limiter's longest wait, so no placement is still in flight
+const PENDING_ORDER_GRACE_MS: i64 = 60_000;
+
+/// Minimum time between saves of a symbol's revalued positions (ms)
+const MARK_PRICE_SAVE_INTERVAL_MS: i64 = 5_000;
+
 /// Trade service for managing orders and positions
 pub struct TradeService {
     exchange: Arc<dyn Exchange>,
     pool: SqlitePool,
-    position_manager: Arc<RwLock<PositionManager>>,
+    /// Positions per (user id, exchange account), loaded on first use
+    position_books: RwLock<HashMap<(String, String), PositionManager>>,
+    /// Last `updated_at` stamped on a position update; strictly increasing,
+    /// so a write that lands late can't overwrite a newer one
+    position_stamp: AtomicI64,
+    position_mode: PositionMode,
+    cost_method: CostMethod,
+    instruments: Arc<InstrumentRegistry>,
+    event_bus: Option<Arc<EventBus>>,
+    /// Exchange account (`exchanges.id`) this service trades on
+    exchange_id: Option<String>,
 }
 
 impl TradeService {
     /// Create a new trade service
     pub fn new(exchange: Arc<dyn Exchange>, pool: SqlitePool) -> Self {
         Self {
+            instruments: Arc::new(InstrumentRegistry::new(exchange.clone())),
             exchange,
             pool,
-            position_manager: Arc::new(RwLock::new(PositionManager::new())),
+            position_books: RwLock::new(HashMap::new()),
+            position_stamp: AtomicI64::new(0),
+            position_mode: PositionMode::default(),
+            cost_method: CostMethod::default(),
+            event_bus: None,
+            exchange_id: None,
         }
     }
 
+    /// Publish order events to the given event bus
+    pub fn with_event_bus(mut self, event_bus: Arc<EventBus>) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// Position accounting: net or hedge mode, average cost or FIFO lots
+    pub fn with_position_accounting(mut self, mode: PositionMode, method: CostMethod) -> Self {
+        self.position_mode = mode;
+        self.cost_method = method;
+        self
+    }
+
+    /// Bind the service to one exchange account
+    ///
+    /// Orders are recorded against the account, and order, position and
+    /// trade queries only see that account's rows.
+    pub fn with_exchange_id(mut self, exchange_id: impl Into<String>) -> Self {
+        self.exchange_id = Some(exchange_id.into());
+        self
+    }
+
+    /// Exchange account this service is bound to
+    pub fn exchange_id(&self) -> Option<&str> {
+        self.exchange_id.as_deref()
+    }
+
+    /// Get the instrument registry used for order precision rules
+    pub fn instruments(&self) -> Arc<InstrumentRegistry> {
+        self.instruments.clone()
+    }
+
     /// Place a new order
     pub async fn place_order(&self, request: OrderRequest, user_id: &str) -> AppResult<Order> {
-        // Validate order request
-        self.validate_order_request(&request)?;
+        self.submit_order(request, user_id, None, None, OrderOrigin::Manual).await
+    }
+
+    /// Place the order a conditional order fires
+    ///
+    /// Like emergency closes, exits that only reduce an open position skip
+    /// the pre-trade limits.
+    pub async fn place_conditional_order(&self, request: OrderRequest, user_id: &str) -> AppResult<Order> {
+        self.submit_order(request, user_id, None, None, OrderOrigin::Conditional).await
+    }
 
-        // Create order ID
-        let order_id = Uuid::new_v4().to_string();
+    /// Place an order on behalf of a strategy instance
+    ///
+    /// The order is attributed to the instance so its fills count towards
+    /// the instance's PnL.
+    pub async fn place_instance_order(
+        &self,
+        request: OrderRequest,
+        user_id: &str,
+        instance_id: &str,
+    ) -> AppResult<Order> {
+        let origin = OrderOrigin::Instance(instance_id.to_string());
+        self.submit_order(request, user_id, Some(instance_id), None, origin).await
+    }
+
+    /// Place a child order of an execution-algo parent order
+    pub async fn place_child_order(
+        &self,
+        request: OrderRequest,
+        user_id: &str,
+        instance_id: Option<&str>,
+        parent_order_id: &str,
+    ) -> AppResult<Order> {
+        let origin = OrderOrigin::for_instance(instance_id);
+        self.submit_order(request, user_id, instance_id, Some(parent_order_id), origin).await
+    }
 
-        // Call exchange to place the order
-        let mut order = self.exchange.place_order(&request).await?;
+    /// Record a parent order for an execution algorithm
+    ///
+    /// The parent never reaches the exchange; its fill state is rolled up
+    /// from its child orders by `refresh_parent_order`.
+    pub async fn create_parent_order(
+        &self,
+        mut request: OrderRequest,
+        algo: &str,
+        arrival_price: Option<f64>,
+        user_id: &str,
+        instance_id: Option<&str>,
+    ) -> AppResult<Order> {
+        let origin = OrderOrigin::for_instance(instance_id);
+        self.validate_order_request(&mut request, user_id, &origin).await?;
+        self.pre_trade_check(&request, user_id, &origin).await?;
 
-        // Update order with our ID and user info
-        order.id = order_id.clone();
-        order.client_order_id = request.client_order_id;
+        let exchange_id = self.account_id(user_id).await?.ok_or_else(|| {
+            AppError::validation(format!("No {} account configured for user", self.exchange.name()))
+        })?;
 
-        // Save to database
-        self.save_order_to_db(&order, user_id).await?;
+        let now = Utc::now().timestamp_millis();
+        let order = Order {
+            id: Uuid::new_v4().to_string(),
+            exchange_order_id: None,
+            client_order_id: None,
+            symbol: request.symbol,
+            side: request.side,
+            order_type: request.order_type,
+            price: request.price,
+            quantity: request.quantity,
+            filled_quantity: 0.0,
+            avg_price: None,
+            status: OrderState::Open,
+            commission: 0.0,
+            commission_asset: None,
+            created_at: now,
+            filled_at: None,
+            updated_at: None,
+        };
 
-        // Publish order event
-        self.publish_order_event(&order).await;
+        sqlx::query(
+            r#"
+            INSERT INTO orders (id, user_id, exchange_id, strategy_instance_id, symbol, side,
+                               order_type, price, quantity, filled_quantity, status, commission,
+                               algo, arrival_price, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, 0, ?, 0, ?, ?, ?, ?)
+            "#
+        )
+        .bind(&order.id)
+        .bind(user_id)
+        .bind(&exchange_id)
+        .bind(instance_id)
+        .bind(&order.symbol)
+        .bind(order.side.to_string())
+        .bind(order.order_type.to_string())
+        .bind(order.price)
+        .bind(order.quantity)
+        .bind(order.status.to_string())
+        .bind(algo)
+        .bind(arrival_price)
+        .bind(now)
+        .bind(Utc::now().timestamp())
+        .execute(&self.pool)
+        .await?;
 
+        let event = OrderEvent::new(&order.id, OrderEventKind::Created, OrderEventSource::Local)
+            .with_status(None, order.status)
+            .with_detail(format!("{} parent order", algo));
+        self.record_order_event(&event).await?;
+
+        self.publish_order_event(&order).await;
         Ok(order)
     }
 
+    /// Child orders of a parent order, oldest first
+    pub async fn get_child_orders(&self, parent_order_id: &str) -> AppResult<Vec<Order>> {
+        let rows = sqlx::query("SELECT * FROM orders WHERE parent_order_id = ? ORDER BY created_at")
+            .bind(parent_order_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(|row| self.row_to_order(row)).collect()
+    }
+
+    /// Roll child fills up into the parent order
+    ///
+    /// Sets the parent's filled quantity, volume-weighted average price and
+    /// commission from its children, and moves it to partially filled or
+    /// filled. With `finish`, an incomplete parent is canceled.
+    pub async fn refresh_parent_order(&self, parent_order_id: &str, finish: bool) -> AppResult<Order> {
+        let row = sqlx::query("SELECT * FROM orders WHERE id = ? AND algo IS NOT NULL")
+            .bind(parent_order_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::validation(format!("Parent order not found: {}", parent_order_id)))?;
+        let mut parent = self.row_to_order(row)?;
+
+        let (filled, notional, commission): (f64, f64, f64) = sqlx::query_as(
+            r#"
+            SELECT COALESCE(SUM(filled_quantity), 0),
+                   COALESCE(SUM(filled_quantity * COALESCE(avg_price, 0)), 0),
+                   COALESCE(SUM(commission), 0)
+            FROM orders WHERE parent_order_id = ?
+            "#
+        )
+        .bind(parent_order_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let mut status = if filled >= parent.quantity - FILL_QTY_TOLERANCE {
+            OrderState::Filled
+        } else if filled > 0.0 {
+            OrderState::PartiallyFilled
+        } else {
+            parent.status
+        };
+        if finish && status != OrderState::Filled {
+            status = OrderState::Canceled;
+        }
+        // A parent canceled by the user stays canceled
+        let previous_status = parent.status;
+        let mut sm = OrderStateMachine::from_state(parent.status);
+        let changed = sm.advance_to(status).unwrap_or(false);
+        parent.status = sm.state();
+
+        parent.filled_quantity = filled;
+        parent.avg_price = (filled > 0.0).then(|| notional / filled);
+        parent.commission = commission;
+        if parent.status == OrderState::Filled && parent.filled_at.is_none() {
+            parent.filled_at = Some(Utc::now().timestamp_millis());
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE orders SET
+                filled_quantity = ?,
+                avg_price = ?,
+                commission = ?,
+                status = ?,
+                filled_at = ?,
+                updated_at = ?
+            WHERE id = ?
+            "#
+        )
+        .bind(parent.filled_quantity)
+        .bind(parent.avg_price)
+        .bind(parent.commission)
+        .bind(parent.status.to_string())
+        .bind(parent.filled_at)
+        .bind(Utc::now().timestamp())
+        .bind(&parent.id)
+        .execute(&self.pool)
+        .await?;
+
+        if changed {
+            let event = OrderEvent::new(&parent.id, OrderEventKind::StatusChanged, OrderEventSource::Local)
+                .with_status(Some(previous_status), parent.status)
+                .with_filled(parent.filled_quantity);
+            self.record_order_event(&event).await?;
+        }
+
+        Ok(parent)
+    }
+
+    /// Execution quality of a parent order
+    pub async fn get_execution_report(&self, parent_order_id: &str, user_id: &str) -> AppResult<ExecutionReport> {
+        let row = sqlx::query("SELECT * FROM orders WHERE id = ? AND user_id = ? AND algo IS NOT NULL")
+            .bind(parent_order_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::validation(format!("Parent order not found: {}", parent_order_id)))?;
+        let algo: String = row.try_get("algo")?;
+        let arrival_price: Option<f64> = row.try_get("arrival_price")?;
+        let parent = self.row_to_order(row)?;
+
+        let child_orders: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM orders WHERE parent_order_id = ?")
+            .bind(parent_order_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(ExecutionReport {
+            slippage_bps: arrival_price
+                .zip(parent.avg_price)
+                .map(|(arrival, avg)| slippage_bps(parent.side, arrival, avg)),
+            parent_order_id: parent.id,
+            symbol: parent.symbol,
+            side: parent.side,
+            algo,
+            status: parent.status,
+            quantity: parent.quantity,
+            filled_quantity: parent.filled_quantity,
+            avg_price: parent.avg_price,
+            arrival_price,
+            commission: parent.commission,
+            child_orders: child_orders as usize,
+        })
+    }
+
+    /// Parent orders still being worked on this account
+    pub async fn get_active_parent_orders(&self) -> AppResult<Vec<Order>> {
+        let rows = sqlx::query(
+            "SELECT * FROM orders WHERE algo IS NOT NULL AND status IN ('open', 'partially_filled') \
+             AND (? IS NULL OR exchange_id = ?)"
+        )
+        .bind(&self.exchange_id)
+        .bind(&self.exchange_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(|row| self.row_to_order(row)).collect()
+    }
+
+    /// Submit an order exactly once per client order id
+    ///
+    /// The order is recorded as `pending` before it is sent. If the
+    /// exchange's answer is lost, the order is looked up by client order id
+    /// instead of being sent again; a retry with the same client order id
+    /// returns the recorded order, or resolves it first if it's still pending.
+    async fn submit_order(
+        &self,
+        mut request: OrderRequest,
+        user_id: &str,
+        instance_id: Option<&str>,
+        parent_order_id: Option<&str>,
+        origin: OrderOrigin,
+    ) -> AppResult<Order> {
+        // Validate order request and round it to the exchange precision
+        self.validate_order_request(&mut request, user_id, &origin).await?;
+
+        let exchange_id = self.account_id(user_id).await?.ok_or_else(|| {
+            AppError::validation(format!("No {} account configured for user", self.exchange.name()))
+        })?;
+
+        let client_order_id = request
+            .client_order_id
+            .get_or_insert_with(|| OrderRequest::new_client_order_id("ord"))
+            .clone();
+
+        if let Some(existing) = self.find_order_by_client_id(&client_order_id, user_id).await? {
+            return self.resume_order(existing, &request).await;
+        }
+
+        // Child orders were checked as part of their parent, but a halt
+        // engaged since stops them too. Each market slice still meets the
+        // book as it is when sent.
+        if parent_order_id.is_none() {
+            self.pre_trade_check(&request, user_id, &origin).await?;
+        } else if let Some(rejection) = self.halt_rejection(&request, user_id).await? {
+            log::warn!("Kill switch refused child order of {}: {}", parent_order_id.unwrap_or_default(), rejection);
+            return Err(AppError::risk_limit(rejection.to_string()));
+        }
+        let estimate = self.slippage_check(&mut request, user_id, &origin).await?;
+
+        // Record the intent before anything reaches the exchange
+        let order = Self::pending_order(&request);
+        let recorded = self
+            .record_pending_order(&order, user_id, &exchange_id, instance_id, parent_order_id, estimate.as_ref())
+            .await?;
+        if let Some(existing) = recorded {
+            return Ok(existing);
+        }
+
+        self.send_pending_order(order, &request).await
+    }
+
+    /// Answer a submission whose client order id is already recorded
+    ///
+    /// Returns the recorded order, resolving it first if it's still pending.
+    async fn resume_order(&self, existing: Order, request: &OrderRequest) -> AppResult<Order> {
+        match existing.status {
+            OrderState::Pending => self.resolve_pending_order(existing, request, true).await,
+            // Not every rejection is the exchange's answer: a batch whose
+            // answer was lost or the stale pending sweep may have rejected an
+            // order that is live after all, so the exchange is asked first
+            OrderState::Rejected => {
+                self.resubmit_order(&existing).await?;
+                let existing = Order { status: OrderState::Pending, ..existing };
+                self.resolve_pending_order(existing, request, true).await
+            }
+            _ => {
+                log::info!(
+                    "Order {} already submitted as {}, not sending again",
+                    existing.client_order_id.as_deref().unwrap_or_default(),
+                    existing.id
+                );
+                Ok(existing)
+            }
+        }
+    }
+
+    /// Record a pending order and its slippage estimate
+    ///
+    /// Returns the recorded order instead when a concurrent submission with
+    /// the same client order id got there first; that submission sends it.
+    async fn record_pending_order(
+        &self,
+        order: &Order,
+        user_id: &str,
+        exchange_id: &str,
+        instance_id: Option<&str>,
+        parent_order_id: Option<&str>,
+        estimate: Option<&SlippageEstimate>,
+    ) -> AppResult<Option<Order>> {
+        if let Err(e) = self.save_order_to_db(order, user_id, exchange_id, instance_id, parent_order_id).await {
+            if !is_unique_violation(&e) {
+                return Err(e);
+            }
+            let client_order_id = order.client_order_id.as_deref().unwrap_or_default();
+            let existing = self.find_order_by_client_id(client_order_id, user_id).await?.ok_or(e)?;
+            log::info!("Order {} is being submitted concurrently as {}, not sending again", client_order_id, existing.id);
+            return Ok(Some(existing));
+        }
+        if let Some(estimate) = estimate {
+            self.save_slippage_estimate(&order.id, estimate).await?;
+        }
+        Ok(None)
+    }
+
+    /// Send a recorded pending order to the exchange
+    async fn send_pending_order(&self, order: Order, request: &OrderRequest) -> AppResult<Order> {
+        match self.exchange.place_order(request).await {
+            Ok(remote) => self.confirm_pending_order(&order, &remote, OrderEventSource::Exchange).await,
+            Err(e) if is_outcome_unknown(&e) => {
+                log::warn!("No answer placing order {}, looking it up: {}", order.id, e);
+                self.resolve_pending_order(order, request, false).await.map_err(|lookup| {
+                    AppError::Exchange(format!(
+                        "Order outcome unknown ({}); retry with the same client order id: {}",
+                        e, lookup
+                    ))
+                })
+            }
+            Err(e) => {
+                let reason = e.to_string();
+                self.update_order_status(&order.id, OrderState::Rejected, OrderEventSource::Exchange, Some(&reason))
+                    .await?;
+                Err(AppError::Exchange(reason))
+            }
+        }
+    }
+
+    /// Find out whether a pending order reached the exchange
+    ///
+    /// A found order is confirmed. With `resend`, one the exchange doesn't
+    /// know is sent again under the same client order id, which the exchange
+    /// refuses if the first attempt turns up after all; otherwise it stays
+    /// pending.
+    async fn resolve_pending_order(&self, order: Order, request: &OrderRequest, resend: bool) -> AppResult<Order> {
+        let client_order_id = order.client_order_id.clone().unwrap_or_default();
+        let remote = self
+            .exchange
+            .get_order_by_client_id(&order.symbol, &client_order_id)
+            .await
+            .map_err(|e| AppError::Exchange(e.to_string()))?;
+
+        match remote {
+            Some(remote) => {
+                log::info!("Pending order {} found on the exchange as {:?}", order.id, remote.exchange_order_id);
+                self.confirm_pending_order(&order, &remote, OrderEventSource::Exchange).await
+            }
+            None if resend => {
+                log::info!("Pending order {} not on the exchange, sending it", order.id);
+                Box::pin(self.send_pending_order(order, request)).await
+            }
+            None => Err(AppError::Exchange(format!("Order {} not found on the exchange yet", order.id))),
+        }
+    }
+
+    /// Record the exchange's view of a pending order
+    ///
+    /// The status moves through the order state machine. Fills that already
+    /// arrived over the user data stream (matched by client order id) are
+    /// kept; the exchange's quantities are only taken while the order is
+    /// still pending.
+    async fn confirm_pending_order(&self, order: &Order, remote: &Order, source: OrderEventSource) -> AppResult<Order> {
+        let now = Utc::now().timestamp();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("UPDATE orders SET exchange_order_id = ?, updated_at = ? WHERE id = ?")
+            .bind(&remote.exchange_order_id)
+            .bind(now)
+            .bind(&order.id)
+            .execute(&mut *tx)
+            .await?;
+
+        let exchange_order = format!("exchange order {}", remote.exchange_order_id.as_deref().unwrap_or("-"));
+        let (current, last_update_at) = Self::stored_state(&mut tx, &order.id).await?;
+        let event = match check_update(current, last_update_at, remote.status, remote.updated_at) {
+            UpdateCheck::Apply { changed } => {
+                if current == OrderState::Pending {
+                    sqlx::query(
+                        r#"
+                        UPDATE orders
+                        SET status = ?, filled_quantity = ?, avg_price = ?, filled_at = ?,
+                            exchange_updated_at = COALESCE(?, exchange_updated_at)
+                        WHERE id = ?
+                        "#
+                    )
+                    .bind(remote.status.to_string())
+                    .bind(remote.filled_quantity)
+                    .bind(remote.avg_price)
+                    .bind(remote.filled_at)
+                    .bind(remote.updated_at)
+                    .bind(&order.id)
+                    .execute(&mut *tx)
+                    .await?;
+                } else {
+                    sqlx::query(
+                        "UPDATE orders SET status = ?, exchange_updated_at = COALESCE(?, exchange_updated_at) WHERE id = ?"
+                    )
+                    .bind(remote.status.to_string())
+                    .bind(remote.updated_at)
+                    .bind(&order.id)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                changed.then(|| {
+                    OrderEvent::new(&order.id, OrderEventKind::StatusChanged, source).with_detail(exchange_order)
+                })
+            }
+            check => {
+                log::warn!("Order {}: ignoring placement answer {:?} -> {:?} ({:?})", order.id, current, remote.status, check);
+                Some(
+                    OrderEvent::new(&order.id, OrderEventKind::Ignored, source)
+                        .with_detail(format!("{:?}; {}", check, exchange_order)),
+                )
+            }
+        };
+        if let Some(event) = event {
+            let event = event
+                .with_status(Some(current), remote.status)
+                .with_filled(remote.filled_quantity)
+                .with_exchange_time(remote.updated_at);
+            Self::insert_order_event(&mut tx, &event).await?;
+        }
+
+        tx.commit().await?;
+
+        let row = sqlx::query("SELECT * FROM orders WHERE id = ?")
+            .bind(&order.id)
+            .fetch_one(&self.pool)
+            .await?;
+        let confirmed = self.row_to_order(row)?;
+
+        // Publish order event
+        self.publish_order_event(&confirmed).await;
+
+        Ok(confirmed)
+    }
+
+    /// A not yet sent order for a request
+    fn pending_order(request: &OrderRequest) -> Order {
+        Order {
+            id: Uuid::new_v4().to_string(),
+            exchange_order_id: None,
+            client_order_id: request.client_order_id.clone(),
+            symbol: request.symbol.clone(),
+            side: request.side,
+            order_type: request.order_type,
+            price: request.price,
+            quantity: request.quantity,
+            filled_quantity: 0.0,
+            avg_price: None,
+            status: OrderState::Pending,
+            commission: 0.0,
+            commission_asset: None,
+            created_at: Utc::now().timestamp_millis(),
+            filled_at: None,
+            updated_at: None,
+        }
+    }
+
+    /// Get a user's order by client order id
+    pub async fn find_order_by_client_id(&self, client_order_id: &str, user_id: &str) -> AppResult<Option<Order>> {
+        let row = sqlx::query("SELECT * FROM orders WHERE client_order_id = ? AND user_id = ?")
+            .bind(client_order_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|row| self.row_to_order(row)).transpose()
+    }
+
     /// Cancel an existing order
     pub async fn cancel_order(&self, order_id: &str, user_id: &str) -> AppResult<()> {
         // Get order from database
@@ -76,16 +649,410 @@ impl TradeService {
         }
 
         // Update order status in database
-        self.update_order_status(order_id, OrderState::Canceled).await?;
+        let source = if order.exchange_order_id.is_some() { OrderEventSource::Exchange } else { OrderEventSource::Local };
+        self.update_order_status(order_id, OrderState::Canceled, source, None).await?;
 
         Ok(())
     }
 
+    /// Place several orders in as few exchange requests as the venue allows
+    ///
+    /// Results are in request order; an order that fails validation or is
+    /// rejected by the exchange doesn't stop the others.
+    pub async fn place_orders(&self, requests: Vec<OrderRequest>, user_id: &str) -> AppResult<Vec<AppResult<Order>>> {
+        self.place_batch(requests, user_id, OrderOrigin::Manual).await
+    }
+
+    /// Place orders that close positions in an emergency
+    ///
+    /// Orders that only reduce an open position skip the pre-trade limits;
+    /// anything else is checked like a manual order.
+    pub async fn place_emergency_orders(
+        &self,
+        requests: Vec<OrderRequest>,
+        user_id: &str,
+    ) -> AppResult<Vec<AppResult<Order>>> {
+        self.place_batch(requests, user_id, OrderOrigin::Emergency).await
+    }
+
+    async fn place_batch(
+        &self,
+        requests: Vec<OrderRequest>,
+        user_id: &str,
+        origin: OrderOrigin,
+    ) -> AppResult<Vec<AppResult<Order>>> {
+        let exchange_id = self.account_id(user_id).await?.ok_or_else(|| {
+            AppError::validation(format!("No {} account configured for user", self.exchange.name()))
+        })?;
+
+        let mut results: Vec<AppResult<Order>> = Vec::with_capacity(requests.len());
+        let mut pending = Vec::new();
+        for mut request in requests {
+            if let Err(e) = self.validate_order_request(&mut request, user_id, &origin).await {
+                results.push(Err(e));
+                continue;
+            }
+            let client_order_id = request
+                .client_order_id
+                .get_or_insert_with(|| OrderRequest::new_client_order_id("ord"))
+                .clone();
+            match self.find_order_by_client_id(&client_order_id, user_id).await {
+                Ok(Some(existing)) => {
+                    results.push(self.resume_order(existing, &request).await);
+                    continue;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    results.push(Err(e));
+                    continue;
+                }
+            }
+            if let Err(e) = self.pre_trade_check(&request, user_id, &origin).await {
+                results.push(Err(e));
+                continue;
+            }
+            let estimate = match self.slippage_check(&mut request, user_id, &origin).await {
+                Ok(estimate) => estimate,
+                Err(e) => {
+                    results.push(Err(e));
+                    continue;
+                }
+            };
+
+            // Record each intent before the batch goes out
+            let order = Self::pending_order(&request);
+            match self.record_pending_order(&order, user_id, &exchange_id, None, None, estimate.as_ref()).await {
+                Ok(None) => {}
+                Ok(Some(existing)) => {
+                    results.push(Ok(existing));
+                    continue;
+                }
+                Err(e) => {
+                    results.push(Err(e));
+                    continue;
+                }
+            }
+            pending.push((results.len(), order, request));
+            results.push(Err(AppError::Exchange("Order not sent".to_string())));
+        }
+        if pending.is_empty() {
+            return Ok(results);
+        }
+
+        let batch: Vec<OrderRequest> = pending.iter().map(|(_, _, request)| request.clone()).collect();
+        let placed = match self.exchange.place_orders(&batch).await {
+            Ok(placed) => placed,
+            Err(e) => {
+                let failure = BatchFailure::new("Batch order failed", e);
+                batch.iter().map(|_| Err(failure.error())).collect()
+            }
+        };
+
+        for ((index, order, _), placed) in pending.into_iter().zip(placed) {
+            results[index] = match placed {
+                Ok(remote) => self.confirm_pending_order(&order, &remote, OrderEventSource::Exchange).await,
+                // Left pending; reconciliation looks it up by client order id
+                Err(e) if is_outcome_unknown(&e) => Err(AppError::Exchange(format!("Order outcome unknown: {}", e))),
+                Err(e) => {
+                    let reason = e.to_string();
+                    self.update_order_status(&order.id, OrderState::Rejected, OrderEventSource::Exchange, Some(&reason))
+                        .await
+                        .and_then(|_| Err(AppError::Exchange(reason)))
+                }
+            };
+        }
+
+        Ok(results)
+    }
+
+    /// Cancel several orders in as few exchange requests as the venue allows
+    ///
+    /// Results are in `order_ids` order.
+    pub async fn cancel_orders(&self, order_ids: &[String], user_id: &str) -> AppResult<Vec<AppResult<()>>> {
+        let mut results: Vec<AppResult<()>> = Vec::with_capacity(order_ids.len());
+        let mut remote = Vec::new();
+        for order_id in order_ids {
+            let order = match self.get_order_from_db(order_id, user_id).await {
+                Ok(order) => order,
+                Err(e) => {
+                    results.push(Err(e));
+                    continue;
+                }
+            };
+            if !OrderStateMachine::from_state(order.status).can_transition_to(&OrderState::Canceled) {
+                results.push(Err(AppError::validation(format!(
+                    "Order cannot be canceled: current state is {:?}",
+                    order.status
+                ))));
+                continue;
+            }
+
+            if order.exchange_order_id.is_some() {
+                remote.push((results.len(), order));
+                results.push(Ok(()));
+            } else {
+                // Never reached the exchange
+                self.update_order_status(&order.id, OrderState::Canceled, OrderEventSource::Local, None).await?;
+                results.push(Ok(()));
+            }
+        }
+
+        if !remote.is_empty() {
+            let orders: Vec<Order> = remote.iter().map(|(_, order)| order.clone()).collect();
+            let canceled = self.exchange.cancel_orders(&orders).await?;
+            for ((index, order), canceled) in remote.into_iter().zip(canceled) {
+                results[index] = match canceled {
+                    Ok(()) => self
+                        .update_order_status(&order.id, OrderState::Canceled, OrderEventSource::Exchange, None)
+                        .await
+                        .map(|_| ()),
+                    Err(e) => Err(AppError::Exchange(e.to_string())),
+                };
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Cancel all of a user's active orders, optionally on one symbol only
+    ///
+    /// Uses the venue's cancel-all per symbol and falls back to a batch
+    /// cancel of the recorded orders when that fails. Parent orders of
+    /// execution algos are left to their engine. Returns how many orders
+    /// were canceled.
+    pub async fn cancel_all_orders(&self, user_id: &str, symbol: Option<&str>) -> AppResult<usize> {
+        let mut by_symbol: HashMap<String, Vec<Order>> = HashMap::new();
+        for status in [OrderState::Open, OrderState::PartiallyFilled] {
+            for order in self.get_orders(user_id, symbol, Some(status), 1000).await? {
+                if order.exchange_order_id.is_some() {
+                    by_symbol.entry(order.symbol.clone()).or_default().push(order);
+                }
+            }
+        }
+
+        let mut canceled = 0;
+        for (symbol, orders) in by_symbol {
+            match self.exchange.cancel_all_orders(&symbol).await {
+                Ok(count) => {
+                    log::info!("Canceled {} {} orders on {}", count, symbol, self.exchange.name());
+                    for order in &orders {
+                        self.update_order_status(&order.id, OrderState::Canceled, OrderEventSource::Exchange, Some("cancel-all"))
+                            .await?;
+                    }
+                    canceled += orders.len();
+                }
+                Err(e) => {
+                    log::warn!("Cancel-all failed for {}, falling back to batch cancel: {}", symbol, e);
+                    let ids: Vec<String> = orders.into_iter().map(|order| order.id).collect();
+                    let results = self.cancel_orders(&ids, user_id).await?;
+                    canceled += results.iter().filter(|r| r.is_ok()).count();
+                }
+            }
+        }
+
+        Ok(canceled)
+    }
+
+    /// Change the price and/or quantity of a resting order
+    ///
+    /// Amends in place where the exchange supports it, keeping the order's
+    /// queue position; otherwise the exchange replaces it and the new
+    /// exchange order id is recorded.
+    pub async fn amend_order(&self, order_id: &str, user_id: &str, amend: AmendRequest) -> AppResult<Order> {
+        let order = self.get_order_from_db(order_id, user_id).await?;
+
+        OrderStateMachine::from_state(order.status)
+            .amend()
+            .map_err(|e| AppError::validation(e.to_string()))?;
+        amend.validate(&order).map_err(|e| AppError::validation(e.to_string()))?;
+
+        // Round the new price and quantity like a fresh order
+        let mut request = OrderRequest {
+            symbol: order.symbol.clone(),
+            side: order.side,
+            order_type: order.order_type,
+            price: amend.price.or(order.price),
+            stop_price: None,
+            quantity: amend.quantity.unwrap_or(order.quantity),
+            client_order_id: None,
+            time_in_force: None,
+        };
+        self.validate_order_request(&mut request, user_id, &OrderOrigin::Manual).await?;
+        if request.quantity > order.quantity {
+            self.check_amend_increase(&order, &request, user_id).await?;
+        }
+        let amend = AmendRequest {
+            price: amend.price.and(request.price),
+            quantity: amend.quantity.map(|_| request.quantity),
+        };
+
+        let remote = match self.exchange.amend_order(&order, &amend).await
+            .map_err(|e| AppError::Exchange(e.to_string()))?
+        {
+            AmendOutcome::Amended(remote) => remote,
+            AmendOutcome::Replaced(replacement) => {
+                return self.record_replacement(&order, replacement, user_id).await;
+            }
+            AmendOutcome::ReplaceFailed(reason) => {
+                self.update_order_status(
+                    &order.id,
+                    OrderState::Canceled,
+                    OrderEventSource::Exchange,
+                    Some(&format!("canceled for an amendment, replacement refused: {}", reason)),
+                ).await?;
+                if let Ok(canceled) = self.get_order_from_db(&order.id, user_id).await {
+                    self.publish_order_event(&canceled).await;
+                }
+                return Err(AppError::Exchange(format!(
+                    "Order {} canceled but its replacement was refused: {}",
+                    order.id, reason
+                )));
+            }
+        };
+
+        let amended = Order {
+            exchange_order_id: remote.exchange_order_id.or(order.exchange_order_id.clone()),
+            ..amend.apply(&order)
+        };
+        sqlx::query(
+            "UPDATE orders SET price = ?, quantity = ?, exchange_order_id = ?, updated_at = ? WHERE id = ?"
+        )
+        .bind(amended.price)
+        .bind(amended.quantity)
+        .bind(&amended.exchange_order_id)
+        .bind(Utc::now().timestamp())
+        .bind(&amended.id)
+        .execute(&self.pool)
+        .await?;
+
+        let event = OrderEvent::new(&amended.id, OrderEventKind::Amended, OrderEventSource::Exchange)
+            .with_filled(amended.filled_quantity)
+            .with_detail(format!(
+                "price {:?} -> {:?}, quantity {} -> {}",
+                order.price, amended.price, order.quantity, amended.quantity
+            ));
+        self.record_order_event(&event).await?;
+
+        log::info!(
+            "Order {} amended: price {:?} -> {:?}, quantity {} -> {}",
+            order.id, order.price, amended.price, order.quantity, amended.quantity
+        );
+        self.publish_order_event(&amended).await;
+        Ok(amended)
+    }
+
+    /// Record a cancel-replace amendment
+    ///
+    /// The original order keeps its fills and is marked canceled; the
+    /// replacement, covering only the unfilled rest, becomes a new order of
+    /// the same account, strategy instance and parent that points back at it.
+    async fn record_replacement(&self, order: &Order, replacement: Order, user_id: &str) -> AppResult<Order> {
+        let (exchange_id, instance_id, parent_order_id): (String, Option<String>, Option<String>) = sqlx::query_as(
+            "SELECT exchange_id, strategy_instance_id, parent_order_id FROM orders WHERE id = ?"
+        )
+        .bind(&order.id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let replacement = Order {
+            id: Uuid::new_v4().to_string(),
+            ..replacement
+        };
+        self.update_order_status(
+            &order.id,
+            OrderState::Canceled,
+            OrderEventSource::Exchange,
+            Some(&format!("replaced by {}", replacement.id)),
+        ).await?;
+        self.save_order_to_db(&replacement, user_id, &exchange_id, instance_id.as_deref(), parent_order_id.as_deref())
+            .await?;
+        sqlx::query("UPDATE orders SET replaces_order_id = ? WHERE id = ?")
+            .bind(&order.id)
+            .bind(&replacement.id)
+            .execute(&self.pool)
+            .await?;
+
+        let event = OrderEvent::new(&replacement.id, OrderEventKind::Amended, OrderEventSource::Exchange)
+            .with_detail(format!(
+                "replaces {}: price {:?} -> {:?}, remaining quantity {}",
+                order.id, order.price, replacement.price, replacement.quantity
+            ));
+        self.record_order_event(&event).await?;
+
+        log::info!(
+            "Order {} replaced by {}: price {:?} -> {:?}, remaining quantity {}",
+            order.id, replacement.id, order.price, replacement.price, replacement.quantity
+        );
+        if let Ok(canceled) = self.get_order_from_db(&order.id, user_id).await {
+            self.publish_order_event(&canceled).await;
+        }
+        self.publish_order_event(&replacement).await;
+        Ok(replacement)
+    }
+
+    /// Refuse an amendment that grows an order past the pre-trade checks
+    ///
+    /// While trading is halted an order may only be repriced or shrunk. The
+    /// order already counts towards today's orders, so the daily count
+    /// limit doesn't apply to it again.
+    async fn check_amend_increase(&self, order: &Order, request: &OrderRequest, user_id: &str) -> AppResult<()> {
+        if let Some(halt) = TradingHaltRepository::new(self.pool.clone()).find().await? {
+            return Err(AppError::risk_limit(format!(
+                "Trading halted ({}): {}; orders can only be repriced or reduced",
+                halt.level, halt.reason
+            )));
+        }
+
+        let instance_id: Option<String> = sqlx::query_scalar("SELECT strategy_instance_id FROM orders WHERE id = ?")
+            .bind(&order.id)
+            .fetch_one(&self.pool)
+            .await?;
+        let origin = OrderOrigin::for_instance(instance_id.as_deref());
+        let reasons: Vec<String> = self
+            .check_order(request, user_id, &origin)
+            .await?
+            .into_iter()
+            .filter(|r| r.code != RejectCode::DailyOrders)
+            .map(|r| r.to_string())
+            .collect();
+        if reasons.is_empty() {
+            return Ok(());
+        }
+
+        log::warn!(
+            "Pre-trade checks refused amending order {} to {} {}: {}",
+            order.id,
+            request.quantity,
+            request.symbol,
+            reasons.join("; ")
+        );
+        Err(AppError::risk_limit(reasons.join("; ")))
+    }
+
     /// Get order by ID
     pub async fn get_order(&self, order_id: &str, user_id: &str) -> AppResult<Order> {
         self.get_order_from_db(order_id, user_id).await
     }
 
+    /// Everything recorded about an order: its history and its fills
+    pub async fn get_order_timeline(&self, order_id: &str, user_id: &str) -> AppResult<OrderTimeline> {
+        let order = self.get_order_from_db(order_id, user_id).await?;
+
+        let rows = sqlx::query("SELECT * FROM order_events WHERE order_id = ? ORDER BY id")
+            .bind(order_id)
+            .fetch_all(&self.pool)
+            .await?;
+        let events = rows.into_iter().map(Self::row_to_order_event).collect::<AppResult<Vec<_>>>()?;
+
+        let rows = sqlx::query("SELECT * FROM trades WHERE order_id = ? ORDER BY timestamp, created_at")
+            .bind(order_id)
+            .fetch_all(&self.pool)
+            .await?;
+        let fills = rows.into_iter().map(Self::row_to_trade_record).collect::<AppResult<Vec<_>>>()?;
+
+        Ok(OrderTimeline { order, events, fills })
+    }
+
     /// Get all orders for a user
     pub async fn get_orders(
         &self,
@@ -98,40 +1065,48 @@ impl TradeService {
         let rows = match (symbol, status) {
             (None, None) => {
                 sqlx::query(
-                    "SELECT * FROM orders WHERE user_id = ? ORDER BY created_at DESC LIMIT ?"
+                    "SELECT * FROM orders WHERE user_id = ? AND (? IS NULL OR exchange_id = ?) ORDER BY created_at DESC LIMIT ?"
                 )
                 .bind(user_id)
+                .bind(&self.exchange_id)
+                .bind(&self.exchange_id)
                 .bind(limit as i64)
                 .fetch_all(&self.pool)
                 .await?
             }
             (Some(sym), None) => {
                 sqlx::query(
-                    "SELECT * FROM orders WHERE user_id = ? AND symbol = ? ORDER BY created_at DESC LIMIT ?"
+                    "SELECT * FROM orders WHERE user_id = ? AND symbol = ? AND (? IS NULL OR exchange_id = ?) ORDER BY created_at DESC LIMIT ?"
                 )
                 .bind(user_id)
                 .bind(sym)
+                .bind(&self.exchange_id)
+                .bind(&self.exchange_id)
                 .bind(limit as i64)
                 .fetch_all(&self.pool)
                 .await?
             }
             (None, Some(st)) => {
                 sqlx::query(
-                    "SELECT * FROM orders WHERE user_id = ? AND status = ? ORDER BY created_at DESC LIMIT ?"
+                    "SELECT * FROM orders WHERE user_id = ? AND status = ? AND (? IS NULL OR exchange_id = ?) ORDER BY created_at DESC LIMIT ?"
                 )
                 .bind(user_id)
                 .bind(st.to_string())
+                .bind(&self.exchange_id)
+                .bind(&self.exchange_id)
                 .bind(limit as i64)
                 .fetch_all(&self.pool)
                 .await?
             }
             (Some(sym), Some(st)) => {
                 sqlx::query(
-                    "SELECT * FROM orders WHERE user_id = ? AND symbol = ? AND status = ? ORDER BY created_at DESC LIMIT ?"
+                    "SELECT * FROM orders WHERE user_id = ? AND symbol = ? AND status = ? AND (? IS NULL OR exchange_id = ?) ORDER BY created_at DESC LIMIT ?"
                 )
                 .bind(user_id)
                 .bind(sym)
                 .bind(st.to_string())
+                .bind(&self.exchange_id)
+                .bind(&self.exchange_id)
                 .bind(limit as i64)
                 .fetch_all(&self.pool)
                 .await?
@@ -151,87 +1126,1205 @@ impl TradeService {
         self.get_orders(user_id, None, Some(OrderState::Open), 1000).await
     }
 
-    /// Sync order status from exchange
-    pub async fn sync_order_status(&self, order_id: &str, user_id: &str) -> AppResult<Order> {
-        let mut order = self.get_order_from_db(order_id, user_id).await?;
+    /// Open orders placed by a strategy instance
+    pub async fn get_instance_open_orders(&self, user_id: &str, instance_id: &str) -> AppResult<Vec<Order>> {
+        let rows = sqlx::query(
+            "SELECT * FROM orders WHERE user_id = ? AND strategy_instance_id = ? \
+             AND status IN ('open', 'partially_filled') AND (? IS NULL OR exchange_id = ?) \
+             ORDER BY created_at DESC"
+        )
+        .bind(user_id)
+        .bind(instance_id)
+        .bind(&self.exchange_id)
+        .bind(&self.exchange_id)
+        .fetch_all(&self.pool)
+        .await?;
 
-        if let Some(exchange_order_id) = &order.exchange_order_id {
-            let updated_order = self.exchange.get_order(exchange_order_id).await?;
+        rows.into_iter().map(|row| self.row_to_order(row)).collect()
+    }
 
-            // Update status if changed
-            if updated_order.status != order.status {
-                order.status = updated_order.status;
-                order.filled_quantity = updated_order.filled_quantity;
-                order.avg_price = updated_order.avg_price;
-                order.filled_at = updated_order.filled_at;
+    /// Sync order status from exchange
+    pub async fn sync_order_status(&self, order_id: &str, user_id: &str) -> AppResult<Order> {
+        let order = self.get_order_from_db(order_id, user_id).await?;
 
-                self.update_order_in_db(&order).await?;
+        let Some(exchange_order_id) = &order.exchange_order_id else {
+            return Ok(order);
+        };
+        let remote = self.exchange.get_order(&order.symbol, exchange_order_id).await?;
 
-                // Update positions if filled
-                if matches!(order.status, OrderState::Filled | OrderState::PartiallyFilled) {
-                    self.update_positions_from_order(&order).await;
-                }
+        // Fills missed by the user data stream are recorded at their implied price
+        match diff_order(&order, &remote) {
+            Some(correction) => {
+                let exchange_id: String = sqlx::query_scalar("SELECT exchange_id FROM orders WHERE id = ?")
+                    .bind(&order.id)
+                    .fetch_one(&self.pool)
+                    .await?;
+                self.apply_correction(user_id, &exchange_id, &correction).await?;
+                self.get_order_from_db(order_id, user_id).await
             }
+            None => Ok(order),
         }
+    }
 
-        Ok(order)
+    /// Record an execution reported by the exchange
+    ///
+    /// Writes the fill to the `trades` table with its realised PnL and rolls
+    /// it into the owning order. Returns `Ok(None)` if the fill was already
+    /// recorded, its order isn't known locally (reconciliation will import
+    /// it), or reconciliation already accounted for the quantity.
+    pub async fn record_fill(&self, fill: &Fill) -> AppResult<Option<TradeRecord>> {
+        let mut tx = self.pool.begin().await?;
+
+        if let Some(trade_id) = &fill.exchange_trade_id {
+            let duplicate = sqlx::query(
+                "SELECT 1 FROM trades WHERE exchange_trade_id = ? AND (? IS NULL OR exchange_id = ?)"
+            )
+            .bind(trade_id)
+            .bind(&self.exchange_id)
+            .bind(&self.exchange_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .is_some();
+            if duplicate {
+                return Ok(None);
+            }
+        }
+
+        let row = sqlx::query(
+            "SELECT * FROM orders WHERE (exchange_order_id = ? \
+             OR (client_order_id IS NOT NULL AND client_order_id = ?)) \
+             AND (? IS NULL OR exchange_id = ?) LIMIT 1"
+        )
+        .bind(&fill.exchange_order_id)
+        .bind(&fill.client_order_id)
+        .bind(&self.exchange_id)
+        .bind(&self.exchange_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(row) = row else {
+            log::debug!("Fill for unknown order {}, leaving it to reconciliation", fill.exchange_order_id);
+            return Ok(None);
+        };
+        let user_id: String = row.try_get("user_id")?;
+        let exchange_id: String = row.try_get("exchange_id")?;
+        let instance_id: Option<String> = row.try_get("strategy_instance_id")?;
+        let arrival_price: Option<f64> = row.try_get("arrival_price")?;
+        let expected_slippage: Option<f64> = row.try_get("expected_slippage_bps")?;
+        let mut order = self.row_to_order(row)?;
+
+        let recorded = sqlx::query(
+            "SELECT COALESCE(SUM(quantity), 0) AS quantity, COALESCE(SUM(quantity * price), 0) AS notional \
+             FROM trades WHERE order_id = ?"
+        )
+        .bind(&order.id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let recorded_qty: f64 = recorded.try_get("quantity")?;
+        let recorded_notional: f64 = recorded.try_get("notional")?;
+
+        if recorded_qty + fill.quantity > order.quantity + FILL_QTY_TOLERANCE {
+            log::debug!("Fill {:?} already accounted for on order {}", fill.exchange_trade_id, order.id);
+            return Ok(None);
+        }
+
+        let record = Self::insert_fill(&mut tx, &user_id, &exchange_id, &order.id, fill).await?;
+
+        let filled = recorded_qty + fill.quantity;
+        order.filled_quantity = order.filled_quantity.max(filled);
+        order.avg_price = Some((recorded_notional + fill.quantity * fill.price) / filled);
+        order.commission += fill.commission;
+        order.commission_asset = fill.commission_asset.clone().or(order.commission_asset);
+        order.filled_at = Some(record.timestamp);
+        let previous_status = order.status;
+        let target = if order.filled_quantity + FILL_QTY_TOLERANCE >= order.quantity {
+            OrderState::Filled
+        } else {
+            OrderState::PartiallyFilled
+        };
+        // A fill landing after a cancel keeps the order canceled
+        let mut sm = OrderStateMachine::from_state(order.status);
+        if sm.advance_to(target).is_ok() {
+            order.status = sm.state();
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE orders SET
+                filled_quantity = ?,
+                avg_price = ?,
+                status = ?,
+                commission = ?,
+                commission_asset = ?,
+                filled_at = ?,
+                updated_at = ?
+            WHERE id = ?
+            "#
+        )
+        .bind(order.filled_quantity)
+        .bind(order.avg_price)
+        .bind(order.status.to_string())
+        .bind(order.commission)
+        .bind(&order.commission_asset)
+        .bind(order.filled_at)
+        .bind(Utc::now().timestamp())
+        .bind(&order.id)
+        .execute(&mut *tx)
+        .await?;
+
+        let mut detail = format!(
+            "{} @ {} (trade {})",
+            fill.quantity, fill.price, fill.exchange_trade_id.as_deref().unwrap_or("-")
+        );
+        if let Some(slippage) = Self::realised_slippage(&order, arrival_price, expected_slippage, fill.price) {
+            detail.push_str(&format!(", {}", slippage));
+        }
+        let event = OrderEvent::new(&order.id, OrderEventKind::Fill, OrderEventSource::Stream)
+            .with_status(Some(previous_status), order.status)
+            .with_filled(order.filled_quantity)
+            .with_exchange_time(Some(record.timestamp))
+            .with_detail(detail);
+        Self::insert_order_event(&mut tx, &event).await?;
+
+        tx.commit().await?;
+
+        self.apply_fill_to_positions(&user_id, &exchange_id, instance_id.as_deref(), fill).await;
+        if let Some(bus) = &self.event_bus {
+            bus.publish_order_filled(order);
+        }
+
+        Ok(Some(record))
+    }
+
+    /// Record fills from the exchange's user data stream in the background
+    pub fn start_fill_recording(self: &Arc<Self>) -> JoinHandle<()> {
+        let service = self.clone();
+        let mut fills = self.exchange.fill_stream();
+
+        tokio::spawn(async move {
+            loop {
+                match fills.recv().await {
+                    Ok(fill) => {
+                        if let Err(e) = service.record_fill(&fill).await {
+                            log::error!("Failed to record fill {:?}: {}", fill.exchange_trade_id, e);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("Fill recorder lagged, {} fills left to reconciliation", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            log::info!("Fill recording stopped");
+        })
+    }
+
+    /// Apply order updates from the exchange's user data stream in the background
+    pub fn start_order_updates(self: &Arc<Self>) -> JoinHandle<()> {
+        let service = self.clone();
+        let mut orders = self.exchange.order_stream();
+
+        tokio::spawn(async move {
+            loop {
+                match orders.recv().await {
+                    Ok(order) => {
+                        if let Err(e) = service.apply_order_update(&order).await {
+                            log::error!("Failed to apply order update {:?}: {}", order.exchange_order_id, e);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("Order updates lagged, {} updates left to reconciliation", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            log::info!("Order updates stopped");
+        })
+    }
+
+    /// Get recorded fills for a user, newest first
+    pub async fn get_trades(
+        &self,
+        user_id: &str,
+        symbol: Option<&str>,
+        limit: usize,
+    ) -> AppResult<Vec<TradeRecord>> {
+        let rows = match symbol {
+            Some(sym) => {
+                sqlx::query(
+                    "SELECT * FROM trades WHERE user_id = ? AND symbol = ? AND (? IS NULL OR exchange_id = ?) ORDER BY timestamp DESC LIMIT ?"
+                )
+                .bind(user_id)
+                .bind(sym)
+                .bind(&self.exchange_id)
+                .bind(&self.exchange_id)
+                .bind(limit as i64)
+                .fetch_all(&self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "SELECT * FROM trades WHERE user_id = ? AND (? IS NULL OR exchange_id = ?) ORDER BY timestamp DESC LIMIT ?"
+                )
+                .bind(user_id)
+                .bind(&self.exchange_id)
+                .bind(&self.exchange_id)
+                .bind(limit as i64)
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+
+        rows.into_iter().map(Self::row_to_trade_record).collect()
+    }
+
+    /// Reconcile a user's orders with the exchange
+    ///
+    /// Compares every active local order with the exchange, applies missed
+    /// fills and external cancels, records missed fills in the `trades`
+    /// table, and imports open orders placed outside the app (e.g. from the
+    /// exchange UI). The exchange is treated as the source of truth.
+    pub async fn reconcile_orders(&self, user_id: &str) -> AppResult<ReconcileReport> {
+        let mut report = ReconcileReport {
+            started_at: Utc::now().timestamp_millis(),
+            ..Default::default()
+        };
+
+        let remote_open: HashMap<String, Order> = self.exchange
+            .get_open_orders(None)
+            .await
+            .map_err(|e| AppError::Exchange(e.to_string()))?
+            .into_iter()
+            .filter_map(|o| match &o.exchange_order_id {
+                Some(id) if !id.is_empty() => Some((id.clone(), o)),
+                _ => None,
+            })
+            .collect();
+
+        self.resolve_stale_pending_orders(user_id, &mut report).await?;
+
+        let rows = sqlx::query(
+            "SELECT * FROM orders WHERE user_id = ? AND exchange_order_id IS NOT NULL \
+             AND status IN ('pending', 'open', 'partially_filled') \
+             AND (? IS NULL OR exchange_id = ?)"
+        )
+        .bind(user_id)
+        .bind(&self.exchange_id)
+        .bind(&self.exchange_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for row in rows {
+            let exchange_id: String = row.try_get("exchange_id")?;
+            let local = self.row_to_order(row)?;
+            let Some(exchange_order_id) = local.exchange_order_id.clone() else {
+                continue;
+            };
+            report.checked += 1;
+
+            // Orders missing from the open list have closed; fetch the final state
+            let remote = match remote_open.get(&exchange_order_id) {
+                Some(order) => order.clone(),
+                None => match self.exchange.get_order(&local.symbol, &exchange_order_id).await {
+                    Ok(order) => order,
+                    Err(e) => {
+                        log::warn!("Reconcile: failed to fetch order {}: {}", exchange_order_id, e);
+                        report.errors += 1;
+                        continue;
+                    }
+                },
+            };
+
+            let Some(correction) = diff_order(&local, &remote) else {
+                continue;
+            };
+
+            match self.apply_correction(user_id, &exchange_id, &correction).await {
+                Ok(false) => {}
+                Ok(true) => {
+                    report.updated += 1;
+                    if correction.fill.is_some() {
+                        report.fills_recorded += 1;
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Reconcile: failed to update order {}: {}", local.id, e);
+                    report.errors += 1;
+                }
+            }
+        }
+
+        for (exchange_order_id, remote) in &remote_open {
+            let known = sqlx::query(
+                "SELECT 1 FROM orders WHERE (exchange_order_id = ? \
+                 OR (client_order_id IS NOT NULL AND client_order_id = ?)) \
+                 AND user_id = ? AND (? IS NULL OR exchange_id = ?)"
+            )
+            .bind(exchange_order_id)
+            .bind(&remote.client_order_id)
+            .bind(user_id)
+            .bind(&self.exchange_id)
+            .bind(&self.exchange_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .is_some();
+            if known {
+                continue;
+            }
+
+            match self.import_exchange_order(user_id, remote).await {
+                Ok(true) => report.imported += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    log::warn!("Reconcile: failed to import order {}: {}", exchange_order_id, e);
+                    report.errors += 1;
+                }
+            }
+        }
+
+        report.finished_at = Utc::now().timestamp_millis();
+        if report.updated > 0 || report.imported > 0 || report.errors > 0 {
+            log::info!(
+                "Reconciled orders for {}: {} checked, {} updated, {} fills, {} imported, {} errors",
+                user_id, report.checked, report.updated, report.fills_recorded, report.imported, report.errors
+            );
+        }
+
+        Ok(report)
+    }
+
+    /// Settle orders whose placement answer was lost
+    ///
+    /// Orders still pending after `PENDING_ORDER_GRACE_MS` are looked up by
+    /// client order id: found ones are confirmed, the rest never reached the
+    /// exchange and are marked rejected.
+    async fn resolve_stale_pending_orders(&self, user_id: &str, report: &mut ReconcileReport) -> AppResult<()> {
+        let rows = sqlx::query(
+            "SELECT * FROM orders WHERE user_id = ? AND status = 'pending' AND exchange_order_id IS NULL \
+             AND client_order_id IS NOT NULL AND algo IS NULL AND created_at < ? \
+             AND (? IS NULL OR exchange_id = ?)"
+        )
+        .bind(user_id)
+        .bind(Utc::now().timestamp_millis() - PENDING_ORDER_GRACE_MS)
+        .bind(&self.exchange_id)
+        .bind(&self.exchange_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for row in rows {
+            let order = self.row_to_order(row)?;
+            let client_order_id = order.client_order_id.clone().unwrap_or_default();
+            report.checked += 1;
+
+            let result = match self.exchange.get_order_by_client_id(&order.symbol, &client_order_id).await {
+                Ok(Some(remote)) => self
+                    .confirm_pending_order(&order, &remote, OrderEventSource::Reconcile)
+                    .await
+                    .map(|_| ()),
+                Ok(None) => {
+                    log::info!("Reconcile: pending order {} never reached the exchange", order.id);
+                    self.update_order_status(
+                        &order.id,
+                        OrderState::Rejected,
+                        OrderEventSource::Reconcile,
+                        Some("not found on the exchange"),
+                    )
+                    .await
+                    .map(|_| ())
+                }
+                Err(e) => Err(AppError::Exchange(e.to_string())),
+            };
+            match result {
+                Ok(()) => report.updated += 1,
+                Err(e) => {
+                    log::warn!("Reconcile: failed to resolve pending order {}: {}", order.id, e);
+                    report.errors += 1;
+                }
+            }
+        }
+
+        Ok(())
     }
 
     /// Get all positions
     pub async fn get_positions(&self, user_id: &str) -> AppResult<Vec<Position>> {
         let rows = sqlx::query(
-            "SELECT * FROM positions WHERE user_id = ? AND quantity > 0 ORDER BY opened_at DESC"
+            "SELECT * FROM positions WHERE user_id = ? AND quantity > 0 \
+             AND (? IS NULL OR exchange_id = ?) ORDER BY opened_at DESC"
+        )
+        .bind(user_id)
+        .bind(&self.exchange_id)
+        .bind(&self.exchange_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut positions = Vec::new();
+        for row in rows {
+            positions.push(self.row_to_position(row)?);
+        }
+
+        Ok(positions)
+    }
+
+    /// Open positions built only by one strategy instance's orders
+    pub async fn get_instance_positions(&self, user_id: &str, instance_id: &str) -> AppResult<Vec<Position>> {
+        let rows = sqlx::query(
+            "SELECT * FROM positions WHERE user_id = ? AND strategy_instance_id = ? AND quantity > 0 \
+             AND (? IS NULL OR exchange_id = ?) ORDER BY opened_at DESC"
         )
         .bind(user_id)
+        .bind(instance_id)
+        .bind(&self.exchange_id)
+        .bind(&self.exchange_id)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut positions = Vec::new();
-        for row in rows {
-            positions.push(self.row_to_position(row)?);
+        rows.into_iter().map(|row| self.row_to_position(row)).collect()
+    }
+
+    /// Get account balance
+    pub async fn get_balance(&self) -> AppResult<Vec<Balance>> {
+        self.exchange.get_balance().await
+            .map_err(|e| AppError::Exchange(e.to_string()))
+    }
+
+    /// Run the pre-trade risk checks for an order without sending it
+    ///
+    /// An empty result means the order would be accepted.
+    pub async fn check_order(
+        &self,
+        request: &OrderRequest,
+        user_id: &str,
+        origin: &OrderOrigin,
+    ) -> AppResult<Vec<PreTradeRejection>> {
+        if let Some(rejection) = self.halt_rejection(request, user_id).await? {
+            return Ok(vec![rejection]);
+        }
+
+        let limits = self.pre_trade_limits().await?;
+        if limits == PreTradeLimits::default() {
+            return Ok(Vec::new());
+        }
+
+        let positions = self.get_positions(user_id).await?;
+        if origin.reduce_only_exempt()
+            && is_reduce_only(request.side, request.quantity, &request.symbol, &positions)
+        {
+            return Ok(Vec::new());
+        }
+
+        let (user_orders_today, instance_orders_today) = self.orders_today(user_id, origin).await?;
+        let price = match request.price {
+            Some(price) => Some(price),
+            None if limits.has_value_limits() => self.reference_price(&request.symbol, &positions).await,
+            None => None,
+        };
+        let balance = if limits.needs_balance() {
+            self.quote_balance(&request.symbol).await
+        } else {
+            None
+        };
+
+        let order = PreTradeOrder {
+            symbol: request.symbol.clone(),
+            side: request.side,
+            quantity: request.quantity,
+            price,
+            origin: origin.clone(),
+        };
+        let snapshot = PreTradeSnapshot {
+            user_orders_today,
+            instance_orders_today,
+            positions,
+            balance,
+        };
+        Ok(limits.evaluate(&order, &snapshot))
+    }
+
+    // ========== Private helper methods ==========
+
+    /// Refuse an order that fails the pre-trade risk checks
+    async fn pre_trade_check(&self, request: &OrderRequest, user_id: &str, origin: &OrderOrigin) -> AppResult<()> {
+        let rejections = self.check_order(request, user_id, origin).await?;
+        if rejections.is_empty() {
+            return Ok(());
+        }
+
+        let reasons: Vec<String> = rejections.iter().map(|r| r.to_string()).collect();
+        log::warn!(
+            "Pre-trade checks refused {} {} {} ({:?}): {}",
+            request.side,
+            request.quantity,
+            request.symbol,
+            origin,
+            reasons.join("; ")
+        );
+        Err(AppError::risk_limit(reasons.join("; ")))
+    }
+
+    /// Why the kill switch refuses an order; `None` when trading isn't
+    /// halted or the order only reduces a position
+    async fn halt_rejection(&self, request: &OrderRequest, user_id: &str) -> AppResult<Option<PreTradeRejection>> {
+        let Some(halt) = TradingHaltRepository::new(self.pool.clone()).find().await? else {
+            return Ok(None);
+        };
+        let positions = self.get_positions(user_id).await?;
+        Ok(halt.rejection(request.side, request.quantity, &request.symbol, &positions))
+    }
+
+    /// Limits of the `pre_trade_limits` rule; none when it's missing or disabled
+    async fn pre_trade_limits(&self) -> AppResult<PreTradeLimits> {
+        let rule = RiskRuleRepository::new(self.pool.clone())
+            .find_by_name(PRE_TRADE_RULE)
+            .await?;
+        match rule {
+            Some(rule) if rule.enabled => {
+                PreTradeLimits::from_params(&rule.get_params()?).map_err(AppError::risk_limit)
+            }
+            _ => Ok(PreTradeLimits::default()),
+        }
+    }
+
+    /// Check a market order's expected slippage against the order book
+    ///
+    /// Returns the estimate to record with the order. When the
+    /// `slippage_guard` rule downsizes, `request.quantity` is reduced to the
+    /// largest size within its limits. Emergency orders, conditional exits
+    /// that only reduce a position and orders that rest on the book are not
+    /// checked.
+    async fn slippage_check(
+        &self,
+        request: &mut OrderRequest,
+        user_id: &str,
+        origin: &OrderOrigin,
+    ) -> AppResult<Option<SlippageEstimate>> {
+        if request.order_type != OrderType::Market || *origin == OrderOrigin::Emergency {
+            return Ok(None);
+        }
+        let limits = self.slippage_limits().await?;
+        if !limits.is_active() {
+            return Ok(None);
+        }
+        // A stop-loss has to get out on a thin book too
+        if origin.reduce_only_exempt() {
+            let positions = self.get_positions(user_id).await?;
+            if is_reduce_only(request.side, request.quantity, &request.symbol, &positions) {
+                return Ok(None);
+            }
+        }
+
+        let book = match self.exchange.get_order_book(&request.symbol, limits.depth_levels).await {
+            Ok(book) => book,
+            Err(e) => {
+                log::warn!("No order book for {} in slippage check: {}", request.symbol, e);
+                OrderBook {
+                    symbol: request.symbol.clone(),
+                    bids: Vec::new(),
+                    asks: Vec::new(),
+                    timestamp: Utc::now().timestamp_millis(),
+                }
+            }
+        };
+
+        match limits.evaluate(&book, request.side, request.quantity) {
+            SlippageDecision::Accept(estimate) => {
+                log::debug!("{} {} {}: {}", request.side, request.quantity, request.symbol, estimate);
+                Ok(Some(estimate))
+            }
+            SlippageDecision::Downsize { quantity, rejections, .. } => {
+                let reasons: Vec<String> = rejections.iter().map(|r| r.to_string()).collect();
+                log::warn!(
+                    "Slippage guard downsized {} {} {} to {} ({:?}): {}",
+                    request.side,
+                    request.quantity,
+                    request.symbol,
+                    quantity,
+                    origin,
+                    reasons.join("; ")
+                );
+                request.quantity = quantity;
+                self.validate_order_request(request, user_id, origin).await?;
+                Ok(estimate_slippage(&book, request.side, request.quantity, limits.top_levels))
+            }
+            SlippageDecision::Reject { estimate, rejections } => {
+                let reasons: Vec<String> = rejections.iter().map(|r| r.to_string()).collect();
+                log::warn!(
+                    "Slippage guard refused {} {} {} ({:?}): {}{}",
+                    request.side,
+                    request.quantity,
+                    request.symbol,
+                    origin,
+                    reasons.join("; "),
+                    estimate.map(|e| format!(" [{}]", e)).unwrap_or_default()
+                );
+                Err(AppError::risk_limit(reasons.join("; ")))
+            }
+        }
+    }
+
+    /// Limits of the `slippage_guard` rule; none when it's missing or disabled
+    async fn slippage_limits(&self) -> AppResult<SlippageLimits> {
+        let rule = RiskRuleRepository::new(self.pool.clone())
+            .find_by_name(SLIPPAGE_GUARD_RULE)
+            .await?;
+        match rule {
+            Some(rule) if rule.enabled => {
+                SlippageLimits::from_params(&rule.get_params()?).map_err(AppError::risk_limit)
+            }
+            _ => Ok(SlippageLimits::default()),
+        }
+    }
+
+    /// Store the pre-trade slippage estimate so fills can be compared with it
+    ///
+    /// The best price at submission goes into `arrival_price`, as for algo
+    /// parent orders.
+    async fn save_slippage_estimate(&self, order_id: &str, estimate: &SlippageEstimate) -> AppResult<()> {
+        sqlx::query("UPDATE orders SET arrival_price = ?, expected_slippage_bps = ? WHERE id = ?")
+            .bind(estimate.reference_price)
+            .bind(estimate.slippage_bps)
+            .bind(order_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Orders placed today by the user and by the origin's instance
+    ///
+    /// Child orders count through their parent; rejected orders don't count.
+    async fn orders_today(&self, user_id: &str, origin: &OrderOrigin) -> AppResult<(u32, u32)> {
+        let instance_id = match origin {
+            OrderOrigin::Instance(id) => Some(id.as_str()),
+            _ => None,
+        };
+        let row = sqlx::query(
+            "SELECT COUNT(*) AS user_orders, \
+                    COALESCE(SUM(CASE WHEN strategy_instance_id = ? THEN 1 ELSE 0 END), 0) AS instance_orders \
+             FROM orders WHERE user_id = ? AND created_at >= ? \
+             AND parent_order_id IS NULL AND status != 'rejected'"
+        )
+        .bind(instance_id)
+        .bind(user_id)
+        .bind(period_start(Utc::now().timestamp_millis(), StatementPeriod::Daily))
+        .fetch_one(&self.pool)
+        .await?;
+
+        let user_orders: i64 = row.try_get("user_orders")?;
+        let instance_orders: i64 = row.try_get("instance_orders")?;
+        Ok((user_orders as u32, instance_orders as u32))
+    }
+
+    /// Latest price of a symbol for valuing a market order
+    async fn reference_price(&self, symbol: &str, positions: &[Position]) -> Option<f64> {
+        match self.exchange.get_ticker(symbol).await {
+            Ok(ticker) if ticker.price > 0.0 => Some(ticker.price),
+            result => {
+                if let Err(e) = result {
+                    log::warn!("No ticker for {} in pre-trade checks: {}", symbol, e);
+                }
+                positions
+                    .iter()
+                    .find(|p| p.symbol == symbol)
+                    .and_then(|p| p.current_price)
+            }
+        }
+    }
+
+    /// Balance of the symbol's quote asset
+    async fn quote_balance(&self, symbol: &str) -> Option<f64> {
+        let quote = match self.instruments.get(symbol).await {
+            Ok(Some(instrument)) => instrument.quote_asset,
+            _ => return None,
+        };
+        match self.exchange.get_balance().await {
+            Ok(balances) => balances.iter().find(|b| b.asset == quote).map(|b| b.total),
+            Err(e) => {
+                log::warn!("No balance for pre-trade checks: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Whether an order is an emergency close or only reduces a position
+    async fn is_closing_order(&self, request: &OrderRequest, user_id: &str, origin: &OrderOrigin) -> bool {
+        if *origin == OrderOrigin::Emergency {
+            return true;
+        }
+        match self.get_positions(user_id).await {
+            Ok(positions) => is_reduce_only(request.side, request.quantity, &request.symbol, &positions),
+            Err(e) => {
+                log::warn!("No positions to check {} {} against: {}", request.side, request.symbol, e);
+                false
+            }
+        }
+    }
+
+    async fn validate_order_request(
+        &self,
+        request: &mut OrderRequest,
+        user_id: &str,
+        origin: &OrderOrigin,
+    ) -> AppResult<()> {
+        if request.quantity <= 0.0 {
+            return Err(AppError::Validation("Order quantity must be positive".to_string()));
+        }
+
+        if let Some(client_order_id) = &request.client_order_id {
+            if !OrderRequest::is_valid_client_order_id(client_order_id) {
+                return Err(AppError::Validation(format!(
+                    "Client order id must be 1-{} letters or digits: {}",
+                    MAX_CLIENT_ORDER_ID_LEN, client_order_id
+                )));
+            }
+        }
+
+        if matches!(request.order_type, OrderType::Limit | OrderType::StopLimit)
+            && (request.price.is_none() || request.price.unwrap() <= 0.0) {
+                return Err(AppError::Validation("Limit orders must have a positive price".to_string()));
+            }
+
+        if matches!(request.order_type, OrderType::StopLoss | OrderType::StopLimit)
+            && (request.stop_price.is_none() || request.stop_price.unwrap() <= 0.0) {
+                return Err(AppError::Validation("Stop orders must have a positive stop price".to_string()));
+            }
+
+        // Round to tick/lot size and check exchange limits. Without exchange
+        // info the order can't be checked, so it is refused unless it only
+        // closes exposure; the venue still has the last word on it.
+        match self.instruments.get(&request.symbol).await {
+            Ok(Some(instrument)) => {
+                instrument
+                    .normalize_order(request)
+                    .map_err(|e| AppError::Validation(e.to_string()))?;
+            }
+            Ok(None) => {
+                return Err(AppError::Validation(format!("Unknown symbol: {}", request.symbol)));
+            }
+            Err(e) => {
+                if self.is_closing_order(request, user_id, origin).await {
+                    log::warn!(
+                        "Instrument rules unavailable for {}, sending closing order unrounded: {}",
+                        request.symbol,
+                        e
+                    );
+                    return Ok(());
+                }
+                log::warn!("Instrument rules unavailable for {}, refusing order: {}", request.symbol, e);
+                return Err(AppError::Exchange(format!(
+                    "Trading rules for {} are unavailable: {}",
+                    request.symbol, e
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn save_order_to_db(
+        &self,
+        order: &Order,
+        user_id: &str,
+        exchange_id: &str,
+        instance_id: Option<&str>,
+        parent_order_id: Option<&str>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO orders (id, user_id, exchange_id, strategy_instance_id, parent_order_id,
+                               exchange_order_id, client_order_id, symbol, side, order_type, price,
+                               quantity, filled_quantity, avg_price, status, commission,
+                               commission_asset, created_at, updated_at, filled_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(&order.id)
+        .bind(user_id)
+        .bind(exchange_id)
+        .bind(instance_id)
+        .bind(parent_order_id)
+        .bind(&order.exchange_order_id)
+        .bind(&order.client_order_id)
+        .bind(&order.symbol)
+        .bind(order.side.to_string())
+        .bind(order.order_type.to_string())
+        .bind(order.price)
+        .bind(order.quantity)
+        .bind(order.filled_quantity)
+        .bind(order.avg_price)
+        .bind(order.status.to_string())
+        .bind(order.commission)
+        .bind(&order.commission_asset)
+        .bind(order.created_at)
+        .bind(Utc::now().timestamp())
+        .bind(order.filled_at)
+        .execute(&self.pool)
+        .await?;
+
+        let mut event = OrderEvent::new(&order.id, OrderEventKind::Created, OrderEventSource::Local)
+            .with_status(None, order.status);
+        if let Some(client_order_id) = &order.client_order_id {
+            event = event.with_detail(format!("client order id {}", client_order_id));
+        }
+        self.record_order_event(&event).await
+    }
+
+    async fn get_order_from_db(&self, order_id: &str, user_id: &str) -> AppResult<Order> {
+        let row = sqlx::query(
+            "SELECT * FROM orders WHERE id = ? AND user_id = ?"
+        )
+        .bind(order_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(r) => self.row_to_order(r),
+            None => Err(AppError::Validation(format!("Order not found: {}", order_id))),
+        }
+    }
+
+    /// Move an order to a new status through the order state machine
+    ///
+    /// Regressions and changes after a terminal state leave the order as it
+    /// is and are recorded as ignored. Returns whether the status changed.
+    async fn update_order_status(
+        &self,
+        order_id: &str,
+        status: OrderState,
+        source: OrderEventSource,
+        detail: Option<&str>,
+    ) -> AppResult<bool> {
+        let mut tx = self.pool.begin().await?;
+        let (current, _) = Self::stored_state(&mut tx, order_id).await?;
+
+        let mut event = match check_update(current, None, status, None) {
+            UpdateCheck::Apply { changed: false } => return Ok(false),
+            UpdateCheck::Apply { changed: true } => {
+                sqlx::query("UPDATE orders SET status = ?, updated_at = ? WHERE id = ?")
+                    .bind(status.to_string())
+                    .bind(Utc::now().timestamp())
+                    .bind(order_id)
+                    .execute(&mut *tx)
+                    .await?;
+                OrderEvent::new(order_id, OrderEventKind::StatusChanged, source).with_status(Some(current), status)
+            }
+            UpdateCheck::Stale | UpdateCheck::Refused(_) => {
+                log::warn!("Order {}: ignoring {} update {:?} -> {:?}", order_id, source.as_str(), current, status);
+                OrderEvent::new(order_id, OrderEventKind::Ignored, source).with_status(Some(current), status)
+            }
+        };
+        if let Some(detail) = detail {
+            event = event.with_detail(detail);
+        }
+        let changed = event.kind == OrderEventKind::StatusChanged;
+        Self::insert_order_event(&mut tx, &event).await?;
+
+        tx.commit().await?;
+        Ok(changed)
+    }
+
+    /// Put a rejected order back to pending before sending it again
+    async fn resubmit_order(&self, order: &Order) -> AppResult<()> {
+        let mut sm = OrderStateMachine::from_state(order.status);
+        sm.resubmit().map_err(|e| AppError::validation(e.to_string()))?;
+
+        let mut tx = self.pool.begin().await?;
+        let updated = sqlx::query("UPDATE orders SET status = ?, updated_at = ? WHERE id = ? AND status = 'rejected'")
+            .bind(sm.state().to_string())
+            .bind(Utc::now().timestamp())
+            .bind(&order.id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if updated == 0 {
+            return Err(AppError::validation(format!("Order {} is no longer rejected", order.id)));
+        }
+        let event = OrderEvent::new(&order.id, OrderEventKind::Resubmitted, OrderEventSource::Local)
+            .with_status(Some(order.status), sm.state());
+        Self::insert_order_event(&mut tx, &event).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Stored status and last exchange update time of an order
+    async fn stored_state(conn: &mut SqliteConnection, order_id: &str) -> AppResult<(OrderState, Option<i64>)> {
+        let row = sqlx::query("SELECT status, exchange_updated_at FROM orders WHERE id = ?")
+            .bind(order_id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .ok_or_else(|| AppError::validation(format!("Order not found: {}", order_id)))?;
+
+        Ok((row.try_get::<String, _>("status")?.parse()?, row.try_get("exchange_updated_at")?))
+    }
+
+    /// Append an entry to an order's history
+    async fn insert_order_event(conn: &mut SqliteConnection, event: &OrderEvent) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO order_events (order_id, kind, source, from_status, to_status,
+                                      filled_quantity, detail, exchange_time, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(&event.order_id)
+        .bind(event.kind.as_str())
+        .bind(event.source.as_str())
+        .bind(event.from_status.map(|s| s.to_string()))
+        .bind(event.to_status.map(|s| s.to_string()))
+        .bind(event.filled_quantity)
+        .bind(&event.detail)
+        .bind(event.exchange_time)
+        .bind(event.created_at)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn record_order_event(&self, event: &OrderEvent) -> AppResult<()> {
+        let mut conn = self.pool.acquire().await?;
+        Self::insert_order_event(&mut conn, event).await
+    }
+
+    /// Exchange account to record a user's orders against
+    ///
+    /// Unbound services fall back to the user's oldest account on this exchange.
+    async fn account_id(&self, user_id: &str) -> AppResult<Option<String>> {
+        if let Some(exchange_id) = &self.exchange_id {
+            return Ok(Some(exchange_id.clone()));
+        }
+
+        let exchange_id = sqlx::query_scalar(
+            "SELECT id FROM exchanges WHERE user_id = ? AND exchange_name = ? ORDER BY created_at LIMIT 1"
+        )
+        .bind(user_id)
+        .bind(self.exchange.name().as_str())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(exchange_id)
+    }
+
+    /// Persist a reconciliation correction and publish the matching event
+    ///
+    /// The status change goes through the order state machine; a stale or
+    /// regressing exchange status is recorded as ignored while missed fills
+    /// are still applied. Returns whether the order changed.
+    async fn apply_correction(
+        &self,
+        user_id: &str,
+        exchange_id: &str,
+        correction: &OrderCorrection,
+    ) -> AppResult<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let remote = &correction.order;
+        let (current, last_update_at) = Self::stored_state(&mut tx, &remote.id).await?;
+        let status = match check_update(current, last_update_at, remote.status, remote.updated_at) {
+            UpdateCheck::Apply { .. } => remote.status,
+            check => {
+                log::warn!("Reconcile: ignoring order {} status {:?} -> {:?} ({:?})", remote.id, current, remote.status, check);
+                let event = OrderEvent::new(&remote.id, OrderEventKind::Ignored, OrderEventSource::Reconcile)
+                    .with_status(Some(current), remote.status)
+                    .with_exchange_time(remote.updated_at)
+                    .with_detail(format!("{:?}", check));
+                Self::insert_order_event(&mut tx, &event).await?;
+                current
+            }
+        };
+        if status == current && correction.fill.is_none() {
+            tx.commit().await?;
+            return Ok(false);
+        }
+
+        let order = Order { status, ..remote.clone() };
+        let correction = OrderCorrection { order, previous_state: current, fill: correction.fill };
+        let order = &correction.order;
+        if correction.is_out_of_sequence() {
+            log::warn!(
+                "Order {} jumped {} -> {} on the exchange",
+                order.id, correction.previous_state, order.status
+            );
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE orders SET
+                filled_quantity = ?,
+                avg_price = ?,
+                status = ?,
+                commission = ?,
+                filled_at = ?,
+                exchange_updated_at = COALESCE(?, exchange_updated_at),
+                updated_at = ?
+            WHERE id = ?
+            "#
+        )
+        .bind(order.filled_quantity)
+        .bind(order.avg_price)
+        .bind(order.status.to_string())
+        .bind(order.commission)
+        .bind(order.filled_at)
+        .bind(order.updated_at.filter(|_| status == remote.status))
+        .bind(Utc::now().timestamp())
+        .bind(&order.id)
+        .execute(&mut *tx)
+        .await?;
+
+        let kind = if correction.fill.is_some() { OrderEventKind::Fill } else { OrderEventKind::StatusChanged };
+        let mut event = OrderEvent::new(&order.id, kind, OrderEventSource::Reconcile)
+            .with_status(Some(current), order.status)
+            .with_filled(order.filled_quantity)
+            .with_exchange_time(order.updated_at);
+        let missed = correction.fill.map(|fill| Self::implied_fill(order, fill.quantity, fill.price));
+        let mut instance_id: Option<String> = None;
+        if let Some(fill) = &missed {
+            let row = sqlx::query(
+                "SELECT strategy_instance_id, arrival_price, expected_slippage_bps FROM orders WHERE id = ?"
+            )
+            .bind(&order.id)
+            .fetch_one(&mut *tx)
+            .await?;
+            instance_id = row.try_get("strategy_instance_id")?;
+            let mut detail = format!("missed fill {} @ {}", fill.quantity, fill.price);
+            let slippage = Self::realised_slippage(
+                order,
+                row.try_get("arrival_price")?,
+                row.try_get("expected_slippage_bps")?,
+                fill.price,
+            );
+            if let Some(slippage) = slippage {
+                detail.push_str(&format!(", {}", slippage));
+            }
+            event = event.with_detail(detail);
+            Self::insert_fill(&mut tx, user_id, exchange_id, &order.id, fill).await?;
         }
+        Self::insert_order_event(&mut tx, &event).await?;
 
-        Ok(positions)
-    }
+        tx.commit().await?;
 
-    /// Get account balance
-    pub async fn get_balance(&self) -> AppResult<Vec<Balance>> {
-        self.exchange.get_balance().await
-            .map_err(|e| AppError::Exchange(e.to_string()))
+        if let Some(fill) = &missed {
+            self.apply_fill_to_positions(user_id, exchange_id, instance_id.as_deref(), fill).await;
+        }
+        self.publish_correction_event(&correction);
+
+        Ok(true)
     }
 
-    // ========== Private helper methods ==========
+    /// Apply an order update from the exchange's user data stream
+    ///
+    /// Only the status is taken from the update; quantities follow the fill
+    /// stream. Updates older than the last one applied, regressions and
+    /// changes after a terminal state are recorded as ignored. Returns
+    /// whether the status changed.
+    pub async fn apply_order_update(&self, remote: &Order) -> AppResult<bool> {
+        let mut tx = self.pool.begin().await?;
 
-    fn validate_order_request(&self, request: &OrderRequest) -> AppResult<()> {
-        if request.quantity <= 0.0 {
-            return Err(AppError::Validation("Order quantity must be positive".to_string()));
-        }
+        // The stream carries one account's orders; the account id scopes the
+        // lookup to its user as well
+        let order_id: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM orders WHERE (exchange_order_id = ? \
+             OR (client_order_id IS NOT NULL AND client_order_id = ?)) \
+             AND (? IS NULL OR exchange_id = ?) LIMIT 1"
+        )
+        .bind(&remote.exchange_order_id)
+        .bind(&remote.client_order_id)
+        .bind(&self.exchange_id)
+        .bind(&self.exchange_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(order_id) = order_id else {
+            log::debug!("Update for unknown order {:?}, leaving it to reconciliation", remote.exchange_order_id);
+            return Ok(false);
+        };
 
-        if matches!(request.order_type, OrderType::Limit | OrderType::StopLimit)
-            && (request.price.is_none() || request.price.unwrap() <= 0.0) {
-                return Err(AppError::Validation("Limit orders must have a positive price".to_string()));
+        let (current, last_update_at) = Self::stored_state(&mut tx, &order_id).await?;
+        let changed = match check_update(current, last_update_at, remote.status, remote.updated_at) {
+            UpdateCheck::Apply { changed } => {
+                sqlx::query(
+                    r#"
+                    UPDATE orders SET
+                        status = ?,
+                        exchange_order_id = COALESCE(exchange_order_id, ?),
+                        exchange_updated_at = COALESCE(?, exchange_updated_at),
+                        updated_at = ?
+                    WHERE id = ?
+                    "#
+                )
+                .bind(remote.status.to_string())
+                .bind(&remote.exchange_order_id)
+                .bind(remote.updated_at)
+                .bind(Utc::now().timestamp())
+                .bind(&order_id)
+                .execute(&mut *tx)
+                .await?;
+                if changed {
+                    let event = OrderEvent::new(&order_id, OrderEventKind::StatusChanged, OrderEventSource::Stream)
+                        .with_status(Some(current), remote.status)
+                        .with_filled(remote.filled_quantity)
+                        .with_exchange_time(remote.updated_at);
+                    Self::insert_order_event(&mut tx, &event).await?;
+                }
+                changed
             }
+            check => {
+                log::warn!("Order {}: ignoring stream update {:?} -> {:?} ({:?})", order_id, current, remote.status, check);
+                let event = OrderEvent::new(&order_id, OrderEventKind::Ignored, OrderEventSource::Stream)
+                    .with_status(Some(current), remote.status)
+                    .with_filled(remote.filled_quantity)
+                    .with_exchange_time(remote.updated_at)
+                    .with_detail(format!("{:?}", check));
+                Self::insert_order_event(&mut tx, &event).await?;
+                false
+            }
+        };
 
-        if matches!(request.order_type, OrderType::StopLoss | OrderType::StopLimit)
-            && (request.stop_price.is_none() || request.stop_price.unwrap() <= 0.0) {
-                return Err(AppError::Validation("Stop orders must have a positive stop price".to_string()));
+        tx.commit().await?;
+
+        if changed && matches!(remote.status, OrderState::Canceled | OrderState::Rejected) {
+            if let Some(bus) = &self.event_bus {
+                let row = sqlx::query("SELECT * FROM orders WHERE id = ?")
+                    .bind(&order_id)
+                    .fetch_one(&self.pool)
+                    .await?;
+                bus.publish_order_canceled(self.row_to_order(row)?);
             }
+        }
 
-        Ok(())
+        Ok(changed)
     }
 
-    async fn save_order_to_db(&self, order: &Order, user_id: &str) -> AppResult<()> {
+    /// Import an order placed outside the app
+    ///
+    /// Returns `Ok(false)` if the user has no exchange account to attach it to.
+    async fn import_exchange_order(&self, user_id: &str, remote: &Order) -> AppResult<bool> {
+        let Some(exchange_id) = self.account_id(user_id).await? else {
+            log::warn!(
+                "Reconcile: no {} account for user {}, skipping external order {:?}",
+                self.exchange.name(), user_id, remote.exchange_order_id
+            );
+            return Ok(false);
+        };
+
+        let mut order = remote.clone();
+        order.id = Uuid::new_v4().to_string();
+        let now = Utc::now().timestamp();
+
+        let mut tx = self.pool.begin().await?;
+
         sqlx::query(
             r#"
-            INSERT INTO orders (id, user_id, exchange_order_id, client_order_id,
+            INSERT INTO orders (id, user_id, exchange_id, exchange_order_id, client_order_id,
                                symbol, side, order_type, price, quantity,
                                filled_quantity, avg_price, status, commission,
-                               created_at, filled_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                               created_at, updated_at, filled_at, exchange_updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#
         )
         .bind(&order.id)
         .bind(user_id)
+        .bind(&exchange_id)
         .bind(&order.exchange_order_id)
         .bind(&order.client_order_id)
         .bind(&order.symbol)
@@ -244,84 +2337,441 @@ impl TradeService {
         .bind(order.status.to_string())
         .bind(order.commission)
         .bind(order.created_at)
+        .bind(now)
         .bind(order.filled_at)
-                .execute(&self.pool)
+        .bind(order.updated_at)
+        .execute(&mut *tx)
         .await?;
 
-        Ok(())
+        let event = OrderEvent::new(&order.id, OrderEventKind::Created, OrderEventSource::Reconcile)
+            .with_status(None, order.status)
+            .with_filled(order.filled_quantity)
+            .with_exchange_time(order.updated_at)
+            .with_detail("imported from the exchange");
+        Self::insert_order_event(&mut tx, &event).await?;
+
+        let filled = match order.avg_price.or(order.price) {
+            Some(price) if order.filled_quantity > 0.0 => Some(Self::implied_fill(&order, order.filled_quantity, price)),
+            _ => None,
+        };
+        if let Some(fill) = &filled {
+            Self::insert_fill(&mut tx, user_id, &exchange_id, &order.id, fill).await?;
+        }
+
+        tx.commit().await?;
+
+        if let Some(fill) = &filled {
+            // Imported orders weren't placed by a strategy instance
+            self.apply_fill_to_positions(user_id, &exchange_id, None, fill).await;
+        }
+
+        log::info!("Imported external order {:?} ({})", order.exchange_order_id, order.symbol);
+        if let Some(bus) = &self.event_bus {
+            bus.publish_order_placed(order);
+        }
+
+        Ok(true)
     }
 
-    async fn get_order_from_db(&self, order_id: &str, user_id: &str) -> AppResult<Order> {
-        let row = sqlx::query(
-            "SELECT * FROM orders WHERE id = ? AND user_id = ?"
+    /// Compare a fill price with the order's pre-trade slippage estimate
+    ///
+    /// Logs the comparison and returns it for the fill event; none when the
+    /// order was placed without an estimate.
+    fn realised_slippage(
+        order: &Order,
+        arrival_price: Option<f64>,
+        expected_bps: Option<f64>,
+        fill_price: f64,
+    ) -> Option<String> {
+        let (arrival, expected) = (arrival_price?, expected_bps?);
+        let realised = slippage_bps(order.side, arrival, fill_price);
+        log::info!(
+            "Fill on {} {}: slippage {:.1} bps against an estimate of {:.1} bps",
+            order.symbol, order.id, realised, expected
+        );
+        Some(format!("slippage {:.1} bps (estimated {:.1} bps)", realised, expected))
+    }
+
+    /// Build a fill for quantity seen only through an order snapshot
+    ///
+    /// Used by reconciliation; there is no exchange trade id and the
+    /// commission is unknown.
+    fn implied_fill(order: &Order, quantity: f64, price: f64) -> Fill {
+        Fill {
+            exchange_order_id: order.exchange_order_id.clone().unwrap_or_default(),
+            exchange_trade_id: None,
+            client_order_id: order.client_order_id.clone(),
+            symbol: order.symbol.clone(),
+            side: order.side,
+            price,
+            quantity,
+            commission: 0.0,
+            commission_asset: None,
+            is_maker: false,
+            timestamp: order.filled_at.unwrap_or_else(|| Utc::now().timestamp_millis()),
+        }
+    }
+
+    /// Record a fill in the trades table
+    ///
+    /// Realised PnL is derived from the account's average-cost basis in the
+    /// symbol, net of fees paid in the quote or base asset. The basis after
+    /// each fill is stored with the trade, so the next fill starts from the
+    /// last trade before it.
+    async fn insert_fill(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        user_id: &str,
+        exchange_id: &str,
+        order_id: &str,
+        fill: &Fill,
+    ) -> AppResult<TradeRecord> {
+        let timestamp = if fill.timestamp > 0 { fill.timestamp } else { Utc::now().timestamp_millis() };
+        let mut basis = Self::cost_basis_before(tx, exchange_id, &fill.symbol, timestamp).await?;
+
+        let gross = basis.apply(fill.side, fill.quantity, fill.price);
+        let fee = commission_in_quote(&fill.symbol, fill.commission_asset.as_deref(), fill.commission, fill.price);
+
+        let record = TradeRecord {
+            id: Uuid::new_v4().to_string(),
+            order_id: order_id.to_string(),
+            exchange_trade_id: fill.exchange_trade_id.clone(),
+            symbol: fill.symbol.clone(),
+            side: fill.side,
+            price: fill.price,
+            quantity: fill.quantity,
+            commission: fill.commission,
+            commission_asset: fill.commission_asset.clone(),
+            realized_pnl: Some(gross - fee),
+            timestamp,
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO trades (id, user_id, exchange_id, order_id, exchange_trade_id, symbol, side,
+                                price, quantity, commission, commission_asset, pnl, timestamp, created_at,
+                                basis_quantity, basis_avg_price)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#
         )
-        .bind(order_id)
+        .bind(&record.id)
         .bind(user_id)
-        .fetch_optional(&self.pool)
+        .bind(exchange_id)
+        .bind(&record.order_id)
+        .bind(&record.exchange_trade_id)
+        .bind(&record.symbol)
+        .bind(record.side.to_string())
+        .bind(record.price)
+        .bind(record.quantity)
+        .bind(record.commission)
+        .bind(&record.commission_asset)
+        .bind(record.realized_pnl)
+        .bind(record.timestamp)
+        .bind(Utc::now().timestamp())
+        .bind(basis.quantity)
+        .bind(basis.avg_price)
+        .execute(&mut **tx)
         .await?;
 
-        match row {
-            Some(r) => self.row_to_order(r),
-            None => Err(AppError::Validation(format!("Order not found: {}", order_id))),
+        Ok(record)
+    }
+
+    /// The account's cost basis in `symbol` just before `timestamp`
+    ///
+    /// Taken from the last trade up to that time. Trades recorded before the
+    /// basis was stored are replayed instead.
+    async fn cost_basis_before(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        exchange_id: &str,
+        symbol: &str,
+        timestamp: i64,
+    ) -> AppResult<CostBasis> {
+        let last: Option<(Option<f64>, Option<f64>)> = sqlx::query_as(
+            "SELECT basis_quantity, basis_avg_price FROM trades \
+             WHERE exchange_id = ? AND symbol = ? AND timestamp <= ? \
+             ORDER BY timestamp DESC, rowid DESC LIMIT 1"
+        )
+        .bind(exchange_id)
+        .bind(symbol)
+        .bind(timestamp)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        match last {
+            None => Ok(CostBasis::default()),
+            Some((Some(quantity), Some(avg_price))) => Ok(CostBasis { quantity, avg_price }),
+            Some(_) => {
+                let history = sqlx::query(
+                    "SELECT side, price, quantity FROM trades \
+                     WHERE exchange_id = ? AND symbol = ? AND timestamp <= ? ORDER BY timestamp, rowid"
+                )
+                .bind(exchange_id)
+                .bind(symbol)
+                .bind(timestamp)
+                .fetch_all(&mut **tx)
+                .await?;
+
+                let mut basis = CostBasis::default();
+                for row in history {
+                    let side: OrderSide = row.try_get::<String, _>("side")?.parse()?;
+                    basis.apply(side, row.try_get("quantity")?, row.try_get("price")?);
+                }
+                Ok(basis)
+            }
         }
     }
 
-    async fn update_order_status(&self, order_id: &str, status: OrderState) -> AppResult<()> {
-        sqlx::query(
-            "UPDATE orders SET status = ?, updated_at = ? WHERE id = ?"
+    fn publish_correction_event(&self, correction: &OrderCorrection) {
+        let Some(bus) = &self.event_bus else {
+            return;
+        };
+
+        let order = correction.order.clone();
+        match order.status {
+            OrderState::Canceled | OrderState::Rejected => bus.publish_order_canceled(order),
+            OrderState::Filled | OrderState::PartiallyFilled if correction.fill.is_some() => {
+                bus.publish_order_filled(order)
+            }
+            OrderState::Open if correction.previous_state == OrderState::Pending => {
+                bus.publish_order_placed(order)
+            }
+            _ => {}
+        }
+    }
+
+    /// Book a fill into the account's positions and persist them
+    ///
+    /// Position bookkeeping never fails the fill; errors are logged.
+    async fn apply_fill_to_positions(&self, user_id: &str, exchange_id: &str, instance_id: Option<&str>, fill: &Fill) {
+        let trade = Trade {
+            symbol: fill.symbol.clone(),
+            side: fill.side.to_string(),
+            quantity: fill.quantity,
+            price: fill.price,
+            commission: commission_in_quote(&fill.symbol, fill.commission_asset.as_deref(), fill.commission, fill.price),
+            position_side: None,
+            timestamp: fill.timestamp,
+        };
+
+        // Stamped under the lock, so the saves after it keep the update order
+        let changed: Vec<(TrackedPosition, i64)> = {
+            let mut books = self.position_books.write().await;
+            let key = (user_id.to_string(), exchange_id.to_string());
+            if !books.contains_key(&key) {
+                match self.load_position_book(user_id, exchange_id).await {
+                    Ok(book) => {
+                        books.insert(key.clone(), book);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to load positions of {}, fill {:?} not booked: {}", user_id, fill.exchange_trade_id, e);
+                        return;
+                    }
+                }
+            }
+            let Some(book) = books.get_mut(&key) else {
+                return;
+            };
+            book.update_position(&trade)
+                .into_iter()
+                .map(|tracked| (tracked, self.next_position_stamp()))
+                .collect()
+        };
+
+        for (tracked, stamp) in changed {
+            if let Err(e) = self.save_position(user_id, exchange_id, instance_id, &tracked, stamp).await {
+                log::error!("Failed to save position {}: {}", tracked.position.id, e);
+                continue;
+            }
+            if let Err(e) = self.attribute_position(&tracked.position.id, instance_id).await {
+                log::error!("Failed to attribute position {}: {}", tracked.position.id, e);
+            }
+        }
+    }
+
+    /// Open positions of an account, as stored
+    async fn load_position_book(&self, user_id: &str, exchange_id: &str) -> AppResult<PositionManager> {
+        let rows = sqlx::query(
+            "SELECT * FROM positions WHERE user_id = ? AND exchange_id = ? AND status = 'open' AND quantity > 0"
         )
-        .bind(status.to_string())
-        .bind(Utc::now().timestamp())
-        .bind(order_id)
-                .execute(&self.pool)
+        .bind(user_id)
+        .bind(exchange_id)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(())
+        let mut book = PositionManager::with_accounting(self.position_mode, self.cost_method);
+        for row in rows {
+            let lots: Option<String> = row.try_get("lots")?;
+            let fees: Option<f64> = row.try_get("fees")?;
+            let lots: std::collections::VecDeque<_> = match lots {
+                Some(json) => serde_json::from_str(&json).map_err(|e| AppError::validation(e.to_string()))?,
+                None => Default::default(),
+            };
+            book.restore(TrackedPosition {
+                position: self.row_to_position(row)?,
+                lots,
+                fees: fees.unwrap_or(0.0),
+            });
+        }
+        Ok(book)
+    }
+
+    /// Load an account's open positions so mark prices revalue them
+    ///
+    /// Returns the number of open positions.
+    pub async fn load_positions(&self, user_id: &str) -> AppResult<usize> {
+        let Some(exchange_id) = self.account_id(user_id).await? else {
+            return Ok(0);
+        };
+        let book = self.load_position_book(user_id, &exchange_id).await?;
+        let count = book.active_position_count();
+        self.position_books.write().await.insert((user_id.to_string(), exchange_id), book);
+        Ok(count)
+    }
+
+    /// Next `updated_at` for a position update, never at or before the last one
+    fn next_position_stamp(&self) -> i64 {
+        let now = Utc::now().timestamp_millis();
+        let last = self
+            .position_stamp
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| Some(now.max(last + 1)))
+            .unwrap_or_default();
+        now.max(last + 1)
     }
 
-    async fn update_order_in_db(&self, order: &Order) -> AppResult<()> {
+    /// Insert or update a position row
+    ///
+    /// A closed position keeps its row with status `closed`, so its
+    /// realised PnL stays on record.
+    ///
+    /// `instance_id` is the strategy instance the position is recorded
+    /// under when it is first saved. `stamp` comes from
+    /// `next_position_stamp`; a row already saved with a later stamp is
+    /// left alone.
+    async fn save_position(
+        &self,
+        user_id: &str,
+        exchange_id: &str,
+        instance_id: Option<&str>,
+        tracked: &TrackedPosition,
+        stamp: i64,
+    ) -> AppResult<()> {
+        let pos = &tracked.position;
+        let closed = tracked.is_closed();
+        let lots = serde_json::to_string(&tracked.lots).map_err(|e| AppError::validation(e.to_string()))?;
+
         sqlx::query(
             r#"
-            UPDATE orders SET
-                filled_quantity = ?,
-                avg_price = ?,
-                status = ?,
-                filled_at = ?,
-                updated_at = ?
-            WHERE id = ?
+            INSERT INTO positions (id, user_id, exchange_id, strategy_instance_id, symbol, side, quantity,
+                                   entry_price, current_price, unrealized_pnl, realized_pnl, fees, lots,
+                                   opened_at, updated_at, closed_at, status)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                quantity = excluded.quantity,
+                entry_price = excluded.entry_price,
+                current_price = excluded.current_price,
+                unrealized_pnl = excluded.unrealized_pnl,
+                realized_pnl = excluded.realized_pnl,
+                fees = excluded.fees,
+                lots = excluded.lots,
+                updated_at = excluded.updated_at,
+                closed_at = excluded.closed_at,
+                status = excluded.status
+            WHERE excluded.updated_at > positions.updated_at
             "#
         )
-        .bind(order.filled_quantity)
-        .bind(order.avg_price)
-        .bind(order.status.to_string())
-        .bind(order.filled_at)
-        .bind(Utc::now().timestamp())
-        .bind(&order.id)
-                .execute(&self.pool)
+        .bind(&pos.id)
+        .bind(user_id)
+        .bind(exchange_id)
+        .bind(instance_id)
+        .bind(&pos.symbol)
+        .bind(&pos.side)
+        .bind(pos.quantity)
+        .bind(pos.entry_price)
+        .bind(pos.current_price)
+        .bind(pos.unrealized_pnl)
+        .bind(pos.realized_pnl)
+        .bind(tracked.fees)
+        .bind(lots)
+        .bind(pos.opened_at)
+        .bind(stamp)
+        .bind(closed.then_some(stamp))
+        .bind(if closed { "closed" } else { "open" })
+        .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
-    async fn update_positions_from_order(&self, order: &Order) {
-        let mut manager = self.position_manager.write().await;
+    /// Mark a position as shared once a fill from another strategy instance,
+    /// or from outside any instance, is booked into it
+    ///
+    /// Positions are netted per account, so only a position built solely by
+    /// one instance keeps that instance's id.
+    async fn attribute_position(&self, position_id: &str, instance_id: Option<&str>) -> AppResult<()> {
+        sqlx::query(
+            "UPDATE positions SET strategy_instance_id = NULL WHERE id = ? AND strategy_instance_id IS NOT ?"
+        )
+        .bind(position_id)
+        .bind(instance_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
 
-        use crate::core::trade::position::Trade;
+    /// Revalue loaded positions in a symbol at a new mark price
+    ///
+    /// With `save`, the revalued positions are written to the database.
+    pub async fn update_mark_price(&self, symbol: &str, price: f64, save: bool) -> AppResult<()> {
+        let mut changed = Vec::new();
+        {
+            let mut books = self.position_books.write().await;
+            for ((user_id, exchange_id), book) in books.iter_mut() {
+                for tracked in book.update_mark_price(symbol, price) {
+                    if save {
+                        changed.push((user_id.clone(), exchange_id.clone(), tracked, self.next_position_stamp()));
+                    }
+                }
+            }
+        }
+        for (user_id, exchange_id, tracked, stamp) in changed {
+            self.save_position(&user_id, &exchange_id, None, &tracked, stamp).await?;
+        }
+        Ok(())
+    }
 
-        let trade = Trade {
-            symbol: order.symbol.clone(),
-            side: if order.side == OrderSide::Buy { "buy".to_string() } else { "sell".to_string() },
-            quantity: order.filled_quantity,
-            price: order.avg_price.unwrap_or(order.price.unwrap_or(0.0)),
-            timestamp: order.created_at,
-        };
+    /// Revalue positions from the exchange's ticker stream in the background
+    pub fn start_mark_price_updates(self: &Arc<Self>) -> JoinHandle<()> {
+        let service = self.clone();
+        let mut tickers = self.exchange.ticker_stream();
 
-        manager.update_position(&trade);
+        tokio::spawn(async move {
+            let mut saved_at: HashMap<String, i64> = HashMap::new();
+            loop {
+                match tickers.recv().await {
+                    Ok(ticker) => {
+                        let now = Utc::now().timestamp_millis();
+                        let save = saved_at
+                            .get(&ticker.symbol)
+                            .is_none_or(|at| now - at >= MARK_PRICE_SAVE_INTERVAL_MS);
+                        if let Err(e) = service.update_mark_price(&ticker.symbol, ticker.price, save).await {
+                            log::error!("Failed to revalue {} positions: {}", ticker.symbol, e);
+                        } else if save {
+                            saved_at.insert(ticker.symbol, now);
+                        }
+                    }
+                    // Only the latest price matters
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            log::info!("Mark price updates stopped");
+        })
     }
 
     async fn publish_order_event(&self, order: &Order) {
-        // TODO: Publish to event bus
         log::info!("Order placed: {:?}", order);
+        if let Some(bus) = &self.event_bus {
+            bus.publish_order_placed(order.clone());
+        }
     }
 
     fn row_to_order(&self, row: sqlx::sqlite::SqliteRow) -> AppResult<Order> {
@@ -339,7 +2789,42 @@ impl TradeService {
             status: row.try_get::<String, _>("status")?.parse()?,
             commission: row.try_get("commission")?,
             created_at: row.try_get("created_at")?,
+            commission_asset: row.try_get("commission_asset")?,
             filled_at: row.try_get("filled_at")?,
+            updated_at: row.try_get("exchange_updated_at")?,
+        })
+    }
+
+    fn row_to_trade_record(row: sqlx::sqlite::SqliteRow) -> AppResult<TradeRecord> {
+        Ok(TradeRecord {
+            id: row.try_get("id")?,
+            order_id: row.try_get("order_id")?,
+            exchange_trade_id: row.try_get("exchange_trade_id")?,
+            symbol: row.try_get("symbol")?,
+            side: row.try_get::<String, _>("side")?.parse()?,
+            price: row.try_get("price")?,
+            quantity: row.try_get("quantity")?,
+            commission: row.try_get("commission")?,
+            commission_asset: row.try_get("commission_asset")?,
+            realized_pnl: row.try_get("pnl")?,
+            timestamp: row.try_get("timestamp")?,
+        })
+    }
+
+    fn row_to_order_event(row: sqlx::sqlite::SqliteRow) -> AppResult<OrderEvent> {
+        let from_status: Option<String> = row.try_get("from_status")?;
+        let to_status: Option<String> = row.try_get("to_status")?;
+        Ok(OrderEvent {
+            id: row.try_get("id")?,
+            order_id: row.try_get("order_id")?,
+            kind: row.try_get::<String, _>("kind")?.parse()?,
+            source: row.try_get::<String, _>("source")?.parse()?,
+            from_status: from_status.map(|s| s.parse()).transpose()?,
+            to_status: to_status.map(|s| s.parse()).transpose()?,
+            filled_quantity: row.try_get("filled_quantity")?,
+            detail: row.try_get("detail")?,
+            exchange_time: row.try_get("exchange_time")?,
+            created_at: row.try_get("created_at")?,
         })
     }
 
@@ -357,3 +2842,368 @@ impl TradeService {
         })
     }
 }
+
+/// Whether an insert failed on a unique constraint
+fn is_unique_violation(error: &AppError) -> bool {
+    matches!(error, AppError::Database(sqlx::Error::Database(e)) if e.is_unique_violation())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::trade::exchange::ExchangeName;
+    use async_trait::async_trait;
+    use sqlx::sqlite::SqliteConnectOptions;
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    const USER: &str = "u_admin";
+    const ACCOUNT: &str = "acct-test";
+
+    /// Exchange double with one instrument that answers placements directly
+    #[derive(Default)]
+    struct MockExchange {
+        /// Placements fail in transport, leaving their outcome unknown
+        transport_down: bool,
+        /// Instrument rules can't be fetched
+        rules_down: bool,
+        /// Orders the venue knows, by client order id
+        remote: Mutex<HashMap<String, Order>>,
+        /// Orders sent to the venue
+        sent: AtomicUsize,
+    }
+
+    impl MockExchange {
+        async fn transport_error() -> anyhow::Error {
+            // Nothing listens on the discard port
+            reqwest::Client::new().get("http://127.0.0.1:9").send().await.unwrap_err().into()
+        }
+
+        fn accept(&self, request: &OrderRequest) -> Order {
+            self.sent.fetch_add(1, Ordering::SeqCst);
+            let client_order_id = request.client_order_id.clone().unwrap_or_default();
+            let order = Order {
+                exchange_order_id: Some(Uuid::new_v4().to_string()),
+                status: OrderState::Open,
+                ..TradeService::pending_order(request)
+            };
+            self.remote.lock().unwrap().insert(client_order_id, order.clone());
+            order
+        }
+    }
+
+    #[async_trait]
+    impl Exchange for MockExchange {
+        fn name(&self) -> ExchangeName {
+            ExchangeName::OKX
+        }
+        fn is_connected(&self) -> bool {
+            true
+        }
+        async fn connect(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+        async fn disconnect(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+        async fn get_ticker(&self, symbol: &str) -> anyhow::Result<Ticker> {
+            Err(anyhow::anyhow!("No ticker for {}", symbol))
+        }
+        async fn get_klines(&self, symbol: &str, _interval: Interval, _limit: usize) -> anyhow::Result<Vec<Kline>> {
+            Err(anyhow::anyhow!("No klines for {}", symbol))
+        }
+        async fn get_server_time(&self) -> anyhow::Result<i64> {
+            Ok(Utc::now().timestamp_millis())
+        }
+        async fn get_instruments(&self) -> anyhow::Result<Vec<Instrument>> {
+            if self.rules_down {
+                return Err(anyhow::anyhow!("Exchange info unavailable"));
+            }
+            Ok(vec![Instrument {
+                symbol: "BTCUSDT".to_string(),
+                base_asset: "BTC".to_string(),
+                quote_asset: "USDT".to_string(),
+                status: InstrumentStatus::Trading,
+                tick_size: 0.01,
+                step_size: 0.0001,
+                min_qty: 0.0001,
+                max_qty: None,
+                min_notional: 0.0,
+            }])
+        }
+        async fn subscribe_ticker(&self, _symbols: Vec<String>) -> anyhow::Result<()> {
+            Ok(())
+        }
+        async fn subscribe_kline(&self, _symbols: Vec<String>, _interval: Interval) -> anyhow::Result<()> {
+            Ok(())
+        }
+        fn ticker_stream(&self) -> broadcast::Receiver<Ticker> {
+            broadcast::channel(1).1
+        }
+        fn kline_stream(&self) -> broadcast::Receiver<Kline> {
+            broadcast::channel(1).1
+        }
+        fn order_stream(&self) -> broadcast::Receiver<Order> {
+            broadcast::channel(1).1
+        }
+        fn fill_stream(&self) -> broadcast::Receiver<Fill> {
+            broadcast::channel(1).1
+        }
+        async fn subscribe_user_data(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+        async fn place_order(&self, request: &OrderRequest) -> anyhow::Result<Order> {
+            if self.transport_down {
+                return Err(Self::transport_error().await);
+            }
+            Ok(self.accept(request))
+        }
+        async fn place_orders(&self, requests: &[OrderRequest]) -> anyhow::Result<Vec<anyhow::Result<Order>>> {
+            if self.transport_down {
+                return Err(Self::transport_error().await);
+            }
+            Ok(requests.iter().map(|request| Ok(self.accept(request))).collect())
+        }
+        async fn cancel_order(&self, _order_id: &str) -> anyhow::Result<()> {
+            Ok(())
+        }
+        async fn amend_order(&self, order: &Order, _amend: &AmendRequest) -> anyhow::Result<AmendOutcome> {
+            Err(anyhow::anyhow!("Cannot amend {}", order.id))
+        }
+        async fn get_order(&self, _symbol: &str, order_id: &str) -> anyhow::Result<Order> {
+            Err(anyhow::anyhow!("Unknown order {}", order_id))
+        }
+        async fn get_order_by_client_id(&self, _symbol: &str, client_order_id: &str) -> anyhow::Result<Option<Order>> {
+            Ok(self.remote.lock().unwrap().get(client_order_id).cloned())
+        }
+        async fn get_open_orders(&self, _symbol: Option<&str>) -> anyhow::Result<Vec<Order>> {
+            Ok(self.remote.lock().unwrap().values().cloned().collect())
+        }
+        async fn get_balance(&self) -> anyhow::Result<Vec<Balance>> {
+            Ok(Vec::new())
+        }
+        async fn get_positions(&self) -> anyhow::Result<Vec<Position>> {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Migrated database with one exchange account and the limits disabled
+    async fn test_pool() -> (TempDir, SqlitePool) {
+        let dir = TempDir::new().unwrap();
+        let options = SqliteConnectOptions::from_str(&format!("sqlite:{}", dir.path().join("test.db").display()))
+            .unwrap()
+            .create_if_missing(true)
+            .foreign_keys(true);
+        let pool = SqlitePool::connect_with(options).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        sqlx::query(
+            "INSERT INTO exchanges (id, user_id, exchange_name, display_name, api_key_encrypted, \
+             api_secret_encrypted, created_at, updated_at) VALUES (?, ?, 'okx', 'OKX', '', '', 0, 0)"
+        )
+        .bind(ACCOUNT)
+        .bind(USER)
+        .execute(&pool)
+        .await
+        .unwrap();
+        // Pre-trade limits and the slippage guard have their own tests
+        sqlx::query("UPDATE risk_rules SET enabled = 0").execute(&pool).await.unwrap();
+
+        (dir, pool)
+    }
+
+    fn service(exchange: MockExchange, pool: SqlitePool) -> TradeService {
+        TradeService::new(Arc::new(exchange), pool).with_exchange_id(ACCOUNT)
+    }
+
+    fn limit_buy(client_order_id: &str) -> OrderRequest {
+        OrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            price: Some(50_000.0),
+            stop_price: None,
+            quantity: 0.01,
+            client_order_id: Some(client_order_id.to_string()),
+            time_in_force: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_transport_failure_leaves_orders_pending() {
+        let (_dir, pool) = test_pool().await;
+        let service = service(MockExchange { transport_down: true, ..Default::default() }, pool);
+
+        let results = service.place_orders(vec![limit_buy("batch1"), limit_buy("batch2")], USER).await.unwrap();
+        for result in &results {
+            assert!(matches!(result, Err(AppError::Exchange(e)) if e.contains("outcome unknown")), "{:?}", result);
+        }
+
+        let orders = service.get_orders(USER, None, None, 10).await.unwrap();
+        assert_eq!(orders.len(), 2);
+        assert!(orders.iter().all(|order| order.status == OrderState::Pending));
+    }
+
+    #[tokio::test]
+    async fn test_batch_resend_returns_recorded_orders() {
+        let (_dir, pool) = test_pool().await;
+        let service = service(MockExchange::default(), pool);
+
+        let requests = vec![limit_buy("resend1"), limit_buy("resend2")];
+        let first = service.place_orders(requests.clone(), USER).await.unwrap();
+        let again = service.place_orders(requests, USER).await.unwrap();
+
+        for (first, again) in first.iter().zip(&again) {
+            let (first, again) = (first.as_ref().unwrap(), again.as_ref().unwrap());
+            assert_eq!(first.id, again.id);
+            assert_eq!(again.status, OrderState::Open);
+        }
+        assert_eq!(service.get_orders(USER, None, None, 10).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_resend_of_locally_rejected_order_finds_it_live() {
+        let (_dir, pool) = test_pool().await;
+        let exchange = Arc::new(MockExchange::default());
+        let service = TradeService::new(exchange.clone(), pool.clone()).with_exchange_id(ACCOUNT);
+
+        let placed = service.place_order(limit_buy("swept1"), USER).await.unwrap();
+        // Rejected locally, as the stale pending sweep does, while it's live
+        sqlx::query("UPDATE orders SET status = 'rejected' WHERE id = ?")
+            .bind(&placed.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let resent = service.place_order(limit_buy("swept1"), USER).await.unwrap();
+        assert_eq!(resent.id, placed.id);
+        assert_eq!(resent.status, OrderState::Open);
+        assert_eq!(exchange.sent.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_slippage_guard_lets_conditional_exits_out() {
+        let (_dir, pool) = test_pool().await;
+        // The mock has no order book, so the guard refuses every market order it checks
+        sqlx::query("UPDATE risk_rules SET enabled = 1 WHERE name = 'slippage_guard'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO positions (id, user_id, exchange_id, symbol, side, quantity, entry_price, opened_at, updated_at) \
+             VALUES ('pos-1', ?, ?, 'BTCUSDT', 'long', 0.5, 50000, 0, 0)"
+        )
+        .bind(USER)
+        .bind(ACCOUNT)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let service = service(MockExchange::default(), pool);
+        let market = |side, quantity| OrderRequest {
+            order_type: OrderType::Market,
+            price: None,
+            side,
+            quantity,
+            client_order_id: None,
+            ..limit_buy("unused")
+        };
+
+        let exit = service.place_conditional_order(market(OrderSide::Sell, 0.5), USER).await.unwrap();
+        assert_eq!(exit.status, OrderState::Open);
+        let entry = service.place_conditional_order(market(OrderSide::Buy, 0.5), USER).await;
+        assert!(matches!(entry, Err(AppError::RiskLimit(_))), "{:?}", entry);
+        let manual = service.place_order(market(OrderSide::Sell, 0.5), USER).await;
+        assert!(matches!(manual, Err(AppError::RiskLimit(_))), "{:?}", manual);
+    }
+
+    #[tokio::test]
+    async fn test_closing_orders_pass_without_instrument_rules() {
+        let (_dir, pool) = test_pool().await;
+        sqlx::query(
+            "INSERT INTO positions (id, user_id, exchange_id, symbol, side, quantity, entry_price, opened_at, updated_at) \
+             VALUES ('pos-1', ?, ?, 'BTCUSDT', 'long', 0.5, 50000, 0, 0)"
+        )
+        .bind(USER)
+        .bind(ACCOUNT)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let service = service(MockExchange { rules_down: true, ..Default::default() }, pool);
+        let sell = |client_order_id, quantity| OrderRequest {
+            side: OrderSide::Sell,
+            price: Some(50_000.123),
+            quantity,
+            ..limit_buy(client_order_id)
+        };
+
+        // Sent as given, since there are no rules to round it to
+        let close = service.place_order(sell("close1", 0.5), USER).await.unwrap();
+        assert_eq!(close.price, Some(50_000.123));
+        let entry = service.place_order(limit_buy("entry1"), USER).await;
+        assert!(matches!(entry, Err(AppError::Exchange(_))), "{:?}", entry);
+        let flip = service.place_order(sell("flip1", 0.6), USER).await;
+        assert!(matches!(flip, Err(AppError::Exchange(_))), "{:?}", flip);
+    }
+
+    #[tokio::test]
+    async fn test_late_position_write_keeps_the_newer_row() {
+        let (_dir, pool) = test_pool().await;
+        let service = service(MockExchange::default(), pool.clone());
+        let tracked = |quantity| TrackedPosition {
+            position: Position {
+                id: "pos-1".to_string(),
+                symbol: "BTCUSDT".to_string(),
+                side: "long".to_string(),
+                quantity,
+                entry_price: 50_000.0,
+                current_price: None,
+                unrealized_pnl: 0.0,
+                realized_pnl: 0.0,
+                opened_at: 0,
+            },
+            lots: Default::default(),
+            fees: 0.0,
+        };
+
+        let older = service.next_position_stamp();
+        let newer = service.next_position_stamp();
+        assert!(newer > older);
+        service.save_position(USER, ACCOUNT, None, &tracked(0.2), newer).await.unwrap();
+        service.save_position(USER, ACCOUNT, None, &tracked(0.1), older).await.unwrap();
+
+        let quantity: f64 = sqlx::query_scalar("SELECT quantity FROM positions WHERE id = 'pos-1'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(quantity, 0.2);
+    }
+
+    #[tokio::test]
+    async fn test_client_order_ids_are_unique_per_user() {
+        let (_dir, pool) = test_pool().await;
+        sqlx::query(
+            "INSERT INTO users (id, username, password_hash, role_id, salt, created_at, updated_at) \
+             VALUES ('u_other', 'other', '', 'role_trader', '', 0, 0)"
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO exchanges (id, user_id, exchange_name, display_name, api_key_encrypted, \
+             api_secret_encrypted, created_at, updated_at) VALUES ('acct-other', 'u_other', 'okx', 'OKX', '', '', 0, 0)"
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let ours = service(MockExchange::default(), pool.clone());
+        let theirs = TradeService::new(Arc::new(MockExchange::default()), pool).with_exchange_id("acct-other");
+        let first = ours.place_order(limit_buy("shared1"), USER).await.unwrap();
+        let second = theirs.place_order(limit_buy("shared1"), "u_other").await.unwrap();
+
+        assert_ne!(first.id, second.id);
+        assert_eq!(second.status, OrderState::Open);
+    }
+}
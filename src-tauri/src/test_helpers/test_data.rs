@@ -50,7 +50,9 @@ pub fn create_test_order(
         status: OrderState::Open,
         commission: 0.0,
         created_at: Utc::now().timestamp(),
+        commission_asset: None,
         filled_at: None,
+        updated_at: None,
     }
 }
 
@@ -68,6 +70,9 @@ pub fn create_test_context(
         balance,
         today_pnl,
         instance_id: instance_id.to_string(),
+        user_id: "test_user".to_string(),
+        position_accounts: Default::default(),
+        timestamp: None,
     }
 }
 
@@ -79,6 +84,9 @@ pub fn create_default_context() -> RiskContext {
         balance: 10000.0,
         today_pnl: 0.0,
         instance_id: "test_instance".to_string(),
+        user_id: "test_user".to_string(),
+        position_accounts: Default::default(),
+        timestamp: None,
     }
 }
 
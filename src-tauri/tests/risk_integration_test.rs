@@ -106,6 +106,7 @@ async fn scenario_1_position_limit_breach_triggers_warning() -> Result<()> {
             enabled: true,
             action: RiskAction::Notify,
             notify_methods: vec!["dingtalk".to_string(), "log".to_string()],
+            halt_trading: false,
         },
     ));
 
@@ -183,6 +184,7 @@ async fn scenario_2_drawdown_limit_triggers_emergency_close() -> Result<()> {
             enabled: true,
             action: RiskAction::EmergencyStop,
             notify_methods: vec!["dingtalk".to_string(), "email".to_string()],
+            halt_trading: false,
         },
     );
 
@@ -268,6 +270,7 @@ async fn scenario_3_multiple_rules_trigger_simultaneously() -> Result<()> {
             enabled: true,
             action: RiskAction::Notify,
             notify_methods: vec!["dingtalk".to_string()],
+            halt_trading: false,
         },
     );
 
@@ -278,6 +281,7 @@ async fn scenario_3_multiple_rules_trigger_simultaneously() -> Result<()> {
             enabled: true,
             action: RiskAction::ClosePositions,
             notify_methods: vec!["email".to_string()],
+            halt_trading: false,
         },
     );
 
@@ -563,6 +567,7 @@ async fn test_risk_rule_configuration_update() -> Result<()> {
         enabled: false,
         action: RiskAction::EmergencyStop,
         notify_methods: vec!["email".to_string()],
+        halt_trading: false,
     };
 
     rule.update_config(new_config)?;
@@ -583,6 +588,7 @@ async fn test_rule_config_validation() -> Result<()> {
         enabled: true,
         action: RiskAction::ClosePositions,
         notify_methods: vec!["dingtalk".to_string(), "email".to_string(), "log".to_string()],
+        halt_trading: false,
     };
 
     assert_eq!(config.notify_methods.len(), 3);
@@ -594,6 +600,7 @@ async fn test_rule_config_validation() -> Result<()> {
         enabled: true,
         action: RiskAction::Notify,
         notify_methods: vec!["dingtalk".to_string(), "invalid_method".to_string()],
+        halt_trading: false,
     };
 
     assert_eq!(config_with_invalid.notify_methods.len(), 2);
@@ -827,6 +834,7 @@ async fn test_rule_behavior_changes_with_config() -> Result<()> {
         enabled: false,
         action: RiskAction::LogOnly,
         notify_methods: vec![],
+        halt_trading: false,
     })?;
 
     // THEN: Rule check should still work (enabled check is in monitor, not rule)
@@ -839,6 +847,7 @@ async fn test_rule_behavior_changes_with_config() -> Result<()> {
         enabled: true,
         action: RiskAction::EmergencyStop,
         notify_methods: vec!["log".to_string()],
+        halt_trading: false,
     })?;
 
     // THEN: New config should be reflected
@@ -859,6 +868,7 @@ async fn test_position_limit_config_updates() -> Result<()> {
         enabled: true,
         action: RiskAction::PauseStrategy,
         notify_methods: vec!["email".to_string()],
+        halt_trading: false,
     })?;
 
     // THEN: Action should stop trading but not close positions
@@ -871,6 +881,7 @@ async fn test_position_limit_config_updates() -> Result<()> {
         enabled: true,
         action: RiskAction::ClosePositions,
         notify_methods: vec!["dingtalk".to_string()],
+        halt_trading: false,
     })?;
 
     // THEN: Action should both stop trading and close positions